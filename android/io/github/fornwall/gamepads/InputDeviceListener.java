@@ -0,0 +1,21 @@
+package io.github.fornwall.gamepads;
+
+import android.hardware.input.InputManager;
+
+/**
+ * Forwards input devices being added, removed or changed to the gamepads crate, so that
+ * gamepads connect and disconnect without polling the input devices.
+ *
+ * <p>Include this class in apps using the {@code backend-android-winit} feature, which
+ * registers it when created.
+ */
+public final class InputDeviceListener implements InputManager.InputDeviceListener {
+    @Override
+    public native void onInputDeviceAdded(int deviceId);
+
+    @Override
+    public native void onInputDeviceRemoved(int deviceId);
+
+    @Override
+    public native void onInputDeviceChanged(int deviceId);
+}
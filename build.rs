@@ -0,0 +1,119 @@
+//! Select the backend to compile, exposed to the crate as `cfg(backend = "...")`.
+//!
+//! Each `backend-*` feature only applies to the targets it supports, so the default features
+//! can enable one backend per target. Exactly one applicable backend may be enabled, or none,
+//! in which case no gamepads are reported.
+//!
+//! Also write the javascript glue of the direct web backend, see `src/js.rs`.
+
+use std::{env, fs};
+
+/// The javascript source defining `registerHostFunctions()`.
+const JS_SOURCE: &str = "js/gamepads-src-0.2.js";
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    write_js_glue();
+    println!(
+        "cargo:rustc-check-cfg=cfg(backend, values(\"android_winit\", \"emscripten\", \"evdev\", \"game_controller\", \"game_input\", \"gilrs\", \"mock\", \"none\", \"paddleboat\", \"raw_input\", \"sdl3\", \"wasi_host\", \"web_bindgen\", \"web_direct\", \"wgi\"))"
+    );
+
+    let feature = |name: &str| env::var_os(format!("CARGO_FEATURE_{name}")).is_some();
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+    let wasm_family = env::var("CARGO_CFG_TARGET_FAMILY")
+        .unwrap_or_default()
+        .split(',')
+        .any(|family| family == "wasm");
+    let (android, wasi) = (target_os == "android", target_os == "wasi");
+    let evdev = target_os == "linux" && feature("BACKEND_EVDEV");
+    let sdl3 = !wasm_family && !android && feature("BACKEND_SDL3");
+    // The sdl3 backend takes precedence over the default game controller backend:
+    let game_controller =
+        (target_os == "ios" || target_os == "tvos") && !sdl3 && feature("BACKEND_GAME_CONTROLLER");
+    let windows = target_os == "windows";
+    let (game_input, raw_input, wgi) = (
+        windows && feature("BACKEND_GAME_INPUT"),
+        windows && feature("BACKEND_RAW_INPUT"),
+        windows && feature("BACKEND_WGI"),
+    );
+    let emscripten = target_os == "emscripten";
+    let web = wasm_family && !wasi && !emscripten;
+
+    let backend = if !feature("STD") {
+        "none"
+    } else if feature("MOCK") {
+        "mock"
+    } else {
+        let applicable = [
+            ("android_winit", android && feature("BACKEND_ANDROID_WINIT")),
+            ("emscripten", emscripten && feature("BACKEND_EMSCRIPTEN")),
+            ("evdev", evdev),
+            ("game_controller", game_controller),
+            ("game_input", game_input),
+            // The platform backends take precedence over the default gilrs backend:
+            (
+                "gilrs",
+                !wasm_family
+                    && !android
+                    && !evdev
+                    && !game_controller
+                    && !game_input
+                    && !raw_input
+                    && !sdl3
+                    && !wgi
+                    && feature("BACKEND_GILRS"),
+            ),
+            ("paddleboat", android && feature("BACKEND_PADDLEBOAT")),
+            ("raw_input", raw_input),
+            ("sdl3", sdl3),
+            ("wasi_host", wasi && feature("BACKEND_WASI_HOST")),
+            ("web_bindgen", web && feature("BACKEND_WEB_BINDGEN")),
+            ("web_direct", web && feature("BACKEND_WEB_DIRECT")),
+            ("wgi", wgi),
+        ]
+        .into_iter()
+        .filter_map(|(backend, enabled)| enabled.then_some(backend))
+        .collect::<Vec<_>>();
+        match applicable[..] {
+            [] => "none",
+            [backend] => backend,
+            _ => panic!(
+                "Only one backend can be used for the target, but these are enabled: {}. \
+                 Disable default features to select one explicitly.",
+                applicable.join(", ")
+            ),
+        }
+    };
+    println!("cargo:rustc-cfg=backend=\"{backend}\"");
+}
+
+/// Write the javascript glue variants to `OUT_DIR`, which is also exposed to build scripts of
+/// dependents as `DEP_GAMEPADS_JS_DIR` thanks to the `links` key in Cargo.toml.
+fn write_js_glue() {
+    println!("cargo:rerun-if-changed={JS_SOURCE}");
+    let out_dir = env::var("OUT_DIR").unwrap_or_else(|_| panic!("OUT_DIR should be set"));
+    let source = fs::read_to_string(JS_SOURCE)
+        .unwrap_or_else(|error| panic!("Failed to read {JS_SOURCE}: {error}"));
+    let unnamed_function = source.replacen("function registerHostFunctions", "function", 1);
+    let variants = [
+        ("gamepads.js", source.clone()),
+        (
+            "gamepads-module.js",
+            source.replacen("function registerHostFunctions", "export default function", 1),
+        ),
+        (
+            "macroquad-gamepads.js",
+            format!(
+                "miniquad_add_plugin({{\n  name: \"gamepads\",\n  version: \"{}\",\n  register_plugin: {}}});\n",
+                env::var("CARGO_PKG_VERSION").unwrap_or_default(),
+                unnamed_function.trim_end(),
+            ),
+        ),
+    ];
+    for (file_name, contents) in variants {
+        let path = format!("{out_dir}/{file_name}");
+        fs::write(&path, contents)
+            .unwrap_or_else(|error| panic!("Failed to write {path}: {error}"));
+    }
+    println!("cargo:js_dir={out_dir}");
+}
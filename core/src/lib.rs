@@ -0,0 +1,713 @@
+//! State of individual gamepads, shared by the backends of the
+//! [gamepads](https://docs.rs/gamepads) crate, which re-exports everything here.
+//!
+//! This crate does not depend on `std`, so that [Gamepad] and [Button] can be fed from custom
+//! backends on platforms without `std`, such as consoles or embedded devices.
+#![no_std]
+
+/// The number of gamepads which can be connected at the same time, see [GamepadId::new()].
+pub const MAX_GAMEPADS: usize = 8;
+
+/// The number of axes beyond the two thumbsticks, see [Gamepad::extra_axis()].
+pub const MAX_EXTRA_AXES: usize = 20;
+
+/// The number of buttons, of which those beyond [Button::Mode] are extra buttons, see
+/// [Gamepad::is_extra_button_pressed()].
+pub const MAX_BUTTONS: usize = 96;
+
+/// An individual gamepad allowing access to information about button presses,
+/// thumbstick positions and its gamepad id.
+///
+/// A gamepad can be obtained using either [Gamepads::all()](https://docs.rs/gamepads/latest/gamepads/struct.Gamepads.html#method.all) to loop through all connected gamepads,
+/// or [Gamepads::get(gamepad_id)](https://docs.rs/gamepads/latest/gamepads/struct.Gamepads.html#method.get) to get it by an id.
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+#[non_exhaustive]
+pub struct Gamepad {
+    // Public for the backends of the gamepads crate, but not part of the API:
+    #[doc(hidden)]
+    pub id: GamepadId,
+    #[doc(hidden)]
+    pub connected: bool,
+    /// The [GamepadMapping] as a `u8`, as written by javascript.
+    #[doc(hidden)]
+    pub mapping: u8,
+    #[doc(hidden)]
+    pub pressed_bits: u32,
+    #[doc(hidden)]
+    pub axes: [f32; 4],
+    #[doc(hidden)]
+    pub just_pressed_bits: u32,
+    #[doc(hidden)]
+    pub just_released_bits: u32,
+    #[doc(hidden)]
+    pub last_change_tick: u64,
+    /// How far the left and right triggers are pressed, or zero if not reported by the backend.
+    #[doc(hidden)]
+    pub triggers: [f32; 2],
+    /// Axes beyond the two thumbsticks, such as of wheels, pedals and flight sticks.
+    #[doc(hidden)]
+    pub extra_axes: [f32; MAX_EXTRA_AXES],
+    /// Bits of the buttons from index 32, such as of flight sticks and throttles.
+    #[doc(hidden)]
+    pub high_pressed_bits: u64,
+    #[doc(hidden)]
+    pub high_just_pressed_bits: u64,
+    #[doc(hidden)]
+    pub high_just_released_bits: u64,
+}
+
+// Assert size of struct Gamepad, which is used by javascript.
+//
+// See https://users.rust-lang.org/t/ensure-that-struct-t-has-size-n-at-compile-time/61108/3
+#[cfg(target_family = "wasm")]
+const _: () = [(); 1][(core::mem::size_of::<Gamepad>() == 152) as usize ^ 1];
+
+impl Gamepad {
+    /// An id unique for each gamepad currently connected to the system.
+    ///
+    /// This can be used to distinguish multiple controllers; a gamepad that is disconnected
+    /// and reconnected will retain the same id.
+    pub const fn id(&self) -> GamepadId {
+        self.id
+    }
+
+    /// The `(x, y)` position of the left thumbstick.
+    ///
+    /// Each component is in the range `[-1.0, 1.0]`, with
+    /// negative values representing down or to the left.
+    pub const fn left_stick(&self) -> (f32, f32) {
+        (self.axes[0], self.axes[1])
+    }
+
+    /// The `x` position of the left thumbstick.
+    ///
+    /// Values are in the range `[-1.0, 1.0]`, with
+    /// negative values representing left.
+    pub const fn left_stick_x(&self) -> f32 {
+        self.axes[0]
+    }
+
+    /// The `y` position of the left thumbstick.
+    ///
+    /// Values are in the range `[-1.0, 1.0]`, with
+    /// negative values representing down.
+    pub const fn left_stick_y(&self) -> f32 {
+        self.axes[1]
+    }
+
+    /// The `(x, y)` position of the right thumbstick.
+    ///
+    /// Each component is in the range `[-1.0, 1.0]`, with
+    /// negative values representing down or to the left.
+    pub const fn right_stick(&self) -> (f32, f32) {
+        (self.axes[2], self.axes[3])
+    }
+
+    /// The `y` position of the right thumbstick.
+    ///
+    /// Values are in the range `[-1.0, 1.0]`, with
+    /// negative values representing left.
+    pub const fn right_stick_x(&self) -> f32 {
+        self.axes[2]
+    }
+
+    /// The `y` position of the right thumbstick.
+    ///
+    /// Values are in the range `[-1.0, 1.0]`, with
+    /// negative values representing down.
+    pub const fn right_stick_y(&self) -> f32 {
+        self.axes[3]
+    }
+
+    /// How far the left trigger ([Button::FrontLeftLower]) is pressed, in the range `[0.0, 1.0]`.
+    ///
+    /// Backends not reporting analog values give `1.0` when the trigger is pressed.
+    pub const fn left_trigger(&self) -> f32 {
+        self.trigger_value(0, Button::FrontLeftLower)
+    }
+
+    /// How far the right trigger ([Button::FrontRightLower]) is pressed, in the range `[0.0, 1.0]`.
+    ///
+    /// Backends not reporting analog values give `1.0` when the trigger is pressed.
+    pub const fn right_trigger(&self) -> f32 {
+        self.trigger_value(1, Button::FrontRightLower)
+    }
+
+    const fn trigger_value(&self, trigger_idx: usize, button: Button) -> f32 {
+        let value = self.triggers[trigger_idx];
+        if value == 0. && self.is_currently_pressed(button) {
+            1.
+        } else {
+            value
+        }
+    }
+
+    /// The value of an axis beyond the two thumbsticks, by its index in the `axes` array of the
+    /// Gamepad API, from 4 up to `4 + `[MAX_EXTRA_AXES], or `0.0` if there is no such axis.
+    ///
+    /// These are axes such as the steering, throttle and pedals of wheels and flight sticks,
+    /// together with their sliders, rotaries and further hat switches, which are only reported
+    /// on the web, by the `backend-raw-input` feature on Windows and by
+    /// the `backend-evdev` feature on Linux, unmodified in the range `[-1.0, 1.0]`. The trackpads
+    /// of the Steam Deck are also reported as axes 4 to 7 by the `steam-input` feature.
+    pub const fn extra_axis(&self, index: usize) -> f32 {
+        match index.checked_sub(4) {
+            Some(extra_idx) if extra_idx < MAX_EXTRA_AXES => self.extra_axes[extra_idx],
+            _ => 0.,
+        }
+    }
+
+    /// How the buttons and axes of the gamepad were mapped to the standard layout, as reported
+    /// by the Gamepad API on the web, or by the `backend-raw-input` and `backend-evdev` features.
+    ///
+    /// Useful for telling players that an unknown device may need to be configured.
+    pub const fn mapping(&self) -> GamepadMapping {
+        GamepadMapping::from_u8(self.mapping)
+    }
+
+    /// The [Gamepads::tick()](https://docs.rs/gamepads/latest/gamepads/struct.Gamepads.html#method.tick) of the last poll in which the state of this
+    /// gamepad changed, such as a button being pressed or a thumbstick moved.
+    ///
+    /// Systems reading input in multiple places can remember the tick they last handled, to
+    /// skip gamepads without new input instead of processing the same state twice.
+    pub const fn last_change_tick(&self) -> u64 {
+        self.last_change_tick
+    }
+
+    /// An iterator over all currently pressed buttons.
+    pub fn all_currently_pressed(&self) -> impl Iterator<Item = Button> + '_ {
+        Button::all().filter(|&t| self.is_currently_pressed(t))
+    }
+
+    /// An iterator over all just pressed buttons.
+    pub fn all_just_pressed(&self) -> impl Iterator<Item = Button> + '_ {
+        Button::all().filter(|&t| self.is_just_pressed(t))
+    }
+
+    /// An iterator over all just released buttons.
+    pub fn all_just_released(&self) -> impl Iterator<Item = Button> + '_ {
+        Button::all().filter(|&t| self.is_just_released(t))
+    }
+
+    /// Check if a button has just been pressed.
+    ///
+    /// A button pressed and released again between two polls is also reported as just pressed.
+    pub const fn is_just_pressed(&self, button: Button) -> bool {
+        let queried_bit = 1 << (button as u32);
+        (self.just_pressed_bits & queried_bit) != 0
+    }
+
+    /// Check if a button has just been released.
+    ///
+    /// A button pressed and released again between two polls is also reported as just released.
+    pub const fn is_just_released(&self, button: Button) -> bool {
+        let queried_bit = 1 << (button as u32);
+        (self.just_released_bits & queried_bit) != 0
+    }
+
+    /// Check if a button is currently pressed.
+    pub const fn is_currently_pressed(&self, button: Button) -> bool {
+        let queried_bit = 1 << (button as u32);
+        (self.pressed_bits & queried_bit) != 0
+    }
+
+    /// Check if a button beyond [Button::Mode] is currently pressed, by its index in the
+    /// `buttons` array of the Gamepad API, from 17 up to [MAX_BUTTONS].
+    ///
+    /// These are buttons such as the touchpad and mute buttons of a DualSense or the share
+    /// button of newer Xbox gamepads, which are only reported on the web. The Assistant (17)
+    /// and Capture (18) buttons of a Stadia controller and the microphone button (17) of a
+    /// Luna controller are also reported by the gilrs and evdev backends on Linux. With the
+    /// `backend-sdl3` feature, the share, capture or microphone button (17), the touchpad (18)
+    /// and the back paddles (19 to 22) are reported, while the `steam-input` feature reports the
+    /// back buttons (19 to 22) and trackpad clicks (23 and 24) of the Steam Deck. The
+    /// `playstation` feature reports the microphone button (17) of a DualSense and the touchpad
+    /// (18) of a DualSense or DualShock 4.
+    ///
+    /// Joysticks without a known mapping, such as flight sticks and throttles, report all their
+    /// buttons in device specific order, with those from index 17 as extra buttons, on the web,
+    /// and by the `backend-raw-input` and `backend-evdev` features. Other indices are never
+    /// reported as pressed.
+    pub const fn is_extra_button_pressed(&self, index: u8) -> bool {
+        has_extra_button(self.pressed_bits, self.high_pressed_bits, index)
+    }
+
+    /// Check if a button beyond [Button::Mode] has just been pressed, see [Gamepad::is_extra_button_pressed()].
+    pub const fn is_extra_button_just_pressed(&self, index: u8) -> bool {
+        has_extra_button(self.just_pressed_bits, self.high_just_pressed_bits, index)
+    }
+
+    /// Check if a button beyond [Button::Mode] has just been released, see [Gamepad::is_extra_button_pressed()].
+    pub const fn is_extra_button_just_released(&self, index: u8) -> bool {
+        has_extra_button(self.just_released_bits, self.high_just_released_bits, index)
+    }
+
+    /// An iterator over the indices of all currently pressed buttons beyond [Button::Mode],
+    /// see [Gamepad::is_extra_button_pressed()].
+    pub fn all_extra_buttons_pressed(&self) -> impl Iterator<Item = u8> + '_ {
+        EXTRA_BUTTONS.filter(|&index| self.is_extra_button_pressed(index))
+    }
+    /// Create the state of a disconnected gamepad, for feeding from a custom backend.
+    ///
+    /// This is mostly useful without the `std` feature, where there is no
+    /// [Gamepads](https://docs.rs/gamepads/latest/gamepads/struct.Gamepads.html) instance. Call [Gamepad::begin_poll()] at the start of each
+    /// poll, followed by the changes observed since the last one:
+    ///
+    /// ```
+    /// use gamepads_core::{Button, Gamepad, GamepadId};
+    ///
+    /// let mut gamepad = Gamepad::new(GamepadId::new(0).unwrap());
+    /// gamepad.set_connected(true);
+    ///
+    /// gamepad.begin_poll();
+    /// gamepad.press(Button::ActionDown);
+    /// assert!(gamepad.is_just_pressed(Button::ActionDown));
+    ///
+    /// gamepad.begin_poll();
+    /// gamepad.release(Button::ActionDown);
+    /// assert!(gamepad.is_just_released(Button::ActionDown));
+    /// assert!(!gamepad.is_currently_pressed(Button::ActionDown));
+    /// ```
+    pub const fn new(id: GamepadId) -> Self {
+        Self {
+            id,
+            connected: false,
+            mapping: GamepadMapping::Unknown as u8,
+            pressed_bits: 0,
+            axes: [0.; 4],
+            just_pressed_bits: 0,
+            just_released_bits: 0,
+            last_change_tick: 0,
+            triggers: [0.; 2],
+            extra_axes: [0.; MAX_EXTRA_AXES],
+            high_pressed_bits: 0,
+            high_just_pressed_bits: 0,
+            high_just_released_bits: 0,
+        }
+    }
+
+    /// Whether the gamepad is connected.
+    pub const fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Set whether the gamepad is connected, releasing its buttons and centering its
+    /// thumbsticks when disconnected.
+    pub const fn set_connected(&mut self, connected: bool) {
+        self.connected = connected;
+        if !connected {
+            self.release_all();
+        }
+    }
+
+    /// Release all buttons and center all thumbsticks, triggers and axes, such as when input
+    /// stops being received while the app is in the background.
+    pub const fn release_all(&mut self) {
+        self.pressed_bits = 0;
+        self.axes = [0.; 4];
+        self.triggers = [0.; 2];
+        self.extra_axes = [0.; MAX_EXTRA_AXES];
+        self.high_pressed_bits = 0;
+    }
+
+    /// Start a new poll, clearing the just pressed and just released buttons.
+    pub const fn begin_poll(&mut self) {
+        self.just_pressed_bits = 0;
+        self.just_released_bits = 0;
+        self.high_just_pressed_bits = 0;
+        self.high_just_released_bits = 0;
+    }
+
+    /// Press a button, which is reported as just pressed until the next [Gamepad::begin_poll()].
+    pub const fn press(&mut self, button: Button) {
+        self.pressed_bits |= 1 << (button as u32);
+        self.just_pressed_bits |= 1 << (button as u32);
+    }
+
+    /// Release a button, which is reported as just released until the next [Gamepad::begin_poll()].
+    pub const fn release(&mut self, button: Button) {
+        self.pressed_bits &= !(1 << (button as u32));
+        self.just_released_bits |= 1 << (button as u32);
+    }
+
+    /// Press a button beyond [Button::Mode] by its index from 17 up to [MAX_BUTTONS], see
+    /// [Gamepad::is_extra_button_pressed()]. Indices out of range are ignored.
+    pub const fn press_extra(&mut self, index: u8) {
+        let (bit, high_bit) = extra_button_bits(index);
+        self.pressed_bits |= bit;
+        self.just_pressed_bits |= bit;
+        self.high_pressed_bits |= high_bit;
+        self.high_just_pressed_bits |= high_bit;
+    }
+
+    /// Release a button beyond [Button::Mode] by its index from 17 up to [MAX_BUTTONS].
+    /// Indices out of range are ignored.
+    pub const fn release_extra(&mut self, index: u8) {
+        let (bit, high_bit) = extra_button_bits(index);
+        self.pressed_bits &= !bit;
+        self.just_released_bits |= bit;
+        self.high_pressed_bits &= !high_bit;
+        self.high_just_released_bits |= high_bit;
+    }
+
+    /// Set the `(x, y)` position of the left thumbstick.
+    pub const fn set_left_stick(&mut self, (x, y): (f32, f32)) {
+        self.axes[0] = x;
+        self.axes[1] = y;
+    }
+
+    /// Set the `(x, y)` position of the right thumbstick.
+    pub const fn set_right_stick(&mut self, (x, y): (f32, f32)) {
+        self.axes[2] = x;
+        self.axes[3] = y;
+    }
+
+    /// Set how far the left and right triggers are pressed, in the range `[0.0, 1.0]`.
+    pub const fn set_triggers(&mut self, (left, right): (f32, f32)) {
+        self.triggers[0] = left;
+        self.triggers[1] = right;
+    }
+
+    /// Set how the buttons and axes of the gamepad were mapped to the standard layout.
+    pub const fn set_mapping(&mut self, mapping: GamepadMapping) {
+        self.mapping = mapping as u8;
+    }
+
+    /// Set the value of an axis beyond the two thumbsticks, see [Gamepad::extra_axis()].
+    /// Indices out of range are ignored.
+    pub const fn set_extra_axis(&mut self, index: usize, value: f32) {
+        if let Some(extra_idx) = index.checked_sub(4) {
+            if extra_idx < MAX_EXTRA_AXES {
+                self.extra_axes[extra_idx] = value;
+            }
+        }
+    }
+}
+
+/// The indices of buttons beyond [Button::Mode], as bits of the pressed bits up to 32 and of the
+/// high pressed bits from there.
+const EXTRA_BUTTONS: core::ops::Range<u8> = 17..MAX_BUTTONS as u8;
+
+/// The bit of a button beyond [Button::Mode] in the pressed bits and in the high pressed bits,
+/// or no bits if out of range.
+const fn extra_button_bits(index: u8) -> (u32, u64) {
+    if index < EXTRA_BUTTONS.start || index >= EXTRA_BUTTONS.end {
+        (0, 0)
+    } else if index < 32 {
+        (1 << index, 0)
+    } else {
+        (0, 1 << (index - 32))
+    }
+}
+
+/// Whether the bit of a button beyond [Button::Mode] is set in pressed bits and high pressed bits.
+const fn has_extra_button(bits: u32, high_bits: u64, index: u8) -> bool {
+    let (bit, high_bit) = extra_button_bits(index);
+    bits & bit != 0 || high_bits & high_bit != 0
+}
+
+/// Apply a deadzone to a raw thumbstick axis value in the range `[-1.0, 1.0]`.
+///
+/// Values within the deadzone are `0.0`, and the rest are scaled so that the magnitude is still
+/// in the range `[0.0, 1.0]` instead of jumping from `0.0` to `deadzone`.
+///
+/// ```
+/// assert_eq!(gamepads_core::apply_deadzone(0.05, 0.1), 0.);
+/// assert_eq!(gamepads_core::apply_deadzone(-1., 0.1), -1.);
+/// assert_eq!(gamepads_core::apply_deadzone(0.75, 0.5), 0.5);
+/// ```
+// mul_add() is not available without std:
+#[allow(clippy::suboptimal_flops)]
+pub fn apply_deadzone(value: f32, deadzone: f32) -> f32 {
+    if value.abs() < deadzone {
+        0.
+    } else {
+        (value - value.signum() * deadzone) / (1. - deadzone)
+    }
+}
+
+/// How the buttons and axes of a gamepad were mapped to the standard layout,
+/// as returned by [Gamepad::mapping()].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum GamepadMapping {
+    /// Not reported by the backend, which is the case outside the web, except for HID devices
+    /// read by the `backend-raw-input` feature on Windows and joysticks read by the
+    /// `backend-evdev` feature on Linux.
+    #[default]
+    Unknown,
+    /// The `standard` mapping of the Gamepad API, with buttons and axes as documented on [Button].
+    Standard,
+    /// No mapping, as reported by an empty `mapping` of the Gamepad API, and for HID devices
+    /// read by the `backend-raw-input` feature on Windows and joysticks read by the
+    /// `backend-evdev` feature on Linux.
+    ///
+    /// The buttons and axes of known devices are remapped to the standard layout,
+    /// while those of other devices are reported in their device specific order.
+    Unmapped,
+    /// The `xr-standard` mapping of WebXR controllers.
+    ///
+    /// The trigger and squeeze buttons are reported as [Button::FrontRightLower] and
+    /// [Button::FrontRightUpper], the thumbstick as the left thumbstick and the touchpad
+    /// as the right thumbstick, with their presses as [Button::LeftStick] and [Button::RightStick].
+    /// The first two extra buttons, such as A and B, are reported as [Button::ActionDown]
+    /// and [Button::ActionRight].
+    XrStandard,
+}
+
+impl GamepadMapping {
+    /// The mapping of a `u8` as written by javascript, with unknown values as [GamepadMapping::Unknown].
+    #[doc(hidden)]
+    pub const fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Standard,
+            2 => Self::Unmapped,
+            3 => Self::XrStandard,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// An opaque gamepad identifier.
+///
+/// Obtained using the [Gamepad::id()] method on a gamepad.
+///
+/// Given a gamepad id, it's possible to get its gamepad state using [Gamepads::get(gamepad_id)](https://docs.rs/gamepads/latest/gamepads/struct.Gamepads.html#method.get).
+///
+/// This is a small handle consisting of a single byte.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct GamepadId(#[doc(hidden)] pub u8);
+
+impl GamepadId {
+    /// Create a gamepad id from its byte value, returning `None` if out of range.
+    ///
+    /// Only needed when feeding [Gamepad] state from a custom backend using [Gamepad::new()].
+    pub const fn new(value: u8) -> Option<Self> {
+        if (value as usize) < MAX_GAMEPADS {
+            Some(Self(value))
+        } else {
+            None
+        }
+    }
+
+    /// The byte value that represents this gamepad id.
+    pub const fn value(self) -> u8 {
+        self.0
+    }
+}
+
+/// A button on a gamepad.
+///
+/// Check for the current state of button presses on a gamepad using one of:
+///
+/// - [Gamepad::all_currently_pressed()]
+/// - [Gamepad::all_just_pressed()]
+/// - [Gamepad::is_currently_pressed()]
+/// - [Gamepad::is_just_pressed()]
+///
+/// Different platforms call the buttons different things, see the below pictures for an overview, as
+/// well as the individual button documentations for a comparison.
+///
+/// # Playstation
+/// ![Playstation gamepad layout](https://www.gran-turismo.com/images/c/i17AZsIsc9rpTb.jpg)
+///
+/// # Switch
+/// ![Switch gamepad layout](https://oyster.ignimgs.com/mediawiki/apis.ign.com/nintendo-nx/b/bb/Joycon.jpg?width=960)
+///
+/// # Xbox
+/// ![Xbox gamepad layout](https://upload.wikimedia.org/wikipedia/commons/thumb/2/2c/360_controller.svg/2880px-360_controller.svg.png)
+///
+/// # W3C Gamepad API standard gamepad layout:
+/// ![Visual representation of a Standard Gamepad layout](https://w3c.github.io/gamepad/standard_gamepad.svg)
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Button {
+    /// Lowermost button in right cluster
+    ///
+    /// - Playstation: `X`/`Cross` button
+    /// - Switch: `B` button
+    /// - Xbox: `A` button
+    /// - Gamepad API: `buttons[0]` / `Bottom button in right cluster`
+    ActionDown,
+    /// Rightmost button in right cluster
+    ///
+    /// - Gamepad API: `buttons[1]` / `Right button in right cluster`
+    /// - Playstation: `O`/`Circle` button
+    /// - Switch: `A`
+    /// - Xbox: `B`
+    ActionRight,
+    /// Leftmost button in right cluster
+    ///
+    /// - Gamepad API: `buttons[2]` / `Left button in right cluster`
+    /// - Playstation: `□`/`Square`
+    /// - Switch: `Y`
+    /// - Xbox: `X`
+    ActionLeft,
+    /// Topmost button in right cluster
+    ///
+    /// - Gamepad API: `buttons[3]` / `Top button in right cluster`
+    /// - Playstation: `△`/`Triangle`
+    /// - Switch: `X`
+    /// - Xbox: `Y`
+    ActionUp,
+    /// Top left front button
+    ///
+    /// - Gamepad API: `buttons[4]` / `Top left front button`
+    /// - Playstation: `L1`
+    /// - Switch: `L`
+    /// - Xbox: `LB` (`Left Bumper`)
+    FrontLeftUpper,
+    /// Top right front button
+    ///
+    /// - Gamepad API: `buttons[5]` / `Top right front button`
+    /// - Playstation: `R1`
+    /// - Switch: `R`
+    /// - Xbox: `RB` (`Right Bumper`)
+    FrontRightUpper,
+    /// Bottom left front button
+    ///
+    /// - Gamepad API: `buttons[6]` / `Bottom left front button`
+    /// - Playstation: `L2`
+    /// - Switch: `ZL`
+    /// - Xbox: `LT` (`Left Trigger`)
+    FrontLeftLower,
+    /// Bottom right front button
+    ///
+    /// - Gamepad API: `buttons[7]` / `Bottom right front button`
+    /// - Playstation: `R2`
+    /// - Switch: `ZR`
+    /// - Xbox: `RT` (`Right Trigger`)
+    FrontRightLower,
+    /// Left button in center cluster - select/back
+    ///
+    /// - Gamepad API: `buttons[8]` / `Left button in center cluster`
+    /// - Playstation: `SELECT`
+    /// - Switch: `Capture`
+    /// - Xbox: `RT` (`Right Trigger`)
+    LeftCenterCluster,
+    /// Right button in center cluster - start/forward.
+    ///
+    /// - Gamepad API: `buttons[9]` / `Right button in center cluster`
+    /// - Playstation: `Start`
+    /// - Switch: `Home`
+    /// - Xbox: `Start`
+    RightCenterCluster,
+    /// Left stick pressed button.
+    LeftStick,
+    /// Right stick pressed button.
+    RightStick,
+    /// D-pad up button.
+    DPadUp,
+    /// D-pad down button.
+    DPadDown,
+    /// D-pad left button.
+    DPadLeft,
+    /// D-pad right button.
+    DPadRight,
+    /// Mode button.
+    ///
+    /// - Gamepad API: `buttons[16]` / `Center button in center cluster`
+    Mode,
+}
+
+impl Button {
+    /// An iterator over all button types.
+    pub fn all() -> impl Iterator<Item = Self> {
+        [
+            Self::ActionDown,
+            Self::ActionRight,
+            Self::ActionLeft,
+            Self::ActionUp,
+            Self::FrontLeftUpper,
+            Self::FrontRightUpper,
+            Self::FrontLeftLower,
+            Self::FrontRightLower,
+            Self::LeftCenterCluster,
+            Self::RightCenterCluster,
+            Self::LeftStick,
+            Self::RightStick,
+            Self::DPadUp,
+            Self::DPadDown,
+            Self::DPadLeft,
+            Self::DPadRight,
+            Self::Mode,
+        ]
+        .into_iter()
+    }
+}
+
+/// The button of the standard layout for a button of gilrs, or the gilrs button back for those
+/// without one, such as `gilrs::Button::C` and `gilrs::Button::Z`.
+///
+/// ```
+/// use gamepads_core::Button;
+///
+/// assert_eq!(Button::try_from(gilrs::Button::South), Ok(Button::ActionDown));
+/// assert_eq!(gilrs::Button::from(Button::ActionDown), gilrs::Button::South);
+/// assert_eq!(Button::try_from(gilrs::Button::C), Err(gilrs::Button::C));
+/// ```
+#[cfg(all(
+    feature = "gilrs",
+    not(any(target_os = "android", target_family = "wasm"))
+))]
+impl TryFrom<gilrs::Button> for Button {
+    type Error = gilrs::Button;
+
+    fn try_from(button: gilrs::Button) -> Result<Self, Self::Error> {
+        Ok(match button {
+            gilrs::Button::South => Self::ActionDown,
+            gilrs::Button::East => Self::ActionRight,
+            gilrs::Button::West => Self::ActionLeft,
+            gilrs::Button::North => Self::ActionUp,
+            gilrs::Button::LeftTrigger => Self::FrontLeftUpper,
+            gilrs::Button::RightTrigger => Self::FrontRightUpper,
+            gilrs::Button::LeftTrigger2 => Self::FrontLeftLower,
+            gilrs::Button::RightTrigger2 => Self::FrontRightLower,
+            gilrs::Button::Select => Self::LeftCenterCluster,
+            gilrs::Button::Start => Self::RightCenterCluster,
+            gilrs::Button::LeftThumb => Self::LeftStick,
+            gilrs::Button::RightThumb => Self::RightStick,
+            gilrs::Button::DPadUp => Self::DPadUp,
+            gilrs::Button::DPadDown => Self::DPadDown,
+            gilrs::Button::DPadLeft => Self::DPadLeft,
+            gilrs::Button::DPadRight => Self::DPadRight,
+            gilrs::Button::Mode => Self::Mode,
+            // Other:
+            other => {
+                return Err(other);
+            }
+        })
+    }
+}
+
+/// The button of gilrs for a button of the standard layout, see the conversion back.
+#[cfg(all(
+    feature = "gilrs",
+    not(any(target_os = "android", target_family = "wasm"))
+))]
+impl From<Button> for gilrs::Button {
+    fn from(button: Button) -> Self {
+        match button {
+            Button::ActionDown => Self::South,
+            Button::ActionRight => Self::East,
+            Button::ActionLeft => Self::West,
+            Button::ActionUp => Self::North,
+            Button::FrontLeftUpper => Self::LeftTrigger,
+            Button::FrontRightUpper => Self::RightTrigger,
+            Button::FrontLeftLower => Self::LeftTrigger2,
+            Button::FrontRightLower => Self::RightTrigger2,
+            Button::LeftCenterCluster => Self::Select,
+            Button::RightCenterCluster => Self::Start,
+            Button::LeftStick => Self::LeftThumb,
+            Button::RightStick => Self::RightThumb,
+            Button::DPadUp => Self::DPadUp,
+            Button::DPadDown => Self::DPadDown,
+            Button::DPadLeft => Self::DPadLeft,
+            Button::DPadRight => Self::DPadRight,
+            Button::Mode => Self::Mode,
+        }
+    }
+}
@@ -1 +0,0 @@
-../../js/macroquad-gamepads-0.1.js
\ No newline at end of file
@@ -1 +0,0 @@
-../../js/gamepads-module-0.1.js
\ No newline at end of file
@@ -0,0 +1,189 @@
+//! A GDExtension exposing the [gamepads] crate to GDScript as a `Gamepads` node.
+//!
+//! Gamepads are identified by their id, from `connected_ids()`, and buttons by the `BUTTON_*`
+//! constants, in the order of [gamepads::Button].
+
+use gamepads::{Button, GamepadId};
+use godot::prelude::*;
+
+struct GamepadsExtension;
+
+#[gdextension]
+unsafe impl ExtensionLibrary for GamepadsExtension {}
+
+/// A node polling gamepads each frame, before the `_process()` of its children.
+#[derive(GodotClass)]
+#[class(base = Node, rename = Gamepads)]
+struct GamepadsNode {
+    gamepads: gamepads::Gamepads,
+    base: Base<Node>,
+}
+
+#[godot_api]
+impl INode for GamepadsNode {
+    fn init(base: Base<Node>) -> Self {
+        Self {
+            gamepads: gamepads::Gamepads::new(),
+            base,
+        }
+    }
+
+    fn process(&mut self, _delta: f64) {
+        self.gamepads.poll();
+    }
+}
+
+/// The gamepad id of a GDScript integer, if in range.
+fn gamepad_id(id: i64) -> Option<GamepadId> {
+    u8::try_from(id).ok().and_then(GamepadId::new)
+}
+
+/// The button of a `BUTTON_*` constant.
+fn button(button: i64) -> Option<Button> {
+    Button::all().nth(usize::try_from(button).ok()?)
+}
+
+/// A thumbstick position as a vector, with y positive downwards as elsewhere in Godot.
+fn stick_vector((x, y): (f32, f32)) -> Vector2 {
+    Vector2::new(x, -y)
+}
+
+#[godot_api]
+impl GamepadsNode {
+    #[constant]
+    const BUTTON_ACTION_DOWN: i64 = Button::ActionDown as i64;
+    #[constant]
+    const BUTTON_ACTION_RIGHT: i64 = Button::ActionRight as i64;
+    #[constant]
+    const BUTTON_ACTION_LEFT: i64 = Button::ActionLeft as i64;
+    #[constant]
+    const BUTTON_ACTION_UP: i64 = Button::ActionUp as i64;
+    #[constant]
+    const BUTTON_FRONT_LEFT_UPPER: i64 = Button::FrontLeftUpper as i64;
+    #[constant]
+    const BUTTON_FRONT_RIGHT_UPPER: i64 = Button::FrontRightUpper as i64;
+    #[constant]
+    const BUTTON_FRONT_LEFT_LOWER: i64 = Button::FrontLeftLower as i64;
+    #[constant]
+    const BUTTON_FRONT_RIGHT_LOWER: i64 = Button::FrontRightLower as i64;
+    #[constant]
+    const BUTTON_LEFT_CENTER_CLUSTER: i64 = Button::LeftCenterCluster as i64;
+    #[constant]
+    const BUTTON_RIGHT_CENTER_CLUSTER: i64 = Button::RightCenterCluster as i64;
+    #[constant]
+    const BUTTON_LEFT_STICK: i64 = Button::LeftStick as i64;
+    #[constant]
+    const BUTTON_RIGHT_STICK: i64 = Button::RightStick as i64;
+    #[constant]
+    const BUTTON_DPAD_UP: i64 = Button::DPadUp as i64;
+    #[constant]
+    const BUTTON_DPAD_DOWN: i64 = Button::DPadDown as i64;
+    #[constant]
+    const BUTTON_DPAD_LEFT: i64 = Button::DPadLeft as i64;
+    #[constant]
+    const BUTTON_DPAD_RIGHT: i64 = Button::DPadRight as i64;
+    #[constant]
+    const BUTTON_MODE: i64 = Button::Mode as i64;
+
+    /// Poll gamepads now, which is otherwise done once per frame.
+    #[func]
+    fn poll(&mut self) {
+        self.gamepads.poll();
+    }
+
+    /// The ids of the connected gamepads.
+    #[func]
+    fn connected_ids(&self) -> PackedInt32Array {
+        self.gamepads
+            .all()
+            .map(|gamepad| i32::from(gamepad.id().value()))
+            .collect()
+    }
+
+    /// The name of a gamepad, or an empty string if not known.
+    #[func]
+    fn gamepad_name(&self, id: i64) -> GString {
+        gamepad_id(id)
+            .and_then(|gamepad_id| self.gamepads.name(gamepad_id))
+            .map_or_else(GString::new, |name| GString::from(name.as_str()))
+    }
+
+    /// Check if a button is currently pressed.
+    #[func]
+    fn is_pressed(&self, id: i64, button_index: i64) -> bool {
+        self.read(id, |gamepad| {
+            button(button_index).is_some_and(|button| gamepad.is_currently_pressed(button))
+        })
+    }
+
+    /// Check if a button was pressed since the last poll.
+    #[func]
+    fn is_just_pressed(&self, id: i64, button_index: i64) -> bool {
+        self.read(id, |gamepad| {
+            button(button_index).is_some_and(|button| gamepad.is_just_pressed(button))
+        })
+    }
+
+    /// Check if a button was released since the last poll.
+    #[func]
+    fn is_just_released(&self, id: i64, button_index: i64) -> bool {
+        self.read(id, |gamepad| {
+            button(button_index).is_some_and(|button| gamepad.is_just_released(button))
+        })
+    }
+
+    /// The position of the left thumbstick, with y positive downwards.
+    #[func]
+    fn left_stick(&self, id: i64) -> Vector2 {
+        self.read(id, |gamepad| stick_vector(gamepad.left_stick()))
+    }
+
+    /// The position of the right thumbstick, with y positive downwards.
+    #[func]
+    fn right_stick(&self, id: i64) -> Vector2 {
+        self.read(id, |gamepad| stick_vector(gamepad.right_stick()))
+    }
+
+    /// The value of the left trigger in the range `[0.0, 1.0]`.
+    #[func]
+    fn left_trigger(&self, id: i64) -> f32 {
+        self.read(id, gamepads::Gamepad::left_trigger)
+    }
+
+    /// The value of the right trigger in the range `[0.0, 1.0]`.
+    #[func]
+    fn right_trigger(&self, id: i64) -> f32 {
+        self.read(id, gamepads::Gamepad::right_trigger)
+    }
+
+    /// Rumble a gamepad, with magnitudes in the range `[0.0, 1.0]`.
+    #[func]
+    fn rumble(
+        &mut self,
+        id: i64,
+        duration_ms: i64,
+        start_delay_ms: i64,
+        strong_magnitude: f32,
+        weak_magnitude: f32,
+    ) {
+        if let Some(gamepad_id) = gamepad_id(id) {
+            self.gamepads.rumble(
+                gamepad_id,
+                u32::try_from(duration_ms).unwrap_or_default(),
+                u32::try_from(start_delay_ms).unwrap_or_default(),
+                strong_magnitude,
+                weak_magnitude,
+            );
+        }
+    }
+}
+
+impl GamepadsNode {
+    /// Read a connected gamepad, or the default value if not connected.
+    fn read<T: Default>(&self, id: i64, f: impl FnOnce(&gamepads::Gamepad) -> T) -> T {
+        gamepad_id(id)
+            .and_then(|gamepad_id| self.gamepads.get(gamepad_id))
+            .map(|gamepad| f(&gamepad))
+            .unwrap_or_default()
+    }
+}
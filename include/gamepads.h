@@ -0,0 +1,167 @@
+#ifndef GAMEPADS_H
+#define GAMEPADS_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * The version of the C ABI, incremented on incompatible changes such as to the layout of
+ * `Gamepad`.
+ */
+#define GAMEPADS_ABI_VERSION 1
+
+/**
+ * The number of axes beyond the two thumbsticks, see [Gamepad::extra_axis()].
+ */
+#define MAX_EXTRA_AXES 20
+
+/**
+ * A button on a gamepad.
+ */
+enum GamepadsButton
+#ifdef __cplusplus
+  : uint8_t
+#endif // __cplusplus
+ {
+  GamepadsButton_ActionDown,
+  GamepadsButton_ActionRight,
+  GamepadsButton_ActionLeft,
+  GamepadsButton_ActionUp,
+  GamepadsButton_FrontLeftUpper,
+  GamepadsButton_FrontRightUpper,
+  GamepadsButton_FrontLeftLower,
+  GamepadsButton_FrontRightLower,
+  GamepadsButton_LeftCenterCluster,
+  GamepadsButton_RightCenterCluster,
+  GamepadsButton_LeftStick,
+  GamepadsButton_RightStick,
+  GamepadsButton_DPadUp,
+  GamepadsButton_DPadDown,
+  GamepadsButton_DPadLeft,
+  GamepadsButton_DPadRight,
+  GamepadsButton_Mode,
+};
+#ifndef __cplusplus
+typedef uint8_t GamepadsButton;
+#endif // __cplusplus
+
+/**
+ * Context for obtaining gamepad information.
+ */
+typedef struct Gamepads Gamepads;
+
+/**
+ * An opaque gamepad identifier.
+ */
+typedef uint8_t GamepadId;
+
+/**
+ * An individual gamepad allowing access to information about button presses,
+ * thumbstick positions and its gamepad id.
+ */
+typedef struct Gamepad {
+  GamepadId id;
+  bool connected;
+  uint8_t mapping;
+  uint32_t pressed_bits;
+  float axes[4];
+  uint32_t just_pressed_bits;
+  uint32_t just_released_bits;
+  uint64_t last_change_tick;
+  float triggers[2];
+  float extra_axes[MAX_EXTRA_AXES];
+  uint64_t high_pressed_bits;
+  uint64_t high_just_pressed_bits;
+  uint64_t high_just_released_bits;
+} Gamepad;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * The ABI version of the library, to compare with `GAMEPADS_ABI_VERSION` of the header when
+ * loading the library dynamically.
+ */
+uint32_t gamepads_abi_version(void);
+
+/**
+ * Construct a new gamepads instance, to be freed using `gamepads_free()`.
+ */
+Gamepads *gamepads_new(void);
+
+/**
+ * Free a gamepads instance constructed using `gamepads_new()`.
+ *
+ * # Safety
+ *
+ * `gamepads` must be null or returned by `gamepads_new()`, and not used after this call.
+ */
+void gamepads_free(Gamepads *gamepads);
+
+/**
+ * Poll gamepad state, to be called on each tick.
+ *
+ * # Safety
+ *
+ * `gamepads` must be null or returned by `gamepads_new()`.
+ */
+void gamepads_poll(Gamepads *gamepads);
+
+/**
+ * Copy the state of a gamepad by id into `out`, returning whether it is connected.
+ *
+ * `gamepad_size` must be `sizeof(Gamepad)`, and nothing is copied if it differs from the
+ * size of `Gamepad` in the library, such as from a header of another version.
+ *
+ * # Safety
+ *
+ * `gamepads` must be null or returned by `gamepads_new()`, and `out` must be null or
+ * valid for writes.
+ */
+bool gamepads_get(const Gamepads *gamepads,
+                  uint8_t gamepad_id,
+                  Gamepad *out,
+                  uintptr_t gamepad_size);
+
+/**
+ * Copy the state of connected gamepads into the `out` array of length `capacity`, returning
+ * the number of gamepads copied.
+ *
+ * At most 8 gamepads are connected at once, so that is a sufficient capacity. As for
+ * `gamepads_get()`, nothing is copied unless `gamepad_size` is the size of `Gamepad` in the
+ * library.
+ *
+ * # Safety
+ *
+ * `gamepads` must be null or returned by `gamepads_new()`, and `out` must be null or
+ * valid for writes of `capacity` gamepads.
+ */
+uintptr_t gamepads_get_all(const Gamepads *gamepads,
+                           Gamepad *out,
+                           uintptr_t capacity,
+                           uintptr_t gamepad_size);
+
+/**
+ * Rumble a gamepad, see `Gamepads::rumble()` for a description of the arguments.
+ *
+ * # Safety
+ *
+ * `gamepads` must be null or returned by `gamepads_new()`.
+ */
+void gamepads_rumble(Gamepads *gamepads,
+                     uint8_t gamepad_id,
+                     uint32_t duration_ms,
+                     uint32_t start_delay_ms,
+                     float strong_magnitude,
+                     float weak_magnitude);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* GAMEPADS_H */
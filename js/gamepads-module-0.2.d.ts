@@ -0,0 +1,103 @@
+// Generated by `cargo xtask generate-ts` from the gamepads crate, do not edit.
+
+/** Layout of the state of each gamepad written to wasm memory by `getGamepads()`. */
+export declare const enum GamepadLayout {
+  /** The version of this layout, passed to `getGamepads()`. */
+  ProtocolVersion = 1,
+  /** The number of gamepads written, one after another. */
+  MaxGamepads = 8,
+  /** The size in bytes of the state of each gamepad. */
+  BytesPerGamepad = 152,
+  /** The offset of the `u8` which is 1 if the gamepad is connected and 0 otherwise. */
+  ConnectedOffset = 1,
+  /** The offset of the `u8` mapping: 0 if unknown, 1 for standard, 2 for none and 3 for xr-standard. */
+  MappingOffset = 2,
+  /** The offset of the `u32` with a bit set for each currently pressed button. */
+  PressedBitsOffset = 4,
+  /** The offset of the four `f32` thumbstick axes: left x, left y, right x and right y. */
+  AxesOffset = 8,
+  /** The offset of the `u32` with a bit set for each button pressed since the previous poll. */
+  JustPressedBitsOffset = 24,
+  /** The offset of the two `f32` values of how far the left and right triggers are pressed. */
+  TriggersOffset = 40,
+  /** The offset of the `f32` values of the axes beyond the two thumbsticks. */
+  ExtraAxesOffset = 48,
+  /** The number of axes beyond the two thumbsticks. */
+  MaxExtraAxes = 20,
+  /** The offset of the `u64` with a bit set for each currently pressed button from index 32. */
+  HighPressedBitsOffset = 128,
+}
+
+/** Host functions imported by the wasm module from `env`, with pointers into wasm memory. */
+export interface GamepadsHostFunctions {
+  /**
+   * Write the state of all gamepads to `dataPtr`, as described by `GamepadLayout` for the
+   * given protocol version.
+   */
+  getGamepads(dataPtr: number, protocolVersion: number): void;
+  /** The highest protocol version supported by `getGamepads()`. */
+  gamepadsProtocolVersion(): number;
+  /**
+   * Write the raw `u32` pressed bits, 24 `f32` axes, the `u8` mapping and, at the next 8 byte
+   * boundary, the `u64` pressed bits of buttons from index 32 of a gamepad without the standard
+   * mapping to `rawPtr`, and its UTF-8 id to `idPtr`, returning the id length or -1.
+   */
+  getGamepadRaw(
+    gamepadIdx: number,
+    rawPtr: number,
+    idPtr: number,
+    idCapacity: number,
+    protocolVersion: number,
+  ): number;
+  /**
+   * Write the UTF-8 id of a connected gamepad to `idPtr`, truncated to `idCapacity` bytes,
+   * returning its full length or -1.
+   */
+  gamepadsId(gamepadIdx: number, idPtr: number, idCapacity: number): number;
+  /** Play a rumble effect, returning an id for `gamepadsEffectStatus()`, or 0 if not played. */
+  playEffect(
+    gamepadId: number,
+    durationMs: number,
+    startDelayMs: number,
+    strongMagnitude: number,
+    weakMagnitude: number,
+    leftTrigger: number,
+    rightTrigger: number,
+  ): number;
+  /** 0 if playing, 1 if complete, 2 if preempted and 3 if failed. */
+  gamepadsEffectStatus(effectId: number): number;
+  gamepadsForgetEffect(effectId: number): void;
+  /** The supported effect types as bits, with 1 for dual-rumble and 2 for trigger-rumble. */
+  gamepadsEffects(gamepadId: number): number;
+  /**
+   * Write the `f32` x and y mouse movement, and the `u32` pressed and just pressed mouse
+   * buttons received while the pointer was locked since the last call, to `samplePtr`.
+   */
+  gamepadsTakeMouse(samplePtr: number): void;
+  /** The current time in milliseconds. */
+  gamepadsNow(): number;
+  /** Call the `gamepads_wake_streams` export after a delay, used by the `async` feature. */
+  gamepadsWakeAfter(delayMs: number): void;
+  /**
+   * 0 if available, 1 if not in a secure context, 2 if blocked, 3 if unsupported and 4 if
+   * available but no gamepad has been exposed yet, awaiting a button press.
+   */
+  gamepadsAvailability(): number;
+}
+
+/** Holds the memory and exports of the wasm instance, to be set after instantiating it. */
+export interface MemoryHolder {
+  memory?: WebAssembly.Memory;
+  exports?: WebAssembly.Exports;
+}
+
+/**
+ * Register the host functions in the `env` of a wasm import object.
+ *
+ * Without a `memoryHolder`, the memory and exports are read from the `wasm_memory` and
+ * `wasm_exports` globals of miniquad.
+ */
+export default function registerHostFunctions(
+  importObject: { env: Record<string, unknown> },
+  memoryHolder?: MemoryHolder,
+): void;
@@ -0,0 +1,356 @@
+function registerHostFunctions(importObject, wasm_memory_holder) {
+  // The highest version of the gamepad layout supported, as requested by the wasm module:
+  const PROTOCOL_VERSION = 1;
+  const MAX_GAMEPADS = 8;
+  const BYTES_PER_GAMEPAD = 152;
+  const MAPPING_OFFSET = 2;
+  const JUST_PRESSED_OFFSET = 24;
+  const TRIGGERS_OFFSET = 40;
+  const EXTRA_AXES_OFFSET = 48;
+  const MAX_EXTRA_AXES = 20;
+  const HIGH_PRESSED_OFFSET = 128;
+  // The mapping as written for the Rust side, with 0 for mappings unknown to it:
+  const mappingCode = (mapping) =>
+    ({ standard: 1, "": 2, "xr-standard": 3 })[mapping] ?? 0;
+  // How often to sample button state in between polls, to not miss quick presses:
+  const SAMPLE_INTERVAL_MS = 4;
+
+  // How should deadzones be handled in browsers?
+  // See e.g. https://github.com/ensemblejs/gamepad-api-mappings
+  // For now use a value picked from some limited data points.
+  const DEADZONE = 0.04;
+
+  // Browsers only expose gamepads once a button has been pressed on one while the page
+  // is visible, so until then games may prompt for that. The listener on 'gamepadconnected'
+  // is also necessary for gamepad listing to work, and samples again right away:
+  let gamepadsActivated = false;
+  globalThis.addEventListener?.(`gamepadconnected`, () => {
+    gamepadsActivated = true;
+    sample();
+  });
+
+  // No gamepads are reported if the Gamepad API is unavailable, such as in web
+  // workers and non-browser hosts, or if it is blocked by a permissions policy:
+  const listGamepads = () => {
+    try {
+      return globalThis.navigator?.getGamepads?.() ?? [];
+    } catch {
+      return [];
+    }
+  };
+
+  // Buttons beyond the 17 of the standard layout are reported as extra buttons:
+  const MAX_BUTTONS = 32;
+  const pressedBits = (gamepad) => {
+    let bits = 0;
+    for (const [index, button] of gamepad.buttons.entries()) {
+      if (index < MAX_BUTTONS && button.pressed) bits |= 1 << index;
+    }
+    return bits >>> 0;
+  };
+  // Buttons from index 32 as two words, for buttons 32 to 63 and 64 to 95:
+  const MAX_HIGH_BUTTONS = 96;
+  const highPressedWords = (gamepad) => {
+    const words = new Uint32Array(2);
+    for (const [index, button] of gamepad.buttons.entries()) {
+      if (index >= 32 && index < MAX_HIGH_BUTTONS && button.pressed) {
+        words[(index - 32) >> 5] |= 1 << (index % 32);
+      }
+    }
+    return words;
+  };
+
+  // Pressed bits as of the last sample, and bits pressed since the last poll:
+  const sampledBits = new Uint32Array(MAX_GAMEPADS);
+  const accumulatedBits = new Uint32Array(MAX_GAMEPADS);
+
+  // Browsers may use gamepad indices of MAX_GAMEPADS and above after many
+  // reconnects, so gamepads are kept in the first slot free when connected.
+  // The browser gamepad index of each slot:
+  const slotIndices = new Array(MAX_GAMEPADS).fill(null);
+  const gamepadSlots = () => {
+    const gamepads = listGamepads().filter((gamepad) => gamepad?.connected);
+    if (gamepads.length > 0) gamepadsActivated = true;
+    for (const [slot, index] of slotIndices.entries()) {
+      const connected = gamepads.some((gamepad) => gamepad.index === index);
+      if (index !== null && !connected) {
+        slotIndices[slot] = null;
+        sampledBits[slot] = 0;
+        accumulatedBits[slot] = 0;
+        heldOnReturnBits[slot] = 0;
+      }
+    }
+    const slots = new Array(MAX_GAMEPADS).fill(null);
+    for (const gamepad of gamepads) {
+      let slot = slotIndices.indexOf(gamepad.index);
+      if (slot === -1) slot = slotIndices.indexOf(null);
+      if (slot === -1) continue;
+      slotIndices[slot] = gamepad.index;
+      slots[slot] = gamepad;
+    }
+    updatePageActive(slots);
+    return slots;
+  };
+
+  // Browsers stop reporting gamepad input while the page is hidden or unfocused, so
+  // buttons are released and rumble is stopped then. Buttons still held when returning
+  // are ignored until released, instead of being reported as just pressed:
+  let pageActive = true;
+  const heldOnReturnBits = new Uint32Array(MAX_GAMEPADS);
+  const isPageActive = () =>
+    !globalThis.document || (!document.hidden && document.hasFocus());
+  const updatePageActive = (slots) => {
+    const active = isPageActive();
+    if (active === pageActive) return;
+    pageActive = active;
+    for (const [slot, gamepad] of slots.entries()) {
+      if (active) {
+        heldOnReturnBits[slot] = gamepad ? pressedBits(gamepad) : 0;
+      } else {
+        sampledBits[slot] = 0;
+        accumulatedBits[slot] = 0;
+        // Playing effects are then reported as preempted:
+        gamepad?.vibrationActuator?.reset?.()?.catch?.(() => {});
+      }
+    }
+  };
+  // The pressed bits reported for the gamepad in a slot:
+  const reportedBits = (slot, gamepad) => {
+    if (!pageActive) return 0;
+    const bits = pressedBits(gamepad);
+    heldOnReturnBits[slot] &= bits;
+    return (bits & ~heldOnReturnBits[slot]) >>> 0;
+  };
+
+  const sample = () => {
+    for (const [gamepad_idx, gamepad] of gamepadSlots().entries()) {
+      if (!gamepad) continue;
+      const bits = reportedBits(gamepad_idx, gamepad);
+      accumulatedBits[gamepad_idx] |= bits & ~sampledBits[gamepad_idx];
+      sampledBits[gamepad_idx] = bits;
+    }
+  };
+  setInterval(sample, SAMPLE_INTERVAL_MS);
+
+  importObject.env.gamepadsProtocolVersion = () => PROTOCOL_VERSION;
+  importObject.env.getGamepads = (wasm_memory_offset) => {
+    const gamepads = gamepadSlots();
+    // 'wasm_memory' is setup in https://github.com/not-fl3/miniquad/blob/master/js/gl.js
+    const memory = wasm_memory_holder ? wasm_memory_holder.memory : wasm_memory;
+
+    const f32 = new Float32Array(memory.buffer);
+    const u32 = new Uint32Array(memory.buffer);
+    const u8 = new Uint8Array(memory.buffer);
+
+    for (const [gamepad_idx, gamepad] of gamepads.entries()) {
+      let byteOffset =
+        wasm_memory_offset +
+        // Skip gamepads with lower index:
+        BYTES_PER_GAMEPAD * gamepad_idx +
+        // Skip the initial u8 gamepad id:
+        1;
+
+      if (!gamepad || !gamepad.connected || gamepad.mapping !== "standard") {
+        u8[byteOffset] = 0;
+        continue;
+      }
+
+      // Mark connected.
+      u8[byteOffset] = 1;
+      byteOffset += 3;
+
+      // Write u8, the mapping:
+      u8[wasm_memory_offset + BYTES_PER_GAMEPAD * gamepad_idx + MAPPING_OFFSET] =
+        mappingCode(gamepad.mapping);
+
+      // Write u32, pressed_bits:
+      const pressed_bits = reportedBits(gamepad_idx, gamepad);
+      u32[byteOffset / 4] = pressed_bits;
+      byteOffset += 4;
+
+      const axesOrCentered = pageActive ? gamepad.axes : [0, 0, 0, 0];
+      for (const [index, axes] of axesOrCentered.slice(0, 4).entries()) {
+        const sign = index === 1 || index === 3 ? -1 : 1;
+        f32[byteOffset / 4] =
+          Math.abs(axes) < DEADZONE
+            ? 0.0
+            : (sign * (axes - Math.sign(axes) * DEADZONE)) / (1 - DEADZONE);
+        byteOffset += 4;
+      }
+
+      // Write u32, just_pressed_bits, including presses sampled in between polls:
+      byteOffset =
+        wasm_memory_offset + BYTES_PER_GAMEPAD * gamepad_idx + JUST_PRESSED_OFFSET;
+      u32[byteOffset / 4] =
+        accumulatedBits[gamepad_idx] | (pressed_bits & ~sampledBits[gamepad_idx]);
+      accumulatedBits[gamepad_idx] = 0;
+      sampledBits[gamepad_idx] = pressed_bits;
+
+      // Write two f32, the analog values of the left and right triggers:
+      byteOffset =
+        wasm_memory_offset + BYTES_PER_GAMEPAD * gamepad_idx + TRIGGERS_OFFSET;
+      f32[byteOffset / 4] = pageActive ? (gamepad.buttons[6]?.value ?? 0) : 0;
+      f32[byteOffset / 4 + 1] = pageActive ? (gamepad.buttons[7]?.value ?? 0) : 0;
+
+      // Write f32 values of the axes beyond the two thumbsticks, such as of wheels and pedals:
+      byteOffset =
+        wasm_memory_offset + BYTES_PER_GAMEPAD * gamepad_idx + EXTRA_AXES_OFFSET;
+      const extraAxes = new Float32Array(MAX_EXTRA_AXES);
+      if (pageActive) extraAxes.set(gamepad.axes.slice(4, 4 + MAX_EXTRA_AXES));
+      f32.set(extraAxes, byteOffset / 4);
+
+      // Write u64, the pressed bits of buttons from index 32, as two u32:
+      byteOffset =
+        wasm_memory_offset + BYTES_PER_GAMEPAD * gamepad_idx + HIGH_PRESSED_OFFSET;
+      u32.set(
+        pageActive ? highPressedWords(gamepad) : new Uint32Array(2),
+        byteOffset / 4,
+      );
+    }
+  };
+  // Gamepads without the standard mapping are remapped on the Rust side if known:
+  const MAX_RAW_AXES = 4 + MAX_EXTRA_AXES;
+  importObject.env.getGamepadRaw = (gamepad_idx, raw_offset, id_offset, id_capacity) => {
+    const gamepad = gamepadSlots()[gamepad_idx];
+    // Raw axes have no known centered value, so while the page is inactive these
+    // gamepads are reported as disconnected instead of being released:
+    if (!gamepad || gamepad.mapping === "standard" || !pageActive) {
+      return -1;
+    }
+    const memory = wasm_memory_holder ? wasm_memory_holder.memory : wasm_memory;
+
+    new Uint32Array(memory.buffer, raw_offset, 1)[0] = pressedBits(gamepad);
+    const axes = new Float32Array(memory.buffer, raw_offset + 4, MAX_RAW_AXES);
+    axes.fill(0);
+    axes.set(gamepad.axes.slice(0, MAX_RAW_AXES));
+    const mappingOffset = raw_offset + 4 + 4 * MAX_RAW_AXES;
+    new Uint8Array(memory.buffer, mappingOffset, 1)[0] = mappingCode(gamepad.mapping);
+    // Write u64, the pressed bits of buttons from index 32, aligned after the mapping:
+    new Uint32Array(memory.buffer, mappingOffset + 4, 2).set(highPressedWords(gamepad));
+
+    const id = new TextEncoder().encode(gamepad.id).slice(0, id_capacity);
+    new Uint8Array(memory.buffer, id_offset, id_capacity).set(id);
+    return id.length;
+  };
+  // The id of a gamepad, returning its full length so that a larger buffer can be passed:
+  importObject.env.gamepadsId = (gamepad_idx, id_offset, id_capacity) => {
+    const gamepad = gamepadSlots()[gamepad_idx];
+    if (!gamepad) return -1;
+    const memory = wasm_memory_holder ? wasm_memory_holder.memory : wasm_memory;
+    const id = new TextEncoder().encode(gamepad.id);
+    new Uint8Array(memory.buffer, id_offset, id_capacity).set(
+      id.slice(0, id_capacity),
+    );
+    return id.length;
+  };
+  // Mouse input while the pointer is locked, for using the mouse as a gamepad:
+  const mouseDelta = new Float32Array(2);
+  let mouseButtons = 0;
+  let mouseJustPressed = 0;
+  const pointerLocked = () => !!globalThis.document?.pointerLockElement;
+  globalThis.addEventListener?.("mousemove", (event) => {
+    if (!pointerLocked()) return;
+    mouseDelta[0] += event.movementX;
+    mouseDelta[1] += event.movementY;
+  });
+  globalThis.addEventListener?.("mousedown", (event) => {
+    if (!pointerLocked() || event.button > 2) return;
+    mouseButtons |= 1 << event.button;
+    mouseJustPressed |= 1 << event.button;
+  });
+  globalThis.addEventListener?.("mouseup", (event) => {
+    if (!pointerLocked() || event.button > 2) return;
+    mouseButtons &= ~(1 << event.button);
+  });
+  importObject.env.gamepadsTakeMouse = (sample_offset) => {
+    const memory = wasm_memory_holder ? wasm_memory_holder.memory : wasm_memory;
+    new Float32Array(memory.buffer, sample_offset, 2).set(mouseDelta);
+    new Uint32Array(memory.buffer, sample_offset + 8, 2).set([
+      mouseButtons,
+      mouseJustPressed,
+    ]);
+    mouseDelta.fill(0);
+    mouseJustPressed = 0;
+  };
+  importObject.env.gamepadsNow = () => performance.now();
+  // Wakes the waiting event streams of the async feature, calling back into the wasm module,
+  // whose exports are read from the holder like the memory:
+  importObject.env.gamepadsWakeAfter = (delayMs) => {
+    setTimeout(() => {
+      const exports = wasm_memory_holder ? wasm_memory_holder.exports : wasm_exports;
+      exports.gamepads_wake_streams();
+    }, delayMs);
+  };
+  importObject.env.gamepadsAvailability = () => {
+    if (globalThis.isSecureContext === false) return 1;
+    if (typeof globalThis.navigator?.getGamepads !== "function") return 3;
+    try {
+      navigator.getGamepads();
+    } catch {
+      return 2;
+    }
+    return gamepadsActivated ? 0 : 4;
+  };
+  // Status of effects by id, as 0 when playing, 1 when complete, 2 when
+  // preempted and 3 when failed:
+  const effectStatuses = new Map();
+  let nextEffectId = 1;
+  importObject.env.playEffect = (
+    gamepadId,
+    duration,
+    startDelay,
+    strongMagnitude,
+    weakMagnitude,
+    leftTrigger,
+    rightTrigger,
+  ) => {
+    const actuator = gamepadSlots()[gamepadId]?.vibrationActuator;
+    if (!actuator) return 0;
+    const params = { duration, startDelay, strongMagnitude, weakMagnitude };
+    let played;
+    try {
+      // Fall back to dual-rumble if the gamepad has no trigger motors:
+      played =
+        (leftTrigger > 0 || rightTrigger > 0) &&
+        actuator.effects?.includes("trigger-rumble")
+          ? actuator.playEffect("trigger-rumble", {
+              ...params,
+              leftTrigger,
+              rightTrigger,
+            })
+          : actuator.playEffect("dual-rumble", params);
+    } catch {
+      return 0;
+    }
+
+    const effectId = nextEffectId++;
+    effectStatuses.set(effectId, 0);
+    const settle = (status) => {
+      if (effectStatuses.has(effectId)) effectStatuses.set(effectId, status);
+    };
+    Promise.resolve(played).then(
+      (result) => settle(result === "complete" ? 1 : 2),
+      () => settle(3),
+    );
+    return effectId;
+  };
+  importObject.env.gamepadsEffectStatus = (effectId) => {
+    const status = effectStatuses.get(effectId) ?? 3;
+    if (status !== 0) effectStatuses.delete(effectId);
+    return status;
+  };
+  importObject.env.gamepadsForgetEffect = (effectId) => {
+    effectStatuses.delete(effectId);
+  };
+  importObject.env.gamepadsEffects = (gamepadId) => {
+    // Not supported by some browsers, such as Firefox:
+    const actuator = gamepadSlots()[gamepadId]?.vibrationActuator;
+    if (!actuator) return 0;
+    // Older browsers only expose a single effect type:
+    const effects = actuator.effects ?? [actuator.type];
+    return (
+      (effects.includes("dual-rumble") ? 1 : 0) |
+      (effects.includes("trigger-rumble") ? 2 : 0)
+    );
+  };
+}
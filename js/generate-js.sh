@@ -4,14 +4,18 @@ set -e -u
 # Install uglify with:
 # npm install uglify-js -g
 
+# Only gamepads-src-0.2.js is checked in, the files generated from it are published using
+# GitHub Pages. The -0.1 files are kept as published for crate versions 0.1.x, generated from
+# gamepads-src-0.1.js by earlier versions of this script.
+
 PACKAGE_VERSION=$(cd .. && cargo metadata --format-version=1 --no-deps | jq '.packages[0].version')
 
-(cat - | uglifyjs --mangle eval - > gamepads-0.1.js) < gamepads-src-0.1.js
+(cat - | uglifyjs --mangle eval - > gamepads-0.2.js) < gamepads-src-0.2.js
 
-sed 's/function registerHostFunctions/export default function/' < gamepads-src-0.1.js | uglifyjs --mangle eval - > gamepads-module-0.1.js
+sed 's/function registerHostFunctions/export default function/' < gamepads-src-0.2.js | uglifyjs --mangle eval - > gamepads-module-0.2.js
 
-SOURCE_UNNAMED_FUNCTION=$(sed 's/ registerHostFunctions//' < gamepads-src-0.1.js)
-(cat - | uglifyjs --mangle toplevel - > macroquad-gamepads-0.1.js) <<EOF
+SOURCE_UNNAMED_FUNCTION=$(sed 's/ registerHostFunctions//' < gamepads-src-0.2.js)
+(cat - | uglifyjs --mangle toplevel - > macroquad-gamepads-0.2.js) <<EOF
 miniquad_add_plugin({
     name: "gamepads",
     version: $PACKAGE_VERSION,
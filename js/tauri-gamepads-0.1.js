@@ -0,0 +1,39 @@
+// Forwards the gamepads of the webview of a Tauri app to its Rust process, see src/tauri.rs.
+(() => {
+  // The command registered by the app, handled using gamepads::tauri::forward():
+  const COMMAND = "gamepads_state";
+  const invoke = (...args) =>
+    (globalThis.__TAURI_INTERNALS__ ?? globalThis.__TAURI__?.core ?? globalThis.__TAURI__)
+      ?.invoke?.(...args)
+      ?.catch?.(() => {});
+
+  const listGamepads = () => {
+    try {
+      return Array.from(navigator.getGamepads?.() ?? []);
+    } catch {
+      // Such as when blocked by a permissions policy:
+      return [];
+    }
+  };
+
+  // Only the state changed since the last frame is sent, to not invoke the command on every frame:
+  let lastSent = undefined;
+  const sample = () => {
+    const gamepads = listGamepads()
+      .filter((gamepad) => gamepad?.connected)
+      .map((gamepad) => ({
+        index: gamepad.index,
+        id: gamepad.id,
+        mapping: gamepad.mapping,
+        buttons: gamepad.buttons.map((button) => [button.pressed, button.value]),
+        axes: Array.from(gamepad.axes),
+      }));
+    const serialized = JSON.stringify(gamepads);
+    if (serialized !== lastSent) {
+      lastSent = serialized;
+      invoke(COMMAND, { gamepads });
+    }
+    requestAnimationFrame(sample);
+  };
+  requestAnimationFrame(sample);
+})();
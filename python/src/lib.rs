@@ -0,0 +1,205 @@
+//! Python bindings of the [gamepads] crate, built as the `gamepads` Python module using maturin.
+
+use gamepads::GamepadId;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+/// A button of a gamepad, in the standard layout.
+#[pyclass(eq, eq_int, frozen, hash, from_py_object, module = "gamepads")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Button {
+    ActionDown,
+    ActionRight,
+    ActionLeft,
+    ActionUp,
+    FrontLeftUpper,
+    FrontRightUpper,
+    FrontLeftLower,
+    FrontRightLower,
+    LeftCenterCluster,
+    RightCenterCluster,
+    LeftStick,
+    RightStick,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    Mode,
+}
+
+impl From<Button> for gamepads::Button {
+    fn from(button: Button) -> Self {
+        match button {
+            Button::ActionDown => Self::ActionDown,
+            Button::ActionRight => Self::ActionRight,
+            Button::ActionLeft => Self::ActionLeft,
+            Button::ActionUp => Self::ActionUp,
+            Button::FrontLeftUpper => Self::FrontLeftUpper,
+            Button::FrontRightUpper => Self::FrontRightUpper,
+            Button::FrontLeftLower => Self::FrontLeftLower,
+            Button::FrontRightLower => Self::FrontRightLower,
+            Button::LeftCenterCluster => Self::LeftCenterCluster,
+            Button::RightCenterCluster => Self::RightCenterCluster,
+            Button::LeftStick => Self::LeftStick,
+            Button::RightStick => Self::RightStick,
+            Button::DPadUp => Self::DPadUp,
+            Button::DPadDown => Self::DPadDown,
+            Button::DPadLeft => Self::DPadLeft,
+            Button::DPadRight => Self::DPadRight,
+            Button::Mode => Self::Mode,
+        }
+    }
+}
+
+impl From<gamepads::Button> for Button {
+    fn from(button: gamepads::Button) -> Self {
+        match button {
+            gamepads::Button::ActionDown => Self::ActionDown,
+            gamepads::Button::ActionRight => Self::ActionRight,
+            gamepads::Button::ActionLeft => Self::ActionLeft,
+            gamepads::Button::ActionUp => Self::ActionUp,
+            gamepads::Button::FrontLeftUpper => Self::FrontLeftUpper,
+            gamepads::Button::FrontRightUpper => Self::FrontRightUpper,
+            gamepads::Button::FrontLeftLower => Self::FrontLeftLower,
+            gamepads::Button::FrontRightLower => Self::FrontRightLower,
+            gamepads::Button::LeftCenterCluster => Self::LeftCenterCluster,
+            gamepads::Button::RightCenterCluster => Self::RightCenterCluster,
+            gamepads::Button::LeftStick => Self::LeftStick,
+            gamepads::Button::RightStick => Self::RightStick,
+            gamepads::Button::DPadUp => Self::DPadUp,
+            gamepads::Button::DPadDown => Self::DPadDown,
+            gamepads::Button::DPadLeft => Self::DPadLeft,
+            gamepads::Button::DPadRight => Self::DPadRight,
+            gamepads::Button::Mode => Self::Mode,
+        }
+    }
+}
+
+/// The state of a gamepad as of the last `Gamepads.poll()`.
+#[pyclass(frozen, skip_from_py_object, module = "gamepads")]
+#[derive(Clone, Copy)]
+struct Gamepad(gamepads::Gamepad);
+
+#[pymethods]
+impl Gamepad {
+    /// The id of the gamepad, stable while it is connected.
+    #[getter]
+    fn id(&self) -> u8 {
+        self.0.id().value()
+    }
+
+    /// Whether a button is currently pressed.
+    fn is_pressed(&self, button: Button) -> bool {
+        self.0.is_currently_pressed(button.into())
+    }
+
+    /// Whether a button was pressed since the last poll.
+    fn is_just_pressed(&self, button: Button) -> bool {
+        self.0.is_just_pressed(button.into())
+    }
+
+    /// Whether a button was released since the last poll.
+    fn is_just_released(&self, button: Button) -> bool {
+        self.0.is_just_released(button.into())
+    }
+
+    /// The currently pressed buttons.
+    fn pressed_buttons(&self) -> Vec<Button> {
+        self.0.all_currently_pressed().map(Button::from).collect()
+    }
+
+    /// The `(x, y)` position of the left thumbstick, with y positive upwards.
+    #[getter]
+    fn left_stick(&self) -> (f32, f32) {
+        self.0.left_stick()
+    }
+
+    /// The `(x, y)` position of the right thumbstick, with y positive upwards.
+    #[getter]
+    fn right_stick(&self) -> (f32, f32) {
+        self.0.right_stick()
+    }
+
+    /// The value of the left trigger in the range `[0.0, 1.0]`.
+    #[getter]
+    fn left_trigger(&self) -> f32 {
+        self.0.left_trigger()
+    }
+
+    /// The value of the right trigger in the range `[0.0, 1.0]`.
+    #[getter]
+    fn right_trigger(&self) -> f32 {
+        self.0.right_trigger()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Gamepad(id={})", self.id())
+    }
+}
+
+/// The connected gamepads, updated by `poll()`.
+#[pyclass(unsendable, module = "gamepads")]
+struct Gamepads(gamepads::Gamepads);
+
+/// The gamepad id of a Python integer.
+fn gamepad_id(id: u8) -> PyResult<GamepadId> {
+    GamepadId::new(id).ok_or_else(|| PyValueError::new_err(format!("invalid gamepad id: {id}")))
+}
+
+#[pymethods]
+impl Gamepads {
+    #[new]
+    fn new() -> Self {
+        Self(gamepads::Gamepads::new())
+    }
+
+    /// Update the state of the gamepads, to be called once per frame or tick.
+    fn poll(&mut self) {
+        self.0.poll();
+    }
+
+    /// All connected gamepads.
+    fn all(&self) -> Vec<Gamepad> {
+        self.0.all().map(Gamepad).collect()
+    }
+
+    /// A connected gamepad by id, or `None` if not connected.
+    fn get(&self, id: u8) -> PyResult<Option<Gamepad>> {
+        Ok(self.0.get(gamepad_id(id)?).map(Gamepad))
+    }
+
+    /// The name of a gamepad, or `None` if not known.
+    fn name(&self, id: u8) -> PyResult<Option<String>> {
+        Ok(self.0.name(gamepad_id(id)?))
+    }
+
+    /// Rumble a gamepad, with magnitudes in the range `[0.0, 1.0]`.
+    #[pyo3(signature = (id, duration_ms, start_delay_ms = 0, strong_magnitude = 1.0, weak_magnitude = 1.0))]
+    fn rumble(
+        &mut self,
+        id: u8,
+        duration_ms: u32,
+        start_delay_ms: u32,
+        strong_magnitude: f32,
+        weak_magnitude: f32,
+    ) -> PyResult<()> {
+        self.0.rumble(
+            gamepad_id(id)?,
+            duration_ms,
+            start_delay_ms,
+            strong_magnitude,
+            weak_magnitude,
+        );
+        Ok(())
+    }
+}
+
+/// Access information about connected gamepads.
+#[pymodule]
+#[pyo3(name = "gamepads")]
+fn gamepads_module(module: &Bound<'_, PyModule>) -> PyResult<()> {
+    module.add_class::<Button>()?;
+    module.add_class::<Gamepad>()?;
+    module.add_class::<Gamepads>()?;
+    Ok(())
+}
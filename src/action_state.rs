@@ -0,0 +1,204 @@
+use crate::{Button, Gamepad};
+
+/// The input of a gamepad which an action can be bound to in an [InputMap].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ActionInput {
+    /// A button, with the value `1.0` while pressed.
+    Button(Button),
+    /// The horizontal axis of the left thumbstick, positive to the right.
+    LeftStickX,
+    /// The vertical axis of the left thumbstick, positive upwards.
+    LeftStickY,
+    /// The horizontal axis of the right thumbstick, positive to the right.
+    RightStickX,
+    /// The vertical axis of the right thumbstick, positive upwards.
+    RightStickY,
+    /// The left analog trigger.
+    LeftTrigger,
+    /// The right analog trigger.
+    RightTrigger,
+}
+
+impl ActionInput {
+    /// The value of the input on a gamepad.
+    const fn value(self, gamepad: &Gamepad) -> f32 {
+        match self {
+            Self::Button(button) => {
+                if gamepad.is_currently_pressed(button) {
+                    1.
+                } else {
+                    0.
+                }
+            }
+            Self::LeftStickX => gamepad.left_stick_x(),
+            Self::LeftStickY => gamepad.left_stick_y(),
+            Self::RightStickX => gamepad.right_stick_x(),
+            Self::RightStickY => gamepad.right_stick_y(),
+            Self::LeftTrigger => gamepad.left_trigger(),
+            Self::RightTrigger => gamepad.right_trigger(),
+        }
+    }
+}
+
+/// The bindings of the actions of a game, such as an enum of its own, to the inputs of gamepads,
+/// in the spirit of the `InputMap` of leafwing-input-manager.
+///
+/// An action may be bound to several inputs, and several actions to the same input. The state
+/// of the actions is read from a gamepad using [ActionState::update()].
+///
+/// ```
+/// use gamepads::{ActionInput, ActionState, Button, Gamepads, InputMap};
+///
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// enum Action {
+///     Jump,
+///     Accelerate,
+/// }
+///
+/// let input_map = InputMap::new()
+///     .with_binding(Action::Jump, ActionInput::Button(Button::ActionDown))
+///     .with_binding(Action::Accelerate, ActionInput::RightTrigger);
+/// let mut action_state = ActionState::new();
+///
+/// let mut gamepads = Gamepads::new();
+/// loop {
+///     # break;
+///     gamepads.poll();
+///     if let Some(gamepad) = gamepads.all().next() {
+///         action_state.update(&input_map, &gamepad);
+///         if action_state.just_pressed(Action::Jump) {
+///             println!("Jump!");
+///         }
+///         println!("Accelerating by {}", action_state.value(Action::Accelerate));
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputMap<A> {
+    bindings: Vec<(A, ActionInput)>,
+    press_threshold: f32,
+}
+
+impl<A> Default for InputMap<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A> InputMap<A> {
+    /// An input map without any bindings.
+    pub const fn new() -> Self {
+        Self {
+            bindings: Vec::new(),
+            press_threshold: 0.5,
+        }
+    }
+
+    /// Bind an action to an input, in addition to the inputs it is already bound to.
+    pub fn with_binding(mut self, action: A, input: ActionInput) -> Self {
+        self.bindings.push((action, input));
+        self
+    }
+
+    /// Set how far an axis needs to be moved for an action bound to it to count as pressed,
+    /// `0.5` by default.
+    pub const fn with_press_threshold(mut self, press_threshold: f32) -> Self {
+        self.press_threshold = press_threshold;
+        self
+    }
+}
+
+/// The state of an action, see [ActionState].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct ActionData {
+    pressed: bool,
+    just_pressed: bool,
+    just_released: bool,
+    value: f32,
+}
+
+/// Whether the actions of an [InputMap] are pressed, and their values, as read from a gamepad on
+/// each poll, in the spirit of the `ActionState` of leafwing-input-manager.
+///
+/// Actions not bound to any input are never pressed and have the value `0.0`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActionState<A> {
+    actions: Vec<(A, ActionData)>,
+}
+
+impl<A> Default for ActionState<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A> ActionState<A> {
+    /// An action state without any pressed actions.
+    pub const fn new() -> Self {
+        Self {
+            actions: Vec::new(),
+        }
+    }
+}
+
+impl<A: Copy + PartialEq> ActionState<A> {
+    /// Update the actions from the inputs of a gamepad, once after each
+    /// [Gamepads::poll()](crate::Gamepads::poll).
+    ///
+    /// The value of an action is the value of its input moved the furthest, and it is pressed if
+    /// any of its buttons are pressed or axes are moved beyond the press threshold.
+    pub fn update(&mut self, input_map: &InputMap<A>, gamepad: &Gamepad) {
+        let mut current: Vec<(A, ActionData)> = Vec::new();
+        for &(action, input) in &input_map.bindings {
+            let value = input.value(gamepad);
+            let pressed = value.abs() >= input_map.press_threshold.max(f32::EPSILON);
+            let idx = current
+                .iter()
+                .position(|&(a, _)| a == action)
+                .unwrap_or_else(|| {
+                    current.push((action, ActionData::default()));
+                    current.len() - 1
+                });
+            let data = &mut current[idx].1;
+            data.pressed |= pressed;
+            if value.abs() > data.value.abs() {
+                data.value = value;
+            }
+        }
+        for (action, data) in &mut current {
+            let was_pressed = self.pressed(*action);
+            data.just_pressed = data.pressed && !was_pressed;
+            data.just_released = !data.pressed && was_pressed;
+        }
+        self.actions = current;
+    }
+
+    fn data(&self, action: A) -> ActionData {
+        self.actions
+            .iter()
+            .find(|(a, _)| *a == action)
+            .map_or_else(ActionData::default, |&(_, data)| data)
+    }
+
+    /// Check if an action is currently pressed.
+    pub fn pressed(&self, action: A) -> bool {
+        self.data(action).pressed
+    }
+
+    /// Check if an action was pressed in the last update.
+    pub fn just_pressed(&self, action: A) -> bool {
+        self.data(action).just_pressed
+    }
+
+    /// Check if an action was released in the last update.
+    pub fn just_released(&self, action: A) -> bool {
+        self.data(action).just_released
+    }
+
+    /// The value of an action, in the range `[-1.0, 1.0]` for thumbsticks, `[0.0, 1.0]` for
+    /// triggers and `0.0` or `1.0` for buttons.
+    pub fn value(&self, action: A) -> f32 {
+        self.data(action).value
+    }
+}
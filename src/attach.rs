@@ -0,0 +1,131 @@
+use crate::{Button, Gamepad, GamepadId, MAX_BUTTONS};
+use gamepads_core::MAX_GAMEPADS;
+
+/// Where the buttons and axes of a unit are reported on the gamepad it is attached to.
+#[derive(Clone, Copy)]
+struct Attachment {
+    primary_id: GamepadId,
+    first_axis: usize,
+    first_button: u8,
+}
+
+/// Bits of the buttons beyond [Button::Mode] in the pressed bits.
+const EXTRA_BITS: u32 = u32::MAX << (Button::Mode as u32 + 1);
+
+/// The units attached to other gamepads, indexed by the unit.
+pub struct Units {
+    attachments: [Option<Attachment>; MAX_GAMEPADS],
+}
+
+impl Default for Units {
+    fn default() -> Self {
+        Self {
+            attachments: [None; MAX_GAMEPADS],
+        }
+    }
+}
+
+impl crate::Gamepads {
+    /// Attach a separate unit, such as pedals or a throttle, to the wheel or flight stick it is
+    /// used with, so that they are reported as one gamepad.
+    ///
+    /// The axes of the unit, in the order of [Gamepad::left_stick()], [Gamepad::right_stick()]
+    /// and [Gamepad::extra_axis()] from 4, are reported as the extra axes of the primary gamepad
+    /// from `first_axis`, and its buttons by their index in the `buttons` array of the Gamepad API
+    /// as the extra buttons of the primary gamepad from `first_button`, see
+    /// [Gamepad::is_extra_button_pressed()]. Axes and buttons beyond [MAX_EXTRA_AXES](crate::MAX_EXTRA_AXES)
+    /// and [MAX_BUTTONS] are not reported, so these should be past the axes and buttons of the
+    /// primary gamepad itself.
+    ///
+    /// The unit is no longer reported as a gamepad of its own, until detached using
+    /// [Gamepads::detach_unit()], or until it or the primary gamepad disconnects. Gamepads which
+    /// are units or have units attached themselves cannot be attached.
+    ///
+    /// ```no_run
+    /// use gamepads::{DeviceKind, Gamepads};
+    ///
+    /// let mut gamepads = Gamepads::new();
+    /// gamepads.poll();
+    /// let ids: Vec<_> = gamepads.all().map(|gamepad| gamepad.id()).collect();
+    /// let wheel = ids.iter().find(|&&id| gamepads.device_kind(id) == DeviceKind::Wheel);
+    /// let pedals = ids.iter().find(|&&id| gamepads.device_kind(id) == DeviceKind::Pedals);
+    /// if let (Some(&wheel), Some(&pedals)) = (wheel, pedals) {
+    ///     // Report the axes of the pedals from axis 8 of the wheel, and their buttons from 64:
+    ///     gamepads.attach_unit(pedals, wheel, 8, 64);
+    /// }
+    /// ```
+    pub fn attach_unit(
+        &mut self,
+        unit_id: GamepadId,
+        primary_id: GamepadId,
+        first_axis: usize,
+        first_button: u8,
+    ) {
+        let attachments = &mut self.units.attachments;
+        let is_primary = |id: GamepadId| attachments.iter().flatten().any(|a| a.primary_id == id);
+        if unit_id == primary_id
+            || attachments[usize::from(primary_id.0)].is_some()
+            || is_primary(unit_id)
+        {
+            return;
+        }
+        attachments[usize::from(unit_id.0)] = Some(Attachment {
+            primary_id,
+            first_axis,
+            first_button,
+        });
+    }
+
+    /// Detach a unit attached using [Gamepads::attach_unit()], reporting it as a gamepad of its
+    /// own again.
+    pub const fn detach_unit(&mut self, unit_id: GamepadId) {
+        self.units.attachments[unit_id.0 as usize] = None;
+    }
+
+    /// Merge the state of the attached units into the gamepads they are attached to, detaching
+    /// units of which it or the primary gamepad has disconnected.
+    pub(crate) fn merge_units(&mut self, gamepads: &mut [Gamepad; MAX_GAMEPADS]) {
+        for (unit_idx, attachment) in self.units.attachments.iter_mut().enumerate() {
+            let Some(Attachment {
+                primary_id,
+                first_axis,
+                first_button,
+            }) = *attachment
+            else {
+                continue;
+            };
+            let primary_idx = usize::from(primary_id.0);
+            let unit = gamepads[unit_idx];
+            if !unit.connected || !gamepads[primary_idx].connected {
+                *attachment = None;
+                continue;
+            }
+
+            let primary = &mut gamepads[primary_idx];
+            for (index, &value) in (first_axis..).zip(unit.axes.iter().chain(&unit.extra_axes)) {
+                primary.set_extra_axis(index, value);
+            }
+            // All buttons of the unit as one set of bits, moved to start from first_button:
+            let shift = |bits: u32, high_bits: u64| {
+                let all_bits = u128::from(bits) | (u128::from(high_bits) << 32);
+                let shifted = if usize::from(first_button) < MAX_BUTTONS {
+                    all_bits << first_button
+                } else {
+                    0
+                };
+                (shifted as u32 & EXTRA_BITS, (shifted >> 32) as u64)
+            };
+            let (bits, high_bits) = shift(unit.pressed_bits, unit.high_pressed_bits);
+            primary.pressed_bits |= bits;
+            primary.high_pressed_bits |= high_bits;
+            let (bits, high_bits) = shift(unit.just_pressed_bits, unit.high_just_pressed_bits);
+            primary.just_pressed_bits |= bits;
+            primary.high_just_pressed_bits |= high_bits;
+
+            gamepads[unit_idx].set_connected(false);
+            if let Some(axes_ms) = self.raw_timestamps[unit_idx].axes_ms() {
+                self.raw_timestamps[primary_idx].record_axes(axes_ms);
+            }
+        }
+    }
+}
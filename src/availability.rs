@@ -0,0 +1,98 @@
+/// Whether gamepads are available, and if not, why.
+///
+/// Obtained using [Gamepads::availability()](crate::Gamepads::availability).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Availability {
+    /// At least one gamepad is connected.
+    Available,
+    /// Gamepads are supported, but none is connected.
+    ///
+    /// Note that browsers only report a gamepad after one of its buttons has been pressed
+    /// while the page is visible, see [Gamepads::needs_user_gesture()](crate::Gamepads::needs_user_gesture).
+    NoDevices,
+    /// Gamepads are not supported on this platform, or by this browser.
+    Unsupported,
+    /// The platform input subsystem could not be initialized, such as when running in a
+    /// container or on a headless server without udev.
+    BackendFailed,
+    /// The web page is not served from a secure context (such as `https://` or `localhost`),
+    /// which browsers require for the Gamepad API.
+    InsecureContext,
+    /// The Gamepad API is blocked by a permissions policy, such as in a cross-origin iframe
+    /// without `allow="gamepad"`.
+    BlockedByPermissionsPolicy,
+}
+
+impl From<&crate::GamepadsError> for Availability {
+    fn from(error: &crate::GamepadsError) -> Self {
+        match error {
+            crate::GamepadsError::Unsupported => Self::Unsupported,
+            crate::GamepadsError::BackendInit(_) => Self::BackendFailed,
+        }
+    }
+}
+
+impl crate::Gamepads {
+    /// Whether gamepads are available, and if not, why.
+    ///
+    /// Useful for explaining to users why no gamepads show up.
+    pub fn availability(&self) -> Availability {
+        if let Some(failure) = self.backend_failure {
+            return failure;
+        }
+        if self.custom_backend.is_none() {
+            #[cfg(backend = "web_bindgen")]
+            if let Some(unavailable) = crate::backend_web_bindgen::availability() {
+                return unavailable;
+            }
+            #[cfg(backend = "web_direct")]
+            match unsafe { crate::backend_web_direct::gamepadsAvailability() } {
+                1 => return Availability::InsecureContext,
+                2 => return Availability::BlockedByPermissionsPolicy,
+                3 => return Availability::Unsupported,
+                _ => {}
+            }
+            // Without a backend for the target, there is no way to read gamepads:
+            #[cfg(backend = "none")]
+            return Availability::Unsupported;
+        }
+        if self.all().next().is_some() {
+            Availability::Available
+        } else {
+            Availability::NoDevices
+        }
+    }
+
+    /// Whether the browser has not exposed any gamepad yet, which it only does once a button
+    /// has been pressed on one while the page is visible.
+    ///
+    /// Useful for prompting "Press a button on your controller" instead of appearing broken.
+    /// Gamepads show up as soon as the browser exposes them. Always `false` outside the web,
+    /// and when the Gamepad API is unavailable as reported by [Gamepads::availability()](crate::Gamepads::availability).
+    pub fn needs_user_gesture(&self) -> bool {
+        if self.backend_failure.is_some() || self.custom_backend.is_some() {
+            return false;
+        }
+        #[cfg(backend = "emscripten")]
+        {
+            crate::backend_emscripten::needs_user_gesture()
+        }
+        #[cfg(backend = "web_bindgen")]
+        {
+            crate::backend_web_bindgen::needs_user_gesture()
+        }
+        #[cfg(backend = "web_direct")]
+        {
+            unsafe { crate::backend_web_direct::gamepadsAvailability() == 4 }
+        }
+        #[cfg(not(any(
+            backend = "emscripten",
+            backend = "web_bindgen",
+            backend = "web_direct"
+        )))]
+        {
+            false
+        }
+    }
+}
@@ -0,0 +1,539 @@
+use crate::event::RawTimestamps;
+use crate::{Button, ForceFeedback, Gamepad, GamepadId, MAX_GAMEPADS};
+
+/// What a gamepad supports, as far as its backend can tell.
+///
+/// Obtained using [Gamepads::capabilities()](crate::Gamepads::capabilities).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// Whether the gamepad can rumble using [Gamepads::rumble()](crate::Gamepads::rumble).
+    pub rumble: bool,
+    /// Whether the gamepad can vibrate its triggers using
+    /// [Gamepads::rumble_triggers()](crate::Gamepads::rumble_triggers).
+    pub trigger_rumble: bool,
+    /// Whether the gamepad reports motion sensor readings using
+    /// [Gamepads::motion()](crate::Gamepads::motion).
+    pub motion: bool,
+    /// Whether the gamepad has a light bar whose color can be set using
+    /// [Gamepads::set_led()](crate::Gamepads::set_led).
+    pub led: bool,
+    /// Whether the gamepad is a racing wheel which can play force feedback effects using
+    /// [Gamepads::set_force_feedback()](crate::Gamepads::set_force_feedback).
+    pub force_feedback: bool,
+}
+
+/// Readings of the motion sensors of a gamepad.
+///
+/// Obtained using [Gamepads::motion()](crate::Gamepads::motion). Axes are relative to the gamepad
+/// held level in front of the player, with x to the right, y upwards and z towards the player.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Motion {
+    /// The acceleration along each axis in m/s², including gravity.
+    pub acceleration: [f32; 3],
+    /// The rate of rotation around each axis in radians per second, counter-clockwise when
+    /// looking along the axis towards its origin.
+    pub rotation_rate: [f32; 3],
+}
+
+/// A source of gamepad state, replacing the platform backend.
+///
+/// Implement this to integrate gamepads on platforms not supported by this crate, or from
+/// an in-house device stack, and register it using [Gamepads::set_backend()](crate::Gamepads::set_backend).
+/// Platforms delivering gamepad state through callbacks instead of being polled can push it
+/// into a [PortBackend](crate::PortBackend) instead.
+///
+/// ```
+/// use gamepads::{BackendState, Button, GamepadBackend, Gamepads};
+///
+/// /// A backend with a single gamepad holding down a button.
+/// struct HoldButton;
+///
+/// impl GamepadBackend for HoldButton {
+///     fn poll(&mut self, state: &mut BackendState) {
+///         if state.ids().next().is_none() {
+///             if let Some(gamepad_id) = state.connect() {
+///                 state.press(gamepad_id, Button::ActionDown);
+///             }
+///         }
+///     }
+/// }
+///
+/// let mut gamepads = Gamepads::new();
+/// gamepads.set_backend(HoldButton);
+/// gamepads.poll();
+/// assert!(gamepads.all().all(|gamepad| gamepad.is_currently_pressed(Button::ActionDown)));
+/// ```
+pub trait GamepadBackend: Send {
+    /// Update the state of gamepads, called on each [Gamepads::poll()](crate::Gamepads::poll).
+    ///
+    /// State persists between polls, so only changes need to be written.
+    fn poll(&mut self, state: &mut BackendState);
+
+    /// Rumble a gamepad, as requested by [Gamepads::rumble()](crate::Gamepads::rumble).
+    ///
+    /// Does nothing by default.
+    fn rumble(
+        &mut self,
+        _gamepad_id: GamepadId,
+        _duration_ms: u32,
+        _start_delay_ms: u32,
+        _strong_magnitude: f32,
+        _weak_magnitude: f32,
+    ) {
+    }
+
+    /// What a gamepad supports, as reported by [Gamepads::capabilities()](crate::Gamepads::capabilities).
+    ///
+    /// Reports no capabilities by default.
+    fn capabilities(&self, _gamepad_id: GamepadId) -> Capabilities {
+        Capabilities::default()
+    }
+
+    /// The name of a gamepad, as reported by [Gamepads::name()](crate::Gamepads::name).
+    ///
+    /// Reports no name by default.
+    fn name(&self, _gamepad_id: GamepadId) -> Option<String> {
+        None
+    }
+
+    /// The USB vendor and product ids of a gamepad, as reported by
+    /// [Gamepads::vendor_product_ids()](crate::Gamepads::vendor_product_ids).
+    ///
+    /// Reports no ids by default.
+    fn vendor_product_ids(&self, _gamepad_id: GamepadId) -> Option<(u16, u16)> {
+        None
+    }
+
+    /// The battery of a gamepad, as reported by [Gamepads::battery()](crate::Gamepads::battery).
+    ///
+    /// Reports no battery by default.
+    fn battery(&self, _gamepad_id: GamepadId) -> Option<crate::Battery> {
+        None
+    }
+
+    /// The motion sensor readings of a gamepad, as reported by [Gamepads::motion()](crate::Gamepads::motion).
+    ///
+    /// Reports no readings by default.
+    fn motion(&self, _gamepad_id: GamepadId) -> Option<Motion> {
+        None
+    }
+
+    /// Set the color of the light bar of a gamepad, as requested by
+    /// [Gamepads::set_led()](crate::Gamepads::set_led).
+    ///
+    /// Does nothing by default.
+    fn set_led(&mut self, _gamepad_id: GamepadId, _color: (u8, u8, u8)) {}
+
+    /// Play a force feedback effect on a racing wheel until replaced by one of the same kind,
+    /// as requested by [Gamepads::set_force_feedback()](crate::Gamepads::set_force_feedback).
+    ///
+    /// Does nothing by default.
+    fn set_force_feedback(&mut self, _gamepad_id: GamepadId, _effect: ForceFeedback) {}
+
+    /// Stop all force feedback effects of a racing wheel, as requested by
+    /// [Gamepads::stop_force_feedback()](crate::Gamepads::stop_force_feedback).
+    ///
+    /// Does nothing by default.
+    fn stop_force_feedback(&mut self, _gamepad_id: GamepadId) {}
+}
+
+/// A backend reporting no gamepads.
+///
+/// Used by [Gamepads::new()](crate::Gamepads::new) when the platform backend cannot be
+/// initialized. Dedicated servers sharing code with clients can also set it explicitly using
+/// [Gamepads::set_backend()](crate::Gamepads::set_backend) to never read gamepads.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NullBackend;
+
+impl GamepadBackend for NullBackend {
+    fn poll(&mut self, _state: &mut BackendState) {}
+}
+
+/// Raw gamepad state written by a [GamepadBackend].
+pub struct BackendState<'a> {
+    pub(crate) gamepads: &'a mut [Gamepad; MAX_GAMEPADS],
+    pub(crate) raw_timestamps: &'a mut [RawTimestamps; MAX_GAMEPADS],
+}
+
+impl BackendState<'_> {
+    /// The ids of connected gamepads.
+    pub fn ids(&self) -> impl Iterator<Item = GamepadId> + '_ {
+        self.gamepads
+            .iter()
+            .filter(|gamepad| gamepad.connected)
+            .map(|gamepad| gamepad.id)
+    }
+
+    /// Connect a gamepad, returning its id or `None` if the maximum number of gamepads are connected.
+    pub fn connect(&mut self) -> Option<GamepadId> {
+        let gamepad = self
+            .gamepads
+            .iter_mut()
+            .find(|gamepad| !gamepad.connected)?;
+        gamepad.connected = true;
+        Some(gamepad.id)
+    }
+
+    /// Disconnect a gamepad, releasing its buttons and centering its thumbsticks.
+    pub const fn disconnect(&mut self, gamepad_id: GamepadId) {
+        let gamepad = &mut self.gamepads[gamepad_id.0 as usize];
+        gamepad.connected = false;
+        gamepad.pressed_bits = 0;
+        gamepad.axes = [0.; 4];
+        gamepad.triggers = [0.; 2];
+        gamepad.extra_axes = [0.; crate::MAX_EXTRA_AXES];
+        gamepad.high_pressed_bits = 0;
+    }
+
+    /// Press a button, which is reported as just pressed in this poll.
+    pub fn press(&mut self, gamepad_id: GamepadId, button: Button) {
+        let idx = gamepad_id.0 as usize;
+        self.gamepads[idx].pressed_bits |= 1 << (button as u32);
+        self.gamepads[idx].just_pressed_bits |= 1 << (button as u32);
+        self.raw_timestamps[idx].record_press(button as usize, crate::time::now_ms());
+    }
+
+    /// Release a button.
+    pub fn release(&mut self, gamepad_id: GamepadId, button: Button) {
+        let idx = gamepad_id.0 as usize;
+        self.gamepads[idx].pressed_bits &= !(1 << (button as u32));
+        self.raw_timestamps[idx].record_release(button as usize, crate::time::now_ms());
+    }
+
+    /// Press a button beyond [Button::Mode] by its index from 17 up to
+    /// [MAX_BUTTONS](crate::MAX_BUTTONS), see
+    /// [Gamepad::is_extra_button_pressed()](crate::Gamepad::is_extra_button_pressed).
+    /// Indices out of range are ignored.
+    pub fn press_extra(&mut self, gamepad_id: GamepadId, index: u8) {
+        let idx = gamepad_id.0 as usize;
+        self.gamepads[idx].press_extra(index);
+        // Timestamps are only recorded for the extra buttons in the first 32 bits:
+        if (17..32).contains(&index) {
+            self.raw_timestamps[idx].record_press(usize::from(index), crate::time::now_ms());
+        }
+    }
+
+    /// Release a button beyond [Button::Mode] by its index from 17 up to
+    /// [MAX_BUTTONS](crate::MAX_BUTTONS). Indices out of range are ignored.
+    pub fn release_extra(&mut self, gamepad_id: GamepadId, index: u8) {
+        let idx = gamepad_id.0 as usize;
+        self.gamepads[idx].release_extra(index);
+        if (17..32).contains(&index) {
+            self.raw_timestamps[idx].record_release(usize::from(index), crate::time::now_ms());
+        }
+    }
+
+    /// Set the `(x, y)` position of the left thumbstick.
+    pub fn set_left_stick(&mut self, gamepad_id: GamepadId, (x, y): (f32, f32)) {
+        let idx = gamepad_id.0 as usize;
+        self.gamepads[idx].axes[0] = x;
+        self.gamepads[idx].axes[1] = y;
+        self.raw_timestamps[idx].record_axes(crate::time::now_ms());
+    }
+
+    /// Set the `(x, y)` position of the right thumbstick.
+    pub fn set_right_stick(&mut self, gamepad_id: GamepadId, (x, y): (f32, f32)) {
+        let idx = gamepad_id.0 as usize;
+        self.gamepads[idx].axes[2] = x;
+        self.gamepads[idx].axes[3] = y;
+        self.raw_timestamps[idx].record_axes(crate::time::now_ms());
+    }
+
+    /// Set how far the left and right triggers are pressed, in the range `[0.0, 1.0]`.
+    pub fn set_triggers(&mut self, gamepad_id: GamepadId, (left, right): (f32, f32)) {
+        let idx = gamepad_id.0 as usize;
+        self.gamepads[idx].triggers[0] = left;
+        self.gamepads[idx].triggers[1] = right;
+        self.raw_timestamps[idx].record_axes(crate::time::now_ms());
+    }
+
+    /// Set the value of an axis beyond the two thumbsticks, by its index from 4 up to
+    /// `4 + `[MAX_EXTRA_AXES](crate::MAX_EXTRA_AXES), see
+    /// [Gamepad::extra_axis()](crate::Gamepad::extra_axis). Indices out of range are ignored.
+    pub fn set_extra_axis(&mut self, gamepad_id: GamepadId, index: usize, value: f32) {
+        let idx = gamepad_id.0 as usize;
+        self.gamepads[idx].set_extra_axis(index, value);
+        self.raw_timestamps[idx].record_axes(crate::time::now_ms());
+    }
+}
+
+impl crate::Gamepads {
+    /// Use a custom backend instead of the platform backend.
+    ///
+    /// See [GamepadBackend] for more information.
+    pub fn set_backend(&mut self, backend: impl GamepadBackend + 'static) {
+        self.custom_backend = Some(Box::new(backend));
+        self.reset_raw_state();
+    }
+
+    /// Stop using a custom backend set by [Gamepads::set_backend()](crate::Gamepads::set_backend),
+    /// returning to the platform backend.
+    pub fn remove_backend(&mut self) {
+        if self.custom_backend.take().is_some() {
+            self.reset_raw_state();
+            #[cfg(backend = "gilrs")]
+            self.setup_initially_connected_gilrs();
+        }
+    }
+
+    fn reset_raw_state(&mut self) {
+        for gamepad in self.gamepads.iter_mut() {
+            gamepad.connected = false;
+            gamepad.pressed_bits = 0;
+            gamepad.axes = [0.; 4];
+            gamepad.triggers = [0.; 2];
+            gamepad.extra_axes = [0.; crate::MAX_EXTRA_AXES];
+            gamepad.just_pressed_bits = 0;
+            gamepad.high_pressed_bits = 0;
+            gamepad.high_just_pressed_bits = 0;
+        }
+    }
+
+    /// What a gamepad supports, as far as the backend can tell.
+    pub fn capabilities(&self, gamepad_id: GamepadId) -> Capabilities {
+        if let Some(backend) = &self.custom_backend {
+            return backend.capabilities(gamepad_id);
+        }
+        #[cfg(all(
+            feature = "playstation",
+            any(target_os = "linux", target_os = "macos", target_os = "windows")
+        ))]
+        if self.is_playstation(gamepad_id) {
+            return Capabilities {
+                rumble: true,
+                trigger_rumble: false,
+                motion: true,
+                led: true,
+                force_feedback: false,
+            };
+        }
+        #[cfg(backend = "gilrs")]
+        {
+            self.capabilities_gilrs(gamepad_id)
+        }
+        #[cfg(backend = "web_bindgen")]
+        {
+            crate::backend_web_bindgen::capabilities(gamepad_id.0)
+        }
+        #[cfg(backend = "web_direct")]
+        {
+            crate::backend_web_direct::capabilities(gamepad_id.0)
+        }
+        #[cfg(backend = "evdev")]
+        {
+            crate::backend_evdev::capabilities(&self.evdev, gamepad_id)
+        }
+        #[cfg(backend = "game_controller")]
+        {
+            crate::backend_game_controller::capabilities(&self.game_controller, gamepad_id)
+        }
+        #[cfg(backend = "game_input")]
+        {
+            crate::backend_game_input::capabilities(&self.game_input, gamepad_id)
+        }
+        #[cfg(backend = "raw_input")]
+        {
+            crate::backend_raw_input::capabilities(&self.raw_input, gamepad_id)
+        }
+        #[cfg(backend = "sdl3")]
+        {
+            crate::backend_sdl3::capabilities(&self.sdl3, gamepad_id)
+        }
+        #[cfg(backend = "wgi")]
+        {
+            crate::backend_wgi::capabilities(&self.wgi, gamepad_id)
+        }
+        #[cfg(not(any(
+            backend = "evdev",
+            backend = "game_controller",
+            backend = "game_input",
+            backend = "gilrs",
+            backend = "raw_input",
+            backend = "sdl3",
+            backend = "web_bindgen",
+            backend = "web_direct",
+            backend = "wgi"
+        )))]
+        {
+            // Rumbling is attempted, but whether it is supported is not known:
+            Capabilities {
+                rumble: !cfg!(backend = "none"),
+                trigger_rumble: false,
+                motion: false,
+                led: false,
+                force_feedback: false,
+            }
+        }
+    }
+
+    /// The name of a connected gamepad as reported by the platform, such as the `id` string of
+    /// the Gamepad API on the web, or `None` if not known.
+    pub fn name(&self, gamepad_id: GamepadId) -> Option<String> {
+        if let Some(backend) = &self.custom_backend {
+            return backend.name(gamepad_id);
+        }
+        #[cfg(backend = "android_winit")]
+        {
+            self.winit
+                .android_device(gamepad_id)
+                .map(|device| device.name.clone())
+        }
+        #[cfg(backend = "gilrs")]
+        {
+            self.name_gilrs(gamepad_id)
+        }
+        #[cfg(backend = "web_bindgen")]
+        {
+            crate::backend_web_bindgen::name(gamepad_id.0)
+        }
+        #[cfg(backend = "web_direct")]
+        {
+            crate::backend_web_direct::name(gamepad_id.0)
+        }
+        #[cfg(backend = "emscripten")]
+        {
+            crate::backend_emscripten::name(gamepad_id.0)
+        }
+        #[cfg(backend = "evdev")]
+        {
+            crate::backend_evdev::name(&self.evdev, gamepad_id)
+        }
+        #[cfg(backend = "game_controller")]
+        {
+            crate::backend_game_controller::name(&self.game_controller, gamepad_id)
+        }
+        #[cfg(backend = "paddleboat")]
+        {
+            crate::backend_paddleboat::name(&self.paddleboat, gamepad_id)
+        }
+        #[cfg(backend = "sdl3")]
+        {
+            crate::backend_sdl3::name(&self.sdl3, gamepad_id)
+        }
+        #[cfg(backend = "wgi")]
+        {
+            crate::backend_wgi::name(&self.wgi, gamepad_id)
+        }
+        #[cfg(not(any(
+            backend = "android_winit",
+            backend = "gilrs",
+            backend = "web_bindgen",
+            backend = "web_direct",
+            backend = "emscripten",
+            backend = "evdev",
+            backend = "game_controller",
+            backend = "paddleboat",
+            backend = "sdl3",
+            backend = "wgi"
+        )))]
+        {
+            None
+        }
+    }
+
+    /// The USB vendor and product ids of a connected gamepad, or `None` if not known.
+    ///
+    /// These are reported on Android, and by the gilrs, evdev and game input backends, so that
+    /// devices can be identified more precisely than by their [Gamepads::name()].
+    pub fn vendor_product_ids(&self, gamepad_id: GamepadId) -> Option<(u16, u16)> {
+        if let Some(backend) = &self.custom_backend {
+            return backend.vendor_product_ids(gamepad_id);
+        }
+        #[cfg(backend = "android_winit")]
+        {
+            self.winit
+                .android_device(gamepad_id)
+                .map(|device| (device.vendor_id, device.product_id))
+        }
+        #[cfg(backend = "gilrs")]
+        {
+            self.vendor_product_ids_gilrs(gamepad_id)
+        }
+        #[cfg(backend = "evdev")]
+        {
+            crate::backend_evdev::vendor_product_ids(&self.evdev, gamepad_id)
+        }
+        #[cfg(backend = "game_input")]
+        {
+            crate::backend_game_input::vendor_product_ids(&self.game_input, gamepad_id)
+        }
+        #[cfg(not(any(
+            backend = "android_winit",
+            backend = "gilrs",
+            backend = "evdev",
+            backend = "game_input"
+        )))]
+        {
+            None
+        }
+    }
+
+    /// The latest readings of the accelerometer and gyroscope of a gamepad, or `None` if it has
+    /// no motion sensors or they are not supported by the backend.
+    ///
+    /// Motion sensors are read by the `backend-sdl3` feature, and of DualSense and DualShock 4
+    /// controllers on desktop by the `playstation` feature, see [Capabilities::motion].
+    pub fn motion(&self, gamepad_id: GamepadId) -> Option<Motion> {
+        if let Some(backend) = &self.custom_backend {
+            return backend.motion(gamepad_id);
+        }
+        #[cfg(all(
+            feature = "playstation",
+            any(target_os = "linux", target_os = "macos", target_os = "windows")
+        ))]
+        if let Some(motion) = self.motion_playstation(gamepad_id) {
+            return Some(motion);
+        }
+        #[cfg(backend = "sdl3")]
+        {
+            crate::backend_sdl3::motion(&self.sdl3, gamepad_id)
+        }
+        #[cfg(not(backend = "sdl3"))]
+        {
+            None
+        }
+    }
+
+    /// Set the color of the light bar of a gamepad, such as to tell players apart.
+    ///
+    /// Light bars are supported by the `backend-sdl3` feature, and of DualSense and DualShock 4
+    /// controllers on desktop by the `playstation` feature, see [Capabilities::led].
+    /// Elsewhere, or if the gamepad has no light bar, this does nothing.
+    pub fn set_led(&mut self, gamepad_id: GamepadId, color: (u8, u8, u8)) {
+        if let Some(backend) = &mut self.custom_backend {
+            backend.set_led(gamepad_id, color);
+            return;
+        }
+        #[cfg(all(
+            feature = "playstation",
+            any(target_os = "linux", target_os = "macos", target_os = "windows")
+        ))]
+        if self.set_led_playstation(gamepad_id, color) {
+            return;
+        }
+        #[cfg(backend = "sdl3")]
+        crate::backend_sdl3::set_led(&self.sdl3, gamepad_id, color);
+        #[cfg(not(backend = "sdl3"))]
+        let _ = (gamepad_id, color);
+    }
+
+    /// Poll the custom backend, returning whether one is set.
+    pub(crate) fn poll_custom_backend(&mut self) -> bool {
+        let Some(backend) = &mut self.custom_backend else {
+            return false;
+        };
+        // Events are not used while a custom backend is set, so just discard them:
+        #[cfg(backend = "gilrs")]
+        self.gilrs.discard_events();
+        for gamepad in self.gamepads.iter_mut() {
+            gamepad.just_pressed_bits = 0;
+            gamepad.high_just_pressed_bits = 0;
+        }
+        backend.poll(&mut BackendState {
+            gamepads: &mut self.gamepads,
+            raw_timestamps: &mut self.raw_timestamps,
+        });
+        true
+    }
+}
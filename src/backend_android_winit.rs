@@ -1,301 +1,691 @@
-//use winit::platform::android::activity::input::{InputEvent, Source};
-use winit::event::{Event, WindowEvent};
-use winit::keyboard::{Key, NativeKey};
+use crate::backend_winit::{MotionRange, AXIS_UPDATE_LEN};
+use crate::logging;
+use std::sync::atomic::{AtomicU64, Ordering};
 
-impl crate::Gamepads {
-    pub fn on_event<T>(&mut self, event: &Event<T>) {
-        if self.just_polled {
-            self.just_polled = false;
-            for gamepad in self.gamepads.iter_mut() {
-                gamepad.just_pressed_bits = 0;
-            }
-        }
+/// Run JNI calls on the current thread, attached to the VM of the app.
+///
+/// A Java exception thrown by a call, such as a `SecurityException` when vibrating without the
+/// `VIBRATE` permission, is logged and cleared on failure, as a pending exception would otherwise
+/// abort the process on the next JNI call.
+fn with_env<T>(
+    f: impl FnOnce(&mut jni::JNIEnv<'_>) -> jni::errors::Result<T>,
+) -> jni::errors::Result<T> {
+    let ctx = ndk_context::android_context();
+    let vm = unsafe { jni::JavaVM::from_raw(ctx.vm().cast()) }?;
+    let mut env = vm.attach_current_thread()?;
+    let result = f(&mut env);
+    if result.is_err() && env.exception_check().unwrap_or(false) {
+        env.exception_describe().ok();
+        env.exception_clear().ok();
+    }
+    result
+}
 
-        if let Event::WindowEvent {
-            event: ref window_event,
-            ..
-        } = event
-        {
-            match window_event {
-                WindowEvent::KeyboardInput {
-                    event: key_event,
-                    device_id,
-                    ..
-                } => {
-                    log::info!("Gamepad keyboard event {key_event:?} from device {device_id:?}");
-
-                    if let Key::Unidentified(NativeKey::Android(scancode)) = key_event.logical_key {
-                        if let Some(gamepad_idx) = self.find_or_insert(*device_id) {
-                            let gamepad_button = match scancode {
-                            // See https://developer.android.com/develop/ui/views/touch-and-input/game-controllers/controller-input#dpad
-                            // Most controllers report hat axis events instead of D-pad presses, but some might:
-                            // "Some controllers instead report D-pad presses with a key code. If your game cares about D-pad
-                            // presses, you should treat the hat axis events and the D-pad key codes as the same input events"
-                            19 /* AKEYCODE_DPAD_UP */ => crate::Button::DPadUp,
-                            20 /* AKEYCODE_DPAD_DOWN */ => crate::Button::DPadDown,
-                            21 /* AKEYCODE_DPAD_LEFT */ => crate::Button::DPadLeft,
-                            22 /* AKEYCODE_DPAD_RIGHT */ => crate::Button::DPadRight,
-                            96 /* AKEYCODE_BUTTON_A */ => crate::Button::ActionDown,
-                            97 /* AKEYCODE_BUTTON_B */ => crate::Button::ActionRight,
-                            99 /* AKEYCODE_BUTTON_X */ => crate::Button::ActionLeft,
-                            100 /* AKEYCODE_BUTTON_Y */ => crate::Button::ActionUp,
-                            102 /* AKEYCODE_BUTTON_L1 */ => crate::Button::FrontLeftUpper,
-                            103 /* AKEYCODE_BUTTON_R1 */ => crate::Button::FrontRightUpper,
-                            104 /* AKEYCODE_BUTTON_L2 */ => crate::Button::FrontLeftLower,
-                            105 /* AKEYCODE_BUTTON_R2 */ => crate::Button::FrontRightLower,
-                            106 /* AKEYCODE_BUTTON_THUMBL */ => crate::Button::LeftStick,
-                            107 /* AKEYCODE_BUTTON_THUMBR */ => crate::Button::RightStick,
-                            108 /* AKEYCODE_BUTTON_START */ => crate::Button::RightCenterCluster,
-                            109 /* AKEYCODE_BUTTON_SELECT */ => crate::Button::LeftCenterCluster,
-                            _ => {
-                                return;
-                            }
-                        };
-                            let bit = 1 << (gamepad_button as u32);
-                            if key_event.state.is_pressed() {
-                                self.gamepads[gamepad_idx].pressed_bits |= bit;
-                                self.gamepads[gamepad_idx].just_pressed_bits |= bit;
-                            } else {
-                                self.gamepads[gamepad_idx].pressed_bits &= !bit;
-                            }
-
-                            log::error!(
-                                "Gamepad button {:?}, device id = {:?}",
-                                gamepad_button,
-                                device_id,
-                            );
-                        }
-                    }
-                }
-                WindowEvent::AxisUpdate { device_id, values } => {
-                    log::error!("Axis update: {:?}, {:?}", device_id, values);
-                    if let Some(gamepad_idx) = self.find_or_insert(*device_id) {
-                        for (val, negative_button, positive_button) in [
-                            (values[0], crate::Button::DPadLeft, crate::Button::DPadRight),
-                            (values[0], crate::Button::DPadUp, crate::Button::DPadDown),
-                        ] {
-                            let negative_bit = 1 << (negative_button as u32);
-                            let posive_bit = 1 << (positive_button as u32);
-                            if val < 0. {
-                                self.gamepads[gamepad_idx].pressed_bits |= negative_bit;
-                                self.gamepads[gamepad_idx].just_pressed_bits |= negative_bit;
-                                self.gamepads[gamepad_idx].pressed_bits &= !posive_bit;
-                            } else if val > 0. {
-                                self.gamepads[gamepad_idx].pressed_bits |= posive_bit;
-                                self.gamepads[gamepad_idx].just_pressed_bits |= posive_bit;
-                                self.gamepads[gamepad_idx].pressed_bits &= !negative_bit;
-                            } else {
-                                self.gamepads[gamepad_idx].pressed_bits &=
-                                    !(negative_bit | posive_bit);
-                            }
-                        }
-
-                        self.gamepads[gamepad_idx].axes =
-                            [values[2], values[3], values[4], values[5]];
-                    }
-                }
-                WindowEvent::Touch(touch) => {
-                    // https://docs.rs/winit/latest/winit/event/struct.Touch.html
-                    // Note device_id being present.
-                    log::error!("Touch event: {:?}", touch);
-                }
-                _ => {}
+/// The Android axes of the values of axis updates from winit, in order.
+const AXIS_UPDATE_AXES: [i32; AXIS_UPDATE_LEN] = [
+    15, /* AXIS_HAT_X */
+    16, /* AXIS_HAT_Y */
+    0,  /* AXIS_X */
+    1,  /* AXIS_Y */
+    11, /* AXIS_Z */
+    14, /* AXIS_RZ */
+];
+
+/// The ranges of the axes of axis updates from an input device, `None` for axes it does not
+/// have, such as the hat switch of controllers reporting the D-pad as key events.
+pub fn axis_update_ranges(
+    device_id: i32,
+) -> jni::errors::Result<[Option<MotionRange>; AXIS_UPDATE_LEN]> {
+    with_env(|env| {
+        let class = env.find_class("android/view/InputDevice")?;
+        let input_device = env
+            .call_static_method(
+                class,
+                "getDevice",
+                "(I)Landroid/view/InputDevice;",
+                &[jni::objects::JValue::Int(device_id)],
+            )?
+            .l()?;
+        let mut ranges = [None; AXIS_UPDATE_LEN];
+        if input_device.is_null() {
+            logging::warning!("No input device with id {device_id}");
+            return Ok(ranges);
+        }
+        for (range, axis) in ranges.iter_mut().zip(AXIS_UPDATE_AXES) {
+            // https://developer.android.com/reference/android/view/InputDevice#getMotionRange(int)
+            // Which returns null if the device does not have the axis:
+            let motion_range = env
+                .call_method(
+                    &input_device,
+                    "getMotionRange",
+                    "(I)Landroid/view/InputDevice$MotionRange;",
+                    &[jni::objects::JValue::Int(axis)],
+                )?
+                .l()?;
+            if motion_range.is_null() {
+                continue;
+            }
+            let mut get = |name| {
+                env.call_method(&motion_range, name, "()F", &[])
+                    .and_then(|value| value.f())
             };
+            *range = Some(MotionRange {
+                min: get("getMin")?,
+                max: get("getMax")?,
+                flat: get("getFlat")?,
+                fuzz: get("getFuzz")?,
+            });
         }
-    }
+        Ok(ranges)
+    })
+}
+
+/// The `InputDevice` sources of gamepads and joysticks, and of the D-pad of remotes.
+const SOURCE_GAMEPAD: i32 = 0x0000_0401;
+const SOURCE_JOYSTICK: i32 = 0x0100_0010;
+const SOURCE_DPAD: i32 = 0x0000_0201;
+
+/// What an input device reporting gamepad input is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeviceClass {
+    /// A gamepad or joystick.
+    GameController,
+    /// A remote of an Android TV device, with a D-pad but no other gamepad buttons.
+    Remote,
+}
+
+/// The ids of the connected input devices which are gamepads, joysticks or remotes.
+pub fn connected_devices() -> jni::errors::Result<Vec<(i32, DeviceClass)>> {
+    with_env(|env| {
+        let class = env.find_class("android/view/InputDevice")?;
+        let java_device_ids = jni::objects::JIntArray::from(
+            env.call_static_method(&class, "getDeviceIds", "()[I", &[])?
+                .l()?,
+        );
+        let mut device_ids =
+            vec![0; usize::try_from(env.get_array_length(&java_device_ids)?).unwrap_or(0)];
+        env.get_int_array_region(&java_device_ids, 0, &mut device_ids)?;
 
-    fn find_or_insert(&mut self, winit_device_id: winit::event::DeviceId) -> Option<usize> {
-        for i in 0..crate::MAX_GAMEPADS {
-            if self.android_winit_gamepad_ids[i] == winit_device_id {
-                return Some(i);
+        let mut connected = Vec::new();
+        for device_id in device_ids {
+            let input_device = env
+                .call_static_method(
+                    &class,
+                    "getDevice",
+                    "(I)Landroid/view/InputDevice;",
+                    &[jni::objects::JValue::Int(device_id)],
+                )?
+                .l()?;
+            if input_device.is_null() {
+                continue;
+            }
+            if let Some(class) = classify(env, &input_device)? {
+                connected.push((device_id, class));
             }
         }
-        if self.num_connected_pads == crate::MAX_GAMEPADS as u8 {
-            None
-        } else {
-            let index = self.num_connected_pads;
-            self.num_connected_pads += 1;
-            self.android_winit_gamepad_ids[index as usize] = winit_device_id;
-            Some(index as usize)
-        }
-    }
+        Ok(connected)
+    })
+}
 
-    pub(crate) fn poll_android_winit(&mut self) {
-        self.just_polled = true;
-    }
+/// The Java class implementing `InputManager.InputDeviceListener` with the native methods below,
+/// which apps include from `android/io/github/fornwall/gamepads/InputDeviceListener.java`.
+const DEVICE_LISTENER_CLASS: &str = "io.github.fornwall.gamepads.InputDeviceListener";
 
-    #[allow(clippy::expect_used)]
-    pub(crate) fn rumble_android(
-        &mut self,
-        _gamepad_id: crate::GamepadId,
-        duration_ms: u32,
-        _start_delay_ms: u32,
-        strong_magnitude: f32,
-        weak_magnitude: f32,
-    ) {
-        // See https://android.googlesource.com/platform/frameworks/opt/gamesdk/+/refs/heads/main/games-controller/src/main/java/com/google/android/games/paddleboat/GameControllerManager.java
-        //
-        // See also implementation in chromium:
-        // https://chromium-review.googlesource.com/c/chromium/src/+/3721715/12/device/gamepad/android/java/src/org/chromium/device/gamepad/GamepadDevice.java#73
-        fn scale_magnitude(magnitude: f32) -> i32 {
-            // Vibration magnitudes on android are between 1 and 255
-            const VIBRATION_MAX_AMPLITUDE: f32 = 255.;
-            (magnitude.clamp(0., 1.) * VIBRATION_MAX_AMPLITUDE).round() as i32
-        }
+/// The number of times input devices have been added, removed or changed, as reported to the
+/// registered listener.
+pub static DEVICE_CHANGES: AtomicU64 = AtomicU64::new(0);
+
+/// The `Context` of the app, being its activity.
+fn app_context() -> jni::objects::JObject<'static> {
+    unsafe { jni::objects::JObject::from_raw(ndk_context::android_context().context().cast()) }
+}
+
+fn input_manager<'local>(
+    env: &mut jni::JNIEnv<'local>,
+) -> jni::errors::Result<jni::objects::JObject<'local>> {
+    let name = env.new_string("input")?;
+    env.call_method(
+        app_context(),
+        "getSystemService",
+        "(Ljava/lang/String;)Ljava/lang/Object;",
+        &[(&name).into()],
+    )?
+    .l()
+}
+
+/// Register a listener counting the input devices added, removed or changed in
+/// [DEVICE_CHANGES], or return `None` if the app does not include its Java class.
+pub fn register_device_listener() -> jni::errors::Result<Option<jni::objects::GlobalRef>> {
+    with_env(|env| {
+        // Classes of the app are only found using its class loader from native threads:
+        let class_loader = env
+            .call_method(
+                app_context(),
+                "getClassLoader",
+                "()Ljava/lang/ClassLoader;",
+                &[],
+            )?
+            .l()?;
+        let class_name = env.new_string(DEVICE_LISTENER_CLASS)?;
+        let class = match env.call_method(
+            &class_loader,
+            "loadClass",
+            "(Ljava/lang/String;)Ljava/lang/Class;",
+            &[(&class_name).into()],
+        ) {
+            Ok(class) => jni::objects::JClass::from(class.l()?),
+            Err(jni::errors::Error::JavaException) => {
+                env.exception_clear()?;
+                return Ok(None);
+            }
+            Err(error) => return Err(error),
+        };
+        let listener = env.new_object(&class, "()V", &[])?;
+        // Listen on the main thread, as the thread of the app has no looper:
+        let looper = env
+            .call_static_method(
+                "android/os/Looper",
+                "getMainLooper",
+                "()Landroid/os/Looper;",
+                &[],
+            )?
+            .l()?;
+        let handler = env.new_object(
+            "android/os/Handler",
+            "(Landroid/os/Looper;)V",
+            &[(&looper).into()],
+        )?;
+        let input_manager = input_manager(env)?;
+        env.call_method(
+            &input_manager,
+            "registerInputDeviceListener",
+            "(Landroid/hardware/input/InputManager$InputDeviceListener;Landroid/os/Handler;)V",
+            &[(&listener).into(), (&handler).into()],
+        )?;
+        env.new_global_ref(listener).map(Some)
+    })
+}
+
+/// Unregister a listener registered by [register_device_listener()].
+pub fn unregister_device_listener(listener: &jni::objects::GlobalRef) -> jni::errors::Result<()> {
+    with_env(|env| {
+        let input_manager = input_manager(env)?;
+        env.call_method(
+            &input_manager,
+            "unregisterInputDeviceListener",
+            "(Landroid/hardware/input/InputManager$InputDeviceListener;)V",
+            &[listener.as_obj().into()],
+        )?;
+        Ok(())
+    })
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+extern "system" fn Java_io_github_fornwall_gamepads_InputDeviceListener_onInputDeviceAdded(
+    _env: jni::JNIEnv<'_>,
+    _listener: jni::objects::JObject<'_>,
+    _device_id: jni::sys::jint,
+) {
+    DEVICE_CHANGES.fetch_add(1, Ordering::Relaxed);
+}
 
-        const STRONG_MAGNITUDE_IDX: i32 = 0;
-        const WEAK_MAGNITUDE_IDX: i32 = 1;
+#[no_mangle]
+#[allow(non_snake_case)]
+extern "system" fn Java_io_github_fornwall_gamepads_InputDeviceListener_onInputDeviceRemoved(
+    _env: jni::JNIEnv<'_>,
+    _listener: jni::objects::JObject<'_>,
+    _device_id: jni::sys::jint,
+) {
+    DEVICE_CHANGES.fetch_add(1, Ordering::Relaxed);
+}
+
+#[no_mangle]
+#[allow(non_snake_case)]
+extern "system" fn Java_io_github_fornwall_gamepads_InputDeviceListener_onInputDeviceChanged(
+    _env: jni::JNIEnv<'_>,
+    _listener: jni::objects::JObject<'_>,
+    _device_id: jni::sys::jint,
+) {
+    DEVICE_CHANGES.fetch_add(1, Ordering::Relaxed);
+}
 
-        let ctx = ndk_context::android_context();
-        let vm = unsafe { jni::JavaVM::from_raw(ctx.vm().cast()) }.unwrap();
-        let mut env = vm.attach_current_thread().unwrap();
+/// Whether an input device is a gamepad, joystick or remote, or `None` for keyboards and
+/// touchscreens which also report key and motion events.
+pub fn device_class(device_id: i32) -> jni::errors::Result<Option<DeviceClass>> {
+    with_env(|env| {
+        let input_device = env
+            .call_static_method(
+                "android/view/InputDevice",
+                "getDevice",
+                "(I)Landroid/view/InputDevice;",
+                &[jni::objects::JValue::Int(device_id)],
+            )?
+            .l()?;
+        if input_device.is_null() {
+            return Ok(None);
+        }
+        classify(env, &input_device)
+    })
+}
 
-        let class = env
-            .find_class("android/view/InputDevice")
-            .expect("Failed to load the target class");
+fn classify(
+    env: &mut jni::JNIEnv,
+    input_device: &jni::objects::JObject,
+) -> jni::errors::Result<Option<DeviceClass>> {
+    let sources = env
+        .call_method(input_device, "getSources", "()I", &[])?
+        .i()?;
+    if sources & SOURCE_GAMEPAD == SOURCE_GAMEPAD || sources & SOURCE_JOYSTICK == SOURCE_JOYSTICK {
+        return Ok(Some(DeviceClass::GameController));
+    }
+    // The virtual keyboard of the system also has a D-pad:
+    let is_virtual = env
+        .call_method(input_device, "isVirtual", "()Z", &[])?
+        .z()?;
+    Ok((sources & SOURCE_DPAD == SOURCE_DPAD && !is_virtual).then_some(DeviceClass::Remote))
+}
 
-        // let device_id = self.android_winit_gamepad_ids[gamepad_id.value() as usize];
-        let device_id_i32 = 0; /* TODO: expose API in winit, or for now: unsafe { std::mem::transmute(device_id) }; */
+/// The name and USB ids of an Android `InputDevice`.
+pub struct DeviceInfo {
+    pub name: String,
+    pub vendor_id: u16,
+    pub product_id: u16,
+}
 
-        let java_input_device = if let jni::objects::JValueGen::Object(java_input_device) = env
+/// The name and USB ids of an input device, or `None` if there is no device with the id.
+pub fn device_info(device_id: i32) -> jni::errors::Result<Option<DeviceInfo>> {
+    with_env(|env| {
+        let input_device = env
             .call_static_method(
-                class,
+                "android/view/InputDevice",
                 "getDevice",
-                "(I)Landroid/view/InputDevice",
-                &[jni::objects::JValue::Int(device_id_i32)],
-            )
-            .expect("getDevice failed")
-        {
-            java_input_device
-        } else {
-            log::error!("getDevice did not return an object");
-            return;
+                "(I)Landroid/view/InputDevice;",
+                &[jni::objects::JValue::Int(device_id)],
+            )?
+            .l()?;
+        if input_device.is_null() {
+            return Ok(None);
+        }
+        let name = jni::objects::JString::from(
+            env.call_method(&input_device, "getName", "()Ljava/lang/String;", &[])?
+                .l()?,
+        );
+        let name = env.get_string(&name)?.into();
+        // The ids are those of USB and Bluetooth devices, and 0 for others:
+        let mut get_id = |name| {
+            env.call_method(&input_device, name, "()I", &[])
+                .and_then(|value| value.i())
+                .map(|id| u16::try_from(id).unwrap_or(0))
         };
+        Ok(Some(DeviceInfo {
+            name,
+            vendor_id: get_id("getVendorId")?,
+            product_id: get_id("getProductId")?,
+        }))
+    })
+}
 
-        let vibration_manager = if let jni::objects::JValueGen::Object(vibration_manager) = env
-            .call_method(
-                java_input_device,
-                "getVibratorManager",
-                "()Landroid/os/VibratorManager;",
-                &[],
-            )
-            .expect("getVibratorManager failed")
+/// The battery of an input device, from its `BatteryState`, or `None` if it has no battery or
+/// the platform is older than Android 12, which added battery states.
+pub fn battery(device_id: i32) -> jni::errors::Result<Option<crate::Battery>> {
+    with_env(|env| {
+        let input_device = env
+            .call_static_method(
+                "android/view/InputDevice",
+                "getDevice",
+                "(I)Landroid/view/InputDevice;",
+                &[jni::objects::JValue::Int(device_id)],
+            )?
+            .l()?;
+        if input_device.is_null() {
+            return Ok(None);
+        }
+        let battery_state = match env.call_method(
+            &input_device,
+            "getBatteryState",
+            "()Landroid/hardware/BatteryState;",
+            &[],
+        ) {
+            Ok(battery_state) => battery_state.l()?,
+            Err(jni::errors::Error::JavaException) => {
+                // NoSuchMethodError before Android 12:
+                env.exception_clear()?;
+                return Ok(None);
+            }
+            Err(error) => return Err(error),
+        };
+        if !env
+            .call_method(&battery_state, "isPresent", "()Z", &[])?
+            .z()?
         {
-            vibration_manager
-        } else {
-            log::error!("getVibratorManager did not return an object");
-            return;
+            return Ok(None);
+        }
+        let status = match env.call_method(&battery_state, "getStatus", "()I", &[])?.i()? {
+            2 /* STATUS_CHARGING */ => crate::BatteryStatus::Charging,
+            3 /* STATUS_DISCHARGING */ => crate::BatteryStatus::Discharging,
+            4 /* STATUS_NOT_CHARGING */ => crate::BatteryStatus::NotCharging,
+            5 /* STATUS_FULL */ => crate::BatteryStatus::Full,
+            _ => crate::BatteryStatus::Unknown,
         };
+        // The capacity is NaN if not known:
+        let capacity = env
+            .call_method(&battery_state, "getCapacity", "()F", &[])?
+            .f()?;
+        Ok(Some(crate::Battery {
+            status,
+            level: (!capacity.is_nan()).then(|| capacity.clamp(0., 1.)),
+        }))
+    })
+}
 
-        let java_vibrator_ids_object =
-            if let jni::objects::JValueGen::Object(java_vibrator_ids_object) = env
-                .call_method(&vibration_manager, "getVibratorIds", "()[I", &[])
-                .expect("getVibratorIds failed")
-            {
-                java_vibrator_ids_object
-            } else {
-                log::error!("getVibratorIds did not return an object");
-                return;
-            };
-        let java_vibrator_ids_array = jni::objects::JIntArray::from(java_vibrator_ids_object);
+/// The API levels of Android 12, which added `VibratorManager` for vibrating the motors of input
+/// devices separately, and of Android 8, which added `VibrationEffect` with amplitudes.
+const API_VIBRATOR_MANAGER: i32 = 31;
+const API_VIBRATION_EFFECT: i32 = 26;
 
-        let num_vibrators = env.get_array_length(&java_vibrator_ids_array).unwrap();
-        if num_vibrators < 2 {
-            log::warn!("Too few vibrators {num_vibrators}");
-            return;
+/// `VibrationEffect.DEFAULT_AMPLITUDE`, for vibrators without amplitude control.
+const DEFAULT_AMPLITUDE: i32 = -1;
+
+/// The API level of the platform.
+fn sdk_int(env: &mut jni::JNIEnv) -> jni::errors::Result<i32> {
+    env.get_static_field("android/os/Build$VERSION", "SDK_INT", "I")?
+        .i()
+}
+
+fn input_device<'local>(
+    env: &mut jni::JNIEnv<'local>,
+    device_id: i32,
+) -> jni::errors::Result<jni::objects::JObject<'local>> {
+    env.call_static_method(
+        "android/view/InputDevice",
+        "getDevice",
+        "(I)Landroid/view/InputDevice;",
+        &[jni::objects::JValue::Int(device_id)],
+    )?
+    .l()
+}
+
+/// Cancel any ongoing vibration of an input device.
+pub fn cancel_vibration(device_id: i32) -> jni::errors::Result<()> {
+    with_env(|env| {
+        let input_device = input_device(env, device_id)?;
+        if input_device.is_null() {
+            return Ok(());
         }
+        let (getter, signature) = if sdk_int(env)? >= API_VIBRATOR_MANAGER {
+            ("getVibratorManager", "()Landroid/os/VibratorManager;")
+        } else {
+            ("getVibrator", "()Landroid/os/Vibrator;")
+        };
+        let vibrator = env
+            .call_method(&input_device, getter, signature, &[])?
+            .l()?;
+        env.call_method(&vibrator, "cancel", "()V", &[])?;
+        Ok(())
+    })
+}
 
-        // https://chromium-review.googlesource.com/c/chromium/src/+/3721715/12/device/gamepad/android/java/src/org/chromium/device/gamepad/GamepadDevice.java#275
-        // TODO: Check for hasAmplitudeControl() on both vibrators?
+/// The amplitude of a vibration of a magnitude in the range `[0.0, 1.0]`, or `None` for none.
+fn amplitude(
+    env: &mut jni::JNIEnv,
+    vibrator: &jni::objects::JObject,
+    magnitude: f32,
+) -> jni::errors::Result<Option<i32>> {
+    // Vibration amplitudes on android are between 1 and 255:
+    const VIBRATION_MAX_AMPLITUDE: f32 = 255.;
+    if magnitude <= 0. {
+        return Ok(None);
+    }
+    // Vibrators without amplitude control only vibrate at their default amplitude:
+    if !env
+        .call_method(vibrator, "hasAmplitudeControl", "()Z", &[])?
+        .z()?
+    {
+        return Ok(Some(DEFAULT_AMPLITUDE));
+    }
+    Ok(Some(
+        ((magnitude.min(1.) * VIBRATION_MAX_AMPLITUDE).round() as i32).max(1),
+    ))
+}
 
-        let vibration_effect_class = env
-            .find_class("android/os/VibrationEffect")
-            .expect("Failed to load the android/os/VibrationEffect class");
+/// `VibrationEffect.createOneShot(durationMillis, amplitude)`, or a waveform starting with a
+/// step without vibration for a start delay.
+fn one_shot<'local>(
+    env: &mut jni::JNIEnv<'local>,
+    start_delay_ms: u32,
+    duration_ms: u32,
+    amplitude: i32,
+) -> jni::errors::Result<jni::objects::JObject<'local>> {
+    if start_delay_ms == 0 {
+        // https://developer.android.com/reference/android/os/VibrationEffect#createOneShot(long,%20int)
+        return env
+            .call_static_method(
+                "android/os/VibrationEffect",
+                "createOneShot",
+                "(JI)Landroid/os/VibrationEffect;",
+                &[
+                    jni::objects::JValue::Long(i64::from(duration_ms)),
+                    jni::objects::JValue::Int(amplitude),
+                ],
+            )?
+            .l();
+    }
+    // https://developer.android.com/reference/android/os/VibrationEffect#createWaveform(long[],%20int[],%20int)
+    let timings = delayed_timings(env, start_delay_ms, duration_ms)?;
+    let amplitudes = env.new_int_array(2)?;
+    env.set_int_array_region(&amplitudes, 0, &[0, amplitude])?;
+    env.call_static_method(
+        "android/os/VibrationEffect",
+        "createWaveform",
+        "([J[II)Landroid/os/VibrationEffect;",
+        &[
+            jni::objects::JValue::Object(&timings),
+            jni::objects::JValue::Object(&amplitudes),
+            // Without repeating:
+            jni::objects::JValue::Int(-1),
+        ],
+    )?
+    .l()
+}
 
-        let combined_vibration_class = env
-            .find_class("android/os/CombinedVibration")
-            .expect("Failed to load the android/os/CombinedVibration class");
+/// The timings of a vibration pattern waiting for a start delay, then vibrating for a duration.
+fn delayed_timings<'local>(
+    env: &mut jni::JNIEnv<'local>,
+    start_delay_ms: u32,
+    duration_ms: u32,
+) -> jni::errors::Result<jni::objects::JLongArray<'local>> {
+    let timings = env.new_long_array(2)?;
+    env.set_long_array_region(
+        &timings,
+        0,
+        &[i64::from(start_delay_ms), i64::from(duration_ms)],
+    )?;
+    Ok(timings)
+}
 
-        let parallel_combination = if let jni::objects::JValueGen::Object(parallel_combination) =
-            env.call_static_method(
-                combined_vibration_class,
-                "startParallel",
-                "()Landroid/os/CombinedVibration#ParallelCombination",
-                &[],
-            )
-            .expect("startParallel failed")
-        {
-            parallel_combination
-        } else {
-            log::error!("startParallel did not return an object");
+impl crate::Gamepads {
+    pub(crate) fn rumble_android(
+        &mut self,
+        gamepad_id: crate::GamepadId,
+        duration_ms: u32,
+        start_delay_ms: u32,
+        strong_magnitude: f32,
+        weak_magnitude: f32,
+    ) {
+        let Some(device_id) = self.winit.android_device_id(gamepad_id) else {
             return;
         };
+        let result = if strong_magnitude <= 0. && weak_magnitude <= 0. {
+            cancel_vibration(device_id)
+        } else {
+            Self::vibrate_android(
+                device_id,
+                duration_ms,
+                start_delay_ms,
+                strong_magnitude,
+                weak_magnitude,
+            )
+        };
+        if let Err(error) = result {
+            logging::warning!("Rumble failed: {error}");
+        }
+    }
 
-        let mut add_vibrator = |vibrator_idx, magnitude| {
-            // public static VibrationEffect createOneShot (long milliseconds, int amplitude)
-            // https://developer.android.com/reference/android/os/VibrationEffect#createOneShot(long,%20int)
-            let vibration_effect = if let jni::objects::JValueGen::Object(vibration_effect) = env
+    /// Vibrate an input device, using its vibrators separately on Android 12 and later, and its
+    /// single vibrator at the larger magnitude otherwise.
+    ///
+    /// The start delay is part of the vibration, so that it is timed by the system.
+    fn vibrate_android(
+        device_id: i32,
+        duration_ms: u32,
+        start_delay_ms: u32,
+        strong_magnitude: f32,
+        weak_magnitude: f32,
+    ) -> jni::errors::Result<()> {
+        // See https://android.googlesource.com/platform/frameworks/opt/gamesdk/+/refs/heads/main/games-controller/src/main/java/com/google/android/games/paddleboat/GameControllerManager.java
+        //
+        // See also implementation in chromium:
+        // https://chromium-review.googlesource.com/c/chromium/src/+/3721715/12/device/gamepad/android/java/src/org/chromium/device/gamepad/GamepadDevice.java#73
+        with_env(|env| {
+            let input_device = input_device(env, device_id)?;
+            if input_device.is_null() {
+                return Ok(());
+            }
+            let sdk_int = sdk_int(env)?;
+            if sdk_int < API_VIBRATOR_MANAGER {
+                return Self::vibrate_android_legacy(
+                    env,
+                    &input_device,
+                    sdk_int,
+                    duration_ms,
+                    start_delay_ms,
+                    strong_magnitude.max(weak_magnitude),
+                );
+            }
+
+            let vibrator_manager = env
+                .call_method(
+                    &input_device,
+                    "getVibratorManager",
+                    "()Landroid/os/VibratorManager;",
+                    &[],
+                )?
+                .l()?;
+            let java_vibrator_ids = jni::objects::JIntArray::from(
+                env.call_method(&vibrator_manager, "getVibratorIds", "()[I", &[])?
+                    .l()?,
+            );
+            let mut vibrator_ids =
+                vec![0; usize::try_from(env.get_array_length(&java_vibrator_ids)?).unwrap_or(0)];
+            env.get_int_array_region(&java_vibrator_ids, 0, &mut vibrator_ids)?;
+            // The first vibrator is the strong one, as in chromium, while a single vibrator plays both:
+            let vibrations = match vibrator_ids[..] {
+                [] => {
+                    logging::trace!("Input device {device_id} has no vibrators");
+                    return Ok(());
+                }
+                [vibrator_id] => vec![(vibrator_id, strong_magnitude.max(weak_magnitude))],
+                [strong_vibrator_id, weak_vibrator_id, ..] => vec![
+                    (strong_vibrator_id, strong_magnitude),
+                    (weak_vibrator_id, weak_magnitude),
+                ],
+            };
+
+            // var effect = CombinedVibration.startParallel();
+            let parallel_combination = env
                 .call_static_method(
-                    &vibration_effect_class,
-                    "createOneShot",
-                    "(JI)Landroid/os/VibrationEffect",
+                    "android/os/CombinedVibration",
+                    "startParallel",
+                    "()Landroid/os/CombinedVibration$ParallelCombination;",
+                    &[],
+                )?
+                .l()?;
+            for (vibrator_id, magnitude) in vibrations {
+                let vibrator = env
+                    .call_method(
+                        &vibrator_manager,
+                        "getVibrator",
+                        "(I)Landroid/os/Vibrator;",
+                        &[jni::objects::JValue::Int(vibrator_id)],
+                    )?
+                    .l()?;
+                let Some(amplitude) = amplitude(env, &vibrator, magnitude)? else {
+                    continue;
+                };
+                // effect.addVibrator(vibratorId, VibrationEffect.createOneShot(durationMillis, amplitude));
+                // https://developer.android.com/reference/android/os/CombinedVibration.ParallelCombination#addVibrator(int,%20android.os.VibrationEffect)
+                let vibration_effect = one_shot(env, start_delay_ms, duration_ms, amplitude)?;
+                env.call_method(
+                    &parallel_combination,
+                    "addVibrator",
+                    "(ILandroid/os/VibrationEffect;)Landroid/os/CombinedVibration$ParallelCombination;",
                     &[
-                        jni::objects::JValue::Long(i64::from(duration_ms)),
-                        jni::objects::JValue::Int(magnitude),
+                        jni::objects::JValue::Int(vibrator_id),
+                        jni::objects::JValue::Object(&vibration_effect),
                     ],
-                )
-                .expect("createOneShot failed")
-            {
-                vibration_effect
-            } else {
-                log::error!("createOneShot did not return an object");
-                return;
-            };
+                )?;
+            }
 
-            // public CombinedVibration.ParallelCombination addVibrator (int vibratorId, VibrationEffect effect)
-            // https://developer.android.com/reference/android/os/CombinedVibration.ParallelCombination#addVibrator(int,%20android.os.VibrationEffect)
+            // vibratorManager.vibrate(effect.combine());
+            let combined_vibration = env
+                .call_method(
+                    &parallel_combination,
+                    "combine",
+                    "()Landroid/os/CombinedVibration;",
+                    &[],
+                )?
+                .l()?;
+            env.call_method(
+                &vibrator_manager,
+                "vibrate",
+                "(Landroid/os/CombinedVibration;)V",
+                &[jni::objects::JValue::Object(&combined_vibration)],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Vibrate the single `Vibrator` of an input device, as before Android 12.
+    fn vibrate_android_legacy(
+        env: &mut jni::JNIEnv,
+        input_device: &jni::objects::JObject,
+        sdk_int: i32,
+        duration_ms: u32,
+        start_delay_ms: u32,
+        magnitude: f32,
+    ) -> jni::errors::Result<()> {
+        let vibrator = env
+            .call_method(input_device, "getVibrator", "()Landroid/os/Vibrator;", &[])?
+            .l()?;
+        if !env.call_method(&vibrator, "hasVibrator", "()Z", &[])?.z()? {
+            logging::trace!("Input device has no vibrator");
+            return Ok(());
+        }
+        if sdk_int < API_VIBRATION_EFFECT {
+            // Before vibration effects, vibrators only vibrate at their default amplitude:
+            let timings = delayed_timings(env, start_delay_ms, duration_ms)?;
             env.call_method(
-                &parallel_combination,
-                "addVibrator",
-                "(ILandroid/os/VibrationEffect;)V",
+                &vibrator,
+                "vibrate",
+                "([JI)V",
                 &[
-                    jni::objects::JValue::Int(vibrator_idx),
-                    jni::objects::JValue::Object(&vibration_effect),
+                    jni::objects::JValue::Object(&timings),
+                    jni::objects::JValue::Int(-1),
                 ],
-            )
-            .expect("addVibrator failed");
-        };
-        let strong = scale_magnitude(strong_magnitude);
-        if strong > 0 {
-            // effect.addVibrator(0, VibrationEffect.createOneShot(durationMillis, strongMagnitude));
-            add_vibrator(WEAK_MAGNITUDE_IDX, strong);
-        }
-        let weak = scale_magnitude(weak_magnitude);
-        if weak > 0 {
-            // effect.addVibrator(1, VibrationEffect.createOneShot(durationMillis, strongMagnitude));
-            add_vibrator(STRONG_MAGNITUDE_IDX, weak);
+            )?;
+            return Ok(());
         }
-
-        // TODO: Verify early that one of strong > 0, weak > 0 is true.
-
-        // var combined = effect.combine();
-        let combined_vibration = if let jni::objects::JValueGen::Object(object) = env
-            .call_method(&parallel_combination, "combine", "()V", &[])
-            .expect("effect.combine() failed")
-        {
-            object
-        } else {
-            log::error!("combine() did not return an object");
-            return;
+        let Some(amplitude) = amplitude(env, &vibrator, magnitude)? else {
+            return Ok(());
         };
-
-        // vibratorManager.vibrate(combined);
+        let vibration_effect = one_shot(env, start_delay_ms, duration_ms, amplitude)?;
         env.call_method(
-            vibration_manager,
+            &vibrator,
             "vibrate",
-            "(L/android/os/CombinedVibration)V",
-            &[jni::objects::JValue::Object(&combined_vibration)],
-        )
-        .expect("vibrate failed");
+            "(Landroid/os/VibrationEffect;)V",
+            &[jni::objects::JValue::Object(&vibration_effect)],
+        )?;
+        Ok(())
     }
 }
@@ -0,0 +1,152 @@
+//! Backend for `wasm32-unknown-emscripten`, enabled by the `backend-emscripten` feature,
+//! reading gamepads using the gamepad functions of the `html5.h` API of emscripten.
+//!
+//! Gamepads are placed in the slot of their index in the Gamepad API, so those with an index
+//! of 8 or more are not reported. Known gamepads without the standard mapping are remapped as
+//! by the other web backends. Emscripten has no API for vibration, so rumble does nothing.
+
+use crate::web_mapping::RawGamepad;
+use crate::GamepadMapping;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// The `EMSCRIPTEN_RESULT_SUCCESS` result code.
+const SUCCESS: i32 = 0;
+
+/// The length of the arrays and strings in `EmscriptenGamepadEvent`.
+const MAX_VALUES: usize = 64;
+
+extern "C" {
+    fn emscripten_sample_gamepad_data() -> i32;
+    fn emscripten_get_gamepad_status(index: i32, gamepad_state: *mut Event) -> i32;
+}
+
+/// The `EmscriptenGamepadEvent` struct of `html5.h`, where `Bool` is the `EM_BOOL` type.
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct GamepadEvent<Bool: Copy> {
+    timestamp: f64,
+    num_axes: i32,
+    num_buttons: i32,
+    axis: [f64; MAX_VALUES],
+    analog_button: [f64; MAX_VALUES],
+    digital_button: [Bool; MAX_VALUES],
+    connected: Bool,
+    index: i32,
+    id: [u8; MAX_VALUES],
+    mapping: [u8; MAX_VALUES],
+}
+
+/// An `EmscriptenGamepadEvent`, where `EM_BOOL` is a `bool` in current versions of emscripten,
+/// but was an `int` in older versions, making the struct larger.
+#[repr(C)]
+union Event {
+    current: GamepadEvent<u8>,
+    legacy: GamepadEvent<i32>,
+}
+
+/// Whether a gamepad has been exposed by the browser, which it only does after a button press.
+static ACTIVATED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the Gamepad API is available.
+pub fn is_supported() -> bool {
+    unsafe { emscripten_sample_gamepad_data() == SUCCESS }
+}
+
+/// Read the state of all gamepads.
+pub fn poll(gamepads: &mut crate::Gamepads) {
+    let sampled = is_supported();
+    for (index, gamepad) in gamepads.gamepads.iter_mut().enumerate() {
+        gamepad.set_connected(false);
+        if !sampled {
+            continue;
+        }
+        let mut event: Event = unsafe { std::mem::zeroed() };
+        // Only written by older versions of emscripten, being beyond the end of the current struct:
+        event.legacy.index = -1;
+        if unsafe { emscripten_get_gamepad_status(index as i32, &mut event) } != SUCCESS {
+            continue;
+        }
+        unsafe {
+            if event.legacy.index >= 0 {
+                read_state(&event.legacy, gamepad);
+            } else {
+                read_state(&event.current, gamepad);
+            }
+        }
+        if gamepad.connected {
+            ACTIVATED.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// The `id` string of a connected gamepad.
+pub fn name(index: u8) -> Option<String> {
+    let mut event: Event = unsafe { std::mem::zeroed() };
+    event.legacy.index = -1;
+    if unsafe { emscripten_get_gamepad_status(i32::from(index), &mut event) } != SUCCESS {
+        return None;
+    }
+    let (connected, id) = unsafe {
+        if event.legacy.index >= 0 {
+            (event.legacy.connected != 0, &event.legacy.id)
+        } else {
+            (event.current.connected != 0, &event.current.id)
+        }
+    };
+    connected.then(|| c_str(id).to_owned())
+}
+
+/// Whether no gamepad has been exposed by the browser yet, which it only does once a button
+/// has been pressed on one while the page is visible.
+pub fn needs_user_gesture() -> bool {
+    is_supported() && !ACTIVATED.load(Ordering::Relaxed)
+}
+
+/// A nul-terminated string of `EmscriptenGamepadEvent`.
+fn c_str(bytes: &[u8]) -> &str {
+    let len = bytes
+        .iter()
+        .position(|&byte| byte == 0)
+        .unwrap_or(bytes.len());
+    std::str::from_utf8(&bytes[..len]).unwrap_or_default()
+}
+
+/// Read the state of a gamepad, remapping known gamepads without the standard mapping.
+fn read_state<Bool: Copy + Into<i32>>(event: &GamepadEvent<Bool>, gamepad: &mut crate::Gamepad) {
+    if event.connected.into() == 0 {
+        return;
+    }
+    let num_buttons = usize::try_from(event.num_buttons).unwrap_or_default();
+    let num_axes = usize::try_from(event.num_axes).unwrap_or_default();
+    let axes = &event.axis[..num_axes.min(MAX_VALUES)];
+
+    let mut raw = RawGamepad::default();
+    for (button_idx, pressed) in event
+        .digital_button
+        .iter()
+        .take(num_buttons.min(MAX_VALUES))
+        .enumerate()
+    {
+        if (*pressed).into() != 0 {
+            raw.press(button_idx);
+        }
+    }
+    for (axis, value) in raw.axes.iter_mut().zip(axes) {
+        *axis = *value as f32;
+    }
+
+    let mapping = crate::web_mapping::mapping_from_str(c_str(&event.mapping));
+    gamepad.connected = true;
+    gamepad.set_mapping(mapping);
+    if mapping == GamepadMapping::Standard {
+        // The analog values of the left and right triggers in the standard layout:
+        gamepad.triggers = [event.analog_button[6] as f32, event.analog_button[7] as f32];
+    } else if let Some((pressed_bits, axes)) =
+        crate::web_mapping::remap(mapping, c_str(&event.id), &raw)
+    {
+        gamepad.pressed_bits = pressed_bits;
+        gamepad.axes = axes;
+        return;
+    }
+    crate::web_mapping::write_unmapped(&raw, gamepad);
+}
@@ -0,0 +1,728 @@
+//! Backend for Linux, enabled by the `backend-evdev` feature, reading gamepads from the
+//! `/dev/input/event*` devices directly instead of through gilrs.
+//!
+//! Gamepads whose drivers follow the Linux gamepad specification are mapped by it, see
+//! [crate::evdev_mapping], while the buttons and axes of other joysticks are in the order of
+//! their HID report, so known devices are remapped as on the web, with a
+//! [GamepadMapping::Unmapped] mapping. Axes beyond the thumbsticks, such as of wheels and flight
+//! sticks, are reported as extra axes. Rumble effects are uploaded to the device, which plays
+//! them with their duration and start delay by itself, as are the force feedback effects of
+//! racing wheels.
+//!
+//! Devices are read if the user has read access to them, and rumbled with write access too,
+//! which udev usually gives the user logged in at the seat. Gamepads connected later are found
+//! by watching `/dev/input` with inotify.
+
+use crate::evdev_mapping::{
+    ABS_BRAKE, ABS_HAT0X, ABS_HAT0Y, ABS_HAT2X, ABS_HAT2Y, ABS_HAT3Y, ABS_RX, ABS_RY, ABS_RZ,
+    ABS_WHEEL, ABS_X, ABS_Y, ABS_Z, BTN_GAMEPAD, BTN_JOYSTICK, BTN_TRIGGER_HAPPY1, EV_ABS, EV_FF,
+    EV_KEY, EV_SYN, FF_CONSTANT, FF_DAMPER, FF_FRICTION, FF_RUMBLE, FF_SPRING, SYN_DROPPED,
+};
+use crate::web_mapping::{RawGamepad, HAT_CENTERED};
+use crate::{Button, ForceFeedback, GamepadMapping, MAX_EXTRA_AXES, MAX_GAMEPADS};
+use std::fs::File;
+use std::io::{ErrorKind, Read, Write};
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+/// The directory of the input devices.
+const INPUT_DIR: &str = "/dev/input";
+
+/// How far an analog trigger needs to be pressed to be reported as a pressed button.
+const TRIGGER_THRESHOLD: f32 = 0.12;
+
+/// The sizes of bit sets of event types, keys, axes, force feedback effects and properties.
+const EV_BYTES: usize = 4;
+const KEY_BYTES: usize = libc::KEY_MAX as usize / 8 + 1;
+const ABS_COUNT: usize = libc::ABS_MAX as usize + 1;
+const ABS_BYTES: usize = ABS_COUNT / 8;
+const FF_BYTES: usize = libc::FF_MAX as usize / 8 + 1;
+const PROP_BYTES: usize = libc::INPUT_PROP_MAX as usize / 8 + 1;
+
+/// The force feedback effect types of racing wheels, indexed by [wheel_effect_index()].
+const FF_WHEEL_EFFECTS: [u16; 4] = [FF_CONSTANT, FF_SPRING, FF_DAMPER, FF_FRICTION];
+
+/// The index in [FF_WHEEL_EFFECTS] of the type of an effect, of which one is played at a time.
+const fn wheel_effect_index(force_feedback: ForceFeedback) -> usize {
+    match force_feedback {
+        ForceFeedback::Constant { .. } => 0,
+        ForceFeedback::Spring { .. } => 1,
+        ForceFeedback::Damper { .. } => 2,
+        ForceFeedback::Friction { .. } => 3,
+    }
+}
+
+/// The `EVIOC*` ioctl requests of `linux/input.h`.
+const EVIOC: u32 = b'E' as u32;
+const EVIOCGID: libc::Ioctl = libc::_IOR::<libc::input_id>(EVIOC, 0x02);
+const EVIOCGNAME: libc::Ioctl = libc::_IOR::<[u8; 128]>(EVIOC, 0x06);
+const EVIOCGPROP: libc::Ioctl = libc::_IOR::<[u8; PROP_BYTES]>(EVIOC, 0x09);
+const EVIOCGKEY: libc::Ioctl = libc::_IOR::<[u8; KEY_BYTES]>(EVIOC, 0x18);
+const EVIOCSFF: libc::Ioctl = libc::_IOW::<libc::ff_effect>(EVIOC, 0x80);
+
+const fn eviocgbit<Bits>(event_type: u16) -> libc::Ioctl {
+    libc::_IOR::<Bits>(EVIOC, 0x20 + event_type as u32)
+}
+
+const fn eviocgabs(axis: u16) -> libc::Ioctl {
+    libc::_IOR::<libc::input_absinfo>(EVIOC, 0x40 + axis as u32)
+}
+
+/// Whether a code is in a bit set, as read with `EVIOCGBIT` or `EVIOCGKEY`.
+fn has_bit(bits: &[u8], code: u16) -> bool {
+    bits.get(usize::from(code / 8))
+        .is_some_and(|byte| byte & (1 << (code % 8)) != 0)
+}
+
+/// An ioctl reading into or writing from a value, returning whether it succeeded.
+fn ioctl<T>(file: &File, request: libc::Ioctl, value: &mut T) -> bool {
+    unsafe { libc::ioctl(file.as_raw_fd(), request, std::ptr::from_mut(value)) >= 0 }
+}
+
+/// An opened gamepad.
+struct Device {
+    file: File,
+    path: PathBuf,
+    name: String,
+    vendor: u16,
+    product: u16,
+    /// Whether the buttons follow the gamepad specification, or are joystick buttons.
+    gamepad_buttons: bool,
+    /// The range of each axis, if the device has it.
+    axes: [Option<libc::input_absinfo>; ABS_COUNT],
+    keys: [u8; KEY_BYTES],
+    /// The keys pressed since the last poll, to also report those released again.
+    keys_pressed_since_poll: [u8; KEY_BYTES],
+    /// The current value of each axis.
+    values: [i32; ABS_COUNT],
+    can_rumble: bool,
+    /// The id of the uploaded rumble effect, or `-1` before uploaded.
+    rumble_effect_id: i16,
+    /// Whether each of [FF_WHEEL_EFFECTS] can be written to the device.
+    can_force_feedback: [bool; 4],
+    /// The id of the uploaded effect of each of [FF_WHEEL_EFFECTS], or `-1` before uploaded.
+    force_feedback_effect_ids: [i16; 4],
+}
+
+impl Device {
+    /// Open a gamepad, returning `None` if the device is not one or cannot be read.
+    fn open(path: &Path) -> Option<Self> {
+        let open = |write| {
+            File::options()
+                .read(true)
+                .write(write)
+                .custom_flags(libc::O_NONBLOCK)
+                .open(path)
+        };
+        // Rumbling needs write access, which is not required for reading:
+        let (file, writable) = match open(true) {
+            Ok(file) => (file, true),
+            Err(_) => (open(false).ok()?, false),
+        };
+
+        let mut key_bits = [0_u8; KEY_BYTES];
+        ioctl(&file, eviocgbit::<[u8; KEY_BYTES]>(EV_KEY), &mut key_bits);
+        let gamepad_buttons = (BTN_GAMEPAD..BTN_GAMEPAD + 16).any(|code| has_bit(&key_bits, code));
+        let joystick_buttons =
+            (BTN_JOYSTICK..BTN_JOYSTICK + 16).any(|code| has_bit(&key_bits, code));
+        let mut abs_bits = [0_u8; ABS_BYTES];
+        ioctl(&file, eviocgbit::<[u8; ABS_BYTES]>(EV_ABS), &mut abs_bits);
+        if !gamepad_buttons && !joystick_buttons {
+            // Pedals and throttles may have no buttons, but axes of joysticks as udev detects
+            // them, unlike the motion sensors of gamepads:
+            let mut prop_bits = [0_u8; PROP_BYTES];
+            ioctl(&file, EVIOCGPROP, &mut prop_bits);
+            if !(ABS_RX..=ABS_BRAKE).any(|code| has_bit(&abs_bits, code))
+                || has_bit(&prop_bits, libc::INPUT_PROP_ACCELEROMETER)
+            {
+                return None;
+            }
+        }
+
+        let mut id = libc::input_id {
+            bustype: 0,
+            vendor: 0,
+            product: 0,
+            version: 0,
+        };
+        ioctl(&file, EVIOCGID, &mut id);
+        let mut name = [0_u8; 128];
+        ioctl(&file, EVIOCGNAME, &mut name);
+        let name_len = name
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(name.len());
+
+        let mut axes = [None; ABS_COUNT];
+        for (code, axis) in (0..).zip(axes.iter_mut()) {
+            let mut info = libc::input_absinfo {
+                value: 0,
+                minimum: 0,
+                maximum: 0,
+                fuzz: 0,
+                flat: 0,
+                resolution: 0,
+            };
+            if has_bit(&abs_bits, code)
+                && ioctl(&file, eviocgabs(code), &mut info)
+                && info.maximum > info.minimum
+            {
+                *axis = Some(info);
+            }
+        }
+
+        let mut ev_bits = [0_u8; EV_BYTES];
+        ioctl(&file, eviocgbit::<[u8; EV_BYTES]>(0), &mut ev_bits);
+        let mut ff_bits = [0_u8; FF_BYTES];
+        if has_bit(&ev_bits, EV_FF) {
+            ioctl(&file, eviocgbit::<[u8; FF_BYTES]>(EV_FF), &mut ff_bits);
+        }
+
+        let mut device = Self {
+            file,
+            path: path.to_path_buf(),
+            name: String::from_utf8_lossy(&name[..name_len]).into_owned(),
+            vendor: id.vendor,
+            product: id.product,
+            gamepad_buttons,
+            axes,
+            keys: [0; KEY_BYTES],
+            keys_pressed_since_poll: [0; KEY_BYTES],
+            values: [0; ABS_COUNT],
+            can_rumble: writable && has_bit(&ff_bits, FF_RUMBLE),
+            rumble_effect_id: -1,
+            can_force_feedback: FF_WHEEL_EFFECTS
+                .map(|effect| writable && has_bit(&ff_bits, effect)),
+            force_feedback_effect_ids: [-1; 4],
+        };
+        device.sync();
+        Some(device)
+    }
+
+    /// Read the current state of all buttons and axes, such as when events were dropped.
+    fn sync(&mut self) {
+        ioctl(&self.file, EVIOCGKEY, &mut self.keys);
+        for (code, (axis, value)) in (0..).zip(self.axes.iter_mut().zip(self.values.iter_mut())) {
+            if let Some(info) = axis {
+                if ioctl(&self.file, eviocgabs(code), info) {
+                    *value = info.value;
+                }
+            }
+        }
+    }
+
+    /// Read the pending events, returning `false` if the device has been disconnected.
+    fn read_events(&mut self) -> bool {
+        let mut events = [libc::input_event {
+            time: libc::timeval {
+                tv_sec: 0,
+                tv_usec: 0,
+            },
+            type_: 0,
+            code: 0,
+            value: 0,
+        }; 64];
+        loop {
+            let bytes = unsafe {
+                std::slice::from_raw_parts_mut(
+                    events.as_mut_ptr().cast::<u8>(),
+                    std::mem::size_of_val(&events),
+                )
+            };
+            let len = match self.file.read(bytes) {
+                Ok(len) => len,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => return true,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(_) => return false,
+            };
+            for event in &events[..len / std::mem::size_of::<libc::input_event>()] {
+                match (event.type_, event.code) {
+                    (EV_KEY, code) if usize::from(code / 8) < KEY_BYTES => {
+                        let (byte_idx, mask) = (usize::from(code / 8), 1 << (code % 8));
+                        if event.value == 0 {
+                            self.keys[byte_idx] &= !mask;
+                        } else {
+                            self.keys[byte_idx] |= mask;
+                            self.keys_pressed_since_poll[byte_idx] |= mask;
+                        }
+                    }
+                    (EV_ABS, code) if usize::from(code) < ABS_COUNT => {
+                        self.values[usize::from(code)] = event.value;
+                    }
+                    (EV_SYN, SYN_DROPPED) => self.sync(),
+                    _ => {}
+                }
+            }
+            if len < std::mem::size_of_val(&events) {
+                return true;
+            }
+        }
+    }
+
+    /// The value of an axis in the range `[-1.0, 1.0]`, or of a trigger in the range `[0.0, 1.0]`,
+    /// with the deadzone reported by the driver.
+    fn axis(&self, code: u16, trigger: bool) -> Option<f32> {
+        let info = self.axes[usize::from(code)]?;
+        let range = (info.maximum - info.minimum) as f32;
+        let value = (self.values[usize::from(code)] - info.minimum) as f32 / range;
+        Some(if trigger {
+            value.clamp(0., 1.)
+        } else {
+            let deadzone = 2. * info.flat as f32 / range;
+            crate::apply_deadzone(value.mul_add(2., -1.).clamp(-1., 1.), deadzone)
+        })
+    }
+
+    /// A hat switch as a single axis, with the 8 directions clockwise from up in the range
+    /// `[-1.0, 1.0]` and [HAT_CENTERED] when centered, or `None` if the device has no such hat.
+    fn hat(&self, x_code: u16) -> Option<f32> {
+        let (x, y) = (self.axis(x_code, false)?, self.axis(x_code + 1, false)?);
+        let direction: Option<u8> = match (x.round() as i32, y.round() as i32) {
+            (0, -1) => Some(0),
+            (1, -1) => Some(1),
+            (1, 0) => Some(2),
+            (1, 1) => Some(3),
+            (0, 1) => Some(4),
+            (-1, 1) => Some(5),
+            (-1, 0) => Some(6),
+            (-1, -1) => Some(7),
+            _ => None,
+        };
+        Some(direction.map_or(HAT_CENTERED, |direction| {
+            f32::from(direction) * 2. / 7. - 1.
+        }))
+    }
+
+    /// The buttons and axes of a joystick, in the order of the HID report.
+    fn raw_state(&self, keys: &[u8; KEY_BYTES]) -> RawGamepad {
+        let mut raw = RawGamepad::default();
+        // The kernel numbers buttons beyond the first 16 from BTN_TRIGGER_HAPPY1 to 40:
+        let buttons =
+            (BTN_JOYSTICK..BTN_JOYSTICK + 16).chain(BTN_TRIGGER_HAPPY1..BTN_TRIGGER_HAPPY1 + 40);
+        for (button_idx, code) in buttons.enumerate() {
+            if has_bit(keys, code) {
+                raw.press(button_idx);
+            }
+        }
+        for (code, axis) in (ABS_X..=ABS_WHEEL).zip(raw.axes.iter_mut()) {
+            *axis = self.axis(code, false).unwrap_or_default();
+        }
+        // The hat switch follows the wheel in HID usage order, as a single axis:
+        if let Some(hat) = self.hat(ABS_HAT0X) {
+            raw.axes[usize::from(ABS_WHEEL) + 1] = hat;
+        }
+        // Followed by the other axes the device has, such as the sliders and dials of flight
+        // sticks and throttles, with further hat switches as single axes too:
+        let other_axes = (ABS_WHEEL + 1..ABS_COUNT as u16).filter_map(|code| match code {
+            ABS_HAT0X..=ABS_HAT3Y if code > ABS_HAT0Y && (code - ABS_HAT0X).is_multiple_of(2) => {
+                self.hat(code)
+            }
+            ABS_HAT0X..=ABS_HAT3Y => None,
+            _ => self.axis(code, false),
+        });
+        for (axis, value) in raw.axes[usize::from(ABS_WHEEL) + 2..]
+            .iter_mut()
+            .zip(other_axes)
+        {
+            *axis = value;
+        }
+        raw
+    }
+
+    /// An id as used by Chromium, for looking up joysticks in [crate::web_mapping].
+    fn id(&self) -> String {
+        format!("Vendor: {:04x} Product: {:04x}", self.vendor, self.product)
+    }
+
+    /// Write the state of the device to a gamepad, with the given keys pressed.
+    fn write_state(&self, keys: &[u8; KEY_BYTES], gamepad: &mut crate::Gamepad) {
+        gamepad.connected = true;
+        if !self.gamepad_buttons {
+            gamepad.set_mapping(GamepadMapping::Unmapped);
+            let raw = self.raw_state(keys);
+            if let Some((pressed_bits, axes)) =
+                crate::web_mapping::remap(GamepadMapping::Unmapped, &self.id(), &raw)
+            {
+                gamepad.pressed_bits = pressed_bits;
+                gamepad.axes = axes;
+            } else {
+                crate::web_mapping::write_unmapped(&raw, gamepad);
+            }
+            return;
+        }
+
+        gamepad.pressed_bits = 0;
+        for (code, button) in crate::evdev_mapping::buttons(self.vendor, self.product) {
+            if has_bit(keys, code) {
+                gamepad.pressed_bits |= 1 << (button as u32);
+            }
+        }
+        for (code, extra_idx) in crate::evdev_mapping::extra_buttons(self.vendor, self.product) {
+            if has_bit(keys, code) {
+                gamepad.pressed_bits |= 1 << extra_idx;
+            }
+        }
+
+        // Devices without the right thumbstick on RX and RY have it on Z and RZ instead,
+        // which otherwise are the analog triggers:
+        let triggers_on_z = self.axes[usize::from(ABS_RX)].is_some();
+        let (right_x, right_y) = if triggers_on_z {
+            (ABS_RX, ABS_RY)
+        } else {
+            (ABS_Z, ABS_RZ)
+        };
+        let axis = |code| self.axis(code, false).unwrap_or_default();
+        // Axes are positive downwards, while thumbsticks are positive upwards:
+        gamepad.axes = [axis(ABS_X), -axis(ABS_Y), axis(right_x), -axis(right_y)];
+
+        let trigger_axes = if triggers_on_z {
+            [ABS_Z, ABS_RZ]
+        } else {
+            [ABS_HAT2Y, ABS_HAT2X]
+        };
+        for ((trigger, code), button) in gamepad
+            .triggers
+            .iter_mut()
+            .zip(trigger_axes)
+            .zip([Button::FrontLeftLower, Button::FrontRightLower])
+        {
+            *trigger = self.axis(code, true).unwrap_or_default();
+            if *trigger > TRIGGER_THRESHOLD {
+                gamepad.pressed_bits |= 1 << (button as u32);
+            }
+        }
+        for (pressed, button) in [
+            (axis(ABS_HAT0Y) < -0.5, Button::DPadUp),
+            (axis(ABS_HAT0Y) > 0.5, Button::DPadDown),
+            (axis(ABS_HAT0X) < -0.5, Button::DPadLeft),
+            (axis(ABS_HAT0X) > 0.5, Button::DPadRight),
+        ] {
+            if pressed {
+                gamepad.pressed_bits |= 1 << (button as u32);
+            }
+        }
+
+        // Axes not part of the gamepad layout, such as of wheels and pedals:
+        let used_axes = [
+            ABS_X,
+            ABS_Y,
+            right_x,
+            right_y,
+            trigger_axes[0],
+            trigger_axes[1],
+            ABS_HAT0X,
+            ABS_HAT0Y,
+        ];
+        let extra_axes = (0..ABS_COUNT as u16)
+            .filter(|code| !used_axes.contains(code))
+            .filter_map(|code| self.axis(code, false));
+        gamepad.extra_axes = [0.; MAX_EXTRA_AXES];
+        for (extra_axis, value) in gamepad.extra_axes.iter_mut().zip(extra_axes) {
+            *extra_axis = value;
+        }
+    }
+
+    /// Upload a rumble effect, replacing the previous one, and play it.
+    fn rumble(&mut self, duration_ms: u32, start_delay_ms: u32, strong: f32, weak: f32) {
+        let magnitude = |magnitude: f32| (magnitude.clamp(0., 1.) * f32::from(u16::MAX)) as u16;
+        let mut effect: libc::ff_effect = unsafe { std::mem::zeroed() };
+        effect.type_ = FF_RUMBLE;
+        effect.id = self.rumble_effect_id;
+        effect.replay = libc::ff_replay {
+            length: duration_ms.try_into().unwrap_or(u16::MAX),
+            delay: start_delay_ms.try_into().unwrap_or(u16::MAX),
+        };
+        // The effect parameters are a union, of which the rumble parameters come first:
+        unsafe {
+            std::ptr::addr_of_mut!(effect.u)
+                .cast::<libc::ff_rumble_effect>()
+                .write(libc::ff_rumble_effect {
+                    strong_magnitude: magnitude(strong),
+                    weak_magnitude: magnitude(weak),
+                });
+        }
+        if !ioctl(&self.file, EVIOCSFF, &mut effect) {
+            crate::logging::warning!(
+                "Failed to upload rumble effect: {}",
+                std::io::Error::last_os_error()
+            );
+            return;
+        }
+        self.rumble_effect_id = effect.id;
+        self.play(effect.id, duration_ms > 0);
+    }
+
+    /// Upload a force feedback effect, replacing the previous one of the same kind, and play it
+    /// until stopped.
+    fn set_force_feedback(&mut self, force_feedback: ForceFeedback) {
+        let kind_index = wheel_effect_index(force_feedback);
+        if !self.can_force_feedback[kind_index] {
+            return;
+        }
+        let signed = |value: f32| (value.clamp(-1., 1.) * f32::from(i16::MAX)) as i16;
+        let mut effect: libc::ff_effect = unsafe { std::mem::zeroed() };
+        effect.type_ = FF_WHEEL_EFFECTS[kind_index];
+        effect.id = self.force_feedback_effect_ids[kind_index];
+        // Played until stopped:
+        effect.replay = libc::ff_replay {
+            length: 0,
+            delay: 0,
+        };
+        let condition = |center: f32, strength: f32| libc::ff_condition_effect {
+            right_saturation: u16::MAX,
+            left_saturation: u16::MAX,
+            right_coeff: signed(strength.clamp(0., 1.)),
+            left_coeff: signed(strength.clamp(0., 1.)),
+            deadband: 0,
+            center: signed(center),
+        };
+        // The effect parameters are a union, of which the conditions are per axis, with the
+        // steering axis first:
+        unsafe {
+            let parameters = std::ptr::addr_of_mut!(effect.u);
+            match force_feedback {
+                ForceFeedback::Constant { force } => {
+                    // Towards the right, see the direction in `linux/input.h`:
+                    effect.direction = 0xC000;
+                    parameters
+                        .cast::<libc::ff_constant_effect>()
+                        .write(libc::ff_constant_effect {
+                            level: signed(force),
+                            envelope: std::mem::zeroed(),
+                        });
+                }
+                ForceFeedback::Spring { center, strength } => {
+                    parameters
+                        .cast::<libc::ff_condition_effect>()
+                        .write(condition(center, strength));
+                }
+                ForceFeedback::Damper { strength } | ForceFeedback::Friction { strength } => {
+                    parameters
+                        .cast::<libc::ff_condition_effect>()
+                        .write(condition(0., strength));
+                }
+            }
+        }
+        if !ioctl(&self.file, EVIOCSFF, &mut effect) {
+            crate::logging::warning!(
+                "Failed to upload force feedback effect: {}",
+                std::io::Error::last_os_error()
+            );
+            return;
+        }
+        self.force_feedback_effect_ids[kind_index] = effect.id;
+        self.play(effect.id, true);
+    }
+
+    /// Stop the force feedback effects played.
+    fn stop_force_feedback(&mut self) {
+        for effect_id in self.force_feedback_effect_ids {
+            if effect_id >= 0 {
+                self.play(effect_id, false);
+            }
+        }
+    }
+
+    /// Start or stop playing an uploaded effect.
+    fn play(&mut self, effect_id: i16, play: bool) {
+        let event = libc::input_event {
+            time: libc::timeval {
+                tv_sec: 0,
+                tv_usec: 0,
+            },
+            type_: EV_FF,
+            code: effect_id as u16,
+            value: i32::from(play),
+        };
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                std::ptr::from_ref(&event).cast::<u8>(),
+                std::mem::size_of_val(&event),
+            )
+        };
+        if let Err(error) = self.file.write_all(bytes) {
+            crate::logging::warning!("Failed to play force feedback effect: {error}");
+        }
+    }
+}
+
+/// The opened gamepad in each slot, and inotify watching for devices added and made readable.
+pub struct State {
+    devices: [Option<Device>; MAX_GAMEPADS],
+    inotify: Option<File>,
+}
+
+impl State {
+    /// Open the gamepads connected, and start watching for new ones.
+    pub fn new() -> Self {
+        let inotify = unsafe {
+            let fd = libc::inotify_init1(libc::IN_NONBLOCK | libc::IN_CLOEXEC);
+            (fd >= 0).then(|| File::from(OwnedFd::from_raw_fd(fd)))
+        };
+        let inotify = inotify.filter(|inotify| {
+            let dir = std::ffi::CString::new(INPUT_DIR).unwrap_or_default();
+            unsafe {
+                libc::inotify_add_watch(
+                    inotify.as_raw_fd(),
+                    dir.as_ptr(),
+                    libc::IN_CREATE | libc::IN_ATTRIB,
+                ) >= 0
+            }
+        });
+        if inotify.is_none() {
+            crate::logging::warning!(
+                "Gamepads connected later will not be found, as {INPUT_DIR} cannot be watched"
+            );
+        }
+        let mut state = Self {
+            devices: std::array::from_fn(|_| None),
+            inotify,
+        };
+        state.open_new_devices();
+        state
+    }
+
+    /// Open the gamepads not opened already, in the order of their device numbers.
+    fn open_new_devices(&mut self) {
+        let Ok(entries) = std::fs::read_dir(INPUT_DIR) else {
+            return;
+        };
+        let mut paths: Vec<(u32, PathBuf)> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let number = entry
+                    .file_name()
+                    .to_str()?
+                    .strip_prefix("event")?
+                    .parse()
+                    .ok()?;
+                Some((number, entry.path()))
+            })
+            .collect();
+        paths.sort_unstable();
+        for (_, path) in paths {
+            if self
+                .devices
+                .iter()
+                .flatten()
+                .any(|device| device.path == path)
+            {
+                continue;
+            }
+            let Some(free_slot) = self.devices.iter_mut().find(|slot| slot.is_none()) else {
+                return;
+            };
+            *free_slot = Device::open(&path);
+        }
+    }
+
+    /// Whether devices have been added or had their permissions changed since last checked.
+    fn devices_changed(&mut self) -> bool {
+        let Some(inotify) = &mut self.inotify else {
+            return false;
+        };
+        let mut changed = false;
+        let mut buffer = [0_u8; 4096];
+        while inotify.read(&mut buffer).is_ok_and(|len| len > 0) {
+            changed = true;
+        }
+        changed
+    }
+}
+
+/// Read the state of all gamepads, opening those connected since the last poll.
+pub fn poll(gamepads: &mut crate::Gamepads) {
+    let state = &mut gamepads.evdev;
+    if state.devices_changed() {
+        state.open_new_devices();
+    }
+    for (gamepad, slot) in gamepads.gamepads.iter_mut().zip(state.devices.iter_mut()) {
+        if slot.as_mut().is_some_and(|device| !device.read_events()) {
+            *slot = None;
+        }
+        let Some(device) = slot else {
+            gamepad.set_connected(false);
+            continue;
+        };
+        device.write_state(&device.keys, gamepad);
+        // Buttons both pressed and released since the last poll:
+        let mut keys_pressed_since_poll = device.keys;
+        for (keys, pressed) in keys_pressed_since_poll.iter_mut().zip(std::mem::replace(
+            &mut device.keys_pressed_since_poll,
+            [0; KEY_BYTES],
+        )) {
+            *keys |= pressed;
+        }
+        let mut with_released = *gamepad;
+        device.write_state(&keys_pressed_since_poll, &mut with_released);
+        gamepad.just_pressed_bits |= with_released.pressed_bits & !gamepad.pressed_bits;
+        gamepad.high_just_pressed_bits |=
+            with_released.high_pressed_bits & !gamepad.high_pressed_bits;
+    }
+}
+
+/// Play a rumble effect, which the device plays by itself.
+pub fn rumble(
+    state: &mut State,
+    gamepad_id: crate::GamepadId,
+    duration_ms: u32,
+    start_delay_ms: u32,
+    strong_magnitude: f32,
+    weak_magnitude: f32,
+) {
+    if let Some(device) = &mut state.devices[usize::from(gamepad_id.0)] {
+        if device.can_rumble {
+            device.rumble(
+                duration_ms,
+                start_delay_ms,
+                strong_magnitude,
+                weak_magnitude,
+            );
+        }
+    }
+}
+
+/// Play a force feedback effect until replaced or stopped, if supported by the device.
+pub fn set_force_feedback(
+    state: &mut State,
+    gamepad_id: crate::GamepadId,
+    force_feedback: ForceFeedback,
+) {
+    if let Some(device) = &mut state.devices[usize::from(gamepad_id.0)] {
+        device.set_force_feedback(force_feedback);
+    }
+}
+
+/// Stop the force feedback effects played on a device.
+pub fn stop_force_feedback(state: &mut State, gamepad_id: crate::GamepadId) {
+    if let Some(device) = &mut state.devices[usize::from(gamepad_id.0)] {
+        device.stop_force_feedback();
+    }
+}
+
+/// Whether a gamepad has rumble motors or force feedback which can be written to.
+pub fn capabilities(state: &State, gamepad_id: crate::GamepadId) -> crate::Capabilities {
+    let device = state.devices[usize::from(gamepad_id.0)].as_ref();
+    crate::Capabilities {
+        rumble: device.is_some_and(|device| device.can_rumble),
+        trigger_rumble: false,
+        motion: false,
+        led: false,
+        force_feedback: device.is_some_and(|device| device.can_force_feedback.contains(&true)),
+    }
+}
+
+/// The name of a connected gamepad, as reported by its driver.
+pub fn name(state: &State, gamepad_id: crate::GamepadId) -> Option<String> {
+    state.devices[usize::from(gamepad_id.0)]
+        .as_ref()
+        .map(|device| device.name.clone())
+}
+
+/// The vendor and product ids of a connected gamepad.
+pub fn vendor_product_ids(state: &State, gamepad_id: crate::GamepadId) -> Option<(u16, u16)> {
+    state.devices[usize::from(gamepad_id.0)]
+        .as_ref()
+        .map(|device| (device.vendor, device.product))
+}
@@ -0,0 +1,345 @@
+//! Backend for iOS and tvOS, enabled by the default `backend-game-controller` feature, reading
+//! gamepads using the `GameController` framework.
+//!
+//! This covers MFi, Xbox, PlayStation and Switch controllers with an extended gamepad profile,
+//! as well as the Siri Remote of Apple TV with its micro gamepad profile, which games there are
+//! required to support. Rumble effects are played using `CoreHaptics` on the handle
+//! motors of a controller, with the strong magnitude on the left handle and the weak magnitude
+//! on the right one, or on the whole controller if the handles cannot be addressed.
+
+use crate::{Button, MAX_GAMEPADS};
+use objc2::rc::{Allocated, Retained};
+use objc2::runtime::AnyObject;
+use objc2::{class, msg_send};
+use objc2_foundation::{NSArray, NSError, NSString};
+
+#[link(name = "GameController", kind = "framework")]
+extern "C" {
+    static GCHapticsLocalityDefault: &'static NSString;
+    static GCHapticsLocalityLeftHandle: &'static NSString;
+    static GCHapticsLocalityRightHandle: &'static NSString;
+}
+
+#[link(name = "CoreHaptics", kind = "framework")]
+extern "C" {
+    static CHHapticEventParameterIDHapticIntensity: &'static NSString;
+    static CHHapticEventTypeHapticContinuous: &'static NSString;
+}
+
+/// Which magnitude of a rumble effect a haptic engine plays.
+#[derive(Clone, Copy)]
+enum Motor {
+    Strong,
+    Weak,
+    Both,
+}
+
+/// A `CHHapticEngine` of a controller, and the `CHHapticPatternPlayer` of its effect.
+struct HapticMotor {
+    engine: Retained<AnyObject>,
+    motor: Motor,
+    player: Option<Retained<AnyObject>>,
+}
+
+/// The `GCController` in each slot, and its haptic engines once rumbled.
+#[derive(Default)]
+pub struct State {
+    slots: [Option<Retained<AnyObject>>; MAX_GAMEPADS],
+    haptics: [Vec<HapticMotor>; MAX_GAMEPADS],
+}
+
+/// The `GCExtendedGamepad` profile of a controller, if it has one.
+fn extended_gamepad(controller: &AnyObject) -> Option<Retained<AnyObject>> {
+    unsafe { msg_send![controller, extendedGamepad] }
+}
+
+/// The `GCMicroGamepad` profile of a controller, if it has one.
+fn micro_gamepad(controller: &AnyObject) -> Option<Retained<AnyObject>> {
+    unsafe { msg_send![controller, microGamepad] }
+}
+
+/// Whether a `GCControllerButtonInput` is pressed.
+fn is_pressed(button: &AnyObject) -> bool {
+    unsafe { msg_send![button, isPressed] }
+}
+
+/// The value of a `GCControllerButtonInput` or `GCControllerAxisInput`.
+fn value(input: &AnyObject) -> f32 {
+    unsafe { msg_send![input, value] }
+}
+
+/// The x and y values of a `GCControllerDirectionPad`, positive upwards.
+fn stick(direction_pad: &AnyObject) -> (f32, f32) {
+    unsafe {
+        let x: Retained<AnyObject> = msg_send![direction_pad, xAxis];
+        let y: Retained<AnyObject> = msg_send![direction_pad, yAxis];
+        (value(&x), value(&y))
+    }
+}
+
+/// Read the state of all gamepads.
+pub fn poll(gamepads: &mut crate::Gamepads) {
+    let controllers: Retained<NSArray<AnyObject>> =
+        unsafe { msg_send![class!(GCController), controllers] };
+    let connected: Vec<Retained<AnyObject>> = controllers
+        .to_vec()
+        .into_iter()
+        .filter(|controller| {
+            extended_gamepad(controller).is_some() || micro_gamepad(controller).is_some()
+        })
+        .collect();
+    let state = &mut gamepads.game_controller;
+
+    // Controllers are kept in the first slot free when connected:
+    let is_connected = |controller: &Retained<AnyObject>| {
+        connected
+            .iter()
+            .any(|other| Retained::as_ptr(other) == Retained::as_ptr(controller))
+    };
+    for (slot, haptics) in state.slots.iter_mut().zip(state.haptics.iter_mut()) {
+        if slot
+            .as_ref()
+            .is_some_and(|controller| !is_connected(controller))
+        {
+            *slot = None;
+            haptics.clear();
+        }
+    }
+    for controller in &connected {
+        if state
+            .slots
+            .iter()
+            .flatten()
+            .any(|slot| Retained::as_ptr(slot) == Retained::as_ptr(controller))
+        {
+            continue;
+        }
+        if let Some(free_slot) = state.slots.iter_mut().find(|slot| slot.is_none()) {
+            *free_slot = Some(controller.clone());
+        } else {
+            crate::logging::warning!("Ignoring gamepad, as {MAX_GAMEPADS} are already in use");
+        }
+    }
+
+    for (gamepad, slot) in gamepads.gamepads.iter_mut().zip(&state.slots) {
+        let Some(controller) = slot else {
+            gamepad.set_connected(false);
+            continue;
+        };
+        gamepad.connected = true;
+        if let Some(extended) = extended_gamepad(controller) {
+            write_extended(&extended, gamepad);
+        } else if let Some(micro) = micro_gamepad(controller) {
+            write_micro(&micro, gamepad);
+        }
+    }
+}
+
+/// Write the state of a `GCExtendedGamepad` profile to a gamepad.
+fn write_extended(extended: &AnyObject, gamepad: &mut crate::Gamepad) {
+    unsafe {
+        let dpad: Retained<AnyObject> = msg_send![extended, dpad];
+        let left_trigger: Retained<AnyObject> = msg_send![extended, leftTrigger];
+        let right_trigger: Retained<AnyObject> = msg_send![extended, rightTrigger];
+        let buttons: [(Option<Retained<AnyObject>>, Button); 17] = [
+            (msg_send![extended, buttonA], Button::ActionDown),
+            (msg_send![extended, buttonB], Button::ActionRight),
+            (msg_send![extended, buttonX], Button::ActionLeft),
+            (msg_send![extended, buttonY], Button::ActionUp),
+            (msg_send![extended, leftShoulder], Button::FrontLeftUpper),
+            (msg_send![extended, rightShoulder], Button::FrontRightUpper),
+            (Some(left_trigger.clone()), Button::FrontLeftLower),
+            (Some(right_trigger.clone()), Button::FrontRightLower),
+            (
+                msg_send![extended, buttonOptions],
+                Button::LeftCenterCluster,
+            ),
+            (msg_send![extended, buttonMenu], Button::RightCenterCluster),
+            (msg_send![extended, leftThumbstickButton], Button::LeftStick),
+            (
+                msg_send![extended, rightThumbstickButton],
+                Button::RightStick,
+            ),
+            (msg_send![&dpad, up], Button::DPadUp),
+            (msg_send![&dpad, down], Button::DPadDown),
+            (msg_send![&dpad, left], Button::DPadLeft),
+            (msg_send![&dpad, right], Button::DPadRight),
+            (msg_send![extended, buttonHome], Button::Mode),
+        ];
+        gamepad.pressed_bits = 0;
+        for (input, button) in buttons {
+            if input.is_some_and(|input| is_pressed(&input)) {
+                gamepad.pressed_bits |= 1 << (button as u32);
+            }
+        }
+        // Deadzones are applied by the framework:
+        let left_stick: Retained<AnyObject> = msg_send![extended, leftThumbstick];
+        let right_stick: Retained<AnyObject> = msg_send![extended, rightThumbstick];
+        let ((left_x, left_y), (right_x, right_y)) = (stick(&left_stick), stick(&right_stick));
+        gamepad.axes = [left_x, left_y, right_x, right_y];
+        gamepad.triggers = [value(&left_trigger), value(&right_trigger)];
+    }
+}
+
+/// Write the state of a `GCMicroGamepad` profile to a gamepad, which is that of the Siri Remote,
+/// with its touch surface reported both as the left thumbstick and as the d-pad.
+fn write_micro(micro: &AnyObject, gamepad: &mut crate::Gamepad) {
+    unsafe {
+        let dpad: Retained<AnyObject> = msg_send![micro, dpad];
+        let buttons: [(Option<Retained<AnyObject>>, Button); 7] = [
+            // Clicking the touch surface:
+            (msg_send![micro, buttonA], Button::ActionDown),
+            // The play/pause button:
+            (msg_send![micro, buttonX], Button::ActionLeft),
+            (msg_send![micro, buttonMenu], Button::RightCenterCluster),
+            (msg_send![&dpad, up], Button::DPadUp),
+            (msg_send![&dpad, down], Button::DPadDown),
+            (msg_send![&dpad, left], Button::DPadLeft),
+            (msg_send![&dpad, right], Button::DPadRight),
+        ];
+        gamepad.pressed_bits = 0;
+        for (input, button) in buttons {
+            if input.is_some_and(|input| is_pressed(&input)) {
+                gamepad.pressed_bits |= 1 << (button as u32);
+            }
+        }
+        let (x, y) = stick(&dpad);
+        gamepad.axes = [x, y, 0., 0.];
+        gamepad.triggers = [0.; 2];
+    }
+}
+
+/// The haptic engines of a controller, on its handles if they can be addressed separately.
+fn haptic_motors(controller: &AnyObject) -> Vec<HapticMotor> {
+    let Some(haptics): Option<Retained<AnyObject>> = (unsafe { msg_send![controller, haptics] })
+    else {
+        return Vec::new();
+    };
+    let engine = |locality: &NSString, motor| {
+        let engine: Option<Retained<AnyObject>> =
+            unsafe { msg_send![&haptics, createEngineWithLocality: locality] };
+        engine.map(|engine| HapticMotor {
+            engine,
+            motor,
+            player: None,
+        })
+    };
+    unsafe {
+        if let (Some(left), Some(right)) = (
+            engine(GCHapticsLocalityLeftHandle, Motor::Strong),
+            engine(GCHapticsLocalityRightHandle, Motor::Weak),
+        ) {
+            vec![left, right]
+        } else {
+            engine(GCHapticsLocalityDefault, Motor::Both)
+                .into_iter()
+                .collect()
+        }
+    }
+}
+
+/// Play a continuous haptic event on an engine, starting the engine if needed.
+fn play(
+    engine: &AnyObject,
+    duration_ms: u32,
+    start_delay_ms: u32,
+    intensity: f32,
+) -> Result<Retained<AnyObject>, Retained<NSError>> {
+    unsafe {
+        let () = msg_send![engine, startAndReturnError: _]?;
+        let parameter: Allocated<AnyObject> = msg_send![class!(CHHapticEventParameter), alloc];
+        let parameter: Retained<AnyObject> = msg_send![
+            parameter,
+            initWithParameterID: CHHapticEventParameterIDHapticIntensity,
+            value: intensity,
+        ];
+        let event: Allocated<AnyObject> = msg_send![class!(CHHapticEvent), alloc];
+        let event: Retained<AnyObject> = msg_send![
+            event,
+            initWithEventType: CHHapticEventTypeHapticContinuous,
+            parameters: &*NSArray::from_retained_slice(&[parameter]),
+            relativeTime: 0_f64,
+            duration: f64::from(duration_ms) / 1000.,
+        ];
+        let pattern: Allocated<AnyObject> = msg_send![class!(CHHapticPattern), alloc];
+        let pattern: Retained<AnyObject> = msg_send![
+            pattern,
+            initWithEvents: &*NSArray::from_retained_slice(&[event]),
+            parameters: &*NSArray::<AnyObject>::new(),
+            error: _
+        ]?;
+        let player: Retained<AnyObject> =
+            msg_send![engine, createPlayerWithPattern: &*pattern, error: _]?;
+        let current_time: f64 = msg_send![engine, currentTime];
+        let start_time = current_time + f64::from(start_delay_ms) / 1000.;
+        let () = msg_send![&player, startAtTime: start_time, error: _]?;
+        Ok(player)
+    }
+}
+
+/// Play a rumble effect, replacing any previous one.
+pub fn rumble(
+    state: &mut State,
+    gamepad_id: crate::GamepadId,
+    duration_ms: u32,
+    start_delay_ms: u32,
+    strong_magnitude: f32,
+    weak_magnitude: f32,
+) {
+    let gamepad_idx = usize::from(gamepad_id.0);
+    let Some(controller) = &state.slots[gamepad_idx] else {
+        return;
+    };
+    let haptics = &mut state.haptics[gamepad_idx];
+    if haptics.is_empty() {
+        *haptics = haptic_motors(controller);
+    }
+    for haptic_motor in haptics.iter_mut() {
+        if let Some(player) = haptic_motor.player.take() {
+            // The previous effect may have ended already, in which case stopping it fails:
+            let _: Result<(), Retained<NSError>> =
+                unsafe { msg_send![&player, stopAtTime: 0_f64, error: _] };
+        }
+        let intensity = match haptic_motor.motor {
+            Motor::Strong => strong_magnitude,
+            Motor::Weak => weak_magnitude,
+            Motor::Both => strong_magnitude.max(weak_magnitude),
+        };
+        if duration_ms == 0 || intensity <= 0. {
+            continue;
+        }
+        match play(
+            &haptic_motor.engine,
+            duration_ms,
+            start_delay_ms,
+            intensity.min(1.),
+        ) {
+            Ok(player) => haptic_motor.player = Some(player),
+            Err(error) => crate::logging::warning!("Failed to play rumble effect: {error}"),
+        }
+    }
+}
+
+/// Whether a gamepad is connected and supports haptics.
+pub fn capabilities(state: &State, gamepad_id: crate::GamepadId) -> crate::Capabilities {
+    crate::Capabilities {
+        rumble: state.slots[usize::from(gamepad_id.0)]
+            .as_ref()
+            .is_some_and(|controller| {
+                let haptics: Option<Retained<AnyObject>> =
+                    unsafe { msg_send![controller, haptics] };
+                haptics.is_some()
+            }),
+        trigger_rumble: false,
+        motion: false,
+        led: false,
+        force_feedback: false,
+    }
+}
+
+/// The name of a connected gamepad, as reported by the framework.
+pub fn name(state: &State, gamepad_id: crate::GamepadId) -> Option<String> {
+    let controller = state.slots[usize::from(gamepad_id.0)].as_ref()?;
+    let name: Option<Retained<NSString>> = unsafe { msg_send![controller, vendorName] };
+    name.map(|name| name.to_string())
+}
@@ -0,0 +1,511 @@
+//! Backend for Windows, enabled by the `backend-game-input` feature, reading gamepads using the
+//! GameInput API instead of through gilrs.
+//!
+//! GameInput covers Xbox controllers as well as other gamepads, reports devices as they connect
+//! and disconnect through a callback instead of by enumerating them on each poll, and reads them
+//! also while the application is in the background. It gives access to the impulse triggers of
+//! Xbox controllers using [Gamepads::rumble_triggers()](crate::Gamepads::rumble_triggers).
+//!
+//! The API is declared here, as in `GameInput.h` of the Microsoft Game Development Kit, since it
+//! has no bindings in the `windows` crate. `GameInput.dll` is loaded when constructing
+//! [Gamepads](crate::Gamepads), and a reading is requested to check the declarations, so that
+//! [Gamepads::try_new()](crate::Gamepads::try_new) fails where it is not installed.
+//! The guide button is not exposed by the API, so [Button::Mode] is never pressed.
+
+use crate::{Button, MAX_GAMEPADS};
+use std::ffi::{c_char, c_void};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// The thumbstick deadzones recommended for XInput, which covers the same controllers.
+const LEFT_STICK_DEADZONE: f32 = 7849. / 32767.;
+const RIGHT_STICK_DEADZONE: f32 = 8689. / 32767.;
+
+/// How far a trigger needs to be pressed to be reported as a pressed button, as for XInput.
+const TRIGGER_THRESHOLD: f32 = 30. / 255.;
+
+/// `GameInputKindGamepad`.
+const KIND_GAMEPAD: u32 = 0x0004_0000;
+/// `GameInputDeviceConnected` and `GameInputDeviceAnyStatus`.
+const DEVICE_CONNECTED: u32 = 0x1;
+const DEVICE_ANY_STATUS: u32 = 0xffff_ffff;
+/// `GameInputBlockingEnumeration`, reporting the devices already connected before returning.
+const BLOCKING_ENUMERATION: u32 = 2;
+/// `GAMEINPUT_E_READING_NOT_FOUND`, returned while no device has reported a reading.
+const READING_NOT_FOUND: HResult = 0x838A_0003_u32 as HResult;
+/// How long to wait for a running device callback when unregistering it.
+const UNREGISTER_TIMEOUT_US: u64 = 5_000_000;
+
+/// The `GameInputGamepadButtons` flags.
+const BUTTONS: [(u32, Button); 14] = [
+    (0x0004, Button::ActionDown),
+    (0x0008, Button::ActionRight),
+    (0x0010, Button::ActionLeft),
+    (0x0020, Button::ActionUp),
+    (0x0400, Button::FrontLeftUpper),
+    (0x0800, Button::FrontRightUpper),
+    (0x0002, Button::LeftCenterCluster),
+    (0x0001, Button::RightCenterCluster),
+    (0x1000, Button::LeftStick),
+    (0x2000, Button::RightStick),
+    (0x0040, Button::DPadUp),
+    (0x0080, Button::DPadDown),
+    (0x0100, Button::DPadLeft),
+    (0x0200, Button::DPadRight),
+];
+
+type HResult = i32;
+
+/// `GameInputDeviceCallback`.
+type DeviceCallback = unsafe extern "system" fn(
+    callback_token: u64,
+    context: *mut c_void,
+    device: *mut IGameInputDevice,
+    timestamp: u64,
+    current_status: u32,
+    previous_status: u32,
+);
+
+/// The methods of `IUnknown`, which all interfaces start with.
+#[repr(C)]
+struct IUnknownVtbl {
+    _query_interface: usize,
+    add_ref: unsafe extern "system" fn(this: *mut c_void) -> u32,
+    release: unsafe extern "system" fn(this: *mut c_void) -> u32,
+}
+
+/// The methods of `IGameInput` up to the last one used, with those not used as placeholders.
+#[repr(C)]
+struct IGameInputVtbl {
+    unknown: IUnknownVtbl,
+    _get_current_timestamp: usize,
+    get_current_reading: unsafe extern "system" fn(
+        this: *mut IGameInput,
+        input_kind: u32,
+        device: *mut IGameInputDevice,
+        reading: *mut *mut IGameInputReading,
+    ) -> HResult,
+    _get_next_reading: usize,
+    _get_previous_reading: usize,
+    _get_temporal_reading: usize,
+    _register_reading_callback: usize,
+    register_device_callback: unsafe extern "system" fn(
+        this: *mut IGameInput,
+        device: *mut IGameInputDevice,
+        input_kind: u32,
+        status_filter: u32,
+        enumeration_kind: u32,
+        context: *mut c_void,
+        callback: DeviceCallback,
+        callback_token: *mut u64,
+    ) -> HResult,
+    _register_system_button_callback: usize,
+    _register_keyboard_layout_callback: usize,
+    _stop_callback: usize,
+    unregister_callback: unsafe extern "system" fn(
+        this: *mut IGameInput,
+        callback_token: u64,
+        timeout_us: u64,
+    ) -> bool,
+}
+
+/// The methods of `IGameInputReading` up to `GetGamepadState`, from `GetInputKind` to
+/// `GetFlightStickState` as placeholders.
+#[repr(C)]
+struct IGameInputReadingVtbl {
+    unknown: IUnknownVtbl,
+    _unused: [usize; 19],
+    get_gamepad_state:
+        unsafe extern "system" fn(this: *mut IGameInputReading, state: *mut GamepadState) -> bool,
+}
+
+/// The methods of `IGameInputDevice` up to `SetRumbleState`, from `GetDeviceStatus` to
+/// `SetHapticMotorState` as placeholders.
+#[repr(C)]
+struct IGameInputDeviceVtbl {
+    unknown: IUnknownVtbl,
+    get_device_info: unsafe extern "system" fn(this: *mut IGameInputDevice) -> *const DeviceInfo,
+    _unused: [usize; 6],
+    set_rumble_state:
+        unsafe extern "system" fn(this: *mut IGameInputDevice, params: *const RumbleParams),
+}
+
+#[repr(C)]
+struct IGameInput {
+    vtbl: *const IGameInputVtbl,
+}
+
+#[repr(C)]
+struct IGameInputReading {
+    vtbl: *const IGameInputReadingVtbl,
+}
+
+#[repr(C)]
+struct IGameInputDevice {
+    vtbl: *const IGameInputDeviceVtbl,
+}
+
+/// `GameInputGamepadState`, with thumbsticks positive upwards.
+#[repr(C)]
+#[derive(Default)]
+struct GamepadState {
+    buttons: u32,
+    left_trigger: f32,
+    right_trigger: f32,
+    left_thumbstick_x: f32,
+    left_thumbstick_y: f32,
+    right_thumbstick_x: f32,
+    right_thumbstick_y: f32,
+}
+
+/// The start of `GameInputDeviceInfo`, up to the fields used.
+#[repr(C)]
+struct DeviceInfo {
+    _info_size: u32,
+    vendor_id: u16,
+    product_id: u16,
+}
+
+/// `GameInputRumbleParams`, with magnitudes in the range `[0.0, 1.0]`.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct RumbleParams {
+    low_frequency: f32,
+    high_frequency: f32,
+    left_trigger: f32,
+    right_trigger: f32,
+}
+
+/// `GameInputCreate`, exported by `GameInput.dll`.
+type GameInputCreate = unsafe extern "system" fn(game_input: *mut *mut IGameInput) -> HResult;
+
+#[link(name = "kernel32")]
+extern "system" {
+    fn LoadLibraryW(file_name: *const u16) -> *mut c_void;
+    fn GetProcAddress(module: *mut c_void, proc_name: *const c_char) -> *mut c_void;
+}
+
+/// Release a reference to an interface.
+unsafe fn release<T>(interface: *mut T) {
+    let vtbl = unsafe { *interface.cast::<*const IUnknownVtbl>() };
+    unsafe { ((*vtbl).release)(interface.cast()) };
+}
+
+/// A reference to a device, released when dropped.
+#[derive(PartialEq, Eq)]
+struct Device(*mut IGameInputDevice);
+
+// GameInput interfaces can be used from any thread, such as from the device callback:
+unsafe impl Send for Device {}
+
+impl Device {
+    /// Take a new reference to a device.
+    unsafe fn new(device: *mut IGameInputDevice) -> Self {
+        unsafe { ((*(*device).vtbl).unknown.add_ref)(device.cast()) };
+        Self(device)
+    }
+
+    fn info(&self) -> Option<&DeviceInfo> {
+        unsafe { ((*(*self.0).vtbl).get_device_info)(self.0).as_ref() }
+    }
+
+    fn set_rumble_state(&self, params: &RumbleParams) {
+        unsafe { ((*(*self.0).vtbl).set_rumble_state)(self.0, params) };
+    }
+}
+
+impl Drop for Device {
+    fn drop(&mut self) {
+        unsafe { release(self.0) };
+    }
+}
+
+/// Devices connected and disconnected since the last poll, as reported by the device callback.
+type DeviceChanges = Mutex<Vec<(Device, bool)>>;
+
+unsafe extern "system" fn device_callback(
+    _callback_token: u64,
+    context: *mut c_void,
+    device: *mut IGameInputDevice,
+    _timestamp: u64,
+    current_status: u32,
+    previous_status: u32,
+) {
+    let connected = current_status & DEVICE_CONNECTED != 0;
+    if connected != (previous_status & DEVICE_CONNECTED != 0) {
+        let changes = unsafe { &*context.cast::<DeviceChanges>() };
+        let device = unsafe { Device::new(device) };
+        changes
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((device, connected));
+    }
+}
+
+/// A rumble effect waiting for its start delay or playing, as the API has no durations.
+#[derive(Clone, Copy)]
+struct ScheduledRumble {
+    start_ms: f64,
+    stop_ms: f64,
+    params: RumbleParams,
+    started: bool,
+}
+
+/// The GameInput instance, or null if it could not be created, the devices reported by its
+/// device callback, and the device in each slot with its scheduled rumble effect.
+pub struct State {
+    game_input: *mut IGameInput,
+    callback_token: u64,
+    changes: Arc<DeviceChanges>,
+    slots: [Option<Device>; MAX_GAMEPADS],
+    rumbles: [Option<ScheduledRumble>; MAX_GAMEPADS],
+}
+
+impl Default for State {
+    /// A state without a GameInput instance, reporting no gamepads.
+    fn default() -> Self {
+        Self {
+            game_input: std::ptr::null_mut(),
+            callback_token: 0,
+            changes: Arc::new(Mutex::new(Vec::new())),
+            slots: std::array::from_fn(|_| None),
+            rumbles: [None; MAX_GAMEPADS],
+        }
+    }
+}
+
+impl State {
+    /// Create the GameInput instance, register for device changes and request a reading of any
+    /// gamepad, failing if GameInput is not installed or does not answer as declared.
+    pub fn new() -> Result<Self, String> {
+        let mut state = Self::default();
+        state.game_input = unsafe { create_game_input() }?;
+        let context = Arc::into_raw(Arc::clone(&state.changes));
+        let result = unsafe {
+            ((*(*state.game_input).vtbl).register_device_callback)(
+                state.game_input,
+                std::ptr::null_mut(),
+                KIND_GAMEPAD,
+                DEVICE_ANY_STATUS,
+                BLOCKING_ENUMERATION,
+                context.cast_mut().cast(),
+                device_callback,
+                &mut state.callback_token,
+            )
+        };
+        if result < 0 {
+            drop(unsafe { Arc::from_raw(context) });
+            state.callback_token = 0;
+            return Err(format!("RegisterDeviceCallback failed: {result:#x}"));
+        }
+        state
+            .current_reading(std::ptr::null_mut())
+            .map_err(|result| format!("GetCurrentReading failed: {result:#x}"))?;
+        Ok(state)
+    }
+
+    /// The current gamepad state of a device, or of any gamepad if `device` is null, which is
+    /// `None` if none has been reported yet.
+    fn current_reading(
+        &self,
+        device: *mut IGameInputDevice,
+    ) -> Result<Option<GamepadState>, HResult> {
+        let mut reading = std::ptr::null_mut();
+        let result = unsafe {
+            ((*(*self.game_input).vtbl).get_current_reading)(
+                self.game_input,
+                KIND_GAMEPAD,
+                device,
+                &mut reading,
+            )
+        };
+        if result == READING_NOT_FOUND {
+            return Ok(None);
+        }
+        if result < 0 || reading.is_null() {
+            return Err(result);
+        }
+        let mut gamepad_state = GamepadState::default();
+        let read = unsafe { ((*(*reading).vtbl).get_gamepad_state)(reading, &mut gamepad_state) };
+        unsafe { release(reading) };
+        Ok(read.then_some(gamepad_state))
+    }
+}
+
+impl Drop for State {
+    fn drop(&mut self) {
+        if self.game_input.is_null() {
+            return;
+        }
+        if self.callback_token != 0 {
+            let unregistered = unsafe {
+                ((*(*self.game_input).vtbl).unregister_callback)(
+                    self.game_input,
+                    self.callback_token,
+                    UNREGISTER_TIMEOUT_US,
+                )
+            };
+            // The context is leaked if the callback may still run:
+            if unregistered {
+                drop(unsafe { Arc::from_raw(Arc::as_ptr(&self.changes)) });
+            }
+        }
+        self.slots = std::array::from_fn(|_| None);
+        unsafe { release(self.game_input) };
+    }
+}
+
+/// Load `GameInput.dll` and create the GameInput instance.
+unsafe fn create_game_input() -> Result<*mut IGameInput, String> {
+    let file_name: Vec<u16> = "GameInput.dll".encode_utf16().chain([0]).collect();
+    let module = unsafe { LoadLibraryW(file_name.as_ptr()) };
+    if module.is_null() {
+        return Err("GameInput.dll not found".into());
+    }
+    let create = unsafe { GetProcAddress(module, c"GameInputCreate".as_ptr()) };
+    if create.is_null() {
+        return Err("GameInputCreate not found".into());
+    }
+    let create: GameInputCreate = unsafe { std::mem::transmute(create) };
+    let mut game_input = std::ptr::null_mut();
+    let result = unsafe { create(&mut game_input) };
+    if result < 0 || game_input.is_null() {
+        return Err(format!("GameInputCreate failed: {result:#x}"));
+    }
+    Ok(game_input)
+}
+
+/// Read the state of all gamepads, and start and stop scheduled rumble effects.
+pub fn poll(gamepads: &mut crate::Gamepads) {
+    let state = &mut gamepads.game_input;
+    if state.game_input.is_null() {
+        return;
+    }
+    let changes =
+        std::mem::take(&mut *state.changes.lock().unwrap_or_else(PoisonError::into_inner));
+
+    // Gamepads are kept in the first slot free when connected:
+    for (device, connected) in changes {
+        if !connected {
+            for (slot, rumble) in state.slots.iter_mut().zip(state.rumbles.iter_mut()) {
+                if slot.as_ref() == Some(&device) {
+                    *slot = None;
+                    *rumble = None;
+                }
+            }
+        } else if !state.slots.iter().flatten().any(|slot| *slot == device) {
+            if let Some(free_slot) = state.slots.iter_mut().find(|slot| slot.is_none()) {
+                *free_slot = Some(device);
+            } else {
+                crate::logging::warning!("Ignoring gamepad, as {MAX_GAMEPADS} are already in use");
+            }
+        }
+    }
+
+    for (gamepad, slot) in gamepads.gamepads.iter_mut().zip(&state.slots) {
+        let Some(device) = slot else {
+            gamepad.set_connected(false);
+            continue;
+        };
+        let reading = state
+            .current_reading(device.0)
+            .ok()
+            .flatten()
+            .unwrap_or_default();
+        gamepad.connected = true;
+        gamepad.pressed_bits = 0;
+        for (flag, button) in BUTTONS {
+            if reading.buttons & flag != 0 {
+                gamepad.pressed_bits |= 1 << (button as u32);
+            }
+        }
+        for (value, button) in [
+            (reading.left_trigger, Button::FrontLeftLower),
+            (reading.right_trigger, Button::FrontRightLower),
+        ] {
+            if value > TRIGGER_THRESHOLD {
+                gamepad.pressed_bits |= 1 << (button as u32);
+            }
+        }
+        gamepad.axes = [
+            crate::apply_deadzone(reading.left_thumbstick_x, LEFT_STICK_DEADZONE),
+            crate::apply_deadzone(reading.left_thumbstick_y, LEFT_STICK_DEADZONE),
+            crate::apply_deadzone(reading.right_thumbstick_x, RIGHT_STICK_DEADZONE),
+            crate::apply_deadzone(reading.right_thumbstick_y, RIGHT_STICK_DEADZONE),
+        ];
+        gamepad.triggers = [reading.left_trigger, reading.right_trigger];
+    }
+
+    update_rumbles(state);
+}
+
+/// Start rumble effects whose start delay has passed, and stop those that have ended.
+fn update_rumbles(state: &mut State) {
+    let now_ms = crate::time::now_ms();
+    for (slot, rumble) in state.slots.iter().zip(state.rumbles.iter_mut()) {
+        let (Some(device), Some(scheduled)) = (slot, rumble.as_mut()) else {
+            continue;
+        };
+        let params = if now_ms >= scheduled.stop_ms {
+            *rumble = None;
+            RumbleParams::default()
+        } else if now_ms >= scheduled.start_ms && !scheduled.started {
+            scheduled.started = true;
+            scheduled.params
+        } else {
+            continue;
+        };
+        device.set_rumble_state(&params);
+    }
+}
+
+/// Schedule a rumble effect, replacing any previous one, which is played from the next poll.
+pub fn rumble(
+    state: &mut State,
+    gamepad_id: crate::GamepadId,
+    duration_ms: u32,
+    start_delay_ms: u32,
+    strong_magnitude: f32,
+    weak_magnitude: f32,
+    trigger_magnitudes: (f32, f32),
+) {
+    let gamepad_idx = usize::from(gamepad_id.0);
+    let Some(device) = &state.slots[gamepad_idx] else {
+        return;
+    };
+    let start_ms = crate::time::now_ms() + f64::from(start_delay_ms);
+    // Stop a previous effect still playing until this one starts:
+    if state.rumbles[gamepad_idx].is_some_and(|previous| previous.started) {
+        device.set_rumble_state(&RumbleParams::default());
+    }
+    state.rumbles[gamepad_idx] = Some(ScheduledRumble {
+        start_ms,
+        stop_ms: start_ms + f64::from(duration_ms),
+        params: RumbleParams {
+            low_frequency: strong_magnitude.clamp(0., 1.),
+            high_frequency: weak_magnitude.clamp(0., 1.),
+            left_trigger: trigger_magnitudes.0.clamp(0., 1.),
+            right_trigger: trigger_magnitudes.1.clamp(0., 1.),
+        },
+        started: false,
+    });
+    update_rumbles(state);
+}
+
+/// Whether a gamepad is connected, in which case it is assumed to have rumble motors and
+/// impulse triggers, as for the wgi backend.
+pub fn capabilities(state: &State, gamepad_id: crate::GamepadId) -> crate::Capabilities {
+    let connected = state.slots[usize::from(gamepad_id.0)].is_some();
+    crate::Capabilities {
+        rumble: connected,
+        trigger_rumble: connected,
+        motion: false,
+        led: false,
+        force_feedback: false,
+    }
+}
+
+/// The USB vendor and product ids of a connected gamepad.
+pub fn vendor_product_ids(state: &State, gamepad_id: crate::GamepadId) -> Option<(u16, u16)> {
+    let info = state.slots[usize::from(gamepad_id.0)].as_ref()?.info()?;
+    Some((info.vendor_id, info.product_id))
+}
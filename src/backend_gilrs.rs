@@ -1,79 +1,134 @@
-impl crate::Gamepads {
-    pub fn setup_initially_connected_gilrs(&mut self) {
-        let gamepad_ids = self
-            .gilrs_instance
-            .gamepads()
-            .map(|(id, g)| (id, g.is_connected()))
-            .collect::<Vec<_>>();
-        for (id, connected) in gamepad_ids {
-            if let Some(p) = self.find_or_insert(id) {
-                self.gamepads[p].connected = connected;
-            }
+use crate::event::RawTimestamps;
+use crate::{logging, Gamepad, MAX_GAMEPADS};
+
+/// The state of the gilrs backend, shared with the background thread while
+/// [background polling](crate::Gamepads::enable_background_polling) is enabled.
+#[derive(Default)]
+pub struct State {
+    instance: Option<gilrs::Gilrs>,
+    gamepad_ids: [Option<gilrs::GamepadId>; MAX_GAMEPADS],
+    num_connected_pads: u8,
+    deadzones: [[f32; 4]; MAX_GAMEPADS],
+    rumble_effects: [Option<RumbleEffects>; MAX_GAMEPADS],
+}
+
+impl State {
+    pub fn new(instance: Option<gilrs::Gilrs>) -> Self {
+        Self {
+            instance,
+            ..Default::default()
         }
     }
 
-    fn find_or_insert(&mut self, gilrs_gamepad_id: gilrs::GamepadId) -> Option<usize> {
-        for i in 0..crate::MAX_GAMEPADS {
-            if self.gilrs_gamepad_ids[i] == gilrs_gamepad_id.into() {
-                return Some(i);
+    pub fn setup_initially_connected(&mut self, gamepads: &mut [Gamepad; MAX_GAMEPADS]) {
+        let Some(instance) = &self.instance else {
+            return;
+        };
+        for (id, gilrs_gamepad) in instance.gamepads() {
+            if let Some(p) = find_or_insert(&mut self.gamepad_ids, &mut self.num_connected_pads, id)
+            {
+                gamepads[p].connected = gilrs_gamepad.is_connected();
             }
         }
-        if self.num_connected_pads == crate::MAX_GAMEPADS as u8 {
-            None
-        } else {
-            let index = self.num_connected_pads;
-            self.num_connected_pads += 1;
-            self.gilrs_gamepad_ids[index as usize] = gilrs_gamepad_id.into();
-            Some(index as usize)
-        }
     }
 
-    pub fn poll_gilrs(&mut self) {
-        for gamepad in self.gamepads.iter_mut() {
+    fn find_or_insert(&mut self, gilrs_gamepad_id: gilrs::GamepadId) -> Option<usize> {
+        find_or_insert(
+            &mut self.gamepad_ids,
+            &mut self.num_connected_pads,
+            gilrs_gamepad_id,
+        )
+    }
+
+    /// Copy the gamepad ids of the state used by the background thread.
+    pub const fn copy_gamepad_ids(&mut self, state: &Self) {
+        self.gamepad_ids = state.gamepad_ids;
+    }
+
+    /// Discard the pending events, such as while another backend is used.
+    pub fn discard_events(&mut self) {
+        while self
+            .instance
+            .as_mut()
+            .and_then(gilrs::Gilrs::next_event)
+            .is_some()
+        {}
+    }
+
+    /// Update the raw state from the pending events, returning the number of events.
+    pub fn poll(
+        &mut self,
+        gamepads: &mut [Gamepad; MAX_GAMEPADS],
+        raw_timestamps: &mut [RawTimestamps; MAX_GAMEPADS],
+    ) -> u32 {
+        for gamepad in gamepads.iter_mut() {
             gamepad.just_pressed_bits = 0;
         }
 
-        while let Some(gilrs::Event { id, event, .. }) = self.gilrs_instance.next_event() {
+        // Clocks are read once per poll, with events timestamped relative to them:
+        let now_system = std::time::SystemTime::now();
+        let now_ms = crate::time::now_ms();
+        let mut events = 0;
+        while let Some(gilrs::Event { id, event, time }) =
+            self.instance.as_mut().and_then(gilrs::Gilrs::next_event)
+        {
+            events += 1;
+            // Convert the wall clock time of the event to the monotonic clock used for events:
+            let age_ms = now_system
+                .duration_since(time)
+                .unwrap_or_default()
+                .as_secs_f64()
+                * 1000.;
+            let time_ms = now_ms - age_ms;
             match event {
                 gilrs::EventType::Connected => {
                     if let Some(gamepad_idx) = self.find_or_insert(id) {
-                        self.gamepads[gamepad_idx].connected = true;
-
-                        for (zone, axis) in [
-                            (0, gilrs::Axis::LeftStickX),
-                            (1, gilrs::Axis::LeftStickY),
-                            (2, gilrs::Axis::RightStickY),
-                            (3, gilrs::Axis::RightStickY),
-                        ] {
-                            if let Some(code) = self.gilrs_instance.gamepad(id).axis_code(axis) {
-                                self.deadzones[gamepad_idx][zone] = self
-                                    .gilrs_instance
-                                    .gamepad(id)
-                                    .deadzone(code)
-                                    .unwrap_or_default();
+                        gamepads[gamepad_idx].connected = true;
+                        // Events are only received from an instance, so it is present here:
+                        if let Some(instance) = &self.instance {
+                            let gilrs_gamepad = instance.gamepad(id);
+                            logging::info!(
+                                "Connected {:?} as gamepad index {gamepad_idx}, using {:?} mapping",
+                                gilrs_gamepad.name(),
+                                gilrs_gamepad.mapping_source(),
+                            );
+                            for (zone, axis) in [
+                                (0, gilrs::Axis::LeftStickX),
+                                (1, gilrs::Axis::LeftStickY),
+                                (2, gilrs::Axis::RightStickX),
+                                (3, gilrs::Axis::RightStickY),
+                            ] {
+                                if let Some(code) = gilrs_gamepad.axis_code(axis) {
+                                    self.deadzones[gamepad_idx][zone] =
+                                        gilrs_gamepad.deadzone(code).unwrap_or_default();
+                                }
                             }
                         }
                     }
                 }
                 gilrs::EventType::Disconnected => {
                     if let Some(gamepad_idx) = self.find_or_insert(id) {
-                        self.gamepads[gamepad_idx].connected = false;
+                        logging::info!("Disconnected gamepad index {gamepad_idx}");
+                        gamepads[gamepad_idx].connected = false;
+                        self.rumble_effects[gamepad_idx] = None;
                     }
                 }
-                gilrs::EventType::ButtonPressed(button, _code) => {
+                gilrs::EventType::ButtonPressed(button, code) => {
                     if let Some(gamepad_idx) = self.find_or_insert(id) {
-                        if let Some(b) = crate::Button::from_gilrs(button) {
-                            let bit = 1 << (b as u32);
-                            self.gamepads[gamepad_idx].pressed_bits |= bit;
-                            self.gamepads[gamepad_idx].just_pressed_bits |= bit;
+                        if let Some(bit_idx) = self.button_bit_idx(id, button, code) {
+                            let bit = 1 << bit_idx;
+                            gamepads[gamepad_idx].pressed_bits |= bit;
+                            gamepads[gamepad_idx].just_pressed_bits |= bit;
+                            raw_timestamps[gamepad_idx].record_press(bit_idx, time_ms);
                         }
                     }
                 }
-                gilrs::EventType::ButtonReleased(button, _code) => {
+                gilrs::EventType::ButtonReleased(button, code) => {
                     if let Some(gamepad_idx) = self.find_or_insert(id) {
-                        if let Some(b) = crate::Button::from_gilrs(button) {
-                            let bit = 1 << (b as u32);
-                            self.gamepads[gamepad_idx].pressed_bits &= !bit;
+                        if let Some(bit_idx) = self.button_bit_idx(id, button, code) {
+                            let bit = 1 << bit_idx;
+                            gamepads[gamepad_idx].pressed_bits &= !bit;
+                            raw_timestamps[gamepad_idx].record_release(bit_idx, time_ms);
                         }
                     }
                 }
@@ -87,22 +142,90 @@ impl crate::Gamepads {
                             _ => None,
                         } {
                             let deadzone = self.deadzones[gamepad_idx][axis_idx];
-                            self.gamepads[gamepad_idx].axes[axis_idx] = if value.abs() < deadzone {
-                                // Axis values within deadzone are 0:
-                                0.
-                            } else {
-                                // Adjust so that interval of magnitude is [0.0, 1.0]:
-                                value.signum().mul_add(-deadzone, value) / (1. - deadzone)
-                            };
+                            gamepads[gamepad_idx].axes[axis_idx] =
+                                crate::apply_deadzone(value, deadzone);
+                            raw_timestamps[gamepad_idx].record_axes(time_ms);
+                        }
+                    }
+                }
+                gilrs::EventType::ButtonChanged(button, value, _code) => {
+                    if let Some(gamepad_idx) = self.find_or_insert(id) {
+                        if let Some(trigger_idx) = match button {
+                            gilrs::Button::LeftTrigger2 => Some(0),
+                            gilrs::Button::RightTrigger2 => Some(1),
+                            _ => None,
+                        } {
+                            gamepads[gamepad_idx].triggers[trigger_idx] = value;
+                            raw_timestamps[gamepad_idx].record_axes(time_ms);
                         }
                     }
                 }
                 _ => {}
             }
         }
+        events
     }
 
-    pub fn rumble_gilrs(
+    /// The bit index of a gilrs button in the pressed bits, which for buttons not mapped by
+    /// gilrs is the index of a known extra button, see [extra_button_index()].
+    fn button_bit_idx(
+        &self,
+        id: gilrs::GamepadId,
+        button: gilrs::Button,
+        code: gilrs::ev::Code,
+    ) -> Option<usize> {
+        if let Ok(b) = crate::Button::try_from(button) {
+            return Some(b as usize);
+        }
+        let instance = self.instance.as_ref()?;
+        extra_button_index(&instance.gamepad(id), code).map(usize::from)
+    }
+
+    /// The gilrs gamepad of a connected gamepad.
+    fn connected_gamepad(&self, gamepad_id: crate::GamepadId) -> Option<gilrs::Gamepad<'_>> {
+        self.instance
+            .as_ref()?
+            .connected_gamepad(self.gamepad_ids[gamepad_id.0 as usize]?)
+    }
+
+    pub fn capabilities(&self, gamepad_id: crate::GamepadId) -> crate::Capabilities {
+        let Some(gilrs_gamepad) = self.connected_gamepad(gamepad_id) else {
+            return crate::Capabilities::default();
+        };
+        crate::Capabilities {
+            rumble: gilrs_gamepad.is_ff_supported(),
+            trigger_rumble: false,
+            motion: false,
+            led: false,
+            force_feedback: false,
+        }
+    }
+
+    pub fn name(&self, gamepad_id: crate::GamepadId) -> Option<String> {
+        Some(self.connected_gamepad(gamepad_id)?.name().to_owned())
+    }
+
+    pub fn vendor_product_ids(&self, gamepad_id: crate::GamepadId) -> Option<(u16, u16)> {
+        let gilrs_gamepad = self.connected_gamepad(gamepad_id)?;
+        Some((gilrs_gamepad.vendor_id()?, gilrs_gamepad.product_id()?))
+    }
+
+    pub fn battery(&self, gamepad_id: crate::GamepadId) -> Option<crate::Battery> {
+        let (status, level) = match self.connected_gamepad(gamepad_id)?.power_info() {
+            gilrs::PowerInfo::Discharging(level) => (crate::BatteryStatus::Discharging, level),
+            gilrs::PowerInfo::Charging(level) => (crate::BatteryStatus::Charging, level),
+            gilrs::PowerInfo::Charged => (crate::BatteryStatus::Full, 100),
+            gilrs::PowerInfo::Unknown | gilrs::PowerInfo::Wired => return None,
+        };
+        Some(crate::Battery {
+            status,
+            level: Some(f32::from(level) / 100.),
+        })
+    }
+
+    /// Rumble using the effects of the gamepad, which are created on the first rumble and then
+    /// reused, so that frequent rumbles do not create new effects each time.
+    pub fn rumble(
         &mut self,
         gamepad_id: crate::GamepadId,
         duration_ms: u32,
@@ -110,84 +233,301 @@ impl crate::Gamepads {
         strong_magnitude: f32,
         weak_magnitude: f32,
     ) {
-        let now_ms = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_millis();
-
-        // Purge old effects.
-        for i in (0..self.playing_ff_effects.len()).rev() {
-            if self.playing_ff_effects[i].1 < now_ms {
-                self.playing_ff_effects.swap_remove(i);
+        let Some(gilrs_gamepad_id) = self.gamepad_ids[gamepad_id.0 as usize] else {
+            return;
+        };
+        let Some(instance) = &mut self.instance else {
+            return;
+        };
+        let effects = &mut self.rumble_effects[usize::from(gamepad_id.0)];
+        // The start delay is part of the effects, so they are recreated when it changes:
+        if effects
+            .as_ref()
+            .is_none_or(|effects| effects.start_delay_ms != start_delay_ms)
+        {
+            *effects = None;
+            match RumbleEffects::new(instance, gilrs_gamepad_id, start_delay_ms) {
+                Ok(created) => *effects = Some(created),
+                Err(error) => {
+                    logging::warning!("Failed to create rumble effect: {error}");
+                    return;
+                }
+            }
+        }
+        if let Some(effects) = effects {
+            if let Err(error) = effects.play(duration_ms, strong_magnitude, weak_magnitude) {
+                logging::warning!("Failed to play rumble effect: {error}");
             }
         }
+    }
+}
+
+impl crate::Gamepads {
+    /// The gilrs instance reading gamepads, for functionality only available through gilrs, such
+    /// as custom mappings using `Gilrs::set_mapping()` or building force feedback effects.
+    ///
+    /// The [gilrs](crate::gilrs) crate is re-exported, so that its types match those used here.
+    /// `None` if gilrs could not be initialized, see [Gamepads::try_new()](crate::Gamepads::try_new),
+    /// and while the instance is used by the thread of
+    /// [background polling](crate::Gamepads::enable_background_polling).
+    pub const fn gilrs(&self) -> Option<&gilrs::Gilrs> {
+        self.gilrs.instance.as_ref()
+    }
+
+    /// Mutable access to the gilrs instance reading gamepads, see [Gamepads::gilrs()].
+    ///
+    /// Events should not be taken from it using `Gilrs::next_event()`, as they are then missed by
+    /// [Gamepads::poll()](crate::Gamepads::poll).
+    pub const fn gilrs_mut(&mut self) -> Option<&mut gilrs::Gilrs> {
+        self.gilrs.instance.as_mut()
+    }
+
+    /// The id of a gamepad in the [Gamepads::gilrs()] instance, if it has been connected.
+    ///
+    /// ```no_run
+    /// use gamepads::{GamepadId, Gamepads};
+    ///
+    /// let gamepads = Gamepads::new();
+    /// let gamepad_id = GamepadId::new(0).unwrap();
+    /// if let (Some(gilrs), Some(gilrs_id)) = (gamepads.gilrs(), gamepads.gilrs_id(gamepad_id)) {
+    ///     println!("Mapped using {:?}", gilrs.gamepad(gilrs_id).mapping_source());
+    /// }
+    /// ```
+    pub const fn gilrs_id(&self, gamepad_id: crate::GamepadId) -> Option<gilrs::GamepadId> {
+        self.gilrs.gamepad_ids[gamepad_id.0 as usize]
+    }
+
+    /// The gamepad of a gamepad id of the [Gamepads::gilrs()] instance, if it has been connected.
+    pub fn gamepad_id_from_gilrs(&self, gilrs_id: gilrs::GamepadId) -> Option<crate::GamepadId> {
+        self.gilrs
+            .gamepad_ids
+            .iter()
+            .position(|&id| id == Some(gilrs_id))
+            .map(|idx| crate::GamepadId(idx as u8))
+    }
+
+    /// Use the gilrs backend state, which is shared with the thread of background polling while
+    /// enabled.
+    pub(crate) fn with_gilrs<T>(&mut self, f: impl FnOnce(&mut State) -> T) -> T {
+        match &self.background_poller {
+            Some(background_poller) => background_poller.with_gilrs(f),
+            None => f(&mut self.gilrs),
+        }
+    }
+
+    /// Read the gilrs backend state, see [Gamepads::with_gilrs()].
+    fn read_gilrs<T>(&self, f: impl FnOnce(&State) -> T) -> T {
+        match &self.background_poller {
+            Some(background_poller) => background_poller.with_gilrs(|state| f(state)),
+            None => f(&self.gilrs),
+        }
+    }
+
+    pub fn setup_initially_connected_gilrs(&mut self) {
+        let mut gamepads = self.gamepads;
+        self.with_gilrs(|state| state.setup_initially_connected(&mut gamepads));
+        self.gamepads = gamepads;
+    }
+
+    pub fn poll_gilrs(&mut self) {
+        self.backend_events += self
+            .gilrs
+            .poll(&mut self.gamepads, &mut self.raw_timestamps);
+    }
+
+    pub fn capabilities_gilrs(&self, gamepad_id: crate::GamepadId) -> crate::Capabilities {
+        self.read_gilrs(|state| state.capabilities(gamepad_id))
+    }
+
+    pub fn name_gilrs(&self, gamepad_id: crate::GamepadId) -> Option<String> {
+        self.read_gilrs(|state| state.name(gamepad_id))
+    }
+
+    pub fn vendor_product_ids_gilrs(&self, gamepad_id: crate::GamepadId) -> Option<(u16, u16)> {
+        self.read_gilrs(|state| state.vendor_product_ids(gamepad_id))
+    }
+
+    pub fn battery_gilrs(&self, gamepad_id: crate::GamepadId) -> Option<crate::Battery> {
+        self.read_gilrs(|state| state.battery(gamepad_id))
+    }
+
+    pub fn rumble_gilrs(
+        &mut self,
+        gamepad_id: crate::GamepadId,
+        duration_ms: u32,
+        start_delay_ms: u32,
+        strong_magnitude: f32,
+        weak_magnitude: f32,
+    ) {
+        self.with_gilrs(|state| {
+            state.rumble(
+                gamepad_id,
+                duration_ms,
+                start_delay_ms,
+                strong_magnitude,
+                weak_magnitude,
+            );
+        });
+    }
+}
 
-        let gilrs_gamepad_id = self.gilrs_gamepad_ids[gamepad_id.0 as usize];
-        let gilrs_gamepad_id: gilrs::GamepadId = unsafe { std::mem::transmute(gilrs_gamepad_id) };
+/// The strong and weak rumble effects of a gamepad, played with the magnitudes as their gain.
+pub struct RumbleEffects {
+    strong: gilrs::ff::Effect,
+    weak: gilrs::ff::Effect,
+    start_delay_ms: u32,
+}
 
-        let play_for = gilrs::ff::Ticks::from_ms(duration_ms);
-        let after = gilrs::ff::Ticks::from_ms(start_delay_ms);
+impl RumbleEffects {
+    fn new(
+        gilrs_instance: &mut gilrs::Gilrs,
+        gilrs_gamepad_id: gilrs::GamepadId,
+        start_delay_ms: u32,
+    ) -> Result<Self, gilrs::ff::Error> {
         let scheduling = gilrs::ff::Replay {
-            play_for,
-            after,
+            after: gilrs::ff::Ticks::from_ms(start_delay_ms),
+            // Played until stopped by the repeat duration set when played:
+            play_for: gilrs::ff::Ticks::from_ms(u32::MAX),
             ..Default::default()
         };
+        let mut create = |kind| {
+            gilrs::ff::EffectBuilder::new()
+                .add_effect(gilrs::ff::BaseEffect {
+                    kind,
+                    scheduling,
+                    ..Default::default()
+                })
+                .gamepads(&[gilrs_gamepad_id])
+                .finish(gilrs_instance)
+        };
+        Ok(Self {
+            strong: create(gilrs::ff::BaseEffectType::Strong {
+                magnitude: u16::MAX,
+            })?,
+            weak: create(gilrs::ff::BaseEffectType::Weak {
+                magnitude: u16::MAX,
+            })?,
+            start_delay_ms,
+        })
+    }
 
-        let strong_magnitude = (f32::from(u16::MAX) * strong_magnitude).round() as u16;
-        let weak_magnitude = (f32::from(u16::MAX) * weak_magnitude).round() as u16;
-
-        if let Ok(effect) = gilrs::ff::EffectBuilder::new()
-            .add_effect(gilrs::ff::BaseEffect {
-                kind: gilrs::ff::BaseEffectType::Strong {
-                    magnitude: strong_magnitude,
-                },
-                scheduling,
-                ..Default::default()
-            })
-            .add_effect(gilrs::ff::BaseEffect {
-                kind: gilrs::ff::BaseEffectType::Weak {
-                    magnitude: weak_magnitude,
-                },
-                scheduling,
-                ..Default::default()
-            })
-            .repeat(gilrs::ff::Repeat::For(play_for + after))
-            .gamepads(&[gilrs_gamepad_id])
-            .finish(&mut self.gilrs_instance)
-        {
-            if effect.play().is_ok() {
-                // Effects stop playing in drop(), so keep a reference.
-                let throw_away_at = now_ms + u128::from(duration_ms) + u128::from(start_delay_ms);
-                self.playing_ff_effects.push((effect, throw_away_at));
-            }
+    /// Restart the effects, replacing any rumble still playing.
+    fn play(
+        &self,
+        duration_ms: u32,
+        strong_magnitude: f32,
+        weak_magnitude: f32,
+    ) -> Result<(), gilrs::ff::Error> {
+        let repeat = gilrs::ff::Repeat::For(gilrs::ff::Ticks::from_ms(
+            self.start_delay_ms.saturating_add(duration_ms),
+        ));
+        for (effect, magnitude) in [
+            (&self.strong, strong_magnitude),
+            (&self.weak, weak_magnitude),
+        ] {
+            effect.set_gain(magnitude.clamp(0., 1.))?;
+            effect.set_repeat(repeat)?;
+            effect.play()?;
         }
+        Ok(())
     }
 }
 
-impl crate::Button {
-    const fn from_gilrs(button: gilrs::Button) -> Option<Self> {
-        Some(match button {
-            gilrs::Button::South => Self::ActionDown,
-            gilrs::Button::East => Self::ActionRight,
-            gilrs::Button::West => Self::ActionLeft,
-            gilrs::Button::North => Self::ActionUp,
-            gilrs::Button::LeftTrigger => Self::FrontLeftUpper,
-            gilrs::Button::RightTrigger => Self::FrontRightUpper,
-            gilrs::Button::LeftTrigger2 => Self::FrontLeftLower,
-            gilrs::Button::RightTrigger2 => Self::FrontRightLower,
-            gilrs::Button::Select => Self::LeftCenterCluster,
-            gilrs::Button::Start => Self::RightCenterCluster,
-            gilrs::Button::LeftThumb => Self::LeftStick,
-            gilrs::Button::RightThumb => Self::RightStick,
-            gilrs::Button::DPadUp => Self::DPadUp,
-            gilrs::Button::DPadDown => Self::DPadDown,
-            gilrs::Button::DPadLeft => Self::DPadLeft,
-            gilrs::Button::DPadRight => Self::DPadRight,
-            gilrs::Button::Mode => Self::Mode,
-            // Other:
-            _ => {
-                return None;
-            }
-        })
+/// Find the index of a gilrs gamepad, assigning the next free index if it is new.
+///
+/// Takes the fields it needs rather than `&mut Gamepads`, so that it can be called while
+/// iterating over the gilrs instance without first collecting its gamepads.
+fn find_or_insert(
+    gilrs_gamepad_ids: &mut [Option<gilrs::GamepadId>; crate::MAX_GAMEPADS],
+    num_connected_pads: &mut u8,
+    gilrs_gamepad_id: gilrs::GamepadId,
+) -> Option<usize> {
+    if let Some(index) = gilrs_gamepad_ids
+        .iter()
+        .position(|&id| id == Some(gilrs_gamepad_id))
+    {
+        return Some(index);
+    }
+    if *num_connected_pads == crate::MAX_GAMEPADS as u8 {
+        logging::warning!(
+            "Ignoring gamepad, as {} are already in use",
+            crate::MAX_GAMEPADS
+        );
+        None
+    } else {
+        let index = *num_connected_pads as usize;
+        *num_connected_pads += 1;
+        gilrs_gamepad_ids[index] = Some(gilrs_gamepad_id);
+        Some(index)
+    }
+}
+
+/// The index beyond [Mode](crate::Button::Mode) of a button not mapped by gilrs, for the
+/// extra buttons of known devices, as checked by [Gamepad::is_extra_button_pressed()](crate::Gamepad::is_extra_button_pressed).
+#[cfg(target_os = "linux")]
+fn extra_button_index(gilrs_gamepad: &gilrs::Gamepad<'_>, code: gilrs::ev::Code) -> Option<u8> {
+    use crate::evdev_mapping::EV_KEY;
+    // The code of gilrs is the event type in the upper half and the event code in the lower:
+    let code = code.into_u32();
+    if code >> 16 != u32::from(EV_KEY) {
+        return None;
+    }
+    crate::evdev_mapping::extra_button_index(
+        gilrs_gamepad.vendor_id()?,
+        gilrs_gamepad.product_id()?,
+        code as u16,
+    )
+}
+
+/// Extra buttons are only known by their event codes on Linux.
+#[cfg(not(target_os = "linux"))]
+const fn extra_button_index(
+    _gilrs_gamepad: &gilrs::Gamepad<'_>,
+    _code: gilrs::ev::Code,
+) -> Option<u8> {
+    None
+}
+
+/// The thumbstick axis for an axis of gilrs, or the gilrs axis back for those without one, such
+/// as `gilrs::Axis::DPadX`. Both have y positive upwards.
+///
+/// Analog triggers are buttons with values in gilrs, so `gilrs::Axis::LeftZ` and
+/// `gilrs::Axis::RightZ` are not converted to the triggers of
+/// [ActionInput](crate::ActionInput).
+///
+/// ```
+/// use gamepads::{gilrs, ActionInput};
+///
+/// assert_eq!(ActionInput::try_from(gilrs::Axis::LeftStickY), Ok(ActionInput::LeftStickY));
+/// assert_eq!(gilrs::Axis::try_from(ActionInput::RightStickX), Ok(gilrs::Axis::RightStickX));
+/// assert_eq!(ActionInput::try_from(gilrs::Axis::DPadX), Err(gilrs::Axis::DPadX));
+/// ```
+impl TryFrom<gilrs::Axis> for crate::ActionInput {
+    type Error = gilrs::Axis;
+
+    fn try_from(axis: gilrs::Axis) -> Result<Self, Self::Error> {
+        match axis {
+            gilrs::Axis::LeftStickX => Ok(Self::LeftStickX),
+            gilrs::Axis::LeftStickY => Ok(Self::LeftStickY),
+            gilrs::Axis::RightStickX => Ok(Self::RightStickX),
+            gilrs::Axis::RightStickY => Ok(Self::RightStickY),
+            other => Err(other),
+        }
+    }
+}
+
+/// The axis of gilrs for a thumbstick axis, or the input back for buttons and triggers, see the
+/// conversion back.
+impl TryFrom<crate::ActionInput> for gilrs::Axis {
+    type Error = crate::ActionInput;
+
+    fn try_from(input: crate::ActionInput) -> Result<Self, Self::Error> {
+        match input {
+            crate::ActionInput::LeftStickX => Ok(Self::LeftStickX),
+            crate::ActionInput::LeftStickY => Ok(Self::LeftStickY),
+            crate::ActionInput::RightStickX => Ok(Self::RightStickX),
+            crate::ActionInput::RightStickY => Ok(Self::RightStickY),
+            other => Err(other),
+        }
     }
 }
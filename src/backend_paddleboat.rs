@@ -0,0 +1,338 @@
+//! Backend for Android, enabled by the `backend-paddleboat` feature, reading gamepads using the
+//! Paddleboat library of the Android Game Development Kit instead of decoding winit events.
+//!
+//! Paddleboat maps controllers to a standard layout using its own database, tracks controllers
+//! connecting and disconnecting, and plays rumble effects. It links to `libpaddleboat.so`, which
+//! is distributed with the Android Game Development Kit, and expects the app to use GameActivity
+//! and forward its input events using [Gamepads::on_game_activity_key_event()](crate::Gamepads::on_game_activity_key_event)
+//! and [Gamepads::on_game_activity_motion_event()](crate::Gamepads::on_game_activity_motion_event).
+
+use crate::{Button, MAX_GAMEPADS};
+use std::ffi::{c_char, c_void, CStr};
+
+/// `JNIEnv` as expected by Paddleboat.
+type JniEnv = *mut jni::sys::JNIEnv;
+
+/// The `Paddleboat_ErrorCode` of success.
+const PADDLEBOAT_NO_ERROR: i32 = 0;
+
+/// The `Paddleboat_ControllerStatus` values of a connected controller.
+const PADDLEBOAT_CONTROLLER_ACTIVE: i32 = 1;
+const PADDLEBOAT_CONTROLLER_JUST_CONNECTED: i32 = 2;
+
+/// The `Paddleboat_Buttons` bits of `buttonsDown`, with the button they are reported as.
+const BUTTONS: [(u32, Button); 17] = [
+    (1 << 0, Button::DPadUp),
+    (1 << 1, Button::DPadLeft),
+    (1 << 2, Button::DPadDown),
+    (1 << 3, Button::DPadRight),
+    (1 << 4, Button::ActionDown),
+    (1 << 5, Button::ActionRight),
+    (1 << 6, Button::ActionLeft),
+    (1 << 7, Button::ActionUp),
+    (1 << 8, Button::FrontLeftUpper),
+    (1 << 9, Button::FrontLeftLower),
+    (1 << 10, Button::LeftStick),
+    (1 << 11, Button::FrontRightUpper),
+    (1 << 12, Button::FrontRightLower),
+    (1 << 13, Button::RightStick),
+    (1 << 14, Button::LeftCenterCluster),
+    (1 << 15, Button::RightCenterCluster),
+    (1 << 16, Button::Mode),
+];
+
+/// The touchpad button and the four auxiliary buttons as `Paddleboat_Buttons` bits, and the
+/// extra button index they are reported as.
+const EXTRA_BUTTONS: [(u32, u8); 5] = [
+    (1 << 17, 17),
+    (1 << 18, 18),
+    (1 << 19, 19),
+    (1 << 20, 20),
+    (1 << 21, 21),
+];
+
+/// The `Paddleboat_BatteryStatus` values, with the status they are reported as.
+const BATTERY_STATUSES: [(i32, crate::BatteryStatus); 4] = [
+    (1, crate::BatteryStatus::Charging),
+    (2, crate::BatteryStatus::Discharging),
+    (3, crate::BatteryStatus::NotCharging),
+    (4, crate::BatteryStatus::Full),
+];
+
+/// `Paddleboat_Controller_Thumbstick`.
+#[repr(C)]
+#[derive(Default)]
+struct Thumbstick {
+    x: f32,
+    y: f32,
+}
+
+/// `Paddleboat_Controller_Data`.
+#[repr(C)]
+#[derive(Default)]
+struct ControllerData {
+    timestamp: u64,
+    buttons_down: u32,
+    left_stick: Thumbstick,
+    right_stick: Thumbstick,
+    trigger_l1: f32,
+    trigger_l2: f32,
+    trigger_r1: f32,
+    trigger_r2: f32,
+    virtual_pointer: Thumbstick,
+    battery_status: i32,
+    battery_level: f32,
+}
+
+/// `Paddleboat_Vibration_Data`.
+#[repr(C)]
+struct VibrationData {
+    duration_left: i32,
+    duration_right: i32,
+    intensity_left: f32,
+    intensity_right: f32,
+}
+
+#[link(name = "paddleboat")]
+extern "C" {
+    fn Paddleboat_init(env: JniEnv, context: jni::sys::jobject) -> i32;
+    fn Paddleboat_onStart(env: JniEnv);
+    fn Paddleboat_onStop(env: JniEnv);
+    fn Paddleboat_update(env: JniEnv);
+    fn Paddleboat_processGameActivityKeyInputEvent(event: *const c_void, event_size: usize) -> i32;
+    fn Paddleboat_processGameActivityMotionInputEvent(
+        event: *const c_void,
+        event_size: usize,
+    ) -> i32;
+    fn Paddleboat_getControllerStatus(controller_index: i32) -> i32;
+    fn Paddleboat_getControllerData(controller_index: i32, data: *mut ControllerData) -> i32;
+    fn Paddleboat_getControllerName(
+        controller_index: i32,
+        buffer_size: usize,
+        name: *mut c_char,
+    ) -> i32;
+    fn Paddleboat_setControllerVibrationData(
+        controller_index: i32,
+        vibration_data: *const VibrationData,
+        env: JniEnv,
+    ) -> i32;
+}
+
+/// A rumble effect waiting for its start delay, as Paddleboat has no start delays.
+#[derive(Clone, Copy)]
+struct ScheduledRumble {
+    start_ms: f64,
+    duration_ms: u32,
+    magnitudes: [f32; 2],
+}
+
+/// The Java VM for calling Paddleboat, or `None` if Paddleboat could not be initialized.
+pub struct State {
+    vm: Option<jni::JavaVM>,
+    scheduled_rumbles: [Option<ScheduledRumble>; MAX_GAMEPADS],
+    /// The batteries of the controllers, as of the last poll.
+    batteries: [Option<crate::Battery>; MAX_GAMEPADS],
+}
+
+impl State {
+    pub fn new() -> Self {
+        let vm = Self::init()
+            .inspect_err(|error| {
+                crate::logging::warning!("Failed to initialize Paddleboat: {error}");
+            })
+            .ok();
+        Self {
+            vm,
+            scheduled_rumbles: [None; MAX_GAMEPADS],
+            batteries: [None; MAX_GAMEPADS],
+        }
+    }
+
+    fn init() -> Result<jni::JavaVM, String> {
+        let ctx = ndk_context::android_context();
+        let vm = unsafe { jni::JavaVM::from_raw(ctx.vm().cast()) }.map_err(|e| e.to_string())?;
+        let env = vm.attach_current_thread().map_err(|e| e.to_string())?;
+        let error = unsafe { Paddleboat_init(env.get_raw(), ctx.context().cast()) };
+        if error != PADDLEBOAT_NO_ERROR {
+            return Err(format!("error code {error}"));
+        }
+        drop(env);
+        Ok(vm)
+    }
+
+    /// Call Paddleboat with the `JNIEnv` of the current thread.
+    fn with_env<T>(&self, call: impl FnOnce(JniEnv) -> T) -> Option<T> {
+        let env = self.vm.as_ref()?.attach_current_thread().ok()?;
+        Some(call(env.get_raw()))
+    }
+
+    /// The battery of a controller, as of the last poll.
+    pub const fn battery(&self, gamepad_id: crate::GamepadId) -> Option<crate::Battery> {
+        self.batteries[gamepad_id.0 as usize]
+    }
+}
+
+impl crate::Gamepads {
+    /// Forward a key event of GameActivity to Paddleboat, returning whether it was an event of
+    /// a controller, which the app should then not handle itself.
+    ///
+    /// # Safety
+    ///
+    /// `event` must point to a `GameActivityKeyEvent` of `event_size` bytes.
+    pub unsafe fn on_game_activity_key_event(
+        &mut self,
+        event: *const c_void,
+        event_size: usize,
+    ) -> bool {
+        self.backend_events += 1;
+        self.paddleboat.vm.is_some()
+            && unsafe { Paddleboat_processGameActivityKeyInputEvent(event, event_size) } != 0
+    }
+
+    /// Forward a motion event of GameActivity to Paddleboat, returning whether it was an event
+    /// of a controller, which the app should then not handle itself.
+    ///
+    /// # Safety
+    ///
+    /// `event` must point to a `GameActivityMotionEvent` of `event_size` bytes.
+    pub unsafe fn on_game_activity_motion_event(
+        &mut self,
+        event: *const c_void,
+        event_size: usize,
+    ) -> bool {
+        self.backend_events += 1;
+        self.paddleboat.vm.is_some()
+            && unsafe { Paddleboat_processGameActivityMotionInputEvent(event, event_size) } != 0
+    }
+
+    /// Let Paddleboat resume tracking controllers when the activity is started, as on
+    /// `MainEvent::Start` of android-activity.
+    pub fn on_activity_start(&mut self) {
+        self.paddleboat
+            .with_env(|env| unsafe { Paddleboat_onStart(env) });
+    }
+
+    /// Let Paddleboat pause tracking controllers when the activity is stopped, as on
+    /// `MainEvent::Stop` of android-activity, dropping rumble effects waiting for their delay.
+    pub fn on_activity_stop(&mut self) {
+        self.paddleboat.scheduled_rumbles = [None; MAX_GAMEPADS];
+        self.paddleboat
+            .with_env(|env| unsafe { Paddleboat_onStop(env) });
+    }
+}
+
+/// Read the state of all controllers, and start rumble effects whose start delay has passed.
+pub fn poll(gamepads: &mut crate::Gamepads) {
+    if gamepads
+        .paddleboat
+        .with_env(|env| unsafe { Paddleboat_update(env) })
+        .is_none()
+    {
+        return;
+    }
+
+    let now_ms = crate::time::now_ms();
+    for (controller_index, gamepad) in (0..).zip(gamepads.gamepads.iter_mut()) {
+        let status = unsafe { Paddleboat_getControllerStatus(controller_index) };
+        let mut data = ControllerData::default();
+        if !matches!(
+            status,
+            PADDLEBOAT_CONTROLLER_ACTIVE | PADDLEBOAT_CONTROLLER_JUST_CONNECTED
+        ) || unsafe { Paddleboat_getControllerData(controller_index, &mut data) }
+            != PADDLEBOAT_NO_ERROR
+        {
+            gamepad.set_connected(false);
+            gamepads.paddleboat.scheduled_rumbles[controller_index as usize] = None;
+            gamepads.paddleboat.batteries[controller_index as usize] = None;
+            continue;
+        }
+        if let Some(rumble) = gamepads.paddleboat.scheduled_rumbles[controller_index as usize]
+            .take_if(|rumble| now_ms >= rumble.start_ms)
+        {
+            play(&gamepads.paddleboat, controller_index, &rumble);
+        }
+
+        gamepad.connected = true;
+        gamepad.pressed_bits = 0;
+        for (paddleboat_bit, button) in BUTTONS {
+            if data.buttons_down & paddleboat_bit != 0 {
+                gamepad.pressed_bits |= 1 << (button as u32);
+            }
+        }
+        for (paddleboat_bit, extra_idx) in EXTRA_BUTTONS {
+            if data.buttons_down & paddleboat_bit != 0 {
+                gamepad.pressed_bits |= 1 << extra_idx;
+            }
+        }
+        // Axes are positive downwards, while thumbsticks are positive upwards:
+        gamepad.axes = [
+            data.left_stick.x,
+            -data.left_stick.y,
+            data.right_stick.x,
+            -data.right_stick.y,
+        ];
+        gamepad.triggers = [data.trigger_l2, data.trigger_r2];
+        // Controllers without battery information report an unknown status:
+        gamepads.paddleboat.batteries[controller_index as usize] = BATTERY_STATUSES
+            .iter()
+            .find(|&&(paddleboat_status, _)| paddleboat_status == data.battery_status)
+            .map(|&(_, status)| crate::Battery {
+                status,
+                level: Some(data.battery_level.clamp(0., 1.)),
+            });
+    }
+}
+
+fn play(state: &State, controller_index: i32, rumble: &ScheduledRumble) {
+    let duration_ms = i32::try_from(rumble.duration_ms).unwrap_or(i32::MAX);
+    // The left motor is the low frequency one:
+    let vibration_data = VibrationData {
+        duration_left: duration_ms,
+        duration_right: duration_ms,
+        intensity_left: rumble.magnitudes[0],
+        intensity_right: rumble.magnitudes[1],
+    };
+    let error = state.with_env(|env| unsafe {
+        Paddleboat_setControllerVibrationData(controller_index, &vibration_data, env)
+    });
+    if error.is_some_and(|error| error != PADDLEBOAT_NO_ERROR) {
+        crate::logging::warning!("Failed to rumble controller: error code {error:?}");
+    }
+}
+
+/// Play a rumble effect, replacing any previous one, from the next poll if it has a start delay.
+pub fn rumble(
+    state: &mut State,
+    gamepad_id: crate::GamepadId,
+    duration_ms: u32,
+    start_delay_ms: u32,
+    strong_magnitude: f32,
+    weak_magnitude: f32,
+) {
+    let rumble = ScheduledRumble {
+        start_ms: crate::time::now_ms() + f64::from(start_delay_ms),
+        duration_ms,
+        magnitudes: [strong_magnitude.clamp(0., 1.), weak_magnitude.clamp(0., 1.)],
+    };
+    let slot = &mut state.scheduled_rumbles[usize::from(gamepad_id.0)];
+    if start_delay_ms == 0 {
+        *slot = None;
+        play(state, i32::from(gamepad_id.0), &rumble);
+    } else {
+        *slot = Some(rumble);
+    }
+}
+
+/// The name of a connected controller, as reported by Paddleboat.
+pub fn name(state: &State, gamepad_id: crate::GamepadId) -> Option<String> {
+    state.vm.as_ref()?;
+    let mut buffer = [0 as c_char; 256];
+    let error = unsafe {
+        Paddleboat_getControllerName(i32::from(gamepad_id.0), buffer.len(), buffer.as_mut_ptr())
+    };
+    (error == PADDLEBOAT_NO_ERROR).then(|| {
+        unsafe { CStr::from_ptr(buffer.as_ptr()) }
+            .to_string_lossy()
+            .into_owned()
+    })
+}
@@ -0,0 +1,698 @@
+//! Backend for Windows, enabled by the `backend-raw-input` feature, reading Xbox controllers
+//! using XInput and all other game controllers as HID devices using Raw Input.
+//!
+//! XInput only handles up to four Xbox compatible controllers, so older gamepads, joysticks,
+//! wheels, flight sticks and adapters are read with Raw Input, which exposes all their buttons
+//! and axes. Their buttons and axes are ordered by HID usage like Chromium does, so known devices
+//! are remapped to the standard layout as on the web, with a [GamepadMapping::Unmapped] mapping.
+//! Raw Input is read on a background thread owning a message-only window, so gamepads are also
+//! read while the application is in the background. Only XInput controllers can rumble.
+
+use crate::web_mapping::{RawGamepad, HAT_CENTERED, MAX_RAW_AXES};
+use crate::{Button, GamepadMapping, MAX_GAMEPADS};
+use std::ffi::c_void;
+use std::sync::{Arc, Mutex};
+use windows::core::w;
+use windows::Win32::Devices::HumanInterfaceDevice::{
+    HidP_GetCaps, HidP_GetUsageValue, HidP_GetUsages, HidP_GetValueCaps, HidP_Input, HIDP_CAPS,
+    HIDP_STATUS_SUCCESS, HIDP_VALUE_CAPS, PHIDP_PREPARSED_DATA,
+};
+use windows::Win32::Foundation::HANDLE;
+use windows::Win32::UI::Input::XboxController::{
+    XInputGetState, XInputSetState, XINPUT_GAMEPAD, XINPUT_GAMEPAD_A, XINPUT_GAMEPAD_B,
+    XINPUT_GAMEPAD_BACK, XINPUT_GAMEPAD_BUTTON_FLAGS, XINPUT_GAMEPAD_DPAD_DOWN,
+    XINPUT_GAMEPAD_DPAD_LEFT, XINPUT_GAMEPAD_DPAD_RIGHT, XINPUT_GAMEPAD_DPAD_UP,
+    XINPUT_GAMEPAD_LEFT_SHOULDER, XINPUT_GAMEPAD_LEFT_THUMB, XINPUT_GAMEPAD_RIGHT_SHOULDER,
+    XINPUT_GAMEPAD_RIGHT_THUMB, XINPUT_GAMEPAD_START, XINPUT_GAMEPAD_X, XINPUT_GAMEPAD_Y,
+    XINPUT_STATE, XINPUT_VIBRATION,
+};
+use windows::Win32::UI::Input::{
+    GetRawInputData, GetRawInputDeviceInfoW, RegisterRawInputDevices, HRAWINPUT, RAWHID, RAWINPUT,
+    RAWINPUTDEVICE, RAWINPUTHEADER, RIDEV_DEVNOTIFY, RIDEV_INPUTSINK, RIDI_DEVICEINFO,
+    RIDI_DEVICENAME, RIDI_PREPARSEDDATA, RID_DEVICE_INFO, RID_INPUT, RIM_TYPEHID,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    CreateWindowExW, DispatchMessageW, GetMessageW, HWND_MESSAGE, MSG, WINDOW_EX_STYLE,
+    WINDOW_STYLE, WM_INPUT, WM_INPUT_DEVICE_CHANGE,
+};
+
+/// The number of controllers handled by XInput.
+const XINPUT_MAX_COUNT: u32 = 4;
+
+/// The thumbstick deadzones and trigger threshold recommended for XInput.
+const LEFT_STICK_DEADZONE: f32 = 7849. / 32767.;
+const RIGHT_STICK_DEADZONE: f32 = 8689. / 32767.;
+const TRIGGER_THRESHOLD: u8 = 30;
+
+const XINPUT_BUTTONS: [(XINPUT_GAMEPAD_BUTTON_FLAGS, Button); 14] = [
+    (XINPUT_GAMEPAD_A, Button::ActionDown),
+    (XINPUT_GAMEPAD_B, Button::ActionRight),
+    (XINPUT_GAMEPAD_X, Button::ActionLeft),
+    (XINPUT_GAMEPAD_Y, Button::ActionUp),
+    (XINPUT_GAMEPAD_LEFT_SHOULDER, Button::FrontLeftUpper),
+    (XINPUT_GAMEPAD_RIGHT_SHOULDER, Button::FrontRightUpper),
+    (XINPUT_GAMEPAD_BACK, Button::LeftCenterCluster),
+    (XINPUT_GAMEPAD_START, Button::RightCenterCluster),
+    (XINPUT_GAMEPAD_LEFT_THUMB, Button::LeftStick),
+    (XINPUT_GAMEPAD_RIGHT_THUMB, Button::RightStick),
+    (XINPUT_GAMEPAD_DPAD_UP, Button::DPadUp),
+    (XINPUT_GAMEPAD_DPAD_DOWN, Button::DPadDown),
+    (XINPUT_GAMEPAD_DPAD_LEFT, Button::DPadLeft),
+    (XINPUT_GAMEPAD_DPAD_RIGHT, Button::DPadRight),
+];
+
+/// HID usage pages and usages of game controllers.
+const USAGE_PAGE_GENERIC_DESKTOP: u16 = 0x01;
+const USAGE_PAGE_SIMULATION_CONTROLS: u16 = 0x02;
+const USAGE_PAGE_BUTTON: u16 = 0x09;
+const USAGE_JOYSTICK: u16 = 0x04;
+const USAGE_GAMEPAD: u16 = 0x05;
+const USAGE_MULTI_AXIS_CONTROLLER: u16 = 0x08;
+/// The usages of axes from X to Wheel, followed by the hat switch, which are exposed as the
+/// raw axes in this order.
+const USAGE_FIRST_AXIS: u16 = 0x30;
+const USAGE_HAT_SWITCH: u16 = 0x39;
+/// The usages of the rudder, throttle, accelerator, brake, clutch and steering axes of pedals,
+/// throttles and wheels, which are exposed in the first raw axes not used by the above.
+const USAGES_SIMULATION: [u16; 6] = [0xba, 0xbb, 0xc4, 0xc5, 0xc6, 0xc8];
+
+/// The `wParam` of `WM_INPUT_DEVICE_CHANGE` when a device is connected.
+const GIDC_ARRIVAL: usize = 1;
+
+/// A device in a slot, by XInput user index or Raw Input device handle.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Device {
+    XInput(u32),
+    Hid(isize),
+}
+
+/// The state of a HID device, as shared by the background thread.
+#[derive(Clone)]
+struct HidState {
+    handle: isize,
+    /// An id as used by Chromium, for looking up the device in [crate::web_mapping].
+    id: String,
+    raw: RawGamepad,
+    /// The raw buttons pressed since the state was last taken.
+    raw_pressed_since_taken: u32,
+    raw_high_pressed_since_taken: u64,
+}
+
+/// A rumble effect waiting for its start delay or playing, as XInput has no durations.
+#[derive(Clone, Copy)]
+struct ScheduledRumble {
+    start_ms: f64,
+    stop_ms: f64,
+    vibration: XINPUT_VIBRATION,
+    started: bool,
+}
+
+/// The device in each slot, the HID devices read by the background thread, and the scheduled
+/// rumble effect of each slot.
+pub struct State {
+    hid_devices: Arc<Mutex<Vec<HidState>>>,
+    slots: [Option<Device>; MAX_GAMEPADS],
+    rumbles: [Option<ScheduledRumble>; MAX_GAMEPADS],
+}
+
+impl State {
+    /// Start the background thread reading HID devices, only reading XInput controllers if
+    /// that fails.
+    pub fn new() -> Self {
+        let hid_devices = Arc::new(Mutex::new(Vec::new()));
+        let thread_devices = Arc::clone(&hid_devices);
+        if let Err(error) = std::thread::Builder::new()
+            .name("gamepads-raw-input".into())
+            .spawn(move || read_raw_input(&thread_devices))
+        {
+            crate::logging::warning!("Failed to start the Raw Input thread: {error}");
+        }
+        Self {
+            hid_devices,
+            slots: [None; MAX_GAMEPADS],
+            rumbles: [None; MAX_GAMEPADS],
+        }
+    }
+}
+
+/// Read the state of all gamepads, and start and stop scheduled rumble effects.
+pub fn poll(gamepads: &mut crate::Gamepads) {
+    let xinput_states: [Option<XINPUT_GAMEPAD>; XINPUT_MAX_COUNT as usize] =
+        std::array::from_fn(|user_index| {
+            let mut state = XINPUT_STATE::default();
+            (unsafe { XInputGetState(user_index as u32, &mut state) } == 0).then_some(state.Gamepad)
+        });
+    let state = &mut gamepads.raw_input;
+    let hid_devices: Vec<HidState> = state
+        .hid_devices
+        .lock()
+        .map(|mut devices| {
+            let taken = devices.clone();
+            for device in devices.iter_mut() {
+                device.raw_pressed_since_taken = 0;
+                device.raw_high_pressed_since_taken = 0;
+            }
+            taken
+        })
+        .unwrap_or_default();
+    let connected: Vec<Device> = (0..XINPUT_MAX_COUNT)
+        .filter(|&user_index| xinput_states[user_index as usize].is_some())
+        .map(Device::XInput)
+        .chain(hid_devices.iter().map(|device| Device::Hid(device.handle)))
+        .collect();
+
+    // Devices are kept in the first slot free when connected:
+    for (slot, rumble) in state.slots.iter_mut().zip(state.rumbles.iter_mut()) {
+        if slot.is_some_and(|device| !connected.contains(&device)) {
+            *slot = None;
+            *rumble = None;
+        }
+    }
+    for device in connected {
+        if state.slots.contains(&Some(device)) {
+            continue;
+        }
+        if let Some(free_slot) = state.slots.iter_mut().find(|slot| slot.is_none()) {
+            *free_slot = Some(device);
+        } else {
+            crate::logging::warning!("Ignoring gamepad, as {MAX_GAMEPADS} are already in use");
+        }
+    }
+
+    for (gamepad, slot) in gamepads.gamepads.iter_mut().zip(&state.slots) {
+        match *slot {
+            Some(Device::XInput(user_index)) => {
+                if let Some(xinput_state) = &xinput_states[user_index as usize] {
+                    read_xinput_state(xinput_state, gamepad);
+                }
+            }
+            Some(Device::Hid(handle)) => {
+                if let Some(device) = hid_devices.iter().find(|device| device.handle == handle) {
+                    read_hid_state(device, gamepad);
+                }
+            }
+            None => gamepad.set_connected(false),
+        }
+    }
+
+    update_rumbles(state);
+}
+
+/// Read the state of an XInput controller.
+fn read_xinput_state(xinput_state: &XINPUT_GAMEPAD, gamepad: &mut crate::Gamepad) {
+    gamepad.connected = true;
+    gamepad.pressed_bits = 0;
+    for (xinput_button, button) in XINPUT_BUTTONS {
+        if xinput_state.wButtons.0 & xinput_button.0 != 0 {
+            gamepad.pressed_bits |= 1 << (button as u32);
+        }
+    }
+    for (value, button) in [
+        (xinput_state.bLeftTrigger, Button::FrontLeftLower),
+        (xinput_state.bRightTrigger, Button::FrontRightLower),
+    ] {
+        if value > TRIGGER_THRESHOLD {
+            gamepad.pressed_bits |= 1 << (button as u32);
+        }
+    }
+    let stick = |value: i16, deadzone: f32| {
+        crate::apply_deadzone((f32::from(value) / 32767.).max(-1.), deadzone)
+    };
+    gamepad.axes = [
+        stick(xinput_state.sThumbLX, LEFT_STICK_DEADZONE),
+        stick(xinput_state.sThumbLY, LEFT_STICK_DEADZONE),
+        stick(xinput_state.sThumbRX, RIGHT_STICK_DEADZONE),
+        stick(xinput_state.sThumbRY, RIGHT_STICK_DEADZONE),
+    ];
+    gamepad.triggers = [
+        f32::from(xinput_state.bLeftTrigger) / 255.,
+        f32::from(xinput_state.bRightTrigger) / 255.,
+    ];
+}
+
+/// Read the state of a HID device, remapping known devices to the standard layout.
+fn read_hid_state(device: &HidState, gamepad: &mut crate::Gamepad) {
+    gamepad.connected = true;
+    gamepad.set_mapping(GamepadMapping::Unmapped);
+    let pressed_since_taken = RawGamepad {
+        pressed_bits: device.raw_pressed_since_taken,
+        ..device.raw
+    };
+    if let Some((pressed_bits, axes)) =
+        crate::web_mapping::remap(GamepadMapping::Unmapped, &device.id, &device.raw)
+    {
+        gamepad.pressed_bits = pressed_bits;
+        gamepad.axes = axes;
+        // Presses released again before this poll:
+        if let Some((just_pressed_bits, _)) =
+            crate::web_mapping::remap(GamepadMapping::Unmapped, &device.id, &pressed_since_taken)
+        {
+            gamepad.just_pressed_bits |= just_pressed_bits & !pressed_bits;
+        }
+        return;
+    }
+    crate::web_mapping::write_unmapped(&device.raw, gamepad);
+    gamepad.just_pressed_bits |= device.raw_pressed_since_taken & !device.raw.pressed_bits;
+    gamepad.high_just_pressed_bits |=
+        device.raw_high_pressed_since_taken & !device.raw.high_pressed_bits;
+}
+
+/// Start rumble effects whose start delay has passed, and stop those that have ended.
+fn update_rumbles(state: &mut State) {
+    let now_ms = crate::time::now_ms();
+    for (slot, rumble) in state.slots.iter().zip(state.rumbles.iter_mut()) {
+        let (Some(Device::XInput(user_index)), Some(scheduled)) = (slot, rumble.as_mut()) else {
+            continue;
+        };
+        let vibration = if now_ms >= scheduled.stop_ms {
+            *rumble = None;
+            XINPUT_VIBRATION::default()
+        } else if now_ms >= scheduled.start_ms && !scheduled.started {
+            scheduled.started = true;
+            scheduled.vibration
+        } else {
+            continue;
+        };
+        unsafe { XInputSetState(*user_index, &vibration) };
+    }
+}
+
+/// Schedule a rumble effect on an XInput controller, replacing any previous one, which is
+/// played from the next poll.
+pub fn rumble(
+    state: &mut State,
+    gamepad_id: crate::GamepadId,
+    duration_ms: u32,
+    start_delay_ms: u32,
+    strong_magnitude: f32,
+    weak_magnitude: f32,
+) {
+    let gamepad_idx = usize::from(gamepad_id.0);
+    let Some(Device::XInput(user_index)) = state.slots[gamepad_idx] else {
+        return;
+    };
+    // Stop a previous effect still playing until this one starts:
+    if state.rumbles[gamepad_idx].is_some_and(|previous| previous.started) {
+        unsafe { XInputSetState(user_index, &XINPUT_VIBRATION::default()) };
+    }
+    let speed = |magnitude: f32| (magnitude.clamp(0., 1.) * f32::from(u16::MAX)) as u16;
+    let start_ms = crate::time::now_ms() + f64::from(start_delay_ms);
+    state.rumbles[gamepad_idx] = Some(ScheduledRumble {
+        start_ms,
+        stop_ms: start_ms + f64::from(duration_ms),
+        vibration: XINPUT_VIBRATION {
+            wLeftMotorSpeed: speed(strong_magnitude),
+            wRightMotorSpeed: speed(weak_magnitude),
+        },
+        started: false,
+    });
+    update_rumbles(state);
+}
+
+/// Whether the gamepad is an XInput controller, which are the ones able to rumble.
+pub fn capabilities(state: &State, gamepad_id: crate::GamepadId) -> crate::Capabilities {
+    crate::Capabilities {
+        rumble: matches!(
+            state.slots[usize::from(gamepad_id.0)],
+            Some(Device::XInput(_))
+        ),
+        trigger_rumble: false,
+        motion: false,
+        led: false,
+        force_feedback: false,
+    }
+}
+
+/// An axis or hat switch of a HID device.
+struct HidAxis {
+    usage_page: u16,
+    usage: u16,
+    /// The index of the raw axis exposing this axis.
+    index: usize,
+    logical_min: i64,
+    logical_max: i64,
+    bit_size: u16,
+}
+
+impl HidAxis {
+    /// The raw axis value of a HID value, in the range `[-1.0, 1.0]`, or [HAT_CENTERED].
+    fn normalize(&self, value: u32) -> f32 {
+        let value = if self.logical_min < 0 && self.bit_size < 32 {
+            // Sign extend values of signed axes:
+            let shift = 64 - u32::from(self.bit_size);
+            (i64::from(value) << shift) >> shift
+        } else {
+            i64::from(value)
+        };
+        let range = (self.logical_max - self.logical_min).max(1);
+        if self.usage == USAGE_HAT_SWITCH {
+            if value < self.logical_min || value > self.logical_max {
+                return HAT_CENTERED;
+            }
+            // Directions clockwise from up in steps of 2/7, also for hats with 4 directions:
+            let direction = (value - self.logical_min) * 8 / (range + 1);
+            return direction as f32 * 2. / 7. - 1.;
+        }
+        (2. * (value - self.logical_min) as f32 / range as f32 - 1.).clamp(-1., 1.)
+    }
+}
+
+/// A HID device read by the background thread.
+struct HidDevice {
+    handle: isize,
+    /// The preparsed data of the HID report descriptor, aligned for the HID parser.
+    preparsed_data: Vec<u64>,
+    axes: Vec<HidAxis>,
+}
+
+impl HidDevice {
+    fn preparsed_data(&self) -> PHIDP_PREPARSED_DATA {
+        PHIDP_PREPARSED_DATA(self.preparsed_data.as_ptr() as isize)
+    }
+
+    /// Open a connected game controller, unless it is an XInput controller, which has
+    /// `IG_` in its device name.
+    fn open(handle: HANDLE) -> Option<(Self, HidState)> {
+        let name = device_info(handle, RIDI_DEVICENAME, |size| vec![0_u16; size as usize])?;
+        if String::from_utf16_lossy(&name)
+            .to_ascii_uppercase()
+            .contains("IG_")
+        {
+            return None;
+        }
+
+        let mut info = RID_DEVICE_INFO {
+            cbSize: std::mem::size_of::<RID_DEVICE_INFO>() as u32,
+            ..Default::default()
+        };
+        let mut size = info.cbSize;
+        if unsafe {
+            GetRawInputDeviceInfoW(
+                handle,
+                RIDI_DEVICEINFO,
+                Some(std::ptr::addr_of_mut!(info).cast()),
+                &mut size,
+            )
+        } == u32::MAX
+            || info.dwType != RIM_TYPEHID
+        {
+            return None;
+        }
+        let hid_info = unsafe { info.Anonymous.hid };
+
+        let preparsed_data = device_info(handle, RIDI_PREPARSEDDATA, |size| {
+            vec![0_u64; (size as usize).div_ceil(8)]
+        })?;
+        let mut device = Self {
+            handle: handle.0 as isize,
+            preparsed_data,
+            axes: Vec::new(),
+        };
+        device.read_axes();
+
+        let mut raw = RawGamepad::default();
+        for axis in &device.axes {
+            if axis.usage == USAGE_HAT_SWITCH {
+                raw.axes[axis.index] = HAT_CENTERED;
+            }
+        }
+        let state = HidState {
+            handle: device.handle,
+            id: format!(
+                "Vendor: {:04x} Product: {:04x}",
+                hid_info.dwVendorId, hid_info.dwProductId
+            ),
+            raw,
+            raw_pressed_since_taken: 0,
+            raw_high_pressed_since_taken: 0,
+        };
+        Some((device, state))
+    }
+
+    /// Find the axes and hat switch of the input reports, followed by the axes of simulation
+    /// controls in the raw axes left unused.
+    fn read_axes(&mut self) {
+        let mut caps = HIDP_CAPS::default();
+        if unsafe { HidP_GetCaps(self.preparsed_data(), &mut caps) } != HIDP_STATUS_SUCCESS {
+            return;
+        }
+        let mut value_caps =
+            vec![HIDP_VALUE_CAPS::default(); usize::from(caps.NumberInputValueCaps)];
+        let mut value_caps_len = caps.NumberInputValueCaps;
+        if unsafe {
+            HidP_GetValueCaps(
+                HidP_Input,
+                value_caps.as_mut_ptr(),
+                &mut value_caps_len,
+                self.preparsed_data(),
+            )
+        } != HIDP_STATUS_SUCCESS
+        {
+            return;
+        }
+        let mut simulation_axes = Vec::new();
+        for value_cap in &value_caps[..usize::from(value_caps_len)] {
+            let is_axis = |usage: &u16| match value_cap.UsagePage {
+                USAGE_PAGE_GENERIC_DESKTOP => (USAGE_FIRST_AXIS..=USAGE_HAT_SWITCH).contains(usage),
+                USAGE_PAGE_SIMULATION_CONTROLS => USAGES_SIMULATION.contains(usage),
+                _ => false,
+            };
+            let usages = unsafe {
+                if value_cap.IsRange.as_bool() {
+                    value_cap.Anonymous.Range.UsageMin..=value_cap.Anonymous.Range.UsageMax
+                } else {
+                    value_cap.Anonymous.NotRange.Usage..=value_cap.Anonymous.NotRange.Usage
+                }
+            };
+            let (mut logical_min, mut logical_max) = (
+                i64::from(value_cap.LogicalMin),
+                i64::from(value_cap.LogicalMax),
+            );
+            if logical_max < logical_min {
+                // Unsigned ranges wrongly declared as signed:
+                let mask = (1_i64 << value_cap.BitSize.min(32)) - 1;
+                (logical_min, logical_max) = (logical_min & mask, logical_max & mask);
+            }
+            for usage in usages.filter(is_axis) {
+                // Simulation controls are given an index once all other axes are known:
+                let (axes, index) = if value_cap.UsagePage == USAGE_PAGE_GENERIC_DESKTOP {
+                    (&mut self.axes, usize::from(usage - USAGE_FIRST_AXIS))
+                } else {
+                    (&mut simulation_axes, 0)
+                };
+                if !axes.iter().any(|axis: &HidAxis| axis.usage == usage) {
+                    axes.push(HidAxis {
+                        usage_page: value_cap.UsagePage,
+                        usage,
+                        index,
+                        logical_min,
+                        logical_max,
+                        bit_size: value_cap.BitSize,
+                    });
+                }
+            }
+        }
+        for mut axis in simulation_axes {
+            let Some(index) =
+                (0..MAX_RAW_AXES).find(|&index| self.axes.iter().all(|other| other.index != index))
+            else {
+                break;
+            };
+            axis.index = index;
+            self.axes.push(axis);
+        }
+    }
+
+    /// Read the pressed buttons and axis values of an input report.
+    fn read_report(&self, report: &mut [u8], raw: &mut RawGamepad) {
+        let mut usages = [0_u16; 128];
+        let mut usages_len = usages.len() as u32;
+        if unsafe {
+            HidP_GetUsages(
+                HidP_Input,
+                USAGE_PAGE_BUTTON,
+                0,
+                usages.as_mut_ptr(),
+                &mut usages_len,
+                self.preparsed_data(),
+                report,
+            )
+        } == HIDP_STATUS_SUCCESS
+        {
+            raw.pressed_bits = 0;
+            raw.high_pressed_bits = 0;
+            // Buttons are numbered from 1:
+            for &usage in usages[..usages_len as usize]
+                .iter()
+                .filter(|&&usage| usage > 0)
+            {
+                raw.press(usize::from(usage - 1));
+            }
+        }
+        for axis in &self.axes {
+            let mut value = 0;
+            if unsafe {
+                HidP_GetUsageValue(
+                    HidP_Input,
+                    axis.usage_page,
+                    0,
+                    axis.usage,
+                    &mut value,
+                    self.preparsed_data(),
+                    report,
+                )
+            } == HIDP_STATUS_SUCCESS
+            {
+                raw.axes[axis.index] = axis.normalize(value);
+            }
+        }
+    }
+}
+
+/// Read device information of a size given in elements by a first call, into a buffer allocated
+/// for that size.
+fn device_info<T>(
+    handle: HANDLE,
+    command: windows::Win32::UI::Input::RAW_INPUT_DEVICE_INFO_COMMAND,
+    allocate: impl FnOnce(u32) -> Vec<T>,
+) -> Option<Vec<T>> {
+    let mut size = 0;
+    unsafe { GetRawInputDeviceInfoW(handle, command, None, &mut size) };
+    let mut buffer = allocate(size);
+    (unsafe {
+        GetRawInputDeviceInfoW(
+            handle,
+            command,
+            Some(buffer.as_mut_ptr().cast::<c_void>()),
+            &mut size,
+        )
+    } != u32::MAX)
+        .then_some(buffer)
+}
+
+/// Read HID game controllers using Raw Input, on a thread owning a message-only window
+/// receiving their input and connection changes.
+fn read_raw_input(shared: &Mutex<Vec<HidState>>) {
+    let window = match unsafe {
+        CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            w!("STATIC"),
+            w!("gamepads"),
+            WINDOW_STYLE::default(),
+            0,
+            0,
+            0,
+            0,
+            HWND_MESSAGE,
+            None,
+            None,
+            None,
+        )
+    } {
+        Ok(window) => window,
+        Err(error) => {
+            crate::logging::warning!("Failed to create a window for Raw Input: {error}");
+            return;
+        }
+    };
+    // Connection changes are also sent for the devices already connected:
+    let registrations =
+        [USAGE_JOYSTICK, USAGE_GAMEPAD, USAGE_MULTI_AXIS_CONTROLLER].map(|usage| RAWINPUTDEVICE {
+            usUsagePage: USAGE_PAGE_GENERIC_DESKTOP,
+            usUsage: usage,
+            dwFlags: RIDEV_INPUTSINK | RIDEV_DEVNOTIFY,
+            hwndTarget: window,
+        });
+    if let Err(error) = unsafe {
+        RegisterRawInputDevices(&registrations, std::mem::size_of::<RAWINPUTDEVICE>() as u32)
+    } {
+        crate::logging::warning!("Failed to register for Raw Input: {error}");
+        return;
+    }
+
+    let mut devices: Vec<HidDevice> = Vec::new();
+    let mut message = MSG::default();
+    while unsafe { GetMessageW(&mut message, None, 0, 0) }.as_bool() {
+        match message.message {
+            WM_INPUT => read_input(HRAWINPUT(message.lParam.0 as *mut c_void), &devices, shared),
+            WM_INPUT_DEVICE_CHANGE => {
+                let handle = HANDLE(message.lParam.0 as *mut c_void);
+                let Ok(mut shared) = shared.lock() else {
+                    return;
+                };
+                devices.retain(|device| device.handle != handle.0 as isize);
+                shared.retain(|device| device.handle != handle.0 as isize);
+                if message.wParam.0 == GIDC_ARRIVAL {
+                    if let Some((device, state)) = HidDevice::open(handle) {
+                        devices.push(device);
+                        shared.push(state);
+                    }
+                }
+            }
+            _ => {}
+        }
+        // Also lets the system clean up after WM_INPUT:
+        unsafe { DispatchMessageW(&message) };
+    }
+}
+
+/// Read a `WM_INPUT` message, updating the state of its device.
+fn read_input(input: HRAWINPUT, devices: &[HidDevice], shared: &Mutex<Vec<HidState>>) {
+    let header_size = std::mem::size_of::<RAWINPUTHEADER>() as u32;
+    let mut size = 0;
+    unsafe { GetRawInputData(input, RID_INPUT, None, &mut size, header_size) };
+    let mut buffer = vec![0_u64; (size as usize).div_ceil(8)];
+    if unsafe {
+        GetRawInputData(
+            input,
+            RID_INPUT,
+            Some(buffer.as_mut_ptr().cast()),
+            &mut size,
+            header_size,
+        )
+    } == u32::MAX
+        || size as usize <= std::mem::offset_of!(RAWINPUT, data)
+    {
+        return;
+    }
+    let (header, hid) = unsafe {
+        let input = &*buffer.as_ptr().cast::<RAWINPUT>();
+        (input.header, input.data.hid)
+    };
+    let Some(device) = devices
+        .iter()
+        .find(|device| device.handle == header.hDevice.0 as isize)
+    else {
+        return;
+    };
+    if header.dwType != RIM_TYPEHID.0 {
+        return;
+    }
+
+    // Several reports may be batched, of which the last one is the current state:
+    let bytes: &mut [u8] =
+        unsafe { std::slice::from_raw_parts_mut(buffer.as_mut_ptr().cast(), buffer.len() * 8) };
+    let reports_offset =
+        std::mem::offset_of!(RAWINPUT, data) + std::mem::offset_of!(RAWHID, bRawData);
+    let report_len = hid.dwSizeHid as usize;
+    let batch_len = report_len * hid.dwCount as usize;
+    if report_len == 0 || reports_offset + batch_len > size as usize {
+        return;
+    }
+    let reports = &mut bytes[reports_offset..reports_offset + batch_len];
+
+    let Ok(mut shared) = shared.lock() else {
+        return;
+    };
+    let Some(state) = shared
+        .iter_mut()
+        .find(|state| state.handle == device.handle)
+    else {
+        return;
+    };
+    for report in reports.chunks_exact_mut(report_len) {
+        device.read_report(report, &mut state.raw);
+        state.raw_pressed_since_taken |= state.raw.pressed_bits;
+        state.raw_high_pressed_since_taken |= state.raw.high_pressed_bits;
+    }
+}
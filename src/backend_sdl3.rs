@@ -0,0 +1,391 @@
+//! Backend for desktop platforms, enabled by the `backend-sdl3` feature, reading gamepads using
+//! the gamepad API of SDL3 instead of through gilrs.
+//!
+//! This links to the SDL3 library installed on the system, and gives access to the motion
+//! sensors and light bars of controllers such as the DualSense and DualShock 4 using
+//! [Gamepads::motion()](crate::Gamepads::motion) and [Gamepads::set_led()](crate::Gamepads::set_led),
+//! as well as to the impulse triggers of Xbox controllers using
+//! [Gamepads::rumble_triggers()](crate::Gamepads::rumble_triggers). Devices are mapped to the
+//! standard layout using the mapping database of SDL, which covers most gamepads.
+//!
+//! SDL is initialized with its gamepad subsystem, which is reference counted, so an application
+//! also using SDL keeps working. Gamepad state is updated on each poll, so no event loop is needed.
+
+use crate::{Button, Motion, MAX_GAMEPADS};
+use std::ffi::{c_char, c_int, CStr};
+
+/// An opened gamepad, `SDL_Gamepad`.
+#[repr(C)]
+struct SdlGamepad {
+    _private: [u8; 0],
+}
+
+type SdlJoystickId = u32;
+type SdlPropertiesId = u32;
+
+const SDL_INIT_GAMEPAD: u32 = 0x0000_2000;
+
+/// The `SDL_GamepadButton` values, of which those following `SDL_GAMEPAD_BUTTON_DPAD_RIGHT`
+/// are reported as extra buttons.
+const BUTTONS: [(c_int, Button); 15] = [
+    (0, Button::ActionDown),
+    (1, Button::ActionRight),
+    (2, Button::ActionLeft),
+    (3, Button::ActionUp),
+    (4, Button::LeftCenterCluster),
+    (5, Button::Mode),
+    (6, Button::RightCenterCluster),
+    (7, Button::LeftStick),
+    (8, Button::RightStick),
+    (9, Button::FrontLeftUpper),
+    (10, Button::FrontRightUpper),
+    (11, Button::DPadUp),
+    (12, Button::DPadDown),
+    (13, Button::DPadLeft),
+    (14, Button::DPadRight),
+];
+
+/// The misc button, the back paddles and the touchpad button as `SDL_GamepadButton` values, and
+/// the extra button index they are reported as.
+const EXTRA_BUTTONS: [(c_int, u8); 6] =
+    [(15, 17), (20, 18), (16, 19), (17, 20), (18, 21), (19, 22)];
+
+/// The `SDL_GamepadAxis` values of the thumbsticks and the triggers.
+const AXIS_LEFTX: c_int = 0;
+const AXIS_LEFTY: c_int = 1;
+const AXIS_RIGHTX: c_int = 2;
+const AXIS_RIGHTY: c_int = 3;
+const AXIS_LEFT_TRIGGER: c_int = 4;
+const AXIS_RIGHT_TRIGGER: c_int = 5;
+
+/// The `SDL_SensorType` values of the accelerometer and gyroscope.
+const SENSOR_ACCEL: c_int = 1;
+const SENSOR_GYRO: c_int = 2;
+
+/// The thumbstick deadzone, as SDL reports raw values.
+const STICK_DEADZONE: f32 = 8000. / 32767.;
+
+/// How far a trigger needs to be pressed to be reported as a pressed button, as for XInput.
+const TRIGGER_THRESHOLD: f32 = 30. / 255.;
+
+const PROP_CAP_RUMBLE: &CStr = c"SDL.joystick.cap.rumble";
+const PROP_CAP_TRIGGER_RUMBLE: &CStr = c"SDL.joystick.cap.trigger_rumble";
+const PROP_CAP_RGB_LED: &CStr = c"SDL.joystick.cap.rgb_led";
+
+#[link(name = "SDL3")]
+extern "C" {
+    fn SDL_InitSubSystem(flags: u32) -> bool;
+    fn SDL_GetError() -> *const c_char;
+    fn SDL_free(mem: *mut std::ffi::c_void);
+    fn SDL_UpdateGamepads();
+    fn SDL_GetGamepads(count: *mut c_int) -> *mut SdlJoystickId;
+    fn SDL_OpenGamepad(instance_id: SdlJoystickId) -> *mut SdlGamepad;
+    fn SDL_CloseGamepad(gamepad: *mut SdlGamepad);
+    fn SDL_GetGamepadName(gamepad: *mut SdlGamepad) -> *const c_char;
+    fn SDL_GetGamepadProperties(gamepad: *mut SdlGamepad) -> SdlPropertiesId;
+    fn SDL_GetBooleanProperty(props: SdlPropertiesId, name: *const c_char, default: bool) -> bool;
+    fn SDL_GetGamepadButton(gamepad: *mut SdlGamepad, button: c_int) -> bool;
+    fn SDL_GetGamepadAxis(gamepad: *mut SdlGamepad, axis: c_int) -> i16;
+    fn SDL_RumbleGamepad(
+        gamepad: *mut SdlGamepad,
+        low_frequency_rumble: u16,
+        high_frequency_rumble: u16,
+        duration_ms: u32,
+    ) -> bool;
+    fn SDL_RumbleGamepadTriggers(
+        gamepad: *mut SdlGamepad,
+        left_rumble: u16,
+        right_rumble: u16,
+        duration_ms: u32,
+    ) -> bool;
+    fn SDL_SetGamepadLED(gamepad: *mut SdlGamepad, red: u8, green: u8, blue: u8) -> bool;
+    fn SDL_GamepadHasSensor(gamepad: *mut SdlGamepad, sensor_type: c_int) -> bool;
+    fn SDL_SetGamepadSensorEnabled(
+        gamepad: *mut SdlGamepad,
+        sensor_type: c_int,
+        enabled: bool,
+    ) -> bool;
+    fn SDL_GetGamepadSensorData(
+        gamepad: *mut SdlGamepad,
+        sensor_type: c_int,
+        data: *mut f32,
+        num_values: c_int,
+    ) -> bool;
+}
+
+/// The last error reported by SDL.
+fn sdl_error() -> String {
+    unsafe { CStr::from_ptr(SDL_GetError()) }
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// A rumble effect waiting for its start delay, as SDL has no start delays.
+#[derive(Clone, Copy)]
+struct ScheduledRumble {
+    start_ms: f64,
+    duration_ms: u32,
+    magnitudes: [u16; 2],
+    trigger_magnitudes: [u16; 2],
+}
+
+/// An opened gamepad, closed when dropped.
+struct OpenedGamepad {
+    instance_id: SdlJoystickId,
+    gamepad: *mut SdlGamepad,
+    has_motion: bool,
+    scheduled_rumble: Option<ScheduledRumble>,
+}
+
+impl OpenedGamepad {
+    fn open(instance_id: SdlJoystickId) -> Option<Self> {
+        let gamepad = unsafe { SDL_OpenGamepad(instance_id) };
+        if gamepad.is_null() {
+            crate::logging::warning!("Failed to open gamepad: {}", sdl_error());
+            return None;
+        }
+        // Sensors are disabled by default, to save power:
+        let has_motion = [SENSOR_ACCEL, SENSOR_GYRO]
+            .into_iter()
+            .all(|sensor_type| unsafe {
+                SDL_GamepadHasSensor(gamepad, sensor_type)
+                    && SDL_SetGamepadSensorEnabled(gamepad, sensor_type, true)
+            });
+        Some(Self {
+            instance_id,
+            gamepad,
+            has_motion,
+            scheduled_rumble: None,
+        })
+    }
+
+    fn has_capability(&self, property: &CStr) -> bool {
+        unsafe {
+            SDL_GetBooleanProperty(
+                SDL_GetGamepadProperties(self.gamepad),
+                property.as_ptr(),
+                false,
+            )
+        }
+    }
+
+    fn play(&self, rumble: &ScheduledRumble) {
+        let [low, high] = rumble.magnitudes;
+        let [left, right] = rumble.trigger_magnitudes;
+        unsafe {
+            if !SDL_RumbleGamepad(self.gamepad, low, high, rumble.duration_ms) {
+                crate::logging::warning!("Failed to rumble gamepad: {}", sdl_error());
+            }
+            if self.has_capability(PROP_CAP_TRIGGER_RUMBLE) {
+                SDL_RumbleGamepadTriggers(self.gamepad, left, right, rumble.duration_ms);
+            }
+        }
+    }
+}
+
+impl Drop for OpenedGamepad {
+    fn drop(&mut self) {
+        unsafe { SDL_CloseGamepad(self.gamepad) };
+    }
+}
+
+/// The gamepad in each slot, or `None` for all if SDL could not be initialized.
+pub struct State {
+    slots: Option<[Option<OpenedGamepad>; MAX_GAMEPADS]>,
+}
+
+impl State {
+    pub fn new() -> Self {
+        let initialized = unsafe { SDL_InitSubSystem(SDL_INIT_GAMEPAD) };
+        if !initialized {
+            crate::logging::warning!("Failed to initialize SDL: {}", sdl_error());
+        }
+        Self {
+            slots: initialized.then(|| std::array::from_fn(|_| None)),
+        }
+    }
+
+    fn gamepad(&self, gamepad_id: crate::GamepadId) -> Option<&OpenedGamepad> {
+        self.slots.as_ref()?[usize::from(gamepad_id.0)].as_ref()
+    }
+}
+
+/// Read the state of all gamepads, and start rumble effects whose start delay has passed.
+pub fn poll(gamepads: &mut crate::Gamepads) {
+    let Some(slots) = &mut gamepads.sdl3.slots else {
+        return;
+    };
+    let connected = unsafe {
+        SDL_UpdateGamepads();
+        let mut count = 0;
+        let ids = SDL_GetGamepads(&mut count);
+        if ids.is_null() {
+            Vec::new()
+        } else {
+            let connected =
+                std::slice::from_raw_parts(ids, usize::try_from(count).unwrap_or(0)).to_vec();
+            SDL_free(ids.cast());
+            connected
+        }
+    };
+
+    // Gamepads are kept in the first slot free when connected:
+    for slot in slots.iter_mut() {
+        if slot
+            .as_ref()
+            .is_some_and(|gamepad| !connected.contains(&gamepad.instance_id))
+        {
+            *slot = None;
+        }
+    }
+    for instance_id in connected {
+        if slots
+            .iter()
+            .flatten()
+            .any(|gamepad| gamepad.instance_id == instance_id)
+        {
+            continue;
+        }
+        if let Some(free_slot) = slots.iter_mut().find(|slot| slot.is_none()) {
+            *free_slot = OpenedGamepad::open(instance_id);
+        } else {
+            crate::logging::warning!("Ignoring gamepad, as {MAX_GAMEPADS} are already in use");
+        }
+    }
+
+    let now_ms = crate::time::now_ms();
+    for (gamepad, slot) in gamepads.gamepads.iter_mut().zip(slots.iter_mut()) {
+        let Some(sdl_gamepad) = slot else {
+            gamepad.set_connected(false);
+            continue;
+        };
+        if let Some(rumble) = sdl_gamepad
+            .scheduled_rumble
+            .take_if(|rumble| now_ms >= rumble.start_ms)
+        {
+            sdl_gamepad.play(&rumble);
+        }
+
+        gamepad.connected = true;
+        gamepad.pressed_bits = 0;
+        let pressed = |button| unsafe { SDL_GetGamepadButton(sdl_gamepad.gamepad, button) };
+        for (sdl_button, button) in BUTTONS {
+            if pressed(sdl_button) {
+                gamepad.pressed_bits |= 1 << (button as u32);
+            }
+        }
+        for (sdl_button, extra_idx) in EXTRA_BUTTONS {
+            if pressed(sdl_button) {
+                gamepad.pressed_bits |= 1 << extra_idx;
+            }
+        }
+
+        let axis = |axis| f32::from(unsafe { SDL_GetGamepadAxis(sdl_gamepad.gamepad, axis) });
+        let stick = |axis_value: f32| crate::apply_deadzone(axis_value / 32767., STICK_DEADZONE);
+        // Axes are positive downwards, while thumbsticks are positive upwards:
+        gamepad.axes = [
+            stick(axis(AXIS_LEFTX)),
+            -stick(axis(AXIS_LEFTY)),
+            stick(axis(AXIS_RIGHTX)),
+            -stick(axis(AXIS_RIGHTY)),
+        ];
+        for ((trigger, sdl_axis), button) in gamepad
+            .triggers
+            .iter_mut()
+            .zip([AXIS_LEFT_TRIGGER, AXIS_RIGHT_TRIGGER])
+            .zip([Button::FrontLeftLower, Button::FrontRightLower])
+        {
+            *trigger = (axis(sdl_axis) / 32767.).clamp(0., 1.);
+            if *trigger > TRIGGER_THRESHOLD {
+                gamepad.pressed_bits |= 1 << (button as u32);
+            }
+        }
+    }
+}
+
+/// Play a rumble effect, replacing any previous one, from the next poll if it has a start delay.
+pub fn rumble(
+    state: &mut State,
+    gamepad_id: crate::GamepadId,
+    duration_ms: u32,
+    start_delay_ms: u32,
+    strong_magnitude: f32,
+    weak_magnitude: f32,
+    trigger_magnitudes: (f32, f32),
+) {
+    let Some(gamepad) = state
+        .slots
+        .as_mut()
+        .and_then(|slots| slots[usize::from(gamepad_id.0)].as_mut())
+    else {
+        return;
+    };
+    let magnitude = |magnitude: f32| (magnitude.clamp(0., 1.) * f32::from(u16::MAX)) as u16;
+    let rumble = ScheduledRumble {
+        start_ms: crate::time::now_ms() + f64::from(start_delay_ms),
+        duration_ms,
+        magnitudes: [magnitude(strong_magnitude), magnitude(weak_magnitude)],
+        trigger_magnitudes: [
+            magnitude(trigger_magnitudes.0),
+            magnitude(trigger_magnitudes.1),
+        ],
+    };
+    if start_delay_ms == 0 {
+        gamepad.scheduled_rumble = None;
+        gamepad.play(&rumble);
+    } else {
+        gamepad.scheduled_rumble = Some(rumble);
+    }
+}
+
+/// What a gamepad supports, as reported by SDL.
+pub fn capabilities(state: &State, gamepad_id: crate::GamepadId) -> crate::Capabilities {
+    state
+        .gamepad(gamepad_id)
+        .map_or_else(crate::Capabilities::default, |gamepad| {
+            crate::Capabilities {
+                rumble: gamepad.has_capability(PROP_CAP_RUMBLE),
+                trigger_rumble: gamepad.has_capability(PROP_CAP_TRIGGER_RUMBLE),
+                motion: gamepad.has_motion,
+                led: gamepad.has_capability(PROP_CAP_RGB_LED),
+                force_feedback: false,
+            }
+        })
+}
+
+/// The name of a connected gamepad, as reported by SDL.
+pub fn name(state: &State, gamepad_id: crate::GamepadId) -> Option<String> {
+    let gamepad = state.gamepad(gamepad_id)?;
+    let name = unsafe { SDL_GetGamepadName(gamepad.gamepad) };
+    (!name.is_null()).then(|| {
+        unsafe { CStr::from_ptr(name) }
+            .to_string_lossy()
+            .into_owned()
+    })
+}
+
+/// The latest readings of the accelerometer and gyroscope of a gamepad.
+pub fn motion(state: &State, gamepad_id: crate::GamepadId) -> Option<Motion> {
+    let gamepad = state
+        .gamepad(gamepad_id)
+        .filter(|gamepad| gamepad.has_motion)?;
+    let mut motion = Motion::default();
+    for (sensor_type, values) in [
+        (SENSOR_ACCEL, &mut motion.acceleration),
+        (SENSOR_GYRO, &mut motion.rotation_rate),
+    ] {
+        if !unsafe {
+            SDL_GetGamepadSensorData(gamepad.gamepad, sensor_type, values.as_mut_ptr(), 3)
+        } {
+            return None;
+        }
+    }
+    Some(motion)
+}
+
+/// Set the color of the light bar of a gamepad.
+pub fn set_led(state: &State, gamepad_id: crate::GamepadId, (red, green, blue): (u8, u8, u8)) {
+    if let Some(gamepad) = state.gamepad(gamepad_id) {
+        unsafe { SDL_SetGamepadLED(gamepad.gamepad, red, green, blue) };
+    }
+}
@@ -0,0 +1,26 @@
+//! Backend for WASI, enabled by the `backend-wasi-host` feature, reading gamepads from the host.
+//!
+//! There is no browser to ask for gamepads, so without the feature no gamepads are reported.
+//! With it, gamepad state is read from functions imported from the `gamepads` module,
+//! which the host must provide:
+//!
+//! - `get_gamepads(data_ptr: i32, protocol_version: i32)`: Write the state of 8 gamepads to
+//!   `data_ptr`, using the same layout per gamepad as the `getGamepads` function of the direct
+//!   web backend, as described in [crate::js] for [PROTOCOL_VERSION](crate::js::PROTOCOL_VERSION).
+//! - `play_effect(gamepad_id: i32, duration_ms: i32, start_delay_ms: i32, strong_magnitude: f32, weak_magnitude: f32)`:
+//!   Rumble a gamepad, or do nothing if not supported.
+
+#[link(wasm_import_module = "gamepads")]
+extern "C" {
+    // Host function.
+    pub fn get_gamepads(data_ptr: *const crate::Gamepad, protocol_version: u32);
+
+    // Host function.
+    pub fn play_effect(
+        gamepad_id: u8,
+        duration_ms: u32,
+        start_delay_ms: u32,
+        strong_magnitude: f32,
+        weak_magnitude: f32,
+    );
+}
@@ -1,92 +1,577 @@
-#[cfg(target_family = "wasm")]
-#[cfg(feature = "wasm-bindgen")]
+use wasm_bindgen::prelude::{wasm_bindgen, JsValue};
+use wasm_bindgen::JsCast;
+
+// Typed bindings for what web-sys lacks, or only has behind `web_sys_unstable_apis`:
+#[wasm_bindgen]
+extern "C" {
+    /// A `Gamepad`, to be cast from [web_sys::Gamepad].
+    type GamepadExt;
+
+    /// The mapping as a string, as `GamepadMappingType` lacks `xr-standard`.
+    #[wasm_bindgen(method, getter)]
+    fn mapping(this: &GamepadExt) -> String;
+
+    #[wasm_bindgen(method, getter = vibrationActuator)]
+    fn vibration_actuator(this: &GamepadExt) -> Option<VibrationActuator>;
+
+    /// A `GamepadHapticActuator`.
+    type VibrationActuator;
+
+    /// The supported effect types, which older browsers lack.
+    #[wasm_bindgen(method, getter)]
+    fn effects(this: &VibrationActuator) -> Option<js_sys::Array>;
+
+    /// The single effect type supported in older browsers.
+    #[wasm_bindgen(method, getter = type)]
+    fn effect_type(this: &VibrationActuator) -> Option<String>;
+
+    #[wasm_bindgen(method, catch, js_name = playEffect)]
+    fn play_effect(
+        this: &VibrationActuator,
+        effect: &str,
+        params: &EffectParameters,
+    ) -> Result<EffectPromise, JsValue>;
+
+    #[wasm_bindgen(method, catch)]
+    fn reset(this: &VibrationActuator) -> Result<JsValue, JsValue>;
+
+    /// The parameters of `playEffect()`, to be cast from a new [js_sys::Object].
+    type EffectParameters;
+
+    #[wasm_bindgen(method, setter = duration)]
+    fn set_duration(this: &EffectParameters, duration_ms: u32);
+
+    #[wasm_bindgen(method, setter = startDelay)]
+    fn set_start_delay(this: &EffectParameters, start_delay_ms: u32);
+
+    #[wasm_bindgen(method, setter = strongMagnitude)]
+    fn set_strong_magnitude(this: &EffectParameters, magnitude: f32);
+
+    #[wasm_bindgen(method, setter = weakMagnitude)]
+    fn set_weak_magnitude(this: &EffectParameters, magnitude: f32);
+
+    #[wasm_bindgen(method, setter = leftTrigger)]
+    fn set_left_trigger(this: &EffectParameters, magnitude: f32);
+
+    #[wasm_bindgen(method, setter = rightTrigger)]
+    fn set_right_trigger(this: &EffectParameters, magnitude: f32);
+
+    /// The promise returned by `playEffect()`.
+    type EffectPromise;
+
+    #[wasm_bindgen(method, catch)]
+    fn then(
+        this: &EffectPromise,
+        on_fulfilled: &JsValue,
+        on_rejected: &JsValue,
+    ) -> Result<JsValue, JsValue>;
+}
+
+/// The vibration actuator of a gamepad, if it has one, which Firefox does not support.
+fn vibration_actuator(gamepad: &web_sys::Gamepad) -> Option<VibrationActuator> {
+    gamepad.unchecked_ref::<GamepadExt>().vibration_actuator()
+}
+
 pub fn play_effect(
     gamepad_id: u8,
     duration_ms: u32,
     start_delay_ms: u32,
     strong_magnitude: f32,
     weak_magnitude: f32,
-) {
-    #![allow(clippy::expect_used)]
-    use wasm_bindgen::JsValue;
-
-    for gamepad in web_sys::window()
-        .expect("Unable to get window")
-        .navigator()
-        .get_gamepads()
-        .expect("Unable to get gamepads")
-        .iter()
-        .filter(|v| !v.is_null())
+    (left_trigger, right_trigger): (f32, f32),
+) -> crate::RumbleHandle {
+    use crate::{RumbleHandle, RumbleStatus};
+
+    let Some(vibration_actuator) = gamepad_in_slot(gamepad_id)
+        .as_ref()
+        .and_then(vibration_actuator)
+    else {
+        return RumbleHandle::new(RumbleStatus::Failed);
+    };
+
+    let params = js_sys::Object::new().unchecked_into::<EffectParameters>();
+    params.set_duration(duration_ms);
+    params.set_start_delay(start_delay_ms);
+    params.set_strong_magnitude(strong_magnitude);
+    params.set_weak_magnitude(weak_magnitude);
+
+    // Fall back to dual-rumble if the gamepad has no trigger motors:
+    let effect = if (left_trigger > 0. || right_trigger > 0.)
+        && supports_effect(&vibration_actuator, "trigger-rumble")
     {
-        let typed_gamepad = web_sys::Gamepad::from(gamepad);
-        if typed_gamepad.index() == u32::from(gamepad_id) {
-            if let Ok(vibration_actuator) =
-                js_sys::Reflect::get(&typed_gamepad, &JsValue::from_str("vibrationActuator"))
-            {
-                if let Ok(play_effect) =
-                    js_sys::Reflect::get(&vibration_actuator, &JsValue::from_str("playEffect"))
-                {
-                    use wasm_bindgen::JsCast;
-                    let arguments_list = js_sys::Array::new();
-                    arguments_list.push(&JsValue::from_str("dual-rumble"));
-                    let arg_obj = js_sys::Object::new();
-                    let _ = js_sys::Reflect::set(&arg_obj, &"duration".into(), &duration_ms.into());
-                    let _ = js_sys::Reflect::set(
-                        &arg_obj,
-                        &"startDelay".into(),
-                        &start_delay_ms.into(),
-                    );
-                    let _ = js_sys::Reflect::set(
-                        &arg_obj,
-                        &"strongMagnitude".into(),
-                        &strong_magnitude.into(),
-                    );
-                    let _ = js_sys::Reflect::set(
-                        &arg_obj,
-                        &"weakMagnitude".into(),
-                        &weak_magnitude.into(),
-                    );
-                    arguments_list.push(&arg_obj);
-
-                    let _ = js_sys::Reflect::apply(
-                        play_effect.unchecked_ref(),
-                        &vibration_actuator,
-                        &arguments_list,
-                    );
-                }
+        params.set_left_trigger(left_trigger);
+        params.set_right_trigger(right_trigger);
+        "trigger-rumble"
+    } else {
+        "dual-rumble"
+    };
+
+    match vibration_actuator.play_effect(effect, &params) {
+        Ok(promise) => track_effect(&promise),
+        Err(error) => {
+            crate::logging::warning!("Failed to play rumble effect: {error:?}");
+            RumbleHandle::new(RumbleStatus::Failed)
+        }
+    }
+}
+
+/// A handle updated when the promise returned by `playEffect()` settles.
+fn track_effect(promise: &EffectPromise) -> crate::RumbleHandle {
+    use crate::{RumbleHandle, RumbleStatus};
+
+    let handle = RumbleHandle::new(RumbleStatus::Playing);
+    let settled_handle = handle.clone();
+    // Called once, with the result when fulfilled or an error when rejected:
+    let on_settled = wasm_bindgen::closure::Closure::once_into_js(move |result: JsValue| {
+        settled_handle.finish(match result.as_string().as_deref() {
+            Some("complete") => RumbleStatus::Complete,
+            Some("preempted") => RumbleStatus::Preempted,
+            _ => RumbleStatus::Failed,
+        });
+    });
+    if promise.then(&on_settled, &on_settled).is_err() {
+        return RumbleHandle::new(RumbleStatus::Unknown);
+    }
+    handle
+}
+
+/// The connected gamepad in a slot, as of the last sample.
+fn gamepad_in_slot(slot: u8) -> Option<web_sys::Gamepad> {
+    SAMPLES.with_borrow(|samples| samples.slots.get(usize::from(slot)).cloned().flatten())
+}
+
+/// The `id` string of a connected gamepad.
+pub fn name(gamepad_id: u8) -> Option<String> {
+    gamepad_in_slot(gamepad_id).map(|gamepad| gamepad.id())
+}
+
+/// What the vibration actuator of a gamepad supports, if it has one.
+pub fn capabilities(gamepad_id: u8) -> crate::Capabilities {
+    let Some(vibration_actuator) = gamepad_in_slot(gamepad_id)
+        .as_ref()
+        .and_then(vibration_actuator)
+    else {
+        return crate::Capabilities::default();
+    };
+    crate::Capabilities {
+        rumble: supports_effect(&vibration_actuator, "dual-rumble")
+            || vibration_actuator.effect_type().as_deref() == Some("dual-rumble"),
+        trigger_rumble: supports_effect(&vibration_actuator, "trigger-rumble"),
+        motion: false,
+        led: false,
+        force_feedback: false,
+    }
+}
+
+/// Whether a `GamepadHapticActuator` lists the given effect type as supported.
+fn supports_effect(vibration_actuator: &VibrationActuator, effect: &str) -> bool {
+    vibration_actuator
+        .effects()
+        .is_some_and(|effects| effects.includes(&effect.into(), 0))
+}
+
+/// How often to sample button state in between polls, to not miss quick presses.
+const SAMPLE_INTERVAL_MS: i32 = 4;
+
+/// Button state sampled in between polls, indexed by slot.
+struct Samples {
+    started: bool,
+    /// The browser gamepad index of the gamepad in each slot.
+    ///
+    /// Browsers may use indices of [MAX_GAMEPADS](crate::MAX_GAMEPADS) and above after many
+    /// reconnects, so gamepads are kept in the first slot free when connected.
+    browser_indices: [Option<u32>; crate::MAX_GAMEPADS],
+    /// The gamepad in each slot as of the last sample, kept to not query gamepads again.
+    slots: [Option<web_sys::Gamepad>; crate::MAX_GAMEPADS],
+    /// Pressed bits as of the last sample.
+    sampled_bits: [u32; crate::MAX_GAMEPADS],
+    /// Bits pressed since the last poll.
+    accumulated_bits: [u32; crate::MAX_GAMEPADS],
+    /// When buttons were sampled as pressed or released since the last poll.
+    timestamps: [crate::event::RawTimestamps; crate::MAX_GAMEPADS],
+    /// Whether the page was visible and focused when last checked.
+    page_active: bool,
+    /// Buttons held when the page became active again, ignored until released.
+    held_on_return_bits: [u32; crate::MAX_GAMEPADS],
+    /// Whether the browser has exposed a gamepad, which it only does after a button press.
+    activated: bool,
+}
+
+thread_local! {
+    static SAMPLES: std::cell::RefCell<Samples> = const {
+        std::cell::RefCell::new(Samples {
+            started: false,
+            browser_indices: [None; crate::MAX_GAMEPADS],
+            slots: [const { None }; crate::MAX_GAMEPADS],
+            sampled_bits: [0; crate::MAX_GAMEPADS],
+            accumulated_bits: [0; crate::MAX_GAMEPADS],
+            timestamps: [crate::event::RawTimestamps::new(); crate::MAX_GAMEPADS],
+            page_active: true,
+            held_on_return_bits: [0; crate::MAX_GAMEPADS],
+            activated: false,
+        })
+    };
+}
+
+impl Samples {
+    /// Assign slots to the connected gamepads, freeing the slots of disconnected ones,
+    /// returning the gamepad in each slot, to be kept in [Samples::slots] once read.
+    fn assign_slots(&mut self) -> [Option<web_sys::Gamepad>; crate::MAX_GAMEPADS] {
+        let mut slots: [Option<web_sys::Gamepad>; crate::MAX_GAMEPADS] = Default::default();
+        let mut unassigned: [Option<web_sys::Gamepad>; crate::MAX_GAMEPADS] = Default::default();
+        for gamepad in connected_gamepads() {
+            self.activated = true;
+            let index = Some(gamepad.index());
+            if let Some(slot) = self.browser_indices.iter().position(|i| *i == index) {
+                slots[slot] = Some(gamepad);
+            } else if let Some(free) = unassigned.iter_mut().find(|free| free.is_none()) {
+                *free = Some(gamepad);
+            }
+        }
+        for (slot, browser_index) in self.browser_indices.iter_mut().enumerate() {
+            if browser_index.is_some() && slots[slot].is_none() {
+                *browser_index = None;
+                self.sampled_bits[slot] = 0;
+                self.accumulated_bits[slot] = 0;
+                self.timestamps[slot] = crate::event::RawTimestamps::new();
+                self.held_on_return_bits[slot] = 0;
+            }
+        }
+        for gamepad in unassigned.into_iter().flatten() {
+            let Some(slot) = self.browser_indices.iter().position(Option::is_none) else {
+                break;
+            };
+            self.browser_indices[slot] = Some(gamepad.index());
+            slots[slot] = Some(gamepad);
+        }
+        self.update_page_active(&slots);
+        slots
+    }
+
+    /// Release all buttons and stop rumble when the page becomes hidden or unfocused, as
+    /// browsers stop reporting gamepad input then, and ignore buttons still held on return
+    /// instead of reporting them as just pressed.
+    fn update_page_active(&mut self, slots: &[Option<web_sys::Gamepad>]) {
+        let active = is_page_active();
+        if active == std::mem::replace(&mut self.page_active, active) {
+            return;
+        }
+        for (slot, gamepad) in slots.iter().enumerate() {
+            let Some(gamepad) = gamepad else {
+                continue;
+            };
+            if active {
+                self.held_on_return_bits[slot] = read_state(gamepad).pressed_bits;
+            } else {
+                self.sampled_bits[slot] = 0;
+                self.accumulated_bits[slot] = 0;
+                reset_vibration(gamepad);
             }
         }
     }
+
+    /// The pressed bits reported for the gamepad in a slot.
+    const fn reported_bits(&mut self, slot: usize, pressed_bits: u32) -> u32 {
+        if !self.page_active {
+            return 0;
+        }
+        self.held_on_return_bits[slot] &= pressed_bits;
+        pressed_bits & !self.held_on_return_bits[slot]
+    }
 }
 
-pub fn poll(gamepads: &mut crate::Gamepads) {
-    #![allow(clippy::expect_used)]
-    for gamepad in web_sys::window()
-        .expect("Unable to get window")
-        .navigator()
-        .get_gamepads()
-        .expect("Unable to get gamepads")
+/// Whether the page is visible and focused.
+fn is_page_active() -> bool {
+    web_sys::window()
+        .and_then(|window| window.document())
+        .is_none_or(|document| !document.hidden() && document.has_focus().unwrap_or(true))
+}
+
+/// Stop rumble on a gamepad, which reports playing effects as preempted.
+fn reset_vibration(gamepad: &web_sys::Gamepad) {
+    // Throws in older browsers without `reset()`:
+    if let Some(vibration_actuator) = vibration_actuator(gamepad) {
+        let _ = vibration_actuator.reset();
+    }
+}
+
+/// The state of a gamepad in the standard layout.
+#[derive(Default)]
+struct State {
+    mapping: crate::GamepadMapping,
+    pressed_bits: u32,
+    axes: [f32; 4],
+    triggers: [f32; 2],
+    extra_axes: [f32; crate::MAX_EXTRA_AXES],
+    high_pressed_bits: u64,
+}
+
+/// Read the mapping of a gamepad.
+fn read_mapping(gamepad: &web_sys::Gamepad) -> crate::GamepadMapping {
+    match gamepad.mapping() {
+        web_sys::GamepadMappingType::Standard => crate::GamepadMapping::Standard,
+        web_sys::GamepadMappingType::None => crate::GamepadMapping::Unmapped,
+        // Such as `xr-standard`, only read as a string when needed to not allocate:
+        _ => crate::web_mapping::mapping_from_str(&gamepad.unchecked_ref::<GamepadExt>().mapping()),
+    }
+}
+
+/// Read the state of a gamepad, remapping known gamepads without the standard mapping.
+fn read_state(gamepad: &web_sys::Gamepad) -> State {
+    let mut raw = crate::web_mapping::RawGamepad::default();
+    let mut state = State {
+        mapping: read_mapping(gamepad),
+        ..State::default()
+    };
+    for (button_idx, button) in gamepad
+        .buttons()
         .iter()
-        .filter(|v| !v.is_null())
+        .take(crate::MAX_BUTTONS)
+        .enumerate()
     {
-        let gamepad = web_sys::Gamepad::from(gamepad);
-        let mut pressed_bits: u32 = 0;
-        for (button_idx, button) in gamepad.buttons().iter().enumerate() {
-            let button = web_sys::GamepadButton::from(button);
-            if button.pressed() {
-                pressed_bits |= 1 << (button_idx as u32);
-            }
+        let button = web_sys::GamepadButton::from(button);
+        if button.pressed() {
+            raw.press(button_idx);
+        }
+        // The analog values of the left and right triggers in the standard layout:
+        if matches!(button_idx, 6 | 7) {
+            state.triggers[button_idx - 6] = button.value() as f32;
         }
-        gamepads.gamepads[gamepad.index() as usize].pressed_bits = pressed_bits;
-        gamepads.gamepads[gamepad.index() as usize].connected = gamepad.connected();
-        for (axes_idx, axes_value) in gamepad
-            .axes()
-            .iter()
-            .map(|a| a.as_f64().expect("axes should be numbers"))
-            .enumerate()
+    }
+    let gamepad_axes = gamepad.axes();
+    for (axis, value) in raw.axes.iter_mut().zip(gamepad_axes.iter()) {
+        *axis = value.as_f64().unwrap_or_default() as f32;
+    }
+    if state.mapping != crate::GamepadMapping::Standard {
+        if let Some((pressed_bits, axes)) =
+            crate::web_mapping::remap(state.mapping, &gamepad.id(), &raw)
         {
-            gamepads.gamepads[gamepad.index() as usize].axes[axes_idx] =
-                axes_value as f32 * if axes_idx % 2 == 1 { -1. } else { 1. };
+            return State {
+                mapping: state.mapping,
+                pressed_bits,
+                axes,
+                ..State::default()
+            };
+        }
+        // Other devices are reported in their device specific order, without triggers:
+        state.triggers = [0.; 2];
+    }
+    state.pressed_bits = raw.pressed_bits;
+    state.high_pressed_bits = raw.high_pressed_bits;
+    state.axes = crate::web_mapping::raw_axes(&raw);
+    // Axes beyond the two thumbsticks, such as of wheels and pedals:
+    state.extra_axes.copy_from_slice(&raw.axes[4..]);
+    state
+}
+
+/// Why gamepads are unavailable, or `None` if the Gamepad API is usable.
+pub fn availability() -> Option<crate::Availability> {
+    let Some(window) = web_sys::window() else {
+        return Some(crate::Availability::Unsupported);
+    };
+    if !window.is_secure_context() {
+        return Some(crate::Availability::InsecureContext);
+    }
+    let navigator = window.navigator();
+    if !js_sys::Reflect::get(&navigator, &"getGamepads".into())
+        .is_ok_and(|get_gamepads| get_gamepads.is_function())
+    {
+        return Some(crate::Availability::Unsupported);
+    }
+    // Throws a SecurityError if blocked by a permissions policy:
+    if navigator.get_gamepads().is_err() {
+        return Some(crate::Availability::BlockedByPermissionsPolicy);
+    }
+    None
+}
+
+thread_local! {
+    /// The navigator, looked up once, or `None` without a window such as in web workers.
+    static NAVIGATOR: Option<web_sys::Navigator> =
+        web_sys::window().map(|window| window.navigator());
+}
+
+fn connected_gamepads() -> impl Iterator<Item = web_sys::Gamepad> {
+    // No gamepads are reported without a window, such as in web workers, or
+    // if the Gamepad API is unavailable or blocked by a permissions policy:
+    NAVIGATOR
+        .with(|navigator| {
+            navigator
+                .as_ref()
+                .and_then(|navigator| navigator.get_gamepads().ok())
+        })
+        .into_iter()
+        .flatten()
+        .filter(|gamepad| !gamepad.is_null())
+        .map(web_sys::Gamepad::from)
+        .filter(web_sys::Gamepad::connected)
+}
+
+fn record_changes(timestamps: &mut crate::event::RawTimestamps, old_bits: u32, new_bits: u32) {
+    let now_ms = crate::time::now_ms();
+    for bit_idx in 0..32 {
+        let bit = 1 << bit_idx;
+        if new_bits & !old_bits & bit != 0 {
+            timestamps.record_press(bit_idx, now_ms);
+        } else if old_bits & !new_bits & bit != 0 {
+            timestamps.record_release(bit_idx, now_ms);
+        }
+    }
+}
+
+fn sample() {
+    SAMPLES.with_borrow_mut(|samples| {
+        let slots = samples.assign_slots();
+        for (slot, gamepad) in slots.iter().enumerate() {
+            let Some(gamepad) = gamepad else {
+                continue;
+            };
+            let bits = samples.reported_bits(slot, read_state(gamepad).pressed_bits);
+            let old_bits = samples.sampled_bits[slot];
+            samples.accumulated_bits[slot] |= bits & !old_bits;
+            record_changes(&mut samples.timestamps[slot], old_bits, bits);
+            samples.sampled_bits[slot] = bits;
         }
+        samples.slots = slots;
+    });
+}
+
+fn start_sampling() {
+    let closure = wasm_bindgen::closure::Closure::<dyn FnMut()>::new(sample);
+    if let Some(window) = web_sys::window() {
+        let _ = window.set_interval_with_callback_and_timeout_and_arguments_0(
+            closure.as_ref().unchecked_ref(),
+            SAMPLE_INTERVAL_MS,
+        );
+        // Also necessary for gamepad listing to work in some browsers, and samples the
+        // newly exposed gamepad right away:
+        let _ = window
+            .add_event_listener_with_callback("gamepadconnected", closure.as_ref().unchecked_ref());
+    }
+    // The closure is kept alive for the remainder of the program:
+    closure.forget();
+}
+
+/// Whether no gamepad has been exposed by the browser yet, which it only does once a button
+/// has been pressed on one while the page is visible.
+pub fn needs_user_gesture() -> bool {
+    availability().is_none() && !SAMPLES.with_borrow(|samples| samples.activated)
+}
+
+pub fn poll(gamepads: &mut crate::Gamepads) {
+    if !SAMPLES.with_borrow_mut(|samples| std::mem::replace(&mut samples.started, true)) {
+        start_sampling();
+    }
+
+    for gamepad in &mut gamepads.gamepads {
+        gamepad.connected = false;
     }
+    SAMPLES.with_borrow_mut(|samples| {
+        let slots = samples.assign_slots();
+        for (slot, gamepad) in slots.iter().enumerate() {
+            let Some(gamepad) = gamepad else {
+                continue;
+            };
+            let State {
+                mapping,
+                pressed_bits,
+                axes,
+                triggers,
+                extra_axes,
+                high_pressed_bits,
+            } = if samples.page_active {
+                read_state(gamepad)
+            } else {
+                State {
+                    mapping: read_mapping(gamepad),
+                    ..State::default()
+                }
+            };
+            let pressed_bits = samples.reported_bits(slot, pressed_bits);
+            let old_bits = samples.sampled_bits[slot];
+            let newly_pressed_bits = pressed_bits & !old_bits;
+            record_changes(&mut samples.timestamps[slot], old_bits, pressed_bits);
+            gamepads.raw_timestamps[slot] = std::mem::take(&mut samples.timestamps[slot]);
+            let state = &mut gamepads.gamepads[slot];
+            state.just_pressed_bits = samples.accumulated_bits[slot] | newly_pressed_bits;
+            state.pressed_bits = pressed_bits;
+            state.connected = true;
+            state.mapping = mapping as u8;
+            state.axes = axes;
+            state.triggers = triggers;
+            state.extra_axes = extra_axes;
+            state.high_pressed_bits = high_pressed_bits;
+            samples.accumulated_bits[slot] = 0;
+            samples.sampled_bits[slot] = pressed_bits;
+        }
+        samples.slots = slots;
+    });
+}
+
+/// Mouse input received while the pointer was locked, since the last call to [take_mouse()].
+#[cfg(feature = "mouse")]
+#[derive(Default)]
+struct MouseSamples {
+    started: bool,
+    delta: [f32; 2],
+    pressed_bits: u32,
+    just_pressed_bits: u32,
+}
+
+#[cfg(feature = "mouse")]
+thread_local! {
+    static MOUSE: std::cell::RefCell<MouseSamples> = std::cell::RefCell::default();
+}
+
+#[cfg(feature = "mouse")]
+fn start_mouse_listeners(window: &web_sys::Window) {
+    let pointer_locked = || {
+        web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.pointer_lock_element())
+            .is_some()
+    };
+    for event_type in ["mousemove", "mousedown", "mouseup"] {
+        let closure = wasm_bindgen::closure::Closure::<dyn FnMut(_)>::new(
+            move |event: web_sys::MouseEvent| {
+                if !pointer_locked() {
+                    return;
+                }
+                MOUSE.with_borrow_mut(|mouse| match event_type {
+                    "mousemove" => {
+                        mouse.delta[0] += event.movement_x() as f32;
+                        mouse.delta[1] += event.movement_y() as f32;
+                    }
+                    _ if event.button() > 2 => {}
+                    "mousedown" => {
+                        mouse.pressed_bits |= 1 << event.button();
+                        mouse.just_pressed_bits |= 1 << event.button();
+                    }
+                    _ => mouse.pressed_bits &= !(1 << event.button()),
+                });
+            },
+        );
+        let _ =
+            window.add_event_listener_with_callback(event_type, closure.as_ref().unchecked_ref());
+        // The closure is kept alive for the remainder of the program:
+        closure.forget();
+    }
+}
+
+/// Take the mouse movement, pressed bits and just pressed bits received while the pointer
+/// was locked since the last call, starting to listen for mouse events on the first call.
+#[cfg(feature = "mouse")]
+pub fn take_mouse() -> ([f32; 2], u32, u32) {
+    MOUSE.with_borrow_mut(|mouse| {
+        if !std::mem::replace(&mut mouse.started, true) {
+            if let Some(window) = web_sys::window() {
+                start_mouse_listeners(&window);
+            }
+        }
+        (
+            std::mem::take(&mut mouse.delta),
+            mouse.pressed_bits,
+            std::mem::take(&mut mouse.just_pressed_bits),
+        )
+    })
 }
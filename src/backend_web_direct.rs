@@ -1,17 +1,183 @@
 use crate::Gamepad;
 
 extern "C" {
-    // Host javascript function.
-    pub fn getGamepads(data_ptr: *const Gamepad);
+    // Host javascript function, writing gamepads in the layout of the given protocol version.
+    pub fn getGamepads(data_ptr: *const Gamepad, protocol_version: u32);
 
-    // Host javascript function.
+    // Host javascript function, returning the highest protocol version supported.
+    #[cfg(backend = "web_direct")]
+    pub fn gamepadsProtocolVersion() -> u32;
+
+    // Host javascript function, playing a trigger-rumble effect if a trigger magnitude
+    // is non-zero and the gamepad supports it, and a dual-rumble effect otherwise.
+    // Returns an id for the effect status, or 0 if the effect could not be played.
     pub fn playEffect(
         gamepad_id: u8,
         duration_ms: u32,
         start_delay_ms: u32,
         strong_magnitude: f32,
         weak_magnitude: f32,
-    );
+        left_trigger: f32,
+        right_trigger: f32,
+    ) -> u32;
+
+    // Host javascript function, returning 0 if the effect is playing, 1 if complete,
+    // 2 if preempted and 3 if failed. Finished effects are forgotten once reported.
+    pub fn gamepadsEffectStatus(effect_id: u32) -> u8;
+
+    // Host javascript function, forgetting an effect no longer tracked.
+    pub fn gamepadsForgetEffect(effect_id: u32);
+
+    // Host javascript function, returning the effect types supported by a gamepad as bits,
+    // with 1 for dual-rumble and 2 for trigger-rumble.
+    pub fn gamepadsEffects(gamepad_id: u8) -> u8;
+
+    // Host javascript function, writing the mouse input received while the pointer was locked
+    // since the last call.
+    #[cfg(all(backend = "web_direct", feature = "mouse"))]
+    pub fn gamepadsTakeMouse(sample_ptr: *mut crate::mouse::MouseSample);
+
+    // Host javascript function.
+    pub fn gamepadsNow() -> f64;
+
+    // Host javascript function, calling the exported `gamepads_wake_streams` after a delay.
+    #[cfg(feature = "async")]
+    pub fn gamepadsWakeAfter(delay_ms: u32);
+
+    // Host javascript function, returning 0 if available, 1 if not in a secure context,
+    // 2 if blocked by a permissions policy, 3 if unsupported and 4 if available but no
+    // gamepad has been exposed yet.
+    pub fn gamepadsAvailability() -> u8;
+
+    // Host javascript function, writing the raw state and id of a connected gamepad without
+    // the standard mapping, returning the length of the id or -1 if there is no such gamepad.
+    #[cfg(backend = "web_direct")]
+    pub fn getGamepadRaw(
+        gamepad_idx: u8,
+        raw_ptr: *mut crate::web_mapping::RawGamepad,
+        id_ptr: *mut u8,
+        id_capacity: u32,
+        protocol_version: u32,
+    ) -> i32;
+
+    // Host javascript function, writing the id of a connected gamepad,
+    // returning its full length or -1 if there is no such gamepad.
+    #[cfg(backend = "web_direct")]
+    pub fn gamepadsId(gamepad_idx: u8, id_ptr: *mut u8, id_capacity: u32) -> i32;
+}
+
+/// The protocol version in use, or 0 until negotiated by [protocol_version()].
+#[cfg(backend = "web_direct")]
+static PROTOCOL_VERSION: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+/// The protocol version in use, being the lower of [PROTOCOL_VERSION](crate::js::PROTOCOL_VERSION)
+/// and what the host javascript supports.
+#[cfg(backend = "web_direct")]
+pub fn protocol_version() -> u32 {
+    use std::sync::atomic::Ordering;
+    let version = PROTOCOL_VERSION.load(Ordering::Relaxed);
+    if version != 0 {
+        return version;
+    }
+    let supported = unsafe { gamepadsProtocolVersion() };
+    if supported < crate::js::PROTOCOL_VERSION {
+        crate::logging::warning!(
+            "The gamepads javascript supports protocol version {supported}, while version {} \
+             is current - update it from gamepads::js to report all gamepad state",
+            crate::js::PROTOCOL_VERSION
+        );
+    }
+    let version = supported.clamp(1, crate::js::PROTOCOL_VERSION);
+    PROTOCOL_VERSION.store(version, Ordering::Relaxed);
+    version
+}
+
+/// Read the state of all gamepads written by the host javascript.
+#[cfg(backend = "web_direct")]
+pub fn get_gamepads(gamepads: &mut [Gamepad; crate::MAX_GAMEPADS]) {
+    unsafe { getGamepads(gamepads.as_ptr(), protocol_version()) };
+}
+
+/// The `id` string of a connected gamepad.
+#[cfg(backend = "web_direct")]
+pub fn name(gamepad_id: u8) -> Option<String> {
+    let mut id = vec![0_u8; 128];
+    loop {
+        let capacity = id.len() as u32;
+        let len = unsafe { gamepadsId(gamepad_id, id.as_mut_ptr(), capacity) };
+        let len = usize::try_from(len).ok()?;
+        if len <= id.len() {
+            id.truncate(len);
+            return String::from_utf8(id).ok();
+        }
+        // Retry with room for the whole id:
+        id.resize(len, 0);
+    }
+}
+
+/// What the vibration actuator of a gamepad supports, if it has one.
+#[cfg(backend = "web_direct")]
+pub fn capabilities(gamepad_id: u8) -> crate::Capabilities {
+    let effects = unsafe { gamepadsEffects(gamepad_id) };
+    crate::Capabilities {
+        rumble: effects & 1 != 0,
+        trigger_rumble: effects & 2 != 0,
+        motion: false,
+        led: false,
+        force_feedback: false,
+    }
+}
+
+impl crate::Gamepads {
+    /// Read gamepads without the standard mapping, which `getGamepads` reports as disconnected,
+    /// remapping those that are known and reporting others in their device specific order.
+    #[cfg(backend = "web_direct")]
+    pub(crate) fn poll_remapped_web_direct(&mut self) {
+        let mut id_buffer = [0_u8; 128];
+        for (gamepad_idx, gamepad) in self.gamepads.iter_mut().enumerate() {
+            if gamepad.connected {
+                continue;
+            }
+            let mut raw = crate::web_mapping::RawGamepad::default();
+            let id_len = get_gamepad_raw(gamepad_idx as u8, &mut raw, &mut id_buffer);
+            let Ok(id_len) = usize::try_from(id_len) else {
+                continue;
+            };
+            let id_len = id_len.min(id_buffer.len());
+            let id = std::str::from_utf8(&id_buffer[..id_len]).unwrap_or_default();
+            let mapping = crate::GamepadMapping::from_u8(raw.mapping);
+            gamepad.connected = true;
+            gamepad.set_mapping(mapping);
+            gamepad.triggers = [0.; 2];
+            if let Some((pressed_bits, axes)) = crate::web_mapping::remap(mapping, id, &raw) {
+                gamepad.pressed_bits = pressed_bits;
+                gamepad.axes = axes;
+                gamepad.extra_axes = [0.; crate::MAX_EXTRA_AXES];
+            } else {
+                crate::web_mapping::write_unmapped(&raw, gamepad);
+            }
+        }
+    }
+}
+
+/// Read the raw state and id of a gamepad using `getGamepadRaw`, returning the length of the id
+/// or -1 if there is no such gamepad.
+#[cfg(backend = "web_direct")]
+fn get_gamepad_raw(
+    gamepad_idx: u8,
+    raw: &mut crate::web_mapping::RawGamepad,
+    id_buffer: &mut [u8],
+) -> i32 {
+    let id_capacity = id_buffer.len() as u32;
+    unsafe {
+        getGamepadRaw(
+            gamepad_idx,
+            raw,
+            id_buffer.as_mut_ptr(),
+            id_capacity,
+            protocol_version(),
+        )
+    }
 }
 
 /// Expose crate version information as expected by
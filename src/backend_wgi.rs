@@ -0,0 +1,199 @@
+//! Backend for Windows, enabled by the `backend-wgi` feature, reading gamepads using the
+//! `Windows.Gaming.Input` API directly instead of through gilrs.
+//!
+//! This gives access to the impulse triggers of Xbox One and later controllers, using
+//! [Gamepads::rumble_triggers()](crate::Gamepads::rumble_triggers), and to the display name of
+//! the device. Gamepads are only read while the application has focus, and are reported
+//! shortly after startup rather than right away, as they are enumerated in the background.
+//! The guide button is not exposed by the API, so [Button::Mode] is never pressed.
+
+use crate::{Button, MAX_GAMEPADS};
+use windows::Gaming::Input::{Gamepad, GamepadButtons, GamepadVibration, RawGameController};
+
+/// The thumbstick deadzones recommended for XInput, which covers the same controllers.
+const LEFT_STICK_DEADZONE: f32 = 7849. / 32767.;
+const RIGHT_STICK_DEADZONE: f32 = 8689. / 32767.;
+
+/// How far a trigger needs to be pressed to be reported as a pressed button, as for XInput.
+const TRIGGER_THRESHOLD: f64 = 30. / 255.;
+
+const BUTTONS: [(GamepadButtons, Button); 14] = [
+    (GamepadButtons::A, Button::ActionDown),
+    (GamepadButtons::B, Button::ActionRight),
+    (GamepadButtons::X, Button::ActionLeft),
+    (GamepadButtons::Y, Button::ActionUp),
+    (GamepadButtons::LeftShoulder, Button::FrontLeftUpper),
+    (GamepadButtons::RightShoulder, Button::FrontRightUpper),
+    (GamepadButtons::View, Button::LeftCenterCluster),
+    (GamepadButtons::Menu, Button::RightCenterCluster),
+    (GamepadButtons::LeftThumbstick, Button::LeftStick),
+    (GamepadButtons::RightThumbstick, Button::RightStick),
+    (GamepadButtons::DPadUp, Button::DPadUp),
+    (GamepadButtons::DPadDown, Button::DPadDown),
+    (GamepadButtons::DPadLeft, Button::DPadLeft),
+    (GamepadButtons::DPadRight, Button::DPadRight),
+];
+
+/// A rumble effect waiting for its start delay or playing, as the API has no durations.
+#[derive(Clone, Copy)]
+struct ScheduledRumble {
+    start_ms: f64,
+    stop_ms: f64,
+    vibration: GamepadVibration,
+    started: bool,
+}
+
+/// The gamepad in each slot, and its scheduled rumble effect.
+#[derive(Default)]
+pub struct State {
+    slots: [Option<Gamepad>; MAX_GAMEPADS],
+    rumbles: [Option<ScheduledRumble>; MAX_GAMEPADS],
+}
+
+/// Read the state of all gamepads, and start and stop scheduled rumble effects.
+pub fn poll(gamepads: &mut crate::Gamepads) {
+    let connected: Vec<Gamepad> = Gamepad::Gamepads()
+        .map(|list| list.into_iter().collect())
+        .unwrap_or_default();
+    let state = &mut gamepads.wgi;
+
+    // Gamepads are kept in the first slot free when connected:
+    for (slot, rumble) in state.slots.iter_mut().zip(state.rumbles.iter_mut()) {
+        if slot
+            .as_ref()
+            .is_some_and(|gamepad| !connected.contains(gamepad))
+        {
+            *slot = None;
+            *rumble = None;
+        }
+    }
+    for wgi_gamepad in connected {
+        if state
+            .slots
+            .iter()
+            .flatten()
+            .any(|slot| *slot == wgi_gamepad)
+        {
+            continue;
+        }
+        if let Some(free_slot) = state.slots.iter_mut().find(|slot| slot.is_none()) {
+            *free_slot = Some(wgi_gamepad);
+        } else {
+            crate::logging::warning!("Ignoring gamepad, as {MAX_GAMEPADS} are already in use");
+        }
+    }
+
+    for (gamepad, slot) in gamepads.gamepads.iter_mut().zip(&state.slots) {
+        let Some(reading) = slot
+            .as_ref()
+            .and_then(|wgi_gamepad| wgi_gamepad.GetCurrentReading().ok())
+        else {
+            gamepad.set_connected(false);
+            continue;
+        };
+        gamepad.connected = true;
+        gamepad.pressed_bits = 0;
+        for (wgi_button, button) in BUTTONS {
+            if reading.Buttons.0 & wgi_button.0 != 0 {
+                gamepad.pressed_bits |= 1 << (button as u32);
+            }
+        }
+        for (value, button) in [
+            (reading.LeftTrigger, Button::FrontLeftLower),
+            (reading.RightTrigger, Button::FrontRightLower),
+        ] {
+            if value > TRIGGER_THRESHOLD {
+                gamepad.pressed_bits |= 1 << (button as u32);
+            }
+        }
+        gamepad.axes = [
+            crate::apply_deadzone(reading.LeftThumbstickX as f32, LEFT_STICK_DEADZONE),
+            crate::apply_deadzone(reading.LeftThumbstickY as f32, LEFT_STICK_DEADZONE),
+            crate::apply_deadzone(reading.RightThumbstickX as f32, RIGHT_STICK_DEADZONE),
+            crate::apply_deadzone(reading.RightThumbstickY as f32, RIGHT_STICK_DEADZONE),
+        ];
+        gamepad.triggers = [reading.LeftTrigger as f32, reading.RightTrigger as f32];
+    }
+
+    update_rumbles(state);
+}
+
+/// Start rumble effects whose start delay has passed, and stop those that have ended.
+fn update_rumbles(state: &mut State) {
+    let now_ms = crate::time::now_ms();
+    for (slot, rumble) in state.slots.iter().zip(state.rumbles.iter_mut()) {
+        let (Some(wgi_gamepad), Some(scheduled)) = (slot, rumble.as_mut()) else {
+            continue;
+        };
+        let vibration = if now_ms >= scheduled.stop_ms {
+            *rumble = None;
+            GamepadVibration::default()
+        } else if now_ms >= scheduled.start_ms && !scheduled.started {
+            scheduled.started = true;
+            scheduled.vibration
+        } else {
+            continue;
+        };
+        if let Err(error) = wgi_gamepad.SetVibration(vibration) {
+            crate::logging::warning!("Failed to set vibration: {error}");
+        }
+    }
+}
+
+/// Schedule a rumble effect, replacing any previous one, which is played from the next poll.
+pub fn rumble(
+    state: &mut State,
+    gamepad_id: crate::GamepadId,
+    duration_ms: u32,
+    start_delay_ms: u32,
+    strong_magnitude: f32,
+    weak_magnitude: f32,
+    trigger_magnitudes: (f32, f32),
+) {
+    let gamepad_idx = usize::from(gamepad_id.0);
+    if state.slots[gamepad_idx].is_none() {
+        return;
+    }
+    let start_ms = crate::time::now_ms() + f64::from(start_delay_ms);
+    // Stop a previous effect still playing until this one starts:
+    if let Some(wgi_gamepad) = &state.slots[gamepad_idx] {
+        if state.rumbles[gamepad_idx].is_some_and(|previous| previous.started) {
+            let _ = wgi_gamepad.SetVibration(GamepadVibration::default());
+        }
+    }
+    state.rumbles[gamepad_idx] = Some(ScheduledRumble {
+        start_ms,
+        stop_ms: start_ms + f64::from(duration_ms),
+        vibration: GamepadVibration {
+            LeftMotor: f64::from(strong_magnitude),
+            RightMotor: f64::from(weak_magnitude),
+            LeftTrigger: f64::from(trigger_magnitudes.0),
+            RightTrigger: f64::from(trigger_magnitudes.1),
+        },
+        started: false,
+    });
+    update_rumbles(state);
+}
+
+/// Whether a gamepad is connected, in which case it is assumed to have rumble motors and
+/// impulse triggers, which the API does not tell.
+pub fn capabilities(state: &State, gamepad_id: crate::GamepadId) -> crate::Capabilities {
+    let connected = state.slots[usize::from(gamepad_id.0)].is_some();
+    crate::Capabilities {
+        rumble: connected,
+        trigger_rumble: connected,
+        motion: false,
+        led: false,
+        force_feedback: false,
+    }
+}
+
+/// The display name of a connected gamepad.
+pub fn name(state: &State, gamepad_id: crate::GamepadId) -> Option<String> {
+    let wgi_gamepad = state.slots[usize::from(gamepad_id.0)].as_ref()?;
+    let raw_controller = RawGameController::FromGameController(wgi_gamepad).ok()?;
+    raw_controller
+        .DisplayName()
+        .ok()
+        .map(|name| name.to_string_lossy())
+}
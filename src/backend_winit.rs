@@ -0,0 +1,501 @@
+//! Gamepads read from the events of a winit event loop, forwarded using
+//! [Gamepads::on_winit_event()](crate::Gamepads::on_winit_event).
+//!
+//! Gamepads are reported as key events and axis updates of windows, by the fork of winit
+//! adding the latter on Android and exposing the ids of the Android input devices.
+use crate::logging;
+use gamepads_core::MAX_GAMEPADS;
+use std::sync::atomic::Ordering;
+use winit::event::{DeviceId, Event, WindowEvent};
+use winit::keyboard::{Key, NativeKey};
+use winit::platform::android::DeviceIdExtAndroid;
+
+/// The number of values of the axis updates of windows: the `x` and `y` of the hat switch,
+/// the left thumbstick and the right thumbstick.
+pub const AXIS_UPDATE_LEN: usize = 6;
+
+/// The range of values of an axis in the axis updates of windows, as described by the
+/// `InputDevice.MotionRange` of the axis on Android.
+#[derive(Clone, Copy, Debug)]
+pub struct MotionRange {
+    pub min: f32,
+    pub max: f32,
+    /// The values around the center within which the axis is considered centered.
+    pub flat: f32,
+    /// The error of values, within which changes are noise.
+    pub fuzz: f32,
+}
+
+impl MotionRange {
+    /// The range assumed when not known.
+    const FULL: Self = Self {
+        min: -1.,
+        max: 1.,
+        flat: 0.,
+        fuzz: 0.,
+    };
+
+    /// Normalize a value to the range `[-1.0, 1.0]`, with the flat region or noise around the
+    /// center as deadzone, as the evdev and gilrs backends do with the deadzones of drivers.
+    fn normalize(self, value: f32) -> f32 {
+        let span = self.max - self.min;
+        if span <= 0. {
+            return 0.;
+        }
+        let normalized = (2. * (value - self.min) / span - 1.).clamp(-1., 1.);
+        // Deadzones of more than half the range would be mistakes of the driver:
+        let deadzone = (2. * self.flat.max(self.fuzz) / span).min(0.5);
+        crate::apply_deadzone(normalized, deadzone)
+    }
+}
+
+/// The button of an Android key code, also mapping the center, back and menu keys of remotes of
+/// Android TV devices, and the back and menu keys of gamepads if enabled.
+const fn android_button(
+    keycode: u32,
+    is_remote: bool,
+    back_menu_keys: bool,
+) -> Option<crate::Button> {
+    Some(match keycode {
+        // See https://developer.android.com/develop/ui/views/touch-and-input/game-controllers/controller-input#dpad
+        // Most controllers report hat axis events instead of D-pad presses, but some might:
+        // "Some controllers instead report D-pad presses with a key code. If your game cares about D-pad
+        // presses, you should treat the hat axis events and the D-pad key codes as the same input events"
+        19 /* AKEYCODE_DPAD_UP */ => crate::Button::DPadUp,
+        20 /* AKEYCODE_DPAD_DOWN */ => crate::Button::DPadDown,
+        21 /* AKEYCODE_DPAD_LEFT */ => crate::Button::DPadLeft,
+        22 /* AKEYCODE_DPAD_RIGHT */ => crate::Button::DPadRight,
+        96 /* AKEYCODE_BUTTON_A */ => crate::Button::ActionDown,
+        97 /* AKEYCODE_BUTTON_B */ => crate::Button::ActionRight,
+        99 /* AKEYCODE_BUTTON_X */ => crate::Button::ActionLeft,
+        100 /* AKEYCODE_BUTTON_Y */ => crate::Button::ActionUp,
+        102 /* AKEYCODE_BUTTON_L1 */ => crate::Button::FrontLeftUpper,
+        103 /* AKEYCODE_BUTTON_R1 */ => crate::Button::FrontRightUpper,
+        104 /* AKEYCODE_BUTTON_L2 */ => crate::Button::FrontLeftLower,
+        105 /* AKEYCODE_BUTTON_R2 */ => crate::Button::FrontRightLower,
+        106 /* AKEYCODE_BUTTON_THUMBL */ => crate::Button::LeftStick,
+        107 /* AKEYCODE_BUTTON_THUMBR */ => crate::Button::RightStick,
+        108 /* AKEYCODE_BUTTON_START */ => crate::Button::RightCenterCluster,
+        109 /* AKEYCODE_BUTTON_SELECT */ => crate::Button::LeftCenterCluster,
+        // Remotes confirm with the center of their D-pad, and go back as with B on gamepads:
+        23 /* AKEYCODE_DPAD_CENTER */ | 66 /* AKEYCODE_ENTER */ | 160 /* AKEYCODE_NUMPAD_ENTER */
+            if is_remote =>
+        {
+            crate::Button::ActionDown
+        }
+        4 /* AKEYCODE_BACK */ if is_remote => crate::Button::ActionRight,
+        82 /* AKEYCODE_MENU */ if is_remote => crate::Button::RightCenterCluster,
+        // Many Bluetooth gamepads report their center buttons as these:
+        4 /* AKEYCODE_BACK */ if back_menu_keys => crate::Button::Mode,
+        82 /* AKEYCODE_MENU */ if back_menu_keys => crate::Button::RightCenterCluster,
+        _ => return None,
+    })
+}
+
+/// The winit devices of the gamepads, and what has been learned about other devices.
+pub struct State {
+    gamepad_ids: [Option<DeviceId>; MAX_GAMEPADS],
+    /// Whether gamepads have been polled since the last event, so that the buttons just pressed
+    /// are cleared before the next event.
+    just_polled: bool,
+    /// The ranges of the values of axis updates of each gamepad, `None` for axes it does not have.
+    axis_update_ranges: [[Option<MotionRange>; AXIS_UPDATE_LEN]; MAX_GAMEPADS],
+    /// The ids of the Android input devices of the gamepads, for rumbling them.
+    android_device_ids: [Option<i32>; MAX_GAMEPADS],
+    /// The names and USB ids of the Android input devices of the gamepads.
+    android_devices: [Option<crate::backend_android_winit::DeviceInfo>; MAX_GAMEPADS],
+    /// Whether the gamepads are remotes of Android TV devices.
+    android_remotes: [bool; MAX_GAMEPADS],
+    /// Whether remotes are used as gamepads, see [Gamepads::set_tv_remotes()](crate::Gamepads::set_tv_remotes).
+    tv_remotes: bool,
+    /// Whether the back and menu keys of gamepads are mapped, see
+    /// [Gamepads::set_back_and_menu_keys()](crate::Gamepads::set_back_and_menu_keys).
+    back_menu_keys: bool,
+    /// Android input devices which are not used as gamepads, such as keyboards.
+    ignored_device_ids: Vec<i32>,
+    /// The listener of input devices being added, removed or changed, if the app includes it.
+    device_listener: Option<jni::objects::GlobalRef>,
+    /// The [DEVICE_CHANGES](crate::backend_android_winit::DEVICE_CHANGES) when the connected
+    /// input devices were last enumerated, or `None` to enumerate them on the next poll.
+    enumerated_changes: Option<u64>,
+}
+
+/// Register the listener of input devices, logging why if it cannot be.
+fn register_device_listener() -> Option<jni::objects::GlobalRef> {
+    match crate::backend_android_winit::register_device_listener() {
+        Ok(Some(listener)) => Some(listener),
+        Ok(None) => {
+            logging::warning!(
+                "The InputDeviceListener class is not included in the app, so gamepads connect \
+                 on their first input and disconnect when the app resumes"
+            );
+            None
+        }
+        Err(error) => {
+            logging::warning!("Failed to register input device listener: {error}");
+            None
+        }
+    }
+}
+
+impl Drop for State {
+    fn drop(&mut self) {
+        if let Some(listener) = &self.device_listener {
+            if let Err(error) = crate::backend_android_winit::unregister_device_listener(listener) {
+                logging::warning!("Failed to unregister input device listener: {error}");
+            }
+        }
+    }
+}
+
+impl State {
+    pub fn new() -> Self {
+        Self {
+            gamepad_ids: [None; MAX_GAMEPADS],
+            just_polled: false,
+            axis_update_ranges: [[Some(MotionRange::FULL); AXIS_UPDATE_LEN]; MAX_GAMEPADS],
+            android_device_ids: [None; MAX_GAMEPADS],
+            android_devices: Default::default(),
+            android_remotes: [false; MAX_GAMEPADS],
+            tv_remotes: false,
+            back_menu_keys: false,
+            ignored_device_ids: Vec::new(),
+            device_listener: register_device_listener(),
+            enumerated_changes: None,
+        }
+    }
+
+    /// The id of the Android input device of a gamepad, if connected.
+    pub const fn android_device_id(&self, gamepad_id: crate::GamepadId) -> Option<i32> {
+        self.android_device_ids[gamepad_id.0 as usize]
+    }
+
+    /// The name and USB ids of the Android input device of a gamepad, if connected.
+    pub fn android_device(
+        &self,
+        gamepad_id: crate::GamepadId,
+    ) -> Option<&crate::backend_android_winit::DeviceInfo> {
+        self.android_devices[gamepad_id.0 as usize].as_ref()
+    }
+}
+
+impl crate::Gamepads {
+    /// Handle an event of a winit event loop, for apps already running one.
+    ///
+    /// Forward all events of the event loop, and keep calling [Gamepads::poll()](crate::Gamepads::poll)
+    /// on each tick, which applies the events received since the last poll. When the `keyboard`
+    /// feature is enabled, keyboard input is also forwarded to
+    /// [Gamepads::handle_key()](crate::Gamepads::handle_key), and when the `touch` feature is
+    /// enabled, touches are forwarded to the on-screen touch gamepad using
+    /// [Gamepads::handle_touch()](crate::Gamepads::handle_touch), so that phone players without
+    /// a gamepad only need to [enable it](crate::Gamepads::enable_touch_gamepad) and draw it.
+    ///
+    /// Returns whether the event was a key event of a gamepad mapped to a button, which the app
+    /// should then not handle itself, such as the back key of a gamepad on Android.
+    ///
+    /// ```ignore
+    /// event_loop.run(move |event, _| {
+    ///     gamepads.on_winit_event(&event);
+    ///     if let Event::AboutToWait = event {
+    ///         gamepads.poll();
+    ///         // Update the game using gamepads.all()
+    ///     }
+    /// })
+    /// ```
+    pub fn on_winit_event<T>(&mut self, event: &Event<T>) -> bool {
+        if self.winit.just_polled {
+            self.winit.just_polled = false;
+            for gamepad in self.gamepads.iter_mut() {
+                gamepad.just_pressed_bits = 0;
+                gamepad.high_just_pressed_bits = 0;
+            }
+        }
+
+        match event {
+            Event::WindowEvent {
+                event: window_event,
+                ..
+            } => return self.on_winit_window_event(window_event),
+            Event::Suspended => self.on_android_suspended(),
+            Event::Resumed => {
+                // Devices may have connected or disconnected while in the background:
+                self.winit.enumerated_changes = None;
+                self.enumerate_android_devices();
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Release the buttons and center the axes of all gamepads, as no key up events are received
+    /// for buttons released while the activity is paused, and stop their vibration.
+    fn on_android_suspended(&mut self) {
+        #[cfg(feature = "touch")]
+        self.cancel_touches();
+        for idx in 0..MAX_GAMEPADS {
+            let Some(android_device_id) = self.winit.android_device_ids[idx] else {
+                continue;
+            };
+            let gamepad = &mut self.gamepads[idx];
+            for index in 0..=16 {
+                if gamepad.pressed_bits & (1 << index) != 0 {
+                    self.raw_timestamps[idx].record_release(index, crate::time::now_ms());
+                }
+            }
+            gamepad.release_all();
+            if let Err(error) = crate::backend_android_winit::cancel_vibration(android_device_id) {
+                logging::warning!("Failed to cancel vibration: {error}");
+            }
+        }
+    }
+
+    fn on_winit_window_event(&mut self, window_event: &WindowEvent) -> bool {
+        match window_event {
+            WindowEvent::KeyboardInput {
+                event: key_event,
+                device_id,
+                ..
+            } => {
+                logging::trace!("Keyboard event {key_event:?} from device {device_id:?}");
+
+                #[cfg(feature = "keyboard")]
+                if let winit::keyboard::PhysicalKey::Code(code) = key_event.physical_key {
+                    if let Some(key) = crate::Key::from_winit_code(code) {
+                        if !key_event.repeat {
+                            self.handle_key(key, key_event.state.is_pressed());
+                        }
+                    }
+                }
+
+                if let Key::Unidentified(NativeKey::Android(keycode)) = key_event.logical_key {
+                    self.backend_events += 1;
+                    let Some(gamepad_idx) = self.find_or_insert(*device_id) else {
+                        return false;
+                    };
+                    let is_remote = self.winit.android_remotes[gamepad_idx];
+                    if let Some(gamepad_button) =
+                        android_button(keycode, is_remote, self.winit.back_menu_keys)
+                    {
+                        self.set_winit_button(
+                            gamepad_idx,
+                            gamepad_button as u32,
+                            key_event.state.is_pressed(),
+                        );
+                        logging::trace!(
+                            "Mapped to {gamepad_button:?} on gamepad index {gamepad_idx}"
+                        );
+                        return true;
+                    }
+                }
+            }
+            // Only reported by the winit fork on Android:
+            WindowEvent::AxisUpdate { device_id, values } => {
+                logging::trace!("Axis update {values:?} from device {device_id:?}");
+                self.backend_events += 1;
+                if let Some(gamepad_idx) = self.find_or_insert(*device_id) {
+                    let ranges = self.winit.axis_update_ranges[gamepad_idx];
+                    let value = |idx: usize| ranges[idx].map(|range| range.normalize(values[idx]));
+                    let gamepad = &mut self.gamepads[gamepad_idx];
+                    for (hat_idx, negative_button, positive_button) in [
+                        (0, crate::Button::DPadLeft, crate::Button::DPadRight),
+                        (1, crate::Button::DPadUp, crate::Button::DPadDown),
+                    ] {
+                        // Without a hat switch, the D-pad is reported as key events instead:
+                        let Some(val) = value(hat_idx) else {
+                            continue;
+                        };
+                        let negative_bit = 1 << (negative_button as u32);
+                        let positive_bit = 1 << (positive_button as u32);
+                        let pressed_bit = if val < 0. {
+                            negative_bit
+                        } else if val > 0. {
+                            positive_bit
+                        } else {
+                            0
+                        };
+                        // Axis updates repeat the hat while held, so only new presses count:
+                        gamepad.just_pressed_bits |= pressed_bit & !gamepad.pressed_bits;
+                        gamepad.pressed_bits &= !(negative_bit | positive_bit);
+                        gamepad.pressed_bits |= pressed_bit;
+                    }
+
+                    // Values of y axes are positive downwards:
+                    let [x, y, right_x, right_y] = [2, 3, 4, 5].map(|idx| value(idx).unwrap_or(0.));
+                    gamepad.axes = [x, -y, right_x, -right_y];
+                    self.raw_timestamps[gamepad_idx].record_axes(crate::time::now_ms());
+                }
+            }
+            WindowEvent::Touch(touch) => {
+                logging::trace!("Touch event {touch:?}");
+                #[cfg(feature = "touch")]
+                {
+                    let phase = match touch.phase {
+                        winit::event::TouchPhase::Started => crate::TouchPhase::Started,
+                        winit::event::TouchPhase::Moved => crate::TouchPhase::Moved,
+                        winit::event::TouchPhase::Ended => crate::TouchPhase::Ended,
+                        winit::event::TouchPhase::Cancelled => crate::TouchPhase::Cancelled,
+                    };
+                    let position = (touch.location.x as f32, touch.location.y as f32);
+                    self.handle_touch(touch.id, phase, position);
+                }
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn remove_winit_device(&mut self, device_id: DeviceId) {
+        if let Some(idx) = self
+            .winit
+            .gamepad_ids
+            .iter()
+            .position(|&id| id == Some(device_id))
+        {
+            self.winit.gamepad_ids[idx] = None;
+            self.winit.android_device_ids[idx] = None;
+            self.winit.android_devices[idx] = None;
+            self.winit.android_remotes[idx] = false;
+            self.gamepads[idx].set_connected(false);
+        }
+    }
+
+    /// Press or release a button by its index in the `buttons` array of the Gamepad API.
+    fn set_winit_button(&mut self, gamepad_idx: usize, index: u32, pressed: bool) {
+        let gamepad = &mut self.gamepads[gamepad_idx];
+        let timestamps = &mut self.raw_timestamps[gamepad_idx];
+        match (u8::try_from(index), pressed) {
+            (Ok(index @ 0..=16), true) => {
+                gamepad.pressed_bits |= 1 << index;
+                gamepad.just_pressed_bits |= 1 << index;
+                timestamps.record_press(usize::from(index), crate::time::now_ms());
+            }
+            (Ok(index @ 0..=16), false) => {
+                gamepad.pressed_bits &= !(1 << index);
+                timestamps.record_release(usize::from(index), crate::time::now_ms());
+            }
+            (Ok(index), true) => gamepad.press_extra(index),
+            (Ok(index), false) => gamepad.release_extra(index),
+            (Err(_), _) => {}
+        }
+    }
+
+    /// The index of the gamepad of a device, connecting it to the first free slot if new.
+    fn find_or_insert(&mut self, device_id: DeviceId) -> Option<usize> {
+        let ids = &mut self.winit.gamepad_ids;
+        if let Some(idx) = ids.iter().position(|&id| id == Some(device_id)) {
+            return Some(idx);
+        }
+        let idx = ids.iter().position(Option::is_none)?;
+        let android_device_id = device_id.android_device_id();
+        let class = self.android_device_class(android_device_id)?;
+        self.winit.gamepad_ids[idx] = Some(device_id);
+        self.winit.android_device_ids[idx] = Some(android_device_id);
+        self.winit.android_remotes[idx] =
+            class == crate::backend_android_winit::DeviceClass::Remote;
+        match crate::backend_android_winit::axis_update_ranges(android_device_id) {
+            Ok(ranges) => self.winit.axis_update_ranges[idx] = ranges,
+            Err(error) => logging::warning!("Failed to read motion ranges: {error}"),
+        }
+        match crate::backend_android_winit::device_info(android_device_id) {
+            Ok(info) => self.winit.android_devices[idx] = info,
+            Err(error) => logging::warning!("Failed to read input device: {error}"),
+        }
+        self.gamepads[idx].set_connected(true);
+        Some(idx)
+    }
+
+    /// Map the back and menu keys of gamepads to [Button::Mode](crate::Button::Mode) and
+    /// [Button::RightCenterCluster](crate::Button::RightCenterCluster), as many Bluetooth
+    /// gamepads report their center buttons as these keys, which are otherwise left to the app.
+    ///
+    /// While enabled, [Gamepads::on_winit_event()](crate::Gamepads::on_winit_event) reports
+    /// these key events as handled, so that the app can skip navigating back on them.
+    pub fn set_back_and_menu_keys(&mut self, enabled: bool) {
+        self.winit.back_menu_keys = enabled;
+    }
+
+    /// What an Android input device is if used as a gamepad, remembering those which are not,
+    /// so that keyboards, touchscreens and remotes unless enabled do not take the slots of gamepads.
+    fn android_device_class(
+        &mut self,
+        android_device_id: i32,
+    ) -> Option<crate::backend_android_winit::DeviceClass> {
+        use crate::backend_android_winit::DeviceClass;
+        if self.winit.ignored_device_ids.contains(&android_device_id) {
+            return None;
+        }
+        match crate::backend_android_winit::device_class(android_device_id) {
+            Ok(Some(DeviceClass::GameController)) => Some(DeviceClass::GameController),
+            Ok(Some(DeviceClass::Remote)) if self.winit.tv_remotes => Some(DeviceClass::Remote),
+            Ok(_) => {
+                logging::trace!("Ignoring input device {android_device_id}, not a game controller");
+                self.winit.ignored_device_ids.push(android_device_id);
+                None
+            }
+            Err(error) => {
+                logging::warning!("Failed to read sources of input device: {error}");
+                None
+            }
+        }
+    }
+
+    /// Use the remotes of Android TV devices as gamepads, with their D-pad as the D-pad, their
+    /// center and back buttons as the lowermost and rightmost action buttons, and their menu
+    /// button as start, so that simple games can be navigated without a gamepad.
+    ///
+    /// Remotes are ignored by default, as they are also used for navigating the system.
+    /// Their back key is reported as handled by [Gamepads::on_winit_event()](crate::Gamepads::on_winit_event)
+    /// while enabled.
+    pub fn set_tv_remotes(&mut self, enabled: bool) {
+        self.winit.tv_remotes = enabled;
+        self.winit.ignored_device_ids.clear();
+        if !enabled {
+            for idx in 0..MAX_GAMEPADS {
+                if let Some(device_id) =
+                    self.winit.gamepad_ids[idx].filter(|_| self.winit.android_remotes[idx])
+                {
+                    self.remove_winit_device(device_id);
+                }
+            }
+        }
+        // Connect remotes on the next poll:
+        self.winit.enumerated_changes = None;
+    }
+
+    pub(crate) fn poll_winit(&mut self) {
+        self.winit.just_polled = true;
+        self.enumerate_android_devices();
+    }
+
+    /// Connect the gamepads, joysticks and enabled remotes among the input devices of Android
+    /// before they send any input, and disconnect those which have been removed.
+    ///
+    /// Input devices are only enumerated again once the registered listener reports a change.
+    fn enumerate_android_devices(&mut self) {
+        let changes = crate::backend_android_winit::DEVICE_CHANGES.load(Ordering::Relaxed);
+        if self.winit.enumerated_changes == Some(changes) {
+            return;
+        }
+        self.winit.enumerated_changes = Some(changes);
+        let connected = match crate::backend_android_winit::connected_devices() {
+            Ok(connected) => connected,
+            Err(error) => {
+                logging::warning!("Failed to enumerate input devices: {error}");
+                return;
+            }
+        };
+        for idx in 0..MAX_GAMEPADS {
+            if let (Some(device_id), Some(android_device_id)) = (
+                self.winit.gamepad_ids[idx],
+                self.winit.android_device_ids[idx],
+            ) {
+                if !connected.iter().any(|&(id, _)| id == android_device_id) {
+                    self.remove_winit_device(device_id);
+                }
+            }
+        }
+        for (android_device_id, _) in connected {
+            self.find_or_insert(DeviceId::from_android_device_id(android_device_id));
+        }
+    }
+}
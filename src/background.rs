@@ -0,0 +1,153 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::JoinHandle;
+
+use crate::backend_gilrs;
+use crate::event::RawTimestamps;
+use crate::{Gamepad, Gamepads, MAX_GAMEPADS};
+
+// The gilrs backend is moved to the background thread, which its platform state must allow:
+const _: fn() = || {
+    const fn assert_send<T: Send>() {}
+    assert_send::<backend_gilrs::State>();
+};
+
+/// State shared with the background thread.
+struct SharedState {
+    /// The gilrs backend, moved here from [Gamepads] while polling in the background.
+    gilrs: backend_gilrs::State,
+    /// Raw state accumulated by the background thread since last taken.
+    gamepads: [Gamepad; MAX_GAMEPADS],
+    raw_timestamps: [RawTimestamps; MAX_GAMEPADS],
+}
+
+/// Handle to a thread polling the backend in the background.
+pub struct BackgroundPoller {
+    state: Arc<Mutex<SharedState>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl BackgroundPoller {
+    fn run(
+        rate_hz: u32,
+        mut gamepads: [Gamepad; MAX_GAMEPADS],
+        state: &Mutex<SharedState>,
+        stop: &AtomicBool,
+    ) {
+        let interval = std::time::Duration::from_secs(1) / rate_hz.max(1);
+        let mut raw_timestamps = [RawTimestamps::default(); MAX_GAMEPADS];
+        while !stop.load(Ordering::Relaxed) {
+            if let Ok(mut state) = state.lock() {
+                let state = &mut *state;
+                state.gilrs.poll(&mut gamepads, &mut raw_timestamps);
+                for (accumulated, raw) in state.gamepads.iter_mut().zip(gamepads.iter()) {
+                    // Keep presses not yet taken by Gamepads::poll():
+                    let just_pressed_bits = accumulated.just_pressed_bits | raw.just_pressed_bits;
+                    let high_just_pressed_bits =
+                        accumulated.high_just_pressed_bits | raw.high_just_pressed_bits;
+                    *accumulated = *raw;
+                    accumulated.just_pressed_bits = just_pressed_bits;
+                    accumulated.high_just_pressed_bits = high_just_pressed_bits;
+                }
+                for (accumulated, raw) in state
+                    .raw_timestamps
+                    .iter_mut()
+                    .zip(raw_timestamps.iter_mut())
+                {
+                    accumulated.merge(raw);
+                    *raw = RawTimestamps::default();
+                }
+            }
+
+            // Woken up early when stopped:
+            std::thread::park_timeout(interval);
+        }
+    }
+
+    /// Take the raw state accumulated since the last call.
+    pub fn take_state(
+        &self,
+        gamepads: &mut [Gamepad; MAX_GAMEPADS],
+        raw_timestamps: &mut [RawTimestamps; MAX_GAMEPADS],
+    ) {
+        if let Ok(mut state) = self.state.lock() {
+            *gamepads = state.gamepads;
+            for gamepad in state.gamepads.iter_mut() {
+                gamepad.just_pressed_bits = 0;
+                gamepad.high_just_pressed_bits = 0;
+            }
+            *raw_timestamps = std::mem::take(&mut state.raw_timestamps);
+        }
+    }
+
+    /// Use the gilrs backend state while it is shared with the background thread.
+    pub fn with_gilrs<T>(&self, f: impl FnOnce(&mut backend_gilrs::State) -> T) -> T {
+        f(&mut self
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .gilrs)
+    }
+
+    /// Stop the background thread, waiting for it to finish its current poll.
+    fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            if thread.join().is_err() {
+                crate::logging::warning!("The background polling thread panicked");
+            }
+        }
+    }
+}
+
+impl Drop for BackgroundPoller {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Gamepads {
+    /// Poll gamepads on a background thread at the given rate.
+    ///
+    /// When enabled, [Gamepads::poll()] takes the state accumulated by the background thread
+    /// instead of polling the backend itself. Button presses happening between two calls to
+    /// `poll()` are never missed, and the state is at most `1 / rate_hz` seconds old when taken.
+    /// This is useful for reducing input latency when running at a low frame rate.
+    ///
+    /// The gilrs instance is used by the background thread while enabled, so that
+    /// [Gamepads::gilrs()] returns `None` until [Gamepads::disable_background_polling()].
+    ///
+    /// Background polling is only available on desktop platforms, and is disabled by default.
+    pub fn enable_background_polling(&mut self, rate_hz: u32) {
+        self.disable_background_polling();
+        let state = Arc::new(Mutex::new(SharedState {
+            gilrs: std::mem::take(&mut self.gilrs),
+            gamepads: self.gamepads,
+            raw_timestamps: [RawTimestamps::default(); MAX_GAMEPADS],
+        }));
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread = {
+            let state = state.clone();
+            let stop = stop.clone();
+            let gamepads = self.gamepads;
+            std::thread::spawn(move || {
+                BackgroundPoller::run(rate_hz, gamepads, &state, &stop);
+            })
+        };
+        self.background_poller = Some(BackgroundPoller {
+            state,
+            stop,
+            thread: Some(thread),
+        });
+    }
+
+    /// Stop polling gamepads on a background thread, as started by [Gamepads::enable_background_polling()].
+    pub fn disable_background_polling(&mut self) {
+        if let Some(mut background_poller) = self.background_poller.take() {
+            background_poller.stop();
+            self.gilrs = background_poller.with_gilrs(std::mem::take);
+        }
+    }
+}
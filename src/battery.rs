@@ -0,0 +1,62 @@
+use crate::GamepadId;
+
+/// Whether the battery of a gamepad is charging, see [Battery].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum BatteryStatus {
+    /// The battery is present, but its status is not known.
+    #[default]
+    Unknown,
+    /// The battery is being charged.
+    Charging,
+    /// The gamepad is running on its battery.
+    Discharging,
+    /// The gamepad is plugged in, but the battery is not being charged.
+    NotCharging,
+    /// The battery is fully charged.
+    Full,
+}
+
+/// The battery of a wireless gamepad, as reported by [Gamepads::battery()](crate::Gamepads::battery).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Battery {
+    /// Whether the battery is charging.
+    pub status: BatteryStatus,
+    /// The charge of the battery in the range `[0.0, 1.0]`, if known.
+    pub level: Option<f32>,
+}
+
+impl crate::Gamepads {
+    /// The battery of a connected gamepad, or `None` for wired gamepads and when not known.
+    ///
+    /// Batteries are reported on Android, and by the gilrs and paddleboat backends, so that games
+    /// can show the charge of controllers. On Android this queries the system on each call, so
+    /// call it when showing the charge rather than on every frame.
+    pub fn battery(&self, gamepad_id: GamepadId) -> Option<Battery> {
+        if let Some(backend) = &self.custom_backend {
+            return backend.battery(gamepad_id);
+        }
+        #[cfg(backend = "android_winit")]
+        {
+            let android_device_id = self.winit.android_device_id(gamepad_id)?;
+            crate::backend_android_winit::battery(android_device_id)
+                .inspect_err(|error| {
+                    crate::logging::warning!("Failed to read battery state: {error}");
+                })
+                .ok()
+                .flatten()
+        }
+        #[cfg(backend = "gilrs")]
+        {
+            self.battery_gilrs(gamepad_id)
+        }
+        #[cfg(backend = "paddleboat")]
+        {
+            self.paddleboat.battery(gamepad_id)
+        }
+        #[cfg(not(any(backend = "android_winit", backend = "gilrs", backend = "paddleboat")))]
+        {
+            None
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{Button, Gamepad, GamepadId, MAX_GAMEPADS};
+
+/// How far the left thumbstick needs to be moved to count as a [Direction] in a combo.
+const DIRECTION_STICK_THRESHOLD: f32 = 0.5;
+
+/// One of eight directions, as input using the D-pad or the left thumbstick.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// Up.
+    Up,
+    /// Diagonally up and to the right.
+    UpRight,
+    /// Right.
+    Right,
+    /// Diagonally down and to the right.
+    DownRight,
+    /// Down.
+    Down,
+    /// Diagonally down and to the left.
+    DownLeft,
+    /// Left.
+    Left,
+    /// Diagonally up and to the left.
+    UpLeft,
+}
+
+impl Direction {
+    /// The direction currently held on a gamepad, using the D-pad if pressed and otherwise the left thumbstick.
+    fn of(gamepad: &Gamepad) -> Option<Self> {
+        let (mut x, mut y) = (0, 0);
+        if gamepad.is_currently_pressed(Button::DPadLeft) {
+            x -= 1;
+        }
+        if gamepad.is_currently_pressed(Button::DPadRight) {
+            x += 1;
+        }
+        if gamepad.is_currently_pressed(Button::DPadDown) {
+            y -= 1;
+        }
+        if gamepad.is_currently_pressed(Button::DPadUp) {
+            y += 1;
+        }
+        if (x, y) == (0, 0) {
+            let (stick_x, stick_y) = gamepad.left_stick();
+            x = i32::from(stick_x >= DIRECTION_STICK_THRESHOLD)
+                - i32::from(stick_x <= -DIRECTION_STICK_THRESHOLD);
+            y = i32::from(stick_y >= DIRECTION_STICK_THRESHOLD)
+                - i32::from(stick_y <= -DIRECTION_STICK_THRESHOLD);
+        }
+        Some(match (x, y) {
+            (0, 1) => Self::Up,
+            (1, 1) => Self::UpRight,
+            (1, 0) => Self::Right,
+            (1, -1) => Self::DownRight,
+            (0, -1) => Self::Down,
+            (-1, -1) => Self::DownLeft,
+            (-1, 0) => Self::Left,
+            (-1, 1) => Self::UpLeft,
+            _ => return None,
+        })
+    }
+}
+
+/// A step in a [Combo].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ComboStep {
+    /// A button being pressed.
+    Press(Button),
+    /// A direction being entered, using the D-pad or the left thumbstick.
+    Direction(Direction),
+}
+
+/// A sequence of inputs to recognize, such as a quarter-circle-forward followed by a punch.
+///
+/// Register a combo using [Gamepads::add_combo()](crate::Gamepads::add_combo), and check for
+/// completed combos after each poll using [Gamepads::completed_combos()](crate::Gamepads::completed_combos).
+///
+/// Inputs in between the steps of a combo are ignored, but the combo is reset if the time between
+/// two steps exceeds the maximum step interval, or if the gamepad is disconnected.
+///
+/// ```
+/// use gamepads::{Button, Combo, ComboStep, Direction, Gamepads};
+///
+/// let mut gamepads = Gamepads::new();
+/// let hadouken = gamepads.add_combo(Combo::new(
+///     [
+///         ComboStep::Direction(Direction::Down),
+///         ComboStep::Direction(Direction::DownRight),
+///         ComboStep::Direction(Direction::Right),
+///         ComboStep::Press(Button::ActionLeft),
+///     ],
+///     200,
+/// ));
+///
+/// loop {
+///     # break;
+///     gamepads.poll();
+///     for gamepad in gamepads.all() {
+///         if gamepads.completed_combos(gamepad.id()).any(|combo| combo == hadouken) {
+///             println!("Hadouken!");
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Combo {
+    steps: Vec<ComboStep>,
+    max_step_interval_ms: u32,
+    /// Index of the next step to match for each gamepad.
+    next_step: [usize; MAX_GAMEPADS],
+    /// When the last step was matched for each gamepad.
+    last_step_ms: [f64; MAX_GAMEPADS],
+    /// Whether the combo was completed in the last poll for each gamepad.
+    completed: [bool; MAX_GAMEPADS],
+}
+
+impl Combo {
+    /// Create a combo from a sequence of steps, where at most `max_step_interval_ms`
+    /// may elapse between two steps.
+    pub fn new(steps: impl IntoIterator<Item = ComboStep>, max_step_interval_ms: u32) -> Self {
+        Self {
+            steps: steps.into_iter().collect(),
+            max_step_interval_ms,
+            next_step: [0; MAX_GAMEPADS],
+            last_step_ms: [0.; MAX_GAMEPADS],
+            completed: [false; MAX_GAMEPADS],
+        }
+    }
+
+    fn advance(&mut self, gamepad_idx: usize, now_ms: f64, input: ComboStep) {
+        if self.next_step[gamepad_idx] > 0
+            && now_ms - self.last_step_ms[gamepad_idx] > f64::from(self.max_step_interval_ms)
+        {
+            self.next_step[gamepad_idx] = 0;
+        }
+
+        // Other inputs are ignored, keeping the progress, while repeating the last matched step
+        // restarts the step interval, so that [A, A, B] is recognized from the inputs A, A, A, B:
+        let next_step = self.next_step[gamepad_idx];
+        if self.steps.get(next_step) == Some(&input) {
+            self.next_step[gamepad_idx] += 1;
+        } else if next_step == 0 || self.steps.get(next_step - 1) != Some(&input) {
+            return;
+        }
+        self.last_step_ms[gamepad_idx] = now_ms;
+
+        if self.next_step[gamepad_idx] == self.steps.len() {
+            self.next_step[gamepad_idx] = 0;
+            self.completed[gamepad_idx] = true;
+        }
+    }
+}
+
+/// An opaque identifier of a combo registered using [Gamepads::add_combo()](crate::Gamepads::add_combo).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ComboId(usize);
+
+/// Registered combos and the state needed to recognize them.
+#[derive(Default)]
+pub struct Combos {
+    combos: Vec<Combo>,
+    previous_directions: [Option<Direction>; MAX_GAMEPADS],
+}
+
+impl crate::Gamepads {
+    /// Register a combo to recognize on all gamepads.
+    ///
+    /// See [Combo] for more information.
+    pub fn add_combo(&mut self, combo: Combo) -> ComboId {
+        self.combos.combos.push(combo);
+        ComboId(self.combos.combos.len() - 1)
+    }
+
+    /// The combos completed on a gamepad in the last [Gamepads::poll()].
+    pub fn completed_combos(&self, gamepad_id: GamepadId) -> impl Iterator<Item = ComboId> + '_ {
+        self.combos
+            .combos
+            .iter()
+            .enumerate()
+            .filter(move |(_, combo)| combo.completed[gamepad_id.0 as usize])
+            .map(|(idx, _)| ComboId(idx))
+    }
+
+    /// Advance combos using the processed gamepad state.
+    pub(crate) fn recognize_combos(&mut self, now_ms: f64) {
+        if self.combos.combos.is_empty() {
+            return;
+        }
+
+        for ((gamepad_idx, gamepad), processing) in self
+            .processed
+            .iter()
+            .enumerate()
+            .zip(self.processing.iter())
+        {
+            let direction = Direction::of(gamepad);
+            let direction_changed = direction != self.combos.previous_directions[gamepad_idx];
+            self.combos.previous_directions[gamepad_idx] = direction;
+
+            for combo in &mut self.combos.combos {
+                combo.completed[gamepad_idx] = false;
+                if !gamepad.connected {
+                    combo.next_step[gamepad_idx] = 0;
+                    continue;
+                }
+                if let Some(direction) = direction.filter(|_| direction_changed) {
+                    combo.advance(gamepad_idx, now_ms, ComboStep::Direction(direction));
+                }
+                for button in gamepad
+                    .all_just_pressed()
+                    .filter(|&button| processing.carried_bits() & (1 << (button as u32)) == 0)
+                {
+                    combo.advance(gamepad_idx, now_ms, ComboStep::Press(button));
+                }
+            }
+        }
+    }
+}
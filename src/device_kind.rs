@@ -0,0 +1,153 @@
+use crate::{GamepadId, Remap};
+use gamepads_core::MAX_GAMEPADS;
+
+/// The kind of a game controller, as detected from its name by
+/// [Gamepads::device_kind()](crate::Gamepads::device_kind).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DeviceKind {
+    /// A gamepad, or a device of unknown kind.
+    #[default]
+    Gamepad,
+    /// A racing wheel, possibly with its own pedals.
+    Wheel,
+    /// A flight stick, possibly with its own throttle.
+    FlightStick,
+    /// Separate pedals, such as those of a wheel or the rudder pedals of a flight stick.
+    Pedals,
+    /// A separate throttle or throttle quadrant of a flight stick.
+    Throttle,
+    /// A dance pad, remapped by default using [Remap::dance_pad()].
+    DancePad,
+    /// An arcade stick or all-button controller for fighting games, remapped by default using
+    /// [Remap::arcade_stick()].
+    ArcadeStick,
+    /// A guitar controller of Guitar Hero or Rock Band, read using
+    /// [Gamepads::guitar()](crate::Gamepads::guitar).
+    Guitar,
+    /// A drum kit controller of Guitar Hero or Rock Band, read using
+    /// [Gamepads::drums()](crate::Gamepads::drums).
+    Drums,
+}
+
+impl DeviceKind {
+    /// The kind of a device identified by its name, such as "Saitek Pro Flight Rudder Pedals"
+    /// or "Thrustmaster T.Flight Hotas X".
+    fn from_name(name: &str) -> Self {
+        let name = name.to_ascii_lowercase();
+        let has_any = |words: &[&str]| words.iter().any(|word| name.contains(word));
+        if has_any(&["pedal", "rudder"]) {
+            Self::Pedals
+        } else if has_any(&["throttle", "quadrant"]) {
+            Self::Throttle
+        } else if has_any(&["wheel", "racing", "driving force"]) {
+            Self::Wheel
+        } else if has_any(&["hotas", "flight", "x52", "x56", "t.16000m", "warthog"]) {
+            Self::FlightStick
+        } else if has_any(&["guitar", "x-plorer", "stratocaster", "les paul"]) {
+            Self::Guitar
+        } else if has_any(&["drum"]) {
+            Self::Drums
+        } else if has_any(&["dance", "ddr", "step pad", "pump it up"]) {
+            Self::DancePad
+        } else if has_any(&[
+            "fight stick",
+            "fightstick",
+            "fighting stick",
+            "arcade",
+            "hitbox",
+            "hit box",
+            "qanba",
+        ]) {
+            Self::ArcadeStick
+        } else {
+            Self::Gamepad
+        }
+    }
+
+    /// The remapping applied by default to devices of this kind, if any.
+    pub const fn profile(self) -> Option<Remap> {
+        match self {
+            Self::DancePad => Some(Remap::dance_pad()),
+            Self::ArcadeStick => Some(Remap::arcade_stick()),
+            _ => None,
+        }
+    }
+}
+
+/// The devices of which the kind has been detected since they connected, and whether the
+/// profile of their kind has been applied to them.
+pub struct Profiles {
+    enabled: bool,
+    detected: [bool; MAX_GAMEPADS],
+    applied: [Option<Remap>; MAX_GAMEPADS],
+}
+
+impl Default for Profiles {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            detected: [false; MAX_GAMEPADS],
+            applied: [None; MAX_GAMEPADS],
+        }
+    }
+}
+
+impl crate::Gamepads {
+    /// The kind of a connected game controller, detected from its
+    /// [Gamepads::name()](crate::Gamepads::name), or [DeviceKind::Gamepad] if not known.
+    ///
+    /// Useful for recognizing the separate pedals and throttles of sim setups, which can be
+    /// attached to their wheel or flight stick using
+    /// [Gamepads::attach_unit()](crate::Gamepads::attach_unit), for showing the layout of
+    /// dance pads and arcade sticks, or for reading instruments.
+    pub fn device_kind(&self, gamepad_id: GamepadId) -> DeviceKind {
+        self.name(gamepad_id)
+            .map_or(DeviceKind::Gamepad, |name| DeviceKind::from_name(&name))
+    }
+
+    /// Set whether the [DeviceKind::profile()] of a device is applied as its remapping when it
+    /// connects, unless it has been remapped using [Gamepads::set_remap()](crate::Gamepads::set_remap).
+    ///
+    /// Profiles are applied by default, so that dance pads and arcade sticks work with games
+    /// expecting a gamepad with the standard layout.
+    pub fn set_device_profiles(&mut self, enabled: bool) {
+        self.profiles.enabled = enabled;
+        if !enabled {
+            for idx in 0..MAX_GAMEPADS {
+                self.clear_profile(idx);
+            }
+        }
+        self.profiles.detected = [false; MAX_GAMEPADS];
+    }
+
+    /// Apply the profile of devices which have connected, and remove it from devices which have
+    /// disconnected.
+    pub(crate) fn apply_device_profiles(&mut self) {
+        for idx in 0..MAX_GAMEPADS {
+            if !self.gamepads[idx].connected {
+                self.clear_profile(idx);
+                self.profiles.detected[idx] = false;
+            } else if !self.profiles.detected[idx] {
+                self.profiles.detected[idx] = true;
+                let gamepad_id = GamepadId(idx as u8);
+                let profile = self.device_kind(gamepad_id).profile();
+                if let Some(profile) = profile.filter(|_| self.profiles.enabled) {
+                    if *self.processing[idx].remap() == Remap::new() {
+                        self.set_remap(gamepad_id, profile);
+                        self.profiles.applied[idx] = Some(profile);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Remove the profile applied to a device, unless remapped since.
+    fn clear_profile(&mut self, idx: usize) {
+        if let Some(profile) = self.profiles.applied[idx].take() {
+            if *self.processing[idx].remap() == profile {
+                self.set_remap(GamepadId(idx as u8), Remap::new());
+            }
+        }
+    }
+}
@@ -0,0 +1,40 @@
+/// An error constructing a [Gamepads](crate::Gamepads) instance using
+/// [Gamepads::try_new()](crate::Gamepads::try_new).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum GamepadsError {
+    /// Gamepads are not supported by the backend on this platform.
+    Unsupported,
+    /// The input subsystem of the platform could not be initialized, such as when
+    /// running in a container or on a headless server without udev.
+    BackendInit(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl std::fmt::Display for GamepadsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unsupported => write!(f, "gamepads are not supported on this platform"),
+            Self::BackendInit(error) => write!(f, "failed to initialize gamepad backend: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for GamepadsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::BackendInit(error) => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(backend = "gilrs")]
+impl From<gilrs::Error> for GamepadsError {
+    fn from(error: gilrs::Error) -> Self {
+        match error {
+            gilrs::Error::NotImplemented(_) => Self::Unsupported,
+            gilrs::Error::Other(error) => Self::BackendInit(error),
+            error @ gilrs::Error::InvalidAxisToBtn => Self::BackendInit(error.to_string().into()),
+        }
+    }
+}
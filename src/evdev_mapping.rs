@@ -0,0 +1,130 @@
+//! Mapping of the event codes of Linux input devices to the standard layout.
+//!
+//! Kernel drivers of common gamepads report buttons and axes as described by the
+//! [Linux gamepad specification](https://docs.kernel.org/input/gamepad.html), which map directly
+//! to [Button] and the standard axes. Buttons beyond those have no common meaning, so they are
+//! looked up for known devices by their vendor and product id.
+
+// The gilrs backend only looks up extra buttons:
+#![cfg_attr(not(backend = "evdev"), allow(dead_code))]
+
+use crate::Button;
+
+/// Event types.
+pub const EV_SYN: u16 = 0x00;
+pub const EV_KEY: u16 = 0x01;
+pub const EV_ABS: u16 = 0x03;
+pub const EV_FF: u16 = 0x15;
+
+/// The `EV_SYN` event code telling that events were dropped, so state needs to be read again.
+pub const SYN_DROPPED: u16 = 0x03;
+
+/// The first buttons of joysticks and of gamepads, where joystick buttons are numbered in the
+/// order of the HID report, followed by those from [BTN_TRIGGER_HAPPY1].
+pub const BTN_JOYSTICK: u16 = 0x120;
+pub const BTN_GAMEPAD: u16 = 0x130;
+pub const BTN_TRIGGER_HAPPY1: u16 = 0x2c0;
+
+/// Axes, of which those up to `ABS_WHEEL` are in the order of the HID usages they are mapped from.
+pub const ABS_X: u16 = 0x00;
+pub const ABS_Y: u16 = 0x01;
+pub const ABS_Z: u16 = 0x02;
+pub const ABS_RX: u16 = 0x03;
+pub const ABS_RY: u16 = 0x04;
+pub const ABS_RZ: u16 = 0x05;
+pub const ABS_WHEEL: u16 = 0x08;
+pub const ABS_BRAKE: u16 = 0x0a;
+pub const ABS_HAT0X: u16 = 0x10;
+pub const ABS_HAT0Y: u16 = 0x11;
+/// Analog lower triggers as named by the gamepad specification, `ABS_HI2X` and `ABS_HI2Y`.
+pub const ABS_HAT2X: u16 = 0x14;
+pub const ABS_HAT2Y: u16 = 0x15;
+pub const ABS_HAT3Y: u16 = 0x17;
+
+/// The force feedback effect type of rumble motors.
+pub const FF_RUMBLE: u16 = 0x50;
+/// The force feedback effect types of racing wheels.
+pub const FF_CONSTANT: u16 = 0x52;
+pub const FF_SPRING: u16 = 0x53;
+pub const FF_FRICTION: u16 = 0x54;
+pub const FF_DAMPER: u16 = 0x55;
+
+/// The buttons of the gamepad specification.
+pub const BUTTONS: [(u16, Button); 17] = [
+    (BTN_GAMEPAD, Button::ActionDown),
+    (0x131, Button::ActionRight),
+    (0x133, Button::ActionUp),
+    (0x134, Button::ActionLeft),
+    (0x136, Button::FrontLeftUpper),
+    (0x137, Button::FrontRightUpper),
+    (0x138, Button::FrontLeftLower),
+    (0x139, Button::FrontRightLower),
+    (0x13a, Button::LeftCenterCluster),
+    (0x13b, Button::RightCenterCluster),
+    (0x13c, Button::Mode),
+    (0x13d, Button::LeftStick),
+    (0x13e, Button::RightStick),
+    (0x220, Button::DPadUp),
+    (0x221, Button::DPadDown),
+    (0x222, Button::DPadLeft),
+    (0x223, Button::DPadRight),
+];
+
+/// Devices whose buttons are mapped by the generic HID driver in the order of the HID report,
+/// given as the vendor and product id, which puts the face buttons of their Nintendo style
+/// layout at the codes of the opposite positions.
+const SWAPPED_FACE_BUTTONS: &[(u16, u16)] = &[
+    // 8BitDo gamepads in D-input mode:
+    (0x2dc8, 0x3100),
+    (0x2dc8, 0x3820),
+    (0x2dc8, 0x6001),
+    (0x2dc8, 0x6002),
+    (0x2dc8, 0x6101),
+    (0x2dc8, 0x9000),
+];
+
+/// The buttons of the gamepad specification as reported by a device, with the quirks of known
+/// devices applied.
+#[cfg(backend = "evdev")]
+pub fn buttons(vendor: u16, product: u16) -> impl Iterator<Item = (u16, Button)> {
+    let swapped = SWAPPED_FACE_BUTTONS.contains(&(vendor, product));
+    BUTTONS.into_iter().map(move |(code, button)| {
+        let button = match button {
+            Button::ActionDown if swapped => Button::ActionRight,
+            Button::ActionRight if swapped => Button::ActionDown,
+            Button::ActionUp if swapped => Button::ActionLeft,
+            Button::ActionLeft if swapped => Button::ActionUp,
+            _ => button,
+        };
+        (code, button)
+    })
+}
+
+/// Buttons beyond the gamepad specification, given as the vendor and product id of the device,
+/// the `EV_KEY` event code and the extra button index it is reported as on the web.
+const EXTRA_BUTTONS: &[(u16, u16, u16, u8)] = &[
+    // Google Stadia controller, Assistant and Capture buttons:
+    (0x18d1, 0x9400, BTN_TRIGGER_HAPPY1 + 1, 17),
+    (0x18d1, 0x9400, BTN_TRIGGER_HAPPY1, 18),
+    // Amazon Luna controller, microphone button:
+    (0x1949, 0x0419, BTN_TRIGGER_HAPPY1, 17),
+];
+
+/// The index beyond [Button::Mode] of an `EV_KEY` event code of a known device, as checked by
+/// [Gamepad::is_extra_button_pressed()](crate::Gamepad::is_extra_button_pressed).
+#[cfg(backend = "gilrs")]
+pub fn extra_button_index(vendor: u16, product: u16, code: u16) -> Option<u8> {
+    EXTRA_BUTTONS
+        .iter()
+        .find(|&&(v, p, key, _)| v == vendor && p == product && key == code)
+        .map(|&(_, _, _, index)| index)
+}
+
+/// The extra buttons of a known device, as event codes and extra button indices.
+#[cfg(backend = "evdev")]
+pub fn extra_buttons(vendor: u16, product: u16) -> impl Iterator<Item = (u16, u8)> {
+    EXTRA_BUTTONS
+        .iter()
+        .filter(move |&&(v, p, _, _)| v == vendor && p == product)
+        .map(|&(_, _, key, index)| (key, index))
+}
@@ -0,0 +1,356 @@
+use crate::{Button, Gamepad, GamepadId, Remap};
+
+/// A change in gamepad state, as detected by [Gamepads::poll()](crate::Gamepads::poll).
+///
+/// Subscribe to events using [Gamepads::on_event()](crate::Gamepads::on_event).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Event {
+    /// A gamepad was connected.
+    Connected { gamepad_id: GamepadId },
+    /// A gamepad was disconnected.
+    Disconnected { gamepad_id: GamepadId },
+    /// A button was pressed.
+    ButtonPressed {
+        gamepad_id: GamepadId,
+        button: Button,
+    },
+    /// A button was released.
+    ButtonReleased {
+        gamepad_id: GamepadId,
+        button: Button,
+    },
+    /// The left thumbstick was moved to a new `(x, y)` position.
+    LeftStickMoved {
+        gamepad_id: GamepadId,
+        position: (f32, f32),
+    },
+    /// The right thumbstick was moved to a new `(x, y)` position.
+    RightStickMoved {
+        gamepad_id: GamepadId,
+        position: (f32, f32),
+    },
+}
+
+/// An [Event] together with when it happened.
+///
+/// Obtained using [Gamepads::events()](crate::Gamepads::events).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TimestampedEvent {
+    /// The event that happened.
+    pub event: Event,
+    /// When the event happened, in milliseconds on the same monotonic clock as
+    /// [Gamepads::last_poll_ms()](crate::Gamepads::last_poll_ms).
+    ///
+    /// This is when the backend observed the change if known, which may be earlier than the poll
+    /// in which the event was reported. Otherwise it is the time of that poll.
+    pub timestamp_ms: f64,
+}
+
+/// When the backend observed raw changes to a gamepad since the last poll.
+#[derive(Clone, Copy)]
+pub struct RawTimestamps {
+    /// Bits of buttons with a recorded press.
+    pressed_bits: u32,
+    pressed_ms: [f64; 32],
+    /// Bits of buttons with a recorded release.
+    released_bits: u32,
+    released_ms: [f64; 32],
+    axes_ms: Option<f64>,
+}
+
+impl Default for RawTimestamps {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RawTimestamps {
+    pub const fn new() -> Self {
+        Self {
+            pressed_bits: 0,
+            pressed_ms: [0.; 32],
+            released_bits: 0,
+            released_ms: [0.; 32],
+            axes_ms: None,
+        }
+    }
+
+    /// The earliest recorded time of a physical button mapped to the given logical button.
+    fn lookup(
+        bits: u32,
+        times_ms: &[f64; 32],
+        remap: &Remap,
+        button: Button,
+        default_ms: f64,
+    ) -> f64 {
+        times_ms
+            .iter()
+            .enumerate()
+            .filter(|&(bit_idx, _)| {
+                bits & (1 << bit_idx) != 0 && remap.targets(bit_idx) & (1 << (button as u32)) != 0
+            })
+            .map(|(_, &time_ms)| time_ms)
+            .reduce(f64::min)
+            .unwrap_or(default_ms)
+    }
+}
+
+// Not every backend records every kind of timestamp:
+#[allow(dead_code)]
+impl RawTimestamps {
+    /// Record that a button was pressed, keeping the first press since the last poll.
+    pub const fn record_press(&mut self, bit_idx: usize, time_ms: f64) {
+        if self.pressed_bits & (1 << bit_idx) == 0 {
+            self.pressed_bits |= 1 << bit_idx;
+            self.pressed_ms[bit_idx] = time_ms;
+        }
+    }
+
+    /// Record that a button was released, keeping the last release since the last poll.
+    pub const fn record_release(&mut self, bit_idx: usize, time_ms: f64) {
+        self.released_bits |= 1 << bit_idx;
+        self.released_ms[bit_idx] = time_ms;
+    }
+
+    /// Record that the axes changed, keeping the last change since the last poll.
+    pub const fn record_axes(&mut self, time_ms: f64) {
+        self.axes_ms = Some(time_ms);
+    }
+
+    /// When the axes last changed since the last poll, if they did.
+    pub const fn axes_ms(&self) -> Option<f64> {
+        self.axes_ms
+    }
+
+    /// Merge timestamps recorded later into these.
+    pub fn merge(&mut self, later: &Self) {
+        for bit_idx in 0..32 {
+            if later.pressed_bits & (1 << bit_idx) != 0 {
+                self.record_press(bit_idx, later.pressed_ms[bit_idx]);
+            }
+            if later.released_bits & (1 << bit_idx) != 0 {
+                self.record_release(bit_idx, later.released_ms[bit_idx]);
+            }
+        }
+        if let Some(axes_ms) = later.axes_ms {
+            self.axes_ms = Some(axes_ms);
+        }
+    }
+}
+
+/// The information needed to detect the events of a gamepad in a poll.
+struct Change<'a> {
+    previous: &'a Gamepad,
+    current: &'a Gamepad,
+    /// Bits of buttons just pressed only due to input buffering, which are not reported again.
+    carried_bits: u32,
+    remap: &'a Remap,
+    timestamps: &'a RawTimestamps,
+    now_ms: f64,
+}
+
+impl Change<'_> {
+    /// Push the events describing the change from `previous` to `current` state of a gamepad.
+    fn push_events(&self, events: &mut Vec<TimestampedEvent>) {
+        let (previous, current) = (self.previous, self.current);
+        let gamepad_id = current.id;
+        let mut push = |event, timestamp_ms| {
+            events.push(TimestampedEvent {
+                event,
+                timestamp_ms,
+            });
+        };
+        let press_ms = |button| {
+            RawTimestamps::lookup(
+                self.timestamps.pressed_bits,
+                &self.timestamps.pressed_ms,
+                self.remap,
+                button,
+                self.now_ms,
+            )
+        };
+        let release_ms = |button| {
+            RawTimestamps::lookup(
+                self.timestamps.released_bits,
+                &self.timestamps.released_ms,
+                self.remap,
+                button,
+                self.now_ms,
+            )
+        };
+
+        if current.connected && !previous.connected {
+            push(Event::Connected { gamepad_id }, self.now_ms);
+        }
+
+        for button in Button::all() {
+            let was_pressed = previous.is_currently_pressed(button);
+            let is_pressed = current.is_currently_pressed(button);
+            let carried = self.carried_bits & (1 << (button as u32)) != 0;
+            if current.is_just_pressed(button) && !carried {
+                if was_pressed {
+                    // Pressed again since last poll, without having been observed as released:
+                    push(
+                        Event::ButtonReleased { gamepad_id, button },
+                        release_ms(button),
+                    );
+                }
+                push(
+                    Event::ButtonPressed { gamepad_id, button },
+                    press_ms(button),
+                );
+                if !is_pressed {
+                    // Pressed and released since last poll:
+                    push(
+                        Event::ButtonReleased { gamepad_id, button },
+                        release_ms(button),
+                    );
+                }
+            } else if was_pressed && !is_pressed {
+                push(
+                    Event::ButtonReleased { gamepad_id, button },
+                    release_ms(button),
+                );
+            }
+        }
+
+        let axes_ms = self.timestamps.axes_ms.unwrap_or(self.now_ms);
+        if current.left_stick() != previous.left_stick() {
+            push(
+                Event::LeftStickMoved {
+                    gamepad_id,
+                    position: current.left_stick(),
+                },
+                axes_ms,
+            );
+        }
+        if current.right_stick() != previous.right_stick() {
+            push(
+                Event::RightStickMoved {
+                    gamepad_id,
+                    position: current.right_stick(),
+                },
+                axes_ms,
+            );
+        }
+
+        if previous.connected && !current.connected {
+            push(Event::Disconnected { gamepad_id }, self.now_ms);
+        }
+    }
+}
+
+type Callback = Box<dyn FnMut(&Event) + Send>;
+
+/// Events detected in the last poll, and callbacks subscribed to them.
+#[derive(Default)]
+pub struct Events {
+    events: Vec<TimestampedEvent>,
+    callbacks: Vec<Callback>,
+}
+
+impl Events {
+    /// The events detected in the last poll.
+    pub fn iter(&self) -> impl Iterator<Item = &TimestampedEvent> {
+        self.events.iter()
+    }
+}
+
+impl crate::Gamepads {
+    /// Register a callback to be invoked with each [Event] during [Gamepads::poll()].
+    ///
+    /// ```
+    /// use gamepads::{Event, Gamepads};
+    ///
+    /// let mut gamepads = Gamepads::new();
+    /// gamepads.on_event(|event| {
+    ///     if let Event::ButtonPressed { gamepad_id, button } = event {
+    ///         println!("{button:?} pressed on {gamepad_id:?}");
+    ///     }
+    /// });
+    /// ```
+    pub fn on_event(&mut self, callback: impl FnMut(&Event) + Send + 'static) {
+        self.events.callbacks.push(Box::new(callback));
+    }
+
+    /// Register a callback to be invoked during [Gamepads::poll()] when a gamepad is connected.
+    pub fn on_connected(&mut self, mut callback: impl FnMut(GamepadId) + Send + 'static) {
+        self.on_event(move |event| {
+            if let Event::Connected { gamepad_id } = *event {
+                callback(gamepad_id);
+            }
+        });
+    }
+
+    /// Register a callback to be invoked during [Gamepads::poll()] when a gamepad is disconnected.
+    pub fn on_disconnected(&mut self, mut callback: impl FnMut(GamepadId) + Send + 'static) {
+        self.on_event(move |event| {
+            if let Event::Disconnected { gamepad_id } = *event {
+                callback(gamepad_id);
+            }
+        });
+    }
+
+    /// Register a callback to be invoked during [Gamepads::poll()] when a button is pressed.
+    pub fn on_button_pressed(
+        &mut self,
+        mut callback: impl FnMut(GamepadId, Button) + Send + 'static,
+    ) {
+        self.on_event(move |event| {
+            if let Event::ButtonPressed { gamepad_id, button } = *event {
+                callback(gamepad_id, button);
+            }
+        });
+    }
+
+    /// Register a callback to be invoked during [Gamepads::poll()] when a button is released.
+    pub fn on_button_released(
+        &mut self,
+        mut callback: impl FnMut(GamepadId, Button) + Send + 'static,
+    ) {
+        self.on_event(move |event| {
+            if let Event::ButtonReleased { gamepad_id, button } = *event {
+                callback(gamepad_id, button);
+            }
+        });
+    }
+
+    /// The events detected in the last [Gamepads::poll()], in the order they were detected.
+    ///
+    /// Events are grouped per gamepad, so they are not necessarily ordered by timestamp.
+    pub fn events(&self) -> impl Iterator<Item = TimestampedEvent> + '_ {
+        self.events.iter().copied()
+    }
+
+    /// Detect events from the change in processed state, and invoke callbacks with them.
+    pub(crate) fn dispatch_events(
+        &mut self,
+        previous: &[Gamepad; crate::MAX_GAMEPADS],
+        now_ms: f64,
+    ) {
+        self.events.events.clear();
+        for (((previous, current), processing), timestamps) in previous
+            .iter()
+            .zip(self.processed.iter())
+            .zip(self.processing.iter())
+            .zip(self.raw_timestamps.iter_mut())
+        {
+            Change {
+                previous,
+                current,
+                carried_bits: processing.carried_bits(),
+                remap: processing.remap(),
+                timestamps,
+                now_ms,
+            }
+            .push_events(&mut self.events.events);
+            *timestamps = RawTimestamps::default();
+        }
+
+        for timestamped_event in &self.events.events {
+            for callback in &mut self.events.callbacks {
+                callback(&timestamped_event.event);
+            }
+        }
+    }
+}
@@ -0,0 +1,136 @@
+//! A C ABI for use from other languages, enabled by the `ffi` feature.
+//!
+//! See `include/gamepads.h` for the corresponding header, generated using `make generate-header`.
+
+use crate::{Gamepad, GamepadId, Gamepads};
+
+/// The version of the C ABI, incremented on incompatible changes such as to the layout of
+/// `Gamepad`.
+pub const GAMEPADS_ABI_VERSION: u32 = 1;
+
+/// The ABI version of the library, to compare with `GAMEPADS_ABI_VERSION` of the header when
+/// loading the library dynamically.
+#[no_mangle]
+pub const extern "C" fn gamepads_abi_version() -> u32 {
+    GAMEPADS_ABI_VERSION
+}
+
+/// Whether the `Gamepad` size of the caller, from `sizeof(Gamepad)`, matches that of the library.
+const fn is_gamepad_size(gamepad_size: usize) -> bool {
+    gamepad_size == std::mem::size_of::<Gamepad>()
+}
+
+/// Construct a new gamepads instance, to be freed using `gamepads_free()`.
+#[no_mangle]
+pub extern "C" fn gamepads_new() -> *mut Gamepads {
+    Box::into_raw(Box::new(Gamepads::new()))
+}
+
+/// Free a gamepads instance constructed using `gamepads_new()`.
+///
+/// # Safety
+///
+/// `gamepads` must be null or returned by `gamepads_new()`, and not used after this call.
+#[no_mangle]
+pub unsafe extern "C" fn gamepads_free(gamepads: *mut Gamepads) {
+    if !gamepads.is_null() {
+        drop(Box::from_raw(gamepads));
+    }
+}
+
+/// Poll gamepad state, to be called on each tick.
+///
+/// # Safety
+///
+/// `gamepads` must be null or returned by `gamepads_new()`.
+#[no_mangle]
+pub unsafe extern "C" fn gamepads_poll(gamepads: *mut Gamepads) {
+    if let Some(gamepads) = gamepads.as_mut() {
+        gamepads.poll();
+    }
+}
+
+/// Copy the state of a gamepad by id into `out`, returning whether it is connected.
+///
+/// `gamepad_size` must be `sizeof(Gamepad)`, and nothing is copied if it differs from the
+/// size of `Gamepad` in the library, such as from a header of another version.
+///
+/// # Safety
+///
+/// `gamepads` must be null or returned by `gamepads_new()`, and `out` must be null or
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn gamepads_get(
+    gamepads: *const Gamepads,
+    gamepad_id: u8,
+    out: *mut Gamepad,
+    gamepad_size: usize,
+) -> bool {
+    if !is_gamepad_size(gamepad_size) {
+        return false;
+    }
+    let (Some(gamepads), Some(out)) = (gamepads.as_ref(), out.as_mut()) else {
+        return false;
+    };
+    let gamepad = GamepadId::new(gamepad_id).and_then(|gamepad_id| gamepads.get_ref(gamepad_id));
+    if let Some(gamepad) = gamepad {
+        *out = *gamepad;
+    }
+    gamepad.is_some()
+}
+
+/// Copy the state of connected gamepads into the `out` array of length `capacity`, returning
+/// the number of gamepads copied.
+///
+/// At most 8 gamepads are connected at once, so that is a sufficient capacity. As for
+/// `gamepads_get()`, nothing is copied unless `gamepad_size` is the size of `Gamepad` in the
+/// library.
+///
+/// # Safety
+///
+/// `gamepads` must be null or returned by `gamepads_new()`, and `out` must be null or
+/// valid for writes of `capacity` gamepads.
+#[no_mangle]
+pub unsafe extern "C" fn gamepads_get_all(
+    gamepads: *const Gamepads,
+    out: *mut Gamepad,
+    capacity: usize,
+    gamepad_size: usize,
+) -> usize {
+    let Some(gamepads) = gamepads.as_ref() else {
+        return 0;
+    };
+    if out.is_null() || !is_gamepad_size(gamepad_size) {
+        return 0;
+    }
+    let out = std::slice::from_raw_parts_mut(out, capacity);
+    out.iter_mut()
+        .zip(gamepads.iter())
+        .map(|(out, gamepad)| *out = *gamepad)
+        .count()
+}
+
+/// Rumble a gamepad, see `Gamepads::rumble()` for a description of the arguments.
+///
+/// # Safety
+///
+/// `gamepads` must be null or returned by `gamepads_new()`.
+#[no_mangle]
+pub unsafe extern "C" fn gamepads_rumble(
+    gamepads: *mut Gamepads,
+    gamepad_id: u8,
+    duration_ms: u32,
+    start_delay_ms: u32,
+    strong_magnitude: f32,
+    weak_magnitude: f32,
+) {
+    if let (Some(gamepads), Some(gamepad_id)) = (gamepads.as_mut(), GamepadId::new(gamepad_id)) {
+        gamepads.rumble(
+            gamepad_id,
+            duration_ms,
+            start_delay_ms,
+            strong_magnitude,
+            weak_magnitude,
+        );
+    }
+}
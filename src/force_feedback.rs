@@ -0,0 +1,57 @@
+use crate::GamepadId;
+
+/// A force feedback effect of a racing wheel, played using
+/// [Gamepads::set_force_feedback()](crate::Gamepads::set_force_feedback).
+///
+/// Effects act on the steering axis, and play until replaced by an effect of the same kind or
+/// stopped using [Gamepads::stop_force_feedback()](crate::Gamepads::stop_force_feedback), so
+/// effects of different kinds can be combined, such as a spring centering the wheel together with
+/// the damper of the tires on the road.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ForceFeedback {
+    /// A constant force in the range `[-1.0, 1.0]`, turning the wheel to the right for positive
+    /// values, such as for the pull of the road.
+    Constant { force: f32 },
+    /// A force pulling the wheel towards a position in the range `[-1.0, 1.0]`, stronger the
+    /// further away the wheel is, with a strength in the range `[0.0, 1.0]`.
+    Spring { center: f32, strength: f32 },
+    /// A force against turning the wheel, stronger the faster it is turned, with a strength in
+    /// the range `[0.0, 1.0]`.
+    Damper { strength: f32 },
+    /// A force against turning the wheel regardless of how fast, with a strength in the range
+    /// `[0.0, 1.0]`.
+    Friction { strength: f32 },
+}
+
+impl crate::Gamepads {
+    /// Play a force feedback effect on a racing wheel, replacing the effect of the same kind
+    /// played before, until stopped using [Gamepads::stop_force_feedback()](crate::Gamepads::stop_force_feedback).
+    ///
+    /// Force feedback is played by the `backend-evdev` feature on Linux and by custom backends,
+    /// see [Capabilities::force_feedback](crate::Capabilities::force_feedback). Effects not
+    /// supported by the wheel, and force feedback elsewhere, such as through DirectInput on
+    /// Windows, are ignored.
+    pub fn set_force_feedback(&mut self, gamepad_id: GamepadId, effect: ForceFeedback) {
+        if let Some(backend) = &mut self.custom_backend {
+            backend.set_force_feedback(gamepad_id, effect);
+            return;
+        }
+        #[cfg(backend = "evdev")]
+        crate::backend_evdev::set_force_feedback(&mut self.evdev, gamepad_id, effect);
+        #[cfg(not(backend = "evdev"))]
+        let _ = (gamepad_id, effect);
+    }
+
+    /// Stop all force feedback effects played on a racing wheel using
+    /// [Gamepads::set_force_feedback()](crate::Gamepads::set_force_feedback).
+    pub fn stop_force_feedback(&mut self, gamepad_id: GamepadId) {
+        if let Some(backend) = &mut self.custom_backend {
+            backend.stop_force_feedback(gamepad_id);
+            return;
+        }
+        #[cfg(backend = "evdev")]
+        crate::backend_evdev::stop_force_feedback(&mut self.evdev, gamepad_id);
+        #[cfg(not(backend = "evdev"))]
+        let _ = gamepad_id;
+    }
+}
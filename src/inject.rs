@@ -0,0 +1,93 @@
+use crate::{Event, Gamepad};
+
+/// Synthetic input injected into a gamepad, merged into its real state on each poll.
+#[derive(Clone, Copy, Default)]
+pub struct Injected {
+    connected: bool,
+    pressed_bits: u32,
+    /// Bits pressed since the last poll.
+    just_pressed_bits: u32,
+    axes: [f32; 4],
+}
+
+impl Injected {
+    /// Merge into the real state of a gamepad, consuming presses injected since the last poll.
+    ///
+    /// Injected buttons are pressed in addition to real ones, while each injected thumbstick
+    /// position is used if further from the center than the real one.
+    pub fn take_merged(&mut self, raw: &Gamepad) -> Gamepad {
+        let mut merged = *raw;
+        merged.connected |= self.connected;
+        merged.pressed_bits |= self.pressed_bits;
+        merged.just_pressed_bits |= std::mem::take(&mut self.just_pressed_bits);
+        for stick_idx in [0, 2] {
+            let injected = (self.axes[stick_idx], self.axes[stick_idx + 1]);
+            let real = (raw.axes[stick_idx], raw.axes[stick_idx + 1]);
+            if injected.0.hypot(injected.1) > real.0.hypot(real.1) {
+                merged.axes[stick_idx] = injected.0;
+                merged.axes[stick_idx + 1] = injected.1;
+            }
+        }
+        merged
+    }
+}
+
+impl crate::Gamepads {
+    /// Inject a synthetic event, which is merged into the real gamepad state from the next poll.
+    ///
+    /// Injected input drives the same processing as physical gamepads, which is useful for
+    /// tutorials, attract modes and accessibility tools such as external switch devices:
+    ///
+    /// - [Event::Connected] makes a gamepad appear connected even without hardware.
+    /// - [Event::Disconnected] removes all input injected into a gamepad, without affecting real input.
+    /// - [Event::ButtonPressed] presses a button until a matching [Event::ButtonReleased] is injected.
+    /// - [Event::LeftStickMoved] and [Event::RightStickMoved] set a thumbstick position, used when
+    ///   further from the center than the real position. Inject `(0., 0.)` to stop overriding.
+    ///
+    /// ```
+    /// use gamepads::{Button, Event, Gamepads};
+    ///
+    /// let mut gamepads = Gamepads::new();
+    /// if let Some(gamepad) = gamepads.all().next() {
+    ///     gamepads.inject(Event::ButtonPressed {
+    ///         gamepad_id: gamepad.id(),
+    ///         button: Button::ActionDown,
+    ///     });
+    ///     gamepads.poll();
+    /// }
+    /// ```
+    pub fn inject(&mut self, event: Event) {
+        match event {
+            Event::Connected { gamepad_id } => {
+                self.injected[gamepad_id.0 as usize].connected = true;
+            }
+            Event::Disconnected { gamepad_id } => {
+                self.injected[gamepad_id.0 as usize] = Injected::default();
+            }
+            Event::ButtonPressed { gamepad_id, button } => {
+                let injected = &mut self.injected[gamepad_id.0 as usize];
+                injected.pressed_bits |= 1 << (button as u32);
+                injected.just_pressed_bits |= 1 << (button as u32);
+            }
+            Event::ButtonReleased { gamepad_id, button } => {
+                self.injected[gamepad_id.0 as usize].pressed_bits &= !(1 << (button as u32));
+            }
+            Event::LeftStickMoved {
+                gamepad_id,
+                position: (x, y),
+            } => {
+                let injected = &mut self.injected[gamepad_id.0 as usize];
+                injected.axes[0] = x;
+                injected.axes[1] = y;
+            }
+            Event::RightStickMoved {
+                gamepad_id,
+                position: (x, y),
+            } => {
+                let injected = &mut self.injected[gamepad_id.0 as usize];
+                injected.axes[2] = x;
+                injected.axes[3] = y;
+            }
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{Button, Gamepad};
+
+/// The input of one gamepad for one tick, in a compact and deterministic form.
+///
+/// Intended for rollback networking, where the input of each tick is sent to peers and
+/// needs to compare equal on all of them. Thumbstick positions are quantized to 16 bits,
+/// so a frame converted to bytes and back is always equal to the original.
+///
+/// Obtain a frame from a [Gamepad] using `InputFrame::from(&gamepad)`.
+///
+/// ```
+/// use gamepads::{Gamepads, InputFrame};
+///
+/// let mut gamepads = Gamepads::new();
+/// gamepads.poll();
+/// for gamepad in gamepads.all() {
+///     let frame = InputFrame::from(&gamepad);
+///     let bytes = frame.to_bytes();
+///     // Send bytes to peers, which decode them using:
+///     assert_eq!(InputFrame::from_bytes(&bytes), Some(frame));
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct InputFrame {
+    pressed_bits: u32,
+    axes: [i16; 4],
+}
+
+impl InputFrame {
+    /// The version of the encoding, stored as the first byte.
+    pub const VERSION: u8 = 1;
+
+    /// The number of bytes of an encoded frame.
+    pub const ENCODED_LEN: usize = 13;
+
+    /// Check if a button is pressed.
+    pub const fn is_pressed(&self, button: Button) -> bool {
+        self.pressed_bits & (1 << (button as u32)) != 0
+    }
+
+    /// An iterator over all pressed buttons.
+    pub fn all_pressed(&self) -> impl Iterator<Item = Button> + '_ {
+        Button::all().filter(|&button| self.is_pressed(button))
+    }
+
+    /// The `(x, y)` position of the left thumbstick, as quantized in the frame.
+    pub fn left_stick(&self) -> (f32, f32) {
+        (dequantize(self.axes[0]), dequantize(self.axes[1]))
+    }
+
+    /// The `(x, y)` position of the right thumbstick, as quantized in the frame.
+    pub fn right_stick(&self) -> (f32, f32) {
+        (dequantize(self.axes[2]), dequantize(self.axes[3]))
+    }
+
+    /// Encode the frame as bytes.
+    ///
+    /// The encoding is a version byte followed by the pressed buttons and the
+    /// quantized thumbstick axes, all in little endian.
+    pub fn to_bytes(&self) -> [u8; Self::ENCODED_LEN] {
+        let mut bytes = [0; Self::ENCODED_LEN];
+        bytes[0] = Self::VERSION;
+        bytes[1..5].copy_from_slice(&self.pressed_bits.to_le_bytes());
+        for (chunk, axis) in bytes[5..].chunks_exact_mut(2).zip(self.axes) {
+            chunk.copy_from_slice(&axis.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decode a frame encoded using [InputFrame::to_bytes()].
+    ///
+    /// Returns `None` if the bytes have the wrong length or an unsupported version.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != Self::ENCODED_LEN || bytes[0] != Self::VERSION {
+            return None;
+        }
+        let mut frame = Self {
+            pressed_bits: u32::from_le_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]),
+            axes: [0; 4],
+        };
+        for (axis, chunk) in frame.axes.iter_mut().zip(bytes[5..].chunks_exact(2)) {
+            *axis = i16::from_le_bytes([chunk[0], chunk[1]]);
+        }
+        Some(frame)
+    }
+}
+
+/// An [InputFrame] encoded as the input of a [GGRS](https://github.com/gschup/ggrs) session,
+/// enabled by the `ggrs` feature.
+///
+/// It is a fixed-size payload implementing the bounds GGRS places on inputs, so it can be used as
+/// the `Input` of a `ggrs::Config` directly. Remote inputs are unpacked using
+/// [GgrsInput::frame()], which reads like a gamepad:
+///
+/// ```
+/// use gamepads::{Button, Gamepads, GgrsInput, InputFrame};
+///
+/// let mut gamepads = Gamepads::new();
+/// gamepads.poll();
+/// let local_input = gamepads
+///     .all()
+///     .next()
+///     .map(|gamepad| GgrsInput::from(InputFrame::from(&gamepad)))
+///     .unwrap_or_default();
+/// // Add local_input to the GGRS session, then read the inputs it hands out as:
+/// if local_input.frame().is_pressed(Button::ActionDown) {
+///     // Jump
+/// }
+/// ```
+#[cfg(feature = "ggrs")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct GgrsInput([u8; InputFrame::ENCODED_LEN]);
+
+#[cfg(feature = "ggrs")]
+impl GgrsInput {
+    /// The frame of the input.
+    ///
+    /// Inputs which do not decode, such as zeroed inputs of disconnected players, read as a frame
+    /// without any pressed buttons and centered thumbsticks.
+    pub fn frame(&self) -> InputFrame {
+        InputFrame::from_bytes(&self.0).unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "ggrs")]
+impl Default for GgrsInput {
+    fn default() -> Self {
+        InputFrame::default().into()
+    }
+}
+
+#[cfg(feature = "ggrs")]
+impl From<InputFrame> for GgrsInput {
+    fn from(frame: InputFrame) -> Self {
+        Self(frame.to_bytes())
+    }
+}
+
+#[cfg(feature = "ggrs")]
+impl From<GgrsInput> for InputFrame {
+    fn from(input: GgrsInput) -> Self {
+        input.frame()
+    }
+}
+
+fn quantize(value: f32) -> i16 {
+    (value.clamp(-1., 1.) * f32::from(i16::MAX)).round() as i16
+}
+
+fn dequantize(value: i16) -> f32 {
+    (f32::from(value) / f32::from(i16::MAX)).max(-1.)
+}
+
+/// Capture the current state of a gamepad.
+impl From<&Gamepad> for InputFrame {
+    fn from(gamepad: &Gamepad) -> Self {
+        Self {
+            pressed_bits: gamepad.pressed_bits,
+            axes: gamepad.axes.map(quantize),
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{Button, DeviceKind, Gamepad, GamepadId};
+
+/// A fret button on the neck of a guitar controller, see [Guitar].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Fret {
+    Green,
+    Red,
+    Yellow,
+    Blue,
+    Orange,
+}
+
+impl Fret {
+    /// All frets, from the head of the neck towards the body.
+    pub const ALL: [Self; 5] = [
+        Self::Green,
+        Self::Red,
+        Self::Yellow,
+        Self::Blue,
+        Self::Orange,
+    ];
+
+    /// The button which guitars report the fret as, in the standard layout of Xbox guitars.
+    const fn button(self) -> Button {
+        match self {
+            Self::Green => Button::ActionDown,
+            Self::Red => Button::ActionRight,
+            Self::Yellow => Button::ActionUp,
+            Self::Blue => Button::ActionLeft,
+            Self::Orange => Button::FrontLeftUpper,
+        }
+    }
+}
+
+/// A pad of a drum kit controller, see [Drums].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DrumPad {
+    Red,
+    Yellow,
+    Blue,
+    Green,
+    /// The kick pedal.
+    Kick,
+}
+
+impl DrumPad {
+    /// All pads, from left to right, followed by the kick pedal.
+    pub const ALL: [Self; 5] = [Self::Red, Self::Yellow, Self::Blue, Self::Green, Self::Kick];
+
+    /// The button which drum kits report the pad as, in the standard layout of Xbox drum kits.
+    const fn button(self) -> Button {
+        match self {
+            Self::Red => Button::ActionRight,
+            Self::Yellow => Button::ActionUp,
+            Self::Blue => Button::ActionLeft,
+            Self::Green => Button::ActionDown,
+            Self::Kick => Button::FrontLeftUpper,
+        }
+    }
+}
+
+/// The state of a guitar controller of Guitar Hero or Rock Band, read using
+/// [Gamepads::guitar()](crate::Gamepads::guitar).
+///
+/// Guitars are reported as gamepads with the standard layout of their Xbox versions, which are
+/// also followed by the platform mappings of their PlayStation versions: the frets as the action
+/// buttons and [Button::FrontLeftUpper], the strum bar as [Button::DPadUp] and [Button::DPadDown],
+/// and the whammy bar and tilt sensor as the right thumbstick.
+///
+/// ```no_run
+/// use gamepads::{Fret, Gamepads};
+///
+/// let mut gamepads = Gamepads::new();
+/// loop {
+///     gamepads.poll();
+///     let ids: Vec<_> = gamepads.all().map(|gamepad| gamepad.id()).collect();
+///     for guitar in ids.into_iter().filter_map(|id| gamepads.guitar(id)) {
+///         if guitar.is_strummed() {
+///             let chord: Vec<Fret> = Fret::ALL
+///                 .into_iter()
+///                 .filter(|&fret| guitar.is_fret_pressed(fret))
+///                 .collect();
+///             println!("Strummed {chord:?} with whammy {}", guitar.whammy());
+///         }
+///     }
+/// }
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct Guitar {
+    gamepad: Gamepad,
+}
+
+impl Guitar {
+    /// Read a gamepad as a guitar, such as one not detected as one by its name.
+    pub const fn new(gamepad: Gamepad) -> Self {
+        Self { gamepad }
+    }
+
+    /// Check if a fret is currently held down.
+    pub const fn is_fret_pressed(&self, fret: Fret) -> bool {
+        self.gamepad.is_currently_pressed(fret.button())
+    }
+
+    /// Check if a fret has just been pressed.
+    pub const fn is_fret_just_pressed(&self, fret: Fret) -> bool {
+        self.gamepad.is_just_pressed(fret.button())
+    }
+
+    /// Check if the strum bar has just been flicked in either direction.
+    pub const fn is_strummed(&self) -> bool {
+        self.gamepad.is_just_pressed(Button::DPadUp)
+            || self.gamepad.is_just_pressed(Button::DPadDown)
+    }
+
+    /// How far the whammy bar is pushed, in the range `[0.0, 1.0]`.
+    pub fn whammy(&self) -> f32 {
+        ((self.gamepad.right_stick_x() + 1.) / 2.).clamp(0., 1.)
+    }
+
+    /// How far the guitar is tilted upwards, as for activating star power, in the range
+    /// `[-1.0, 1.0]`, if the guitar has a tilt sensor.
+    pub const fn tilt(&self) -> f32 {
+        self.gamepad.right_stick_y()
+    }
+
+    /// The underlying gamepad, such as for the start and select buttons.
+    pub const fn gamepad(&self) -> &Gamepad {
+        &self.gamepad
+    }
+}
+
+/// The state of a drum kit controller of Guitar Hero or Rock Band, read using
+/// [Gamepads::drums()](crate::Gamepads::drums).
+///
+/// Drum kits are reported as gamepads with the standard layout of their Xbox versions: the pads
+/// as the action buttons, the kick pedal as [Button::FrontLeftUpper], and hits on the cymbals of
+/// pro drum kits together with [Button::FrontRightUpper].
+#[derive(Copy, Clone, Debug)]
+pub struct Drums {
+    gamepad: Gamepad,
+}
+
+impl Drums {
+    /// Read a gamepad as a drum kit, such as one not detected as one by its name.
+    pub const fn new(gamepad: Gamepad) -> Self {
+        Self { gamepad }
+    }
+
+    /// Check if a pad or the kick pedal has just been hit, including the cymbal of its color.
+    pub const fn is_hit(&self, pad: DrumPad) -> bool {
+        self.gamepad.is_just_pressed(pad.button())
+    }
+
+    /// Check if the cymbal of a color, rather than its pad, has just been hit on a pro drum kit.
+    pub const fn is_cymbal_hit(&self, pad: DrumPad) -> bool {
+        !matches!(pad, DrumPad::Kick)
+            && self.is_hit(pad)
+            && self.gamepad.is_currently_pressed(Button::FrontRightUpper)
+    }
+
+    /// The underlying gamepad, such as for the start and select buttons.
+    pub const fn gamepad(&self) -> &Gamepad {
+        &self.gamepad
+    }
+}
+
+impl crate::Gamepads {
+    /// Read a connected gamepad as a guitar, if it is one as reported by
+    /// [Gamepads::device_kind()](crate::Gamepads::device_kind).
+    pub fn guitar(&self, gamepad_id: GamepadId) -> Option<Guitar> {
+        let gamepad = self.get(gamepad_id)?;
+        (self.device_kind(gamepad_id) == DeviceKind::Guitar).then_some(Guitar::new(gamepad))
+    }
+
+    /// Read a connected gamepad as a drum kit, if it is one as reported by
+    /// [Gamepads::device_kind()](crate::Gamepads::device_kind).
+    pub fn drums(&self, gamepad_id: GamepadId) -> Option<Drums> {
+        let gamepad = self.get(gamepad_id)?;
+        (self.device_kind(gamepad_id) == DeviceKind::Drums).then_some(Drums::new(gamepad))
+    }
+}
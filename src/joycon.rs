@@ -0,0 +1,203 @@
+use crate::{Button, Gamepad, GamepadId, GamepadMapping};
+use gamepads_core::MAX_GAMEPADS;
+
+/// How Joy-Cons connected as separate gamepads are reported, set using
+/// [Gamepads::set_joycon_pairing()](crate::Gamepads::set_joycon_pairing).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JoyConPairing {
+    /// Combine a left and a right Joy-Con into one gamepad with the standard layout, reported
+    /// using the id of the left Joy-Con.
+    #[default]
+    Combined,
+    /// Report each Joy-Con as a separate gamepad, as done by the platform.
+    Separate,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    /// The side of a Joy-Con identified by its name, such as "Nintendo Switch Left Joy-Con" from
+    /// the kernel or "Joy-Con (L)" from the SDL mappings used by gilrs and from browsers.
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        if !name.contains("joy-con") {
+            None
+        } else if name.contains("left") || name.contains("(l)") {
+            Some(Self::Left)
+        } else if name.contains("right") || name.contains("(r)") {
+            Some(Self::Right)
+        } else {
+            None
+        }
+    }
+
+    /// The buttons of a Joy-Con held upright on this side, taken from it when combined, while
+    /// other buttons such as its SL and SR buttons are ignored.
+    const fn bits(self) -> u32 {
+        let buttons: &[Button] = match self {
+            Self::Left => &[
+                Button::DPadUp,
+                Button::DPadDown,
+                Button::DPadLeft,
+                Button::DPadRight,
+                Button::FrontLeftUpper,
+                Button::FrontLeftLower,
+                Button::LeftCenterCluster,
+                Button::LeftStick,
+            ],
+            Self::Right => &[
+                Button::ActionDown,
+                Button::ActionRight,
+                Button::ActionLeft,
+                Button::ActionUp,
+                Button::FrontRightUpper,
+                Button::FrontRightLower,
+                Button::RightCenterCluster,
+                Button::RightStick,
+                Button::Mode,
+            ],
+        };
+        let mut bits = 0;
+        let mut idx = 0;
+        while idx < buttons.len() {
+            bits |= 1 << (buttons[idx] as u32);
+            idx += 1;
+        }
+        bits
+    }
+}
+
+/// Bits of the buttons beyond [Button::Mode], such as the capture button, kept from both sides.
+const EXTRA_BITS: u32 = !((1 << (Button::Mode as u32 + 1)) - 1);
+
+/// The thumbstick of a Joy-Con, which backends report as either thumbstick of the gamepad.
+fn stick(gamepad: &Gamepad) -> (f32, f32) {
+    let (left, right) = (gamepad.left_stick(), gamepad.right_stick());
+    if left.0.hypot(left.1) >= right.0.hypot(right.1) {
+        left
+    } else {
+        right
+    }
+}
+
+/// The detected Joy-Cons and the pairs combined of them.
+pub struct JoyCons {
+    pairing: JoyConPairing,
+    /// The side of each connected gamepad which is a Joy-Con, detected when it connects.
+    sides: [Option<Side>; MAX_GAMEPADS],
+    /// Whether the side of each gamepad has been detected since it connected.
+    detected: [bool; MAX_GAMEPADS],
+    /// The right Joy-Con combined into each left Joy-Con, indexed by the left one.
+    pairs: [Option<GamepadId>; MAX_GAMEPADS],
+}
+
+impl Default for JoyCons {
+    fn default() -> Self {
+        Self {
+            pairing: JoyConPairing::default(),
+            sides: [None; MAX_GAMEPADS],
+            detected: [false; MAX_GAMEPADS],
+            pairs: [None; MAX_GAMEPADS],
+        }
+    }
+}
+
+impl JoyCons {
+    /// The right Joy-Con combined into a gamepad, if any.
+    pub const fn paired_right(&self, gamepad_id: GamepadId) -> Option<GamepadId> {
+        self.pairs[gamepad_id.0 as usize]
+    }
+}
+
+impl crate::Gamepads {
+    /// Set how Joy-Cons connected as separate gamepads are reported.
+    ///
+    /// By default, a left and a right Joy-Con are combined into one gamepad with the standard
+    /// layout as if attached to a grip, reported using the id of the left Joy-Con, while the
+    /// right Joy-Con is not reported. Joy-Cons are paired in the order they connect, and
+    /// reported separately again when the other one disconnects. Rumbling the combined gamepad
+    /// rumbles both Joy-Cons.
+    ///
+    /// This applies to backends reporting each Joy-Con held upright as its half of the standard
+    /// layout, such as gilrs and evdev on Linux, while platforms combining Joy-Cons themselves
+    /// report them as one gamepad already.
+    pub const fn set_joycon_pairing(&mut self, pairing: JoyConPairing) {
+        self.joycons.pairing = pairing;
+        self.joycons.pairs = [None; MAX_GAMEPADS];
+    }
+
+    /// The raw state of all gamepads with paired Joy-Cons combined, detecting Joy-Cons which
+    /// have connected and pairing them.
+    pub(crate) fn combine_joycons(&mut self) -> [Gamepad; MAX_GAMEPADS] {
+        let mut gamepads = self.gamepads;
+        for (idx, gamepad) in self.gamepads.iter().enumerate() {
+            if !gamepad.connected {
+                self.joycons.sides[idx] = None;
+                self.joycons.detected[idx] = false;
+            } else if !self.joycons.detected[idx] {
+                self.joycons.sides[idx] = self
+                    .name(gamepad.id)
+                    .and_then(|name| Side::from_name(&name));
+                self.joycons.detected[idx] = true;
+            }
+        }
+        let joycons = &mut self.joycons;
+        if joycons.pairing == JoyConPairing::Separate {
+            return gamepads;
+        }
+
+        // Split pairs of which a Joy-Con has disconnected:
+        for left_idx in 0..MAX_GAMEPADS {
+            if let Some(right_id) = joycons.pairs[left_idx] {
+                if joycons.sides[left_idx] != Some(Side::Left)
+                    || joycons.sides[usize::from(right_id.0)] != Some(Side::Right)
+                {
+                    joycons.pairs[left_idx] = None;
+                }
+            }
+        }
+        // Pair the remaining Joy-Cons in order:
+        let is_paired = |pairs: &[Option<GamepadId>; MAX_GAMEPADS], idx: usize| {
+            pairs[idx].is_some() || pairs.contains(&Some(GamepadId(idx as u8)))
+        };
+        for left_idx in 0..MAX_GAMEPADS {
+            if joycons.sides[left_idx] != Some(Side::Left) || is_paired(&joycons.pairs, left_idx) {
+                continue;
+            }
+            let Some(right_idx) = (0..MAX_GAMEPADS).find(|&right_idx| {
+                joycons.sides[right_idx] == Some(Side::Right)
+                    && !is_paired(&joycons.pairs, right_idx)
+            }) else {
+                break;
+            };
+            joycons.pairs[left_idx] = Some(GamepadId(right_idx as u8));
+        }
+
+        for (left_idx, right_id) in joycons.pairs.iter().enumerate() {
+            let Some(right_id) = right_id else {
+                continue;
+            };
+            let right_idx = usize::from(right_id.0);
+            let (left, right) = (self.gamepads[left_idx], self.gamepads[right_idx]);
+            let combine = |left_bits: u32, right_bits: u32| {
+                (left_bits & (Side::Left.bits() | EXTRA_BITS))
+                    | (right_bits & (Side::Right.bits() | EXTRA_BITS))
+            };
+            let combined = &mut gamepads[left_idx];
+            combined.set_mapping(GamepadMapping::Standard);
+            combined.pressed_bits = combine(left.pressed_bits, right.pressed_bits);
+            combined.just_pressed_bits = combine(left.just_pressed_bits, right.just_pressed_bits);
+            let (left_stick, right_stick) = (stick(&left), stick(&right));
+            combined.axes = [left_stick.0, left_stick.1, right_stick.0, right_stick.1];
+            combined.triggers = [left.triggers[0], right.triggers[1]];
+            gamepads[right_idx].set_connected(false);
+            let right_timestamps = self.raw_timestamps[right_idx];
+            self.raw_timestamps[left_idx].merge(&right_timestamps);
+        }
+        gamepads
+    }
+}
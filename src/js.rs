@@ -0,0 +1,188 @@
+//! Javascript glue needed by the direct web backend, matching this version of the crate.
+//!
+//! Without `wasm-bindgen`, the page embedding the wasm file needs to provide host functions
+//! for reading gamepads. Serving the glue from here instead of downloading a separate file
+//! keeps it in sync with the crate version compiled against.
+//!
+//! The same files are available to build scripts of dependent crates in the directory
+//! given by the `DEP_GAMEPADS_JS_DIR` environment variable, as `gamepads.js`,
+//! `gamepads-module.js` and `macroquad-gamepads.js`.
+//!
+//! ```no_run
+//! // In a tool preparing the web assets of a macroquad game:
+//! std::fs::write("dist/macroquad-gamepads.js", gamepads::js::MACROQUAD_PLUGIN).unwrap();
+//! ```
+
+use crate::Gamepad;
+
+/// A script defining `registerHostFunctions(importObject, memoryHolder)`, which registers the
+/// host functions in the `env` of a wasm import object.
+///
+/// The wasm memory is read from `memoryHolder.memory`, which should be set after instantiating,
+/// or from the `wasm_memory` global of miniquad if `memoryHolder` is not given. The same goes
+/// for the wasm exports, from `memoryHolder.exports` or the `wasm_exports` global, which are
+/// called back into by the event streams of the `async` feature.
+pub const SCRIPT: &str = include_str!(concat!(env!("OUT_DIR"), "/gamepads.js"));
+
+/// An ES module exporting the function of [SCRIPT] as default.
+pub const MODULE: &str = include_str!(concat!(env!("OUT_DIR"), "/gamepads-module.js"));
+
+/// A macroquad plugin registering the host functions, to be loaded after `mq_js_bundle.js`.
+pub const MACROQUAD_PLUGIN: &str = include_str!(concat!(env!("OUT_DIR"), "/macroquad-gamepads.js"));
+
+/// The current version of the layout written by the `getGamepads` host function.
+///
+/// The crate passes the lower of this and what the `gamepadsProtocolVersion` host function
+/// returns, so that newer javascript can keep writing the layout of older crate versions.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// The number of gamepads written by the `getGamepads` host function, one after another.
+pub const MAX_GAMEPADS: usize = crate::MAX_GAMEPADS;
+
+/// The number of axes beyond the two thumbsticks written for each gamepad.
+pub const MAX_EXTRA_AXES: usize = crate::MAX_EXTRA_AXES;
+
+/// The size in bytes of the state of each gamepad written by the `getGamepads` host function.
+pub const BYTES_PER_GAMEPAD: usize = std::mem::size_of::<Gamepad>();
+
+/// The offset of the `u8` which is 1 if the gamepad is connected and 0 otherwise.
+pub const CONNECTED_OFFSET: usize = std::mem::offset_of!(Gamepad, connected);
+
+/// The offset of the `u8` [GamepadMapping](crate::GamepadMapping), being 0 if unknown,
+/// 1 for `standard`, 2 for no mapping and 3 for `xr-standard`.
+pub const MAPPING_OFFSET: usize = std::mem::offset_of!(Gamepad, mapping);
+
+/// The offset of the `u32` with a bit set for each currently pressed [Button](crate::Button).
+pub const PRESSED_BITS_OFFSET: usize = std::mem::offset_of!(Gamepad, pressed_bits);
+
+/// The offset of the four `f32` axes, being the x and y of the left and then the right
+/// thumbstick, with y positive upwards.
+pub const AXES_OFFSET: usize = std::mem::offset_of!(Gamepad, axes);
+
+/// The offset of the `u32` with a bit set for each [Button](crate::Button) pressed since the
+/// previous poll.
+pub const JUST_PRESSED_BITS_OFFSET: usize = std::mem::offset_of!(Gamepad, just_pressed_bits);
+
+/// The offset of the two `f32` values of how far the left and right triggers are pressed,
+/// in the range `[0.0, 1.0]`.
+pub const TRIGGERS_OFFSET: usize = std::mem::offset_of!(Gamepad, triggers);
+
+/// The offset of the [MAX_EXTRA_AXES](crate::MAX_EXTRA_AXES) `f32` values of the axes beyond
+/// the two thumbsticks, starting with index 4 of the `axes` array of the Gamepad API.
+pub const EXTRA_AXES_OFFSET: usize = std::mem::offset_of!(Gamepad, extra_axes);
+
+/// The offset of the `u64` with a bit set for each currently pressed button from index 32,
+/// up to [MAX_BUTTONS](crate::MAX_BUTTONS).
+pub const HIGH_PRESSED_BITS_OFFSET: usize = std::mem::offset_of!(Gamepad, high_pressed_bits);
+
+/// TypeScript definitions for [MODULE], including the layout of gamepad state in wasm memory.
+///
+/// Written to `js/gamepads-module-0.2.d.ts` by `cargo xtask generate-ts`.
+pub fn typescript_definitions() -> String {
+    format!(
+        r#"// Generated by `cargo xtask generate-ts` from the gamepads crate, do not edit.
+
+/** Layout of the state of each gamepad written to wasm memory by `getGamepads()`. */
+export declare const enum GamepadLayout {{
+  /** The version of this layout, passed to `getGamepads()`. */
+  ProtocolVersion = {PROTOCOL_VERSION},
+  /** The number of gamepads written, one after another. */
+  MaxGamepads = {MAX_GAMEPADS},
+  /** The size in bytes of the state of each gamepad. */
+  BytesPerGamepad = {BYTES_PER_GAMEPAD},
+  /** The offset of the `u8` which is 1 if the gamepad is connected and 0 otherwise. */
+  ConnectedOffset = {CONNECTED_OFFSET},
+  /** The offset of the `u8` mapping: 0 if unknown, 1 for standard, 2 for none and 3 for xr-standard. */
+  MappingOffset = {MAPPING_OFFSET},
+  /** The offset of the `u32` with a bit set for each currently pressed button. */
+  PressedBitsOffset = {PRESSED_BITS_OFFSET},
+  /** The offset of the four `f32` thumbstick axes: left x, left y, right x and right y. */
+  AxesOffset = {AXES_OFFSET},
+  /** The offset of the `u32` with a bit set for each button pressed since the previous poll. */
+  JustPressedBitsOffset = {JUST_PRESSED_BITS_OFFSET},
+  /** The offset of the two `f32` values of how far the left and right triggers are pressed. */
+  TriggersOffset = {TRIGGERS_OFFSET},
+  /** The offset of the `f32` values of the axes beyond the two thumbsticks. */
+  ExtraAxesOffset = {EXTRA_AXES_OFFSET},
+  /** The number of axes beyond the two thumbsticks. */
+  MaxExtraAxes = {MAX_EXTRA_AXES},
+  /** The offset of the `u64` with a bit set for each currently pressed button from index 32. */
+  HighPressedBitsOffset = {HIGH_PRESSED_BITS_OFFSET},
+}}
+
+/** Host functions imported by the wasm module from `env`, with pointers into wasm memory. */
+export interface GamepadsHostFunctions {{
+  /**
+   * Write the state of all gamepads to `dataPtr`, as described by `GamepadLayout` for the
+   * given protocol version.
+   */
+  getGamepads(dataPtr: number, protocolVersion: number): void;
+  /** The highest protocol version supported by `getGamepads()`. */
+  gamepadsProtocolVersion(): number;
+  /**
+   * Write the raw `u32` pressed bits, 24 `f32` axes, the `u8` mapping and, at the next 8 byte
+   * boundary, the `u64` pressed bits of buttons from index 32 of a gamepad without the standard
+   * mapping to `rawPtr`, and its UTF-8 id to `idPtr`, returning the id length or -1.
+   */
+  getGamepadRaw(
+    gamepadIdx: number,
+    rawPtr: number,
+    idPtr: number,
+    idCapacity: number,
+    protocolVersion: number,
+  ): number;
+  /**
+   * Write the UTF-8 id of a connected gamepad to `idPtr`, truncated to `idCapacity` bytes,
+   * returning its full length or -1.
+   */
+  gamepadsId(gamepadIdx: number, idPtr: number, idCapacity: number): number;
+  /** Play a rumble effect, returning an id for `gamepadsEffectStatus()`, or 0 if not played. */
+  playEffect(
+    gamepadId: number,
+    durationMs: number,
+    startDelayMs: number,
+    strongMagnitude: number,
+    weakMagnitude: number,
+    leftTrigger: number,
+    rightTrigger: number,
+  ): number;
+  /** 0 if playing, 1 if complete, 2 if preempted and 3 if failed. */
+  gamepadsEffectStatus(effectId: number): number;
+  gamepadsForgetEffect(effectId: number): void;
+  /** The supported effect types as bits, with 1 for dual-rumble and 2 for trigger-rumble. */
+  gamepadsEffects(gamepadId: number): number;
+  /**
+   * Write the `f32` x and y mouse movement, and the `u32` pressed and just pressed mouse
+   * buttons received while the pointer was locked since the last call, to `samplePtr`.
+   */
+  gamepadsTakeMouse(samplePtr: number): void;
+  /** The current time in milliseconds. */
+  gamepadsNow(): number;
+  /** Call the `gamepads_wake_streams` export after a delay, used by the `async` feature. */
+  gamepadsWakeAfter(delayMs: number): void;
+  /**
+   * 0 if available, 1 if not in a secure context, 2 if blocked, 3 if unsupported and 4 if
+   * available but no gamepad has been exposed yet, awaiting a button press.
+   */
+  gamepadsAvailability(): number;
+}}
+
+/** Holds the memory and exports of the wasm instance, to be set after instantiating it. */
+export interface MemoryHolder {{
+  memory?: WebAssembly.Memory;
+  exports?: WebAssembly.Exports;
+}}
+
+/**
+ * Register the host functions in the `env` of a wasm import object.
+ *
+ * Without a `memoryHolder`, the memory and exports are read from the `wasm_memory` and
+ * `wasm_exports` globals of miniquad.
+ */
+export default function registerHostFunctions(
+  importObject: {{ env: Record<string, unknown> }},
+  memoryHolder?: MemoryHolder,
+): void;
+"#
+    )
+}
@@ -0,0 +1,399 @@
+use crate::{Button, Direction, Event, GamepadId};
+
+/// A key of a keyboard, by its position as on a US QWERTY keyboard, as the `code` of browser
+/// keyboard events and the `KeyCode` of winit, see [Key::from_code()].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Key {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+    Digit0,
+    Digit1,
+    Digit2,
+    Digit3,
+    Digit4,
+    Digit5,
+    Digit6,
+    Digit7,
+    Digit8,
+    Digit9,
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    Space,
+    Enter,
+    Escape,
+    Tab,
+    Backspace,
+    ShiftLeft,
+    ShiftRight,
+    ControlLeft,
+    ControlRight,
+    AltLeft,
+    AltRight,
+}
+
+/// Each key with its code, as the `code` of browser keyboard events.
+const KEY_CODES: [(Key, &str); 51] = [
+    (Key::A, "KeyA"),
+    (Key::B, "KeyB"),
+    (Key::C, "KeyC"),
+    (Key::D, "KeyD"),
+    (Key::E, "KeyE"),
+    (Key::F, "KeyF"),
+    (Key::G, "KeyG"),
+    (Key::H, "KeyH"),
+    (Key::I, "KeyI"),
+    (Key::J, "KeyJ"),
+    (Key::K, "KeyK"),
+    (Key::L, "KeyL"),
+    (Key::M, "KeyM"),
+    (Key::N, "KeyN"),
+    (Key::O, "KeyO"),
+    (Key::P, "KeyP"),
+    (Key::Q, "KeyQ"),
+    (Key::R, "KeyR"),
+    (Key::S, "KeyS"),
+    (Key::T, "KeyT"),
+    (Key::U, "KeyU"),
+    (Key::V, "KeyV"),
+    (Key::W, "KeyW"),
+    (Key::X, "KeyX"),
+    (Key::Y, "KeyY"),
+    (Key::Z, "KeyZ"),
+    (Key::Digit0, "Digit0"),
+    (Key::Digit1, "Digit1"),
+    (Key::Digit2, "Digit2"),
+    (Key::Digit3, "Digit3"),
+    (Key::Digit4, "Digit4"),
+    (Key::Digit5, "Digit5"),
+    (Key::Digit6, "Digit6"),
+    (Key::Digit7, "Digit7"),
+    (Key::Digit8, "Digit8"),
+    (Key::Digit9, "Digit9"),
+    (Key::ArrowUp, "ArrowUp"),
+    (Key::ArrowDown, "ArrowDown"),
+    (Key::ArrowLeft, "ArrowLeft"),
+    (Key::ArrowRight, "ArrowRight"),
+    (Key::Space, "Space"),
+    (Key::Enter, "Enter"),
+    (Key::Escape, "Escape"),
+    (Key::Tab, "Tab"),
+    (Key::Backspace, "Backspace"),
+    (Key::ShiftLeft, "ShiftLeft"),
+    (Key::ShiftRight, "ShiftRight"),
+    (Key::ControlLeft, "ControlLeft"),
+    (Key::ControlRight, "ControlRight"),
+    (Key::AltLeft, "AltLeft"),
+    (Key::AltRight, "AltRight"),
+];
+
+impl Key {
+    /// The key of a `code` of browser keyboard events, such as `"KeyW"` or `"ArrowUp"`, or `None`
+    /// for other keys.
+    pub fn from_code(code: &str) -> Option<Self> {
+        KEY_CODES
+            .iter()
+            .find(|(_, key_code)| *key_code == code)
+            .map(|&(key, _)| key)
+    }
+
+    /// The key of a `KeyCode` of winit, or `None` for other keys.
+    #[cfg(backend = "android_winit")]
+    pub(crate) const fn from_winit_code(code: winit::keyboard::KeyCode) -> Option<Self> {
+        use winit::keyboard::KeyCode;
+        Some(match code {
+            KeyCode::KeyA => Self::A,
+            KeyCode::KeyB => Self::B,
+            KeyCode::KeyC => Self::C,
+            KeyCode::KeyD => Self::D,
+            KeyCode::KeyE => Self::E,
+            KeyCode::KeyF => Self::F,
+            KeyCode::KeyG => Self::G,
+            KeyCode::KeyH => Self::H,
+            KeyCode::KeyI => Self::I,
+            KeyCode::KeyJ => Self::J,
+            KeyCode::KeyK => Self::K,
+            KeyCode::KeyL => Self::L,
+            KeyCode::KeyM => Self::M,
+            KeyCode::KeyN => Self::N,
+            KeyCode::KeyO => Self::O,
+            KeyCode::KeyP => Self::P,
+            KeyCode::KeyQ => Self::Q,
+            KeyCode::KeyR => Self::R,
+            KeyCode::KeyS => Self::S,
+            KeyCode::KeyT => Self::T,
+            KeyCode::KeyU => Self::U,
+            KeyCode::KeyV => Self::V,
+            KeyCode::KeyW => Self::W,
+            KeyCode::KeyX => Self::X,
+            KeyCode::KeyY => Self::Y,
+            KeyCode::KeyZ => Self::Z,
+            KeyCode::Digit0 => Self::Digit0,
+            KeyCode::Digit1 => Self::Digit1,
+            KeyCode::Digit2 => Self::Digit2,
+            KeyCode::Digit3 => Self::Digit3,
+            KeyCode::Digit4 => Self::Digit4,
+            KeyCode::Digit5 => Self::Digit5,
+            KeyCode::Digit6 => Self::Digit6,
+            KeyCode::Digit7 => Self::Digit7,
+            KeyCode::Digit8 => Self::Digit8,
+            KeyCode::Digit9 => Self::Digit9,
+            KeyCode::ArrowUp => Self::ArrowUp,
+            KeyCode::ArrowDown => Self::ArrowDown,
+            KeyCode::ArrowLeft => Self::ArrowLeft,
+            KeyCode::ArrowRight => Self::ArrowRight,
+            KeyCode::Space => Self::Space,
+            KeyCode::Enter => Self::Enter,
+            KeyCode::Escape => Self::Escape,
+            KeyCode::Tab => Self::Tab,
+            KeyCode::Backspace => Self::Backspace,
+            KeyCode::ShiftLeft => Self::ShiftLeft,
+            KeyCode::ShiftRight => Self::ShiftRight,
+            KeyCode::ControlLeft => Self::ControlLeft,
+            KeyCode::ControlRight => Self::ControlRight,
+            KeyCode::AltLeft => Self::AltLeft,
+            KeyCode::AltRight => Self::AltRight,
+            _ => return None,
+        })
+    }
+
+    /// The bit of the key in the pressed keys.
+    const fn bit(self) -> u64 {
+        1 << self as u32
+    }
+}
+
+/// What a key acts as on a keyboard gamepad.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyAction {
+    /// A button, pressed while the key is held.
+    Button(Button),
+    /// Moving the left thumbstick in a direction, combined with other keys held.
+    LeftStick(Direction),
+    /// Moving the right thumbstick in a direction, combined with other keys held.
+    RightStick(Direction),
+}
+
+/// The keys of a keyboard acting as a gamepad, for players without one.
+///
+/// Enable it using [Gamepads::enable_keyboard_gamepad()](crate::Gamepads::enable_keyboard_gamepad).
+/// A key may be mapped to several actions, and several keys to the same action.
+///
+/// ```
+/// use gamepads::{Button, Key, KeyAction, KeyboardLayout};
+///
+/// // The standard layout, with K also pressing the lowermost action button:
+/// let layout = KeyboardLayout::standard().with_key(Key::K, KeyAction::Button(Button::ActionDown));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeyboardLayout {
+    keys: Vec<(Key, KeyAction)>,
+}
+
+impl KeyboardLayout {
+    /// A layout without any keys.
+    pub const fn new() -> Self {
+        Self { keys: Vec::new() }
+    }
+
+    /// A layout for playing with keyboard only:
+    ///
+    /// - W, A, S and D move the left thumbstick, and I, J, K and L the right thumbstick.
+    /// - The arrow keys act as the D-pad.
+    /// - Space, E, R and F act as the lowermost, rightmost, leftmost and topmost action buttons.
+    /// - Q and Tab act as the upper front buttons, and Z and X as the lower ones.
+    /// - Left shift and C press the left and right thumbstick.
+    /// - Backspace acts as select, while Enter and Escape act as start.
+    pub fn standard() -> Self {
+        let stick_keys = [
+            (Direction::Up, Key::W, Key::I),
+            (Direction::Left, Key::A, Key::J),
+            (Direction::Down, Key::S, Key::K),
+            (Direction::Right, Key::D, Key::L),
+        ];
+        let button_keys = [
+            (Key::ArrowUp, Button::DPadUp),
+            (Key::ArrowDown, Button::DPadDown),
+            (Key::ArrowLeft, Button::DPadLeft),
+            (Key::ArrowRight, Button::DPadRight),
+            (Key::Space, Button::ActionDown),
+            (Key::E, Button::ActionRight),
+            (Key::R, Button::ActionLeft),
+            (Key::F, Button::ActionUp),
+            (Key::Q, Button::FrontLeftUpper),
+            (Key::Tab, Button::FrontRightUpper),
+            (Key::Z, Button::FrontLeftLower),
+            (Key::X, Button::FrontRightLower),
+            (Key::ShiftLeft, Button::LeftStick),
+            (Key::C, Button::RightStick),
+            (Key::Backspace, Button::LeftCenterCluster),
+            (Key::Enter, Button::RightCenterCluster),
+            (Key::Escape, Button::RightCenterCluster),
+        ];
+        let mut layout = Self::new();
+        for (direction, left_key, right_key) in stick_keys {
+            layout = layout
+                .with_key(left_key, KeyAction::LeftStick(direction))
+                .with_key(right_key, KeyAction::RightStick(direction));
+        }
+        for (key, button) in button_keys {
+            layout = layout.with_key(key, KeyAction::Button(button));
+        }
+        layout
+    }
+
+    /// Map a key to an action, in addition to the actions it is already mapped to.
+    pub fn with_key(mut self, key: Key, action: KeyAction) -> Self {
+        self.keys.push((key, action));
+        self
+    }
+}
+
+/// An enabled keyboard gamepad, the gamepad slot it feeds and the keys currently held.
+pub struct KeyboardGamepad {
+    gamepad_id: GamepadId,
+    layout: KeyboardLayout,
+    /// A bit for each held [Key].
+    pressed_keys: u64,
+    /// The buttons pressed by the held keys.
+    pressed_bits: u32,
+}
+
+/// The `(x, y)` offset of a direction, positive upwards and to the right.
+const fn direction_offset(direction: Direction) -> (f32, f32) {
+    match direction {
+        Direction::Up => (0., 1.),
+        Direction::UpRight => (1., 1.),
+        Direction::Right => (1., 0.),
+        Direction::DownRight => (1., -1.),
+        Direction::Down => (0., -1.),
+        Direction::DownLeft => (-1., -1.),
+        Direction::Left => (-1., 0.),
+        Direction::UpLeft => (-1., 1.),
+    }
+}
+
+impl crate::Gamepads {
+    /// Use keyboard keys as a gamepad, so that games reading gamepads work for keyboard players
+    /// too. The gamepad slot is made to appear connected, and the keys are merged into its state
+    /// as by [Gamepads::inject()](crate::Gamepads::inject).
+    ///
+    /// The game forwards keyboard input using [Gamepads::handle_key()](crate::Gamepads::handle_key).
+    ///
+    /// ```
+    /// use gamepads::{GamepadId, Gamepads, Key, KeyboardLayout};
+    ///
+    /// let mut gamepads = Gamepads::new();
+    /// let gamepad_id = GamepadId::new(0).unwrap();
+    /// gamepads.enable_keyboard_gamepad(gamepad_id, KeyboardLayout::standard());
+    ///
+    /// gamepads.handle_key(Key::W, true);
+    /// gamepads.handle_key(Key::D, true);
+    /// gamepads.poll();
+    /// let (x, y) = gamepads.get(gamepad_id).unwrap().left_stick();
+    /// assert!(x > 0. && y > 0.);
+    /// ```
+    pub fn enable_keyboard_gamepad(&mut self, gamepad_id: GamepadId, layout: KeyboardLayout) {
+        self.disable_keyboard_gamepad();
+        self.inject(Event::Connected { gamepad_id });
+        self.keyboard = Some(Box::new(KeyboardGamepad {
+            gamepad_id,
+            layout,
+            pressed_keys: 0,
+            pressed_bits: 0,
+        }));
+    }
+
+    /// Stop using keyboard keys as a gamepad, removing all input injected into its slot, which
+    /// disconnects it unless a physical gamepad is connected there.
+    pub fn disable_keyboard_gamepad(&mut self) {
+        if let Some(keyboard) = self.keyboard.take() {
+            self.inject(Event::Disconnected {
+                gamepad_id: keyboard.gamepad_id,
+            });
+        }
+    }
+
+    /// Handle a keyboard key being pressed or released.
+    ///
+    /// Does nothing unless enabled by [Gamepads::enable_keyboard_gamepad()](crate::Gamepads::enable_keyboard_gamepad).
+    pub fn handle_key(&mut self, key: Key, pressed: bool) {
+        let Some(keyboard) = &mut self.keyboard else {
+            return;
+        };
+        if pressed {
+            keyboard.pressed_keys |= key.bit();
+        } else {
+            keyboard.pressed_keys &= !key.bit();
+        }
+
+        let mut pressed_bits = 0;
+        let mut sticks = [(0., 0.); 2];
+        for &(key, action) in &keyboard.layout.keys {
+            if keyboard.pressed_keys & key.bit() == 0 {
+                continue;
+            }
+            let (stick, direction) = match action {
+                KeyAction::Button(button) => {
+                    pressed_bits |= 1 << (button as u32);
+                    continue;
+                }
+                KeyAction::LeftStick(direction) => (&mut sticks[0], direction),
+                KeyAction::RightStick(direction) => (&mut sticks[1], direction),
+            };
+            let (dx, dy) = direction_offset(direction);
+            stick.0 += dx;
+            stick.1 += dy;
+        }
+
+        let gamepad_id = keyboard.gamepad_id;
+        let changed_bits = keyboard.pressed_bits ^ pressed_bits;
+        keyboard.pressed_bits = pressed_bits;
+        for button in Button::all().filter(|&button| changed_bits & (1 << button as u32) != 0) {
+            self.inject(if pressed_bits & (1 << button as u32) != 0 {
+                Event::ButtonPressed { gamepad_id, button }
+            } else {
+                Event::ButtonReleased { gamepad_id, button }
+            });
+        }
+        // Diagonals at the edge of the thumbstick, not beyond it:
+        let [left, right] = sticks.map(|(x, y): (f32, f32)| {
+            let scale = x.hypot(y).max(1.);
+            (x / scale, y / scale)
+        });
+        self.inject(Event::LeftStickMoved {
+            gamepad_id,
+            position: left,
+        });
+        self.inject(Event::RightStickMoved {
+            gamepad_id,
+            position: right,
+        });
+    }
+}
@@ -0,0 +1,121 @@
+/// The number of most recent button presses kept for computing latency statistics.
+const MAX_SAMPLES: usize = 1024;
+
+/// Distribution of input latency, from when the backend observed a button press until
+/// it was available to the game after [Gamepads::poll()](crate::Gamepads::poll).
+///
+/// Obtained using [Gamepads::latency_stats()](crate::Gamepads::latency_stats), with all values in milliseconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct LatencyStats {
+    /// The number of button presses measured, up to the 1024 most recent ones.
+    pub samples: usize,
+    /// The lowest latency.
+    pub min_ms: f64,
+    /// The average latency.
+    pub mean_ms: f64,
+    /// The latency which half of the presses were below.
+    pub median_ms: f64,
+    /// The latency which 95% of the presses were below.
+    pub p95_ms: f64,
+    /// The latency which 99% of the presses were below.
+    pub p99_ms: f64,
+    /// The highest latency.
+    pub max_ms: f64,
+}
+
+/// Latency of the most recent button presses, in a ring buffer.
+pub struct LatencyTracker {
+    samples_ms: [f64; MAX_SAMPLES],
+    len: usize,
+    next: usize,
+}
+
+impl LatencyTracker {
+    const fn new() -> Self {
+        Self {
+            samples_ms: [0.; MAX_SAMPLES],
+            len: 0,
+            next: 0,
+        }
+    }
+
+    const fn record(&mut self, latency_ms: f64) {
+        self.samples_ms[self.next] = latency_ms;
+        self.next = (self.next + 1) % MAX_SAMPLES;
+        if self.len < MAX_SAMPLES {
+            self.len += 1;
+        }
+    }
+
+    fn stats(&self) -> LatencyStats {
+        let mut sorted = self.samples_ms;
+        let sorted = &mut sorted[..self.len];
+        if sorted.is_empty() {
+            return LatencyStats::default();
+        }
+        sorted.sort_unstable_by(f64::total_cmp);
+        let percentile = |p: f64| sorted[(p * (sorted.len() - 1) as f64).round() as usize];
+        LatencyStats {
+            samples: sorted.len(),
+            min_ms: sorted[0],
+            mean_ms: sorted.iter().sum::<f64>() / sorted.len() as f64,
+            median_ms: percentile(0.5),
+            p95_ms: percentile(0.95),
+            p99_ms: percentile(0.99),
+            max_ms: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+impl crate::Gamepads {
+    /// Enable or disable measuring the latency of button presses, for tuning how input feels.
+    ///
+    /// Latency is measured from when the backend observed a press until the end of the
+    /// [Gamepads::poll()](crate::Gamepads::poll) making it available, so polling right before
+    /// reading input keeps it representative. Backends without timestamps of changes only
+    /// observe presses when polled, so for them mostly the processing time is measured.
+    ///
+    /// ```
+    /// use gamepads::Gamepads;
+    ///
+    /// let mut gamepads = Gamepads::new();
+    /// gamepads.set_latency_tracking(true);
+    /// gamepads.poll();
+    /// if let Some(stats) = gamepads.latency_stats() {
+    ///     println!("Median latency: {} ms over {} presses", stats.median_ms, stats.samples);
+    /// }
+    /// ```
+    pub fn set_latency_tracking(&mut self, enabled: bool) {
+        if !enabled {
+            self.latency = None;
+        } else if self.latency.is_none() {
+            self.latency = Some(Box::new(LatencyTracker::new()));
+        }
+    }
+
+    /// Statistics of the latency measured since enabled by [Gamepads::set_latency_tracking()](crate::Gamepads::set_latency_tracking),
+    /// or `None` if not enabled.
+    pub fn latency_stats(&self) -> Option<LatencyStats> {
+        self.latency.as_ref().map(|latency| latency.stats())
+    }
+
+    /// Discard the latency measured so far, e.g. when changing settings to compare.
+    pub fn reset_latency_stats(&mut self) {
+        if let Some(latency) = &mut self.latency {
+            **latency = LatencyTracker::new();
+        }
+    }
+
+    /// Measure the latency of the presses in the last poll, which completed at `read_ms`.
+    pub(crate) fn record_latency(&mut self, read_ms: f64) {
+        let Some(latency) = &mut self.latency else {
+            return;
+        };
+        for event in self.events.iter() {
+            if let crate::Event::ButtonPressed { .. } = event.event {
+                latency.record(read_ms - event.timestamp_ms);
+            }
+        }
+    }
+}
@@ -69,7 +69,10 @@
 //!
 //! # Usage as a macroquad web plugin
 //! See the [documentation in the README](https://github.com/fornwall/gamepads#how-to-use-as-a-macroquad-plugin)
-//! for how to use `gamepads` with `macroquad`.
+//! for how to use `gamepads` with `macroquad`, enabling the `macroquad-plugin` feature. The
+//! javascript plugin matching this version of the crate is available from the [js] module, and
+//! needs to be registered from javascript, as miniquad resolves the imports of the wasm file
+//! before any Rust code runs.
 //!
 //! # Example showing gamepad iteration
 //!
@@ -137,146 +140,224 @@
 //!     }
 //! }
 //! ```
-
-#[cfg(all(target_os = "android", feature = "android-winit"))]
+//!
+//! # Usage without `std`
+//! Disabling the default `std` feature leaves the [Gamepad], [GamepadId] and [Button] types,
+//! as well as [apply_deadzone()]. To build for platforms without `std`, also enable the
+//! `core-only` feature, which only builds on targets where the `cdylib` crate type is not built.
+//! There is no [Gamepads] instance then, so state is fed from a custom backend using
+//! [Gamepad::new()] and its setters. These types are defined in the
+//! [gamepads-core](https://docs.rs/gamepads-core) crate shared by the backends, which custom
+//! backends and libraries can also depend on directly, without any backend.
+//!
+//! On the Nintendo Switch, homebrew built with devkitPro can enable the `libnx` feature to
+//! read controllers into [Gamepad] values using `Npads`, which needs no `std`.
+
+#![cfg_attr(all(feature = "core-only", not(feature = "std")), no_std)]
+
+#[cfg(feature = "std")]
+mod action_state;
+#[cfg(feature = "std")]
+mod attach;
+#[cfg(feature = "std")]
+mod availability;
+#[cfg(feature = "std")]
+mod backend;
+#[cfg(backend = "android_winit")]
 mod backend_android_winit;
-#[cfg(not(any(target_family = "wasm", target_os = "android")))]
+#[cfg(backend = "emscripten")]
+mod backend_emscripten;
+#[cfg(backend = "evdev")]
+mod backend_evdev;
+#[cfg(backend = "game_controller")]
+mod backend_game_controller;
+#[cfg(backend = "game_input")]
+mod backend_game_input;
+#[cfg(backend = "gilrs")]
 mod backend_gilrs;
-#[cfg(all(target_family = "wasm", feature = "wasm-bindgen"))]
+#[cfg(backend = "paddleboat")]
+mod backend_paddleboat;
+#[cfg(backend = "raw_input")]
+mod backend_raw_input;
+#[cfg(backend = "sdl3")]
+mod backend_sdl3;
+#[cfg(backend = "wasi_host")]
+mod backend_wasi;
+#[cfg(backend = "web_bindgen")]
 mod backend_web_bindgen;
-#[cfg(all(target_family = "wasm", not(feature = "wasm-bindgen")))]
+// Also used for the clock on the web without wasm-bindgen:
+#[cfg(all(
+    target_family = "wasm",
+    not(any(target_os = "emscripten", target_os = "wasi")),
+    feature = "std",
+    not(feature = "backend-web-bindgen")
+))]
 mod backend_web_direct;
-
-const MAX_GAMEPADS: usize = 8;
-
-/// An individual gamepad allowing access to information about button presses,
-/// thumbstick positions and its gamepad id.
-///
-/// A gamepad can be obtained using either [Gamepads::all()] to loop through all connected gamepads,
-/// or [Gamepads::get(gamepad_id)](Gamepads::get) to get it by an id.
-#[derive(Debug, Clone, Copy)]
-#[repr(C)]
-pub struct Gamepad {
-    id: GamepadId,
-    connected: bool,
-    pressed_bits: u32,
-    axes: [f32; 4],
-    #[cfg(target_family = "wasm")]
-    last_pressed_bits: u32,
-    #[cfg(not(target_family = "wasm"))]
-    just_pressed_bits: u32,
-}
-
-// Assert size of struct Gamepad, which is used by javascript.
-//
-// See https://users.rust-lang.org/t/ensure-that-struct-t-has-size-n-at-compile-time/61108/3
-#[cfg(target_family = "wasm")]
-const _: () = [(); 1][(core::mem::size_of::<Gamepad>() == 28) as usize ^ 1];
-
-impl Gamepad {
-    /// An id unique for each gamepad currently connected to the system.
-    ///
-    /// This can be used to distinguish multiple controllers; a gamepad that is disconnected
-    /// and reconnected will retain the same id.
-    pub const fn id(&self) -> GamepadId {
-        self.id
-    }
-
-    /// The `(x, y)` position of the left thumbstick.
-    ///
-    /// Each component is in the range `[-1.0, 1.0]`, with
-    /// negative values representing down or to the left.
-    pub const fn left_stick(&self) -> (f32, f32) {
-        (self.axes[0], self.axes[1])
-    }
-
-    /// The `x` position of the left thumbstick.
-    ///
-    /// Values are in the range `[-1.0, 1.0]`, with
-    /// negative values representing left.
-    pub const fn left_stick_x(&self) -> f32 {
-        self.axes[0]
-    }
-
-    /// The `y` position of the left thumbstick.
-    ///
-    /// Values are in the range `[-1.0, 1.0]`, with
-    /// negative values representing down.
-    pub const fn left_stick_y(&self) -> f32 {
-        self.axes[1]
-    }
-
-    /// The `(x, y)` position of the right thumbstick.
-    ///
-    /// Each component is in the range `[-1.0, 1.0]`, with
-    /// negative values representing down or to the left.
-    pub const fn right_stick(&self) -> (f32, f32) {
-        (self.axes[2], self.axes[3])
-    }
-
-    /// The `y` position of the right thumbstick.
-    ///
-    /// Values are in the range `[-1.0, 1.0]`, with
-    /// negative values representing left.
-    pub const fn right_stick_x(&self) -> f32 {
-        self.axes[2]
-    }
-
-    /// The `y` position of the right thumbstick.
-    ///
-    /// Values are in the range `[-1.0, 1.0]`, with
-    /// negative values representing down.
-    pub const fn right_stick_y(&self) -> f32 {
-        self.axes[3]
-    }
-
-    /// An iterator over all currently pressed buttons.
-    pub fn all_currently_pressed(&self) -> impl Iterator<Item = Button> + '_ {
-        Button::all().filter(|&t| self.is_currently_pressed(t))
-    }
-
-    /// An iterator over all just pressed buttons.
-    pub fn all_just_pressed(&self) -> impl Iterator<Item = Button> + '_ {
-        Button::all().filter(|&t| self.is_just_pressed(t))
-    }
-
-    /// Check if a button has just been pressed.
-    pub const fn is_just_pressed(&self, button: Button) -> bool {
-        let queried_bit = 1 << (button as u32);
-        #[cfg(target_family = "wasm")]
-        {
-            (self.pressed_bits & queried_bit) != 0 && (self.last_pressed_bits & queried_bit) == 0
-        }
-        #[cfg(not(target_family = "wasm"))]
-        {
-            (self.just_pressed_bits & queried_bit) != 0
-        }
-    }
-
-    /// Check if a button is currently pressed.
-    pub const fn is_currently_pressed(&self, button: Button) -> bool {
-        let queried_bit = 1 << (button as u32);
-        (self.pressed_bits & queried_bit) != 0
-    }
-}
-
-/// An opaque gamepad identifier.
-///
-/// Obtained using the [Gamepad::id()] method on a gamepad.
-///
-/// Given a gamepad id, it's possible to get its gamepad state using [Gamepads::get(gamepad_id)](Gamepads::get).
-///
-/// This is a small handle consisting of a single byte.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-#[repr(transparent)]
-pub struct GamepadId(u8);
-
-impl GamepadId {
-    /// The byte value that represents this gamepad id.
-    pub const fn value(self) -> u8 {
-        self.0
-    }
-}
+#[cfg(backend = "wgi")]
+mod backend_wgi;
+#[cfg(backend = "android_winit")]
+mod backend_winit;
+#[cfg(backend = "gilrs")]
+mod background;
+#[cfg(feature = "std")]
+mod battery;
+#[cfg(feature = "std")]
+mod combo;
+#[cfg(feature = "std")]
+mod device_kind;
+#[cfg(feature = "std")]
+mod error;
+#[cfg(any(
+    backend = "evdev",
+    all(any(backend = "gilrs", feature = "uinput"), target_os = "linux")
+))]
+mod evdev_mapping;
+#[cfg(feature = "std")]
+mod event;
+#[cfg(feature = "ffi")]
+mod ffi;
+#[cfg(feature = "std")]
+mod force_feedback;
+#[cfg(feature = "std")]
+mod inject;
+#[cfg(feature = "std")]
+mod input_frame;
+#[cfg(feature = "std")]
+mod instrument;
+#[cfg(feature = "std")]
+mod joycon;
+#[cfg(feature = "std")]
+pub mod js;
+#[cfg(feature = "keyboard")]
+mod keyboard;
+#[cfg(feature = "std")]
+mod latency;
+#[cfg(all(feature = "libnx", target_os = "horizon"))]
+mod libnx;
+#[cfg(feature = "std")]
+mod logging;
+#[cfg(feature = "macroquad")]
+pub mod macroquad;
+#[cfg(feature = "mock")]
+mod mock;
+#[cfg(feature = "mouse")]
+mod mouse;
+#[cfg(all(
+    feature = "playstation",
+    any(target_os = "linux", target_os = "macos", target_os = "windows")
+))]
+mod playstation;
+#[cfg(feature = "std")]
+mod port;
+#[cfg(feature = "std")]
+mod processing;
+#[cfg(feature = "std")]
+mod reader;
+#[cfg(feature = "std")]
+mod recording;
+#[cfg(feature = "std")]
+mod remap;
+#[cfg(feature = "std")]
+mod rumble;
+#[cfg(feature = "std")]
+mod stats;
+#[cfg(all(
+    feature = "steam-input",
+    any(target_os = "linux", target_os = "macos", target_os = "windows")
+))]
+mod steam_input;
+#[cfg(feature = "async")]
+mod stream;
+#[cfg(feature = "tauri")]
+pub mod tauri;
+#[cfg(feature = "std")]
+mod tick;
+#[cfg(feature = "std")]
+mod time;
+#[cfg(feature = "touch")]
+mod touch;
+#[cfg(all(feature = "uinput", target_os = "linux"))]
+mod uinput;
+#[cfg(any(
+    backend = "emscripten",
+    backend = "evdev",
+    backend = "raw_input",
+    backend = "web_bindgen",
+    backend = "web_direct",
+    feature = "tauri"
+))]
+mod web_mapping;
+
+#[cfg(feature = "std")]
+pub use action_state::{ActionInput, ActionState, InputMap};
+#[cfg(feature = "std")]
+pub use availability::Availability;
+#[cfg(feature = "std")]
+pub use backend::{BackendState, Capabilities, GamepadBackend, Motion, NullBackend};
+#[cfg(feature = "std")]
+pub use battery::{Battery, BatteryStatus};
+#[cfg(feature = "std")]
+pub use combo::{Combo, ComboId, ComboStep, Direction};
+#[cfg(feature = "std")]
+pub use device_kind::DeviceKind;
+#[cfg(feature = "std")]
+pub use error::GamepadsError;
+#[cfg(feature = "std")]
+pub use event::{Event, TimestampedEvent};
+#[cfg(feature = "std")]
+pub use force_feedback::ForceFeedback;
+#[cfg(feature = "std")]
+use gamepads_core::MAX_GAMEPADS;
+pub use gamepads_core::{
+    apply_deadzone, Button, Gamepad, GamepadId, GamepadMapping, MAX_BUTTONS, MAX_EXTRA_AXES,
+};
+/// The gilrs crate used by the gilrs backend, see [Gamepads::gilrs()].
+#[cfg(backend = "gilrs")]
+pub use gilrs;
+#[cfg(feature = "ggrs")]
+pub use input_frame::GgrsInput;
+#[cfg(feature = "std")]
+pub use input_frame::InputFrame;
+#[cfg(feature = "std")]
+pub use instrument::{DrumPad, Drums, Fret, Guitar};
+#[cfg(feature = "std")]
+pub use joycon::JoyConPairing;
+#[cfg(feature = "keyboard")]
+pub use keyboard::{Key, KeyAction, KeyboardLayout};
+#[cfg(feature = "std")]
+pub use latency::LatencyStats;
+#[cfg(all(feature = "libnx", target_os = "horizon"))]
+pub use libnx::Npads;
+#[cfg(feature = "mock")]
+pub use mock::MockGamepads;
+#[cfg(feature = "mouse")]
+pub use mouse::{MouseButton, MouseStick};
+#[cfg(all(
+    feature = "playstation",
+    any(target_os = "linux", target_os = "macos", target_os = "windows")
+))]
+pub use playstation::TriggerEffect;
+#[cfg(feature = "std")]
+pub use port::{PortBackend, PortHandle, RumbleRequest};
+#[cfg(feature = "std")]
+pub use reader::GamepadsReader;
+#[cfg(feature = "std")]
+pub use remap::Remap;
+#[cfg(feature = "std")]
+pub use rumble::{RumbleHandle, RumbleStatus};
+#[cfg(feature = "std")]
+pub use stats::PollStats;
+#[cfg(all(
+    feature = "steam-input",
+    any(target_os = "linux", target_os = "macos", target_os = "windows")
+))]
+pub use steam_input::SteamInputBackend;
+#[cfg(feature = "async")]
+pub use stream::EventStream;
+#[cfg(feature = "touch")]
+pub use touch::{TouchControl, TouchControlKind, TouchLayout, TouchPhase};
+#[cfg(all(feature = "uinput", target_os = "linux"))]
+pub use uinput::VirtualGamepad;
 
 /// Context for obtaining gamepad information.
 ///
@@ -286,74 +367,230 @@ impl GamepadId {
 ///
 /// Then use [Gamepads::all()] to list all connected gamepads, or [Gamepads::get(gamepad_id)](Gamepads::get)
 /// to get a gamepad by id.
+#[cfg(feature = "std")]
 pub struct Gamepads {
+    // Raw state as written by the backend:
     gamepads: [Gamepad; MAX_GAMEPADS],
+    // State after processing, as exposed to the user:
+    processed: [Gamepad; MAX_GAMEPADS],
+    processing: [processing::Processing; MAX_GAMEPADS],
+    injected: [inject::Injected; MAX_GAMEPADS],
+    custom_backend: Option<Box<dyn GamepadBackend>>,
+    // Why the platform backend failed to initialize, if it did:
+    backend_failure: Option<Availability>,
+    // Processed state shared with readers on other threads:
+    reader_state: Option<std::sync::Arc<std::sync::RwLock<[Gamepad; MAX_GAMEPADS]>>>,
+    combos: combo::Combos,
+    events: event::Events,
+    // When the backend observed raw changes since the last poll:
+    raw_timestamps: [event::RawTimestamps; MAX_GAMEPADS],
+    last_poll_ms: f64,
+    poll_stats: PollStats,
+    // Raw events read from the backend since the last poll:
+    backend_events: u32,
+    recorder: recording::Recorder,
+    // The number of the last poll:
+    tick: u64,
+    // Latency of button presses, if measured:
+    latency: Option<Box<latency::LatencyTracker>>,
+    joycons: joycon::JoyCons,
+    // Separate units attached to other gamepads:
+    units: attach::Units,
+    // Remappings applied by the kind of each device:
+    profiles: device_kind::Profiles,
+    // Keyboard used as a gamepad, if enabled:
+    #[cfg(feature = "keyboard")]
+    keyboard: Option<Box<keyboard::KeyboardGamepad>>,
+    // Mouse used as a gamepad, if enabled:
+    #[cfg(feature = "mouse")]
+    mouse: Option<Box<mouse::MouseState>>,
+    // On-screen touch gamepad, if enabled:
+    #[cfg(feature = "touch")]
+    touch: Option<Box<touch::TouchGamepad>>,
+    #[cfg(all(
+        feature = "playstation",
+        any(target_os = "linux", target_os = "macos", target_os = "windows")
+    ))]
+    playstation: playstation::Layer,
+
+    // mock backend:
+    #[cfg(feature = "mock")]
+    mock: MockGamepads,
 
     // android winit backend:
-    #[cfg(all(target_os = "android", feature = "android-winit"))]
-    android_winit_gamepad_ids: [winit::event::DeviceId; MAX_GAMEPADS],
-    #[cfg(all(target_os = "android", feature = "android-winit"))]
-    num_connected_pads: u8,
-    #[cfg(all(target_os = "android", feature = "android-winit"))]
-    just_polled: bool,
+    #[cfg(backend = "android_winit")]
+    winit: backend_winit::State,
+
+    // evdev backend:
+    #[cfg(backend = "evdev")]
+    evdev: backend_evdev::State,
+
+    // game controller backend:
+    #[cfg(backend = "game_controller")]
+    game_controller: backend_game_controller::State,
+
+    // game input backend:
+    #[cfg(backend = "game_input")]
+    game_input: backend_game_input::State,
 
     // gilrs backend:
-    #[cfg(not(any(target_family = "wasm", target_os = "android")))]
-    gilrs_gamepad_ids: [usize; MAX_GAMEPADS],
-    #[cfg(not(any(target_family = "wasm", target_os = "android")))]
-    gilrs_instance: gilrs::Gilrs,
-    #[cfg(not(any(target_family = "wasm", target_os = "android")))]
-    num_connected_pads: u8,
-    #[cfg(not(any(target_family = "wasm", target_os = "android")))]
-    deadzones: [[f32; 4]; MAX_GAMEPADS],
-    #[cfg(not(any(target_family = "wasm", target_os = "android")))]
-    playing_ff_effects: Vec<(gilrs::ff::Effect, u128)>,
+    #[cfg(backend = "gilrs")]
+    gilrs: backend_gilrs::State,
+    #[cfg(backend = "gilrs")]
+    background_poller: Option<background::BackgroundPoller>,
+
+    // paddleboat backend:
+    #[cfg(backend = "paddleboat")]
+    paddleboat: backend_paddleboat::State,
+
+    // raw input backend:
+    #[cfg(backend = "raw_input")]
+    raw_input: backend_raw_input::State,
+
+    // sdl3 backend:
+    #[cfg(backend = "sdl3")]
+    sdl3: backend_sdl3::State,
+
+    // wgi backend:
+    #[cfg(backend = "wgi")]
+    wgi: backend_wgi::State,
 }
 
+#[cfg(feature = "std")]
 impl Gamepads {
     /// Construct a new gamepads instance.
+    ///
+    /// If the platform backend cannot be initialized, such as when running in a container
+    /// without udev, the [NullBackend] is used and no gamepads are reported.
+    /// Use [Gamepads::try_new()] to detect this.
     #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
+        Self::try_new().unwrap_or_else(|error| {
+            #[cfg(backend = "gilrs")]
+            let mut gamepads = Self::construct(None);
+            #[cfg(backend = "game_input")]
+            let mut gamepads = Self::construct(backend_game_input::State::default());
+            #[cfg(not(any(backend = "game_input", backend = "gilrs")))]
+            let mut gamepads = Self::construct();
+            gamepads.set_backend(NullBackend);
+            logging::warning!("No gamepads will be reported: {error}");
+            gamepads.backend_failure = Some(Availability::from(&error));
+            gamepads
+        })
+    }
+
+    /// Construct a new gamepads instance, failing if the platform backend cannot be initialized.
+    pub fn try_new() -> Result<Self, GamepadsError> {
+        #[cfg(backend = "gilrs")]
+        {
+            Ok(Self::construct(Some(gilrs::Gilrs::new()?)))
+        }
+        #[cfg(backend = "game_input")]
+        {
+            let state = backend_game_input::State::new()
+                .map_err(|error| GamepadsError::BackendInit(error.into()))?;
+            Ok(Self::construct(state))
+        }
+        #[cfg(not(any(backend = "game_input", backend = "gilrs")))]
+        {
+            #[cfg(backend = "emscripten")]
+            if !backend_emscripten::is_supported() {
+                return Err(GamepadsError::Unsupported);
+            }
+            Ok(Self::construct())
+        }
+    }
+
+    fn construct(
+        #[cfg(backend = "gilrs")] gilrs_instance: Option<gilrs::Gilrs>,
+        #[cfg(backend = "game_input")] game_input: backend_game_input::State,
+    ) -> Self {
         #[cfg(target_os = "android")]
         android_logger::Config::default().with_max_level(log::LevelFilter::Warn);
 
+        let initial_gamepads = std::array::from_fn(|idx| Gamepad::new(GamepadId(idx as u8)));
         let mut gamepads = Self {
-            gamepads: std::array::from_fn(|idx| Gamepad {
-                id: GamepadId(idx as u8),
-                connected: false,
-                pressed_bits: 0,
-                axes: [0.; 4],
-                #[cfg(target_family = "wasm")]
-                last_pressed_bits: 0,
-                #[cfg(not(target_family = "wasm"))]
-                just_pressed_bits: 0,
-            }),
-
-            // android backend:
-            #[cfg(all(target_os = "android", feature = "android-winit"))]
-            android_winit_gamepad_ids: [unsafe { winit::event::DeviceId::dummy() }; MAX_GAMEPADS],
-            #[cfg(all(target_os = "android", feature = "android-winit"))]
-            num_connected_pads: 0,
-            #[cfg(all(target_os = "android", feature = "android-winit"))]
-            just_polled: false,
+            gamepads: initial_gamepads,
+            processed: initial_gamepads,
+            processing: [processing::Processing::default(); MAX_GAMEPADS],
+            injected: [inject::Injected::default(); MAX_GAMEPADS],
+            custom_backend: None,
+            backend_failure: None,
+            reader_state: None,
+            combos: combo::Combos::default(),
+            events: event::Events::default(),
+            raw_timestamps: [event::RawTimestamps::default(); MAX_GAMEPADS],
+            last_poll_ms: 0.,
+            poll_stats: PollStats::default(),
+            backend_events: 0,
+            recorder: recording::Recorder::Inactive,
+            tick: 0,
+            latency: None,
+            joycons: joycon::JoyCons::default(),
+            units: attach::Units::default(),
+            profiles: device_kind::Profiles::default(),
+            #[cfg(feature = "keyboard")]
+            keyboard: None,
+            #[cfg(feature = "mouse")]
+            mouse: None,
+            #[cfg(feature = "touch")]
+            touch: None,
+            #[cfg(all(
+                feature = "playstation",
+                any(target_os = "linux", target_os = "macos", target_os = "windows")
+            ))]
+            playstation: playstation::Layer::new(),
+
+            // mock backend:
+            #[cfg(feature = "mock")]
+            mock: MockGamepads::new(),
+
+            // android winit backend:
+            #[cfg(backend = "android_winit")]
+            winit: backend_winit::State::new(),
+
+            // evdev backend:
+            #[cfg(backend = "evdev")]
+            evdev: backend_evdev::State::new(),
+
+            // game controller backend:
+            #[cfg(backend = "game_controller")]
+            game_controller: backend_game_controller::State::default(),
+
+            // game input backend:
+            #[cfg(backend = "game_input")]
+            game_input,
 
             // gilrs backend:
-            #[cfg(not(any(target_family = "wasm", target_os = "android")))]
-            gilrs_gamepad_ids: [usize::MAX; MAX_GAMEPADS],
-            #[cfg(not(any(target_family = "wasm", target_os = "android")))]
-            gilrs_instance: gilrs::Gilrs::new().unwrap(),
-            #[cfg(not(any(target_family = "wasm", target_os = "android")))]
-            num_connected_pads: 0,
-            #[cfg(not(any(target_family = "wasm", target_os = "android")))]
-            deadzones: [[0.; 4]; MAX_GAMEPADS],
-            #[cfg(not(any(target_family = "wasm", target_os = "android")))]
-            playing_ff_effects: Vec::new(),
+            #[cfg(backend = "gilrs")]
+            gilrs: backend_gilrs::State::new(gilrs_instance),
+            #[cfg(backend = "gilrs")]
+            background_poller: None,
+
+            // paddleboat backend:
+            #[cfg(backend = "paddleboat")]
+            paddleboat: backend_paddleboat::State::new(),
+
+            // raw input backend:
+            #[cfg(backend = "raw_input")]
+            raw_input: backend_raw_input::State::new(),
+
+            // sdl3 backend:
+            #[cfg(backend = "sdl3")]
+            sdl3: backend_sdl3::State::new(),
+
+            // wgi backend:
+            #[cfg(backend = "wgi")]
+            wgi: backend_wgi::State::default(),
         };
 
         gamepads.poll();
 
-        #[cfg(not(any(target_family = "wasm", target_os = "android")))]
-        gamepads.setup_initially_connected_gilrs();
+        #[cfg(backend = "gilrs")]
+        {
+            gamepads.setup_initially_connected_gilrs();
+            gamepads.process();
+        }
 
         gamepads
     }
@@ -363,7 +600,14 @@ impl Gamepads {
     /// The gamepad state obtained here will reflect the state the last time [Gamepads::poll()]
     /// was called.
     pub fn get(&self, gamepad_id: GamepadId) -> Option<Gamepad> {
-        let pad = self.gamepads[gamepad_id.0 as usize];
+        self.get_ref(gamepad_id).copied()
+    }
+
+    /// Borrow a gamepad by id, returning `None` if it is no longer connected.
+    ///
+    /// Like [Gamepads::get()], but without copying the gamepad state.
+    pub fn get_ref(&self, gamepad_id: GamepadId) -> Option<&Gamepad> {
+        let pad = &self.processed[gamepad_id.0 as usize];
         pad.connected.then_some(pad)
     }
 
@@ -372,7 +616,24 @@ impl Gamepads {
     /// The gamepad state obtained here will reflect the state the last time [Gamepads::poll()]
     /// was called.
     pub fn all(&self) -> impl Iterator<Item = Gamepad> {
-        self.gamepads.into_iter().filter(|p| p.connected)
+        self.processed.into_iter().filter(|p| p.connected)
+    }
+
+    /// Iterate over all connected gamepads by reference.
+    ///
+    /// Like [Gamepads::all()], but without copying the gamepad state.
+    ///
+    /// ```
+    /// use gamepads::Gamepads;
+    ///
+    /// let mut gamepads = Gamepads::new();
+    /// gamepads.poll();
+    /// for gamepad in gamepads.iter() {
+    ///     println!("Left thumbstick: {:?}", gamepad.left_stick());
+    /// }
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = &Gamepad> {
+        self.processed.iter().filter(|p| p.connected)
     }
 
     /// Provide haptic feedback by rumbling the gamepad (if supported).
@@ -389,6 +650,9 @@ impl Gamepads {
     /// * `start_delay_ms` - Delay of the rumble in milliseconds
     /// * `strong_magnitude` - The vibration magnitude for the low frequency rumble in the range `[0.0, 1.0]`
     /// * `weak_magnitude` - The vibration magnitude for the high frequency rumble in the range `[0.0, 1.0]`
+    ///
+    /// The returned [RumbleHandle] reports when the effect has finished on the web, where
+    /// browsers report it. Dropping it does not stop the effect.
     pub fn rumble(
         &mut self,
         gamepad_id: GamepadId,
@@ -396,21 +660,75 @@ impl Gamepads {
         start_delay_ms: u32,
         strong_magnitude: f32,
         weak_magnitude: f32,
-    ) {
-        #[cfg(target_family = "wasm")]
+    ) -> RumbleHandle {
+        self.rumble_triggers(
+            gamepad_id,
+            duration_ms,
+            start_delay_ms,
+            strong_magnitude,
+            weak_magnitude,
+            (0., 0.),
+        )
+    }
+
+    /// Rumble a gamepad like [Gamepads::rumble()], while also vibrating the motors in its triggers.
+    ///
+    /// This is the "trigger-rumble" effect of Xbox controllers on the web, with the `backend-wgi`
+    /// and `backend-game-input` features on Windows and with the `backend-sdl3` feature, where
+    /// `trigger_magnitudes` are the vibration magnitudes of the left and right trigger in the
+    /// range `[0.0, 1.0]`. Elsewhere, or if the gamepad has no trigger motors, the triggers do not
+    /// vibrate.
+    pub fn rumble_triggers(
+        &mut self,
+        gamepad_id: GamepadId,
+        duration_ms: u32,
+        start_delay_ms: u32,
+        strong_magnitude: f32,
+        weak_magnitude: f32,
+        trigger_magnitudes: (f32, f32),
+    ) -> RumbleHandle {
+        if let Some(right_id) = self.joycons.paired_right(gamepad_id) {
+            self.rumble_triggers(
+                right_id,
+                duration_ms,
+                start_delay_ms,
+                strong_magnitude,
+                weak_magnitude,
+                trigger_magnitudes,
+            );
+        }
+        if let Some(backend) = &mut self.custom_backend {
+            backend.rumble(
+                gamepad_id,
+                duration_ms,
+                start_delay_ms,
+                strong_magnitude,
+                weak_magnitude,
+            );
+            return RumbleHandle::new(RumbleStatus::Unknown);
+        }
+        #[cfg(all(
+            feature = "playstation",
+            any(target_os = "linux", target_os = "macos", target_os = "windows")
+        ))]
+        if self.rumble_playstation(
+            gamepad_id,
+            duration_ms,
+            start_delay_ms,
+            strong_magnitude,
+            weak_magnitude,
+        ) {
+            return RumbleHandle::new(RumbleStatus::Unknown);
+        }
+        #[cfg(feature = "mock")]
         {
-            #[cfg(not(feature = "wasm-bindgen"))]
-            unsafe {
-                backend_web_direct::playEffect(
-                    gamepad_id.0,
-                    duration_ms,
-                    start_delay_ms,
-                    strong_magnitude,
-                    weak_magnitude,
-                );
-            }
-            #[cfg(feature = "wasm-bindgen")]
-            backend_web_bindgen::play_effect(
+            let _ = (duration_ms, start_delay_ms);
+            self.mock
+                .rumble(gamepad_id, strong_magnitude, weak_magnitude);
+        }
+        #[cfg(backend = "wasi_host")]
+        unsafe {
+            backend_wasi::play_effect(
                 gamepad_id.0,
                 duration_ms,
                 start_delay_ms,
@@ -418,17 +736,109 @@ impl Gamepads {
                 weak_magnitude,
             );
         }
-        #[cfg(not(any(target_family = "wasm", target_os = "android")))]
+        #[cfg(backend = "web_direct")]
+        let handle = RumbleHandle::web_direct(unsafe {
+            backend_web_direct::playEffect(
+                gamepad_id.0,
+                duration_ms,
+                start_delay_ms,
+                strong_magnitude,
+                weak_magnitude,
+                trigger_magnitudes.0,
+                trigger_magnitudes.1,
+            )
+        });
+        #[cfg(backend = "web_bindgen")]
+        let handle = backend_web_bindgen::play_effect(
+            gamepad_id.0,
+            duration_ms,
+            start_delay_ms,
+            strong_magnitude,
+            weak_magnitude,
+            trigger_magnitudes,
+        );
+        #[cfg(backend = "evdev")]
+        backend_evdev::rumble(
+            &mut self.evdev,
+            gamepad_id,
+            duration_ms,
+            start_delay_ms,
+            strong_magnitude,
+            weak_magnitude,
+        );
+        #[cfg(backend = "game_controller")]
+        backend_game_controller::rumble(
+            &mut self.game_controller,
+            gamepad_id,
+            duration_ms,
+            start_delay_ms,
+            strong_magnitude,
+            weak_magnitude,
+        );
+        #[cfg(backend = "paddleboat")]
+        backend_paddleboat::rumble(
+            &mut self.paddleboat,
+            gamepad_id,
+            duration_ms,
+            start_delay_ms,
+            strong_magnitude,
+            weak_magnitude,
+        );
+        #[cfg(backend = "raw_input")]
+        backend_raw_input::rumble(
+            &mut self.raw_input,
+            gamepad_id,
+            duration_ms,
+            start_delay_ms,
+            strong_magnitude,
+            weak_magnitude,
+        );
+        // The native backends with trigger rumble, which share a signature:
+        #[cfg(any(backend = "game_input", backend = "sdl3", backend = "wgi"))]
         {
-            self.rumble_gilrs(
+            #[cfg(backend = "game_input")]
+            let (rumble, state) = (backend_game_input::rumble, &mut self.game_input);
+            #[cfg(backend = "sdl3")]
+            let (rumble, state) = (backend_sdl3::rumble, &mut self.sdl3);
+            #[cfg(backend = "wgi")]
+            let (rumble, state) = (backend_wgi::rumble, &mut self.wgi);
+            rumble(
+                state,
                 gamepad_id,
                 duration_ms,
                 start_delay_ms,
                 strong_magnitude,
                 weak_magnitude,
+                trigger_magnitudes,
             );
         }
-        #[cfg(all(target_os = "android", feature = "android-winit"))]
+        #[cfg(not(any(
+            backend = "game_input",
+            backend = "sdl3",
+            backend = "web_bindgen",
+            backend = "web_direct",
+            backend = "wgi"
+        )))]
+        let _ = trigger_magnitudes;
+        #[cfg(not(any(backend = "web_bindgen", backend = "web_direct")))]
+        let handle = RumbleHandle::new(RumbleStatus::Unknown);
+        #[cfg(any(backend = "emscripten", backend = "none"))]
+        let _ = (
+            gamepad_id,
+            duration_ms,
+            start_delay_ms,
+            strong_magnitude,
+            weak_magnitude,
+        );
+        #[cfg(backend = "gilrs")]
+        self.rumble_gilrs(
+            gamepad_id,
+            duration_ms,
+            start_delay_ms,
+            strong_magnitude,
+            weak_magnitude,
+        );
+        #[cfg(backend = "android_winit")]
         {
             self.rumble_android(
                 gamepad_id,
@@ -438,173 +848,168 @@ impl Gamepads {
                 weak_magnitude,
             );
         }
+        handle
     }
 
     /// Update gamepad state.
     ///
     /// Should be called on each tick before reading gamepad state.
     pub fn poll(&mut self) {
-        #[cfg(target_os = "android")]
-        {
-            self.poll_android_winit();
+        let started_ms = time::now_ms();
+        self.tick += 1;
+        if !self.poll_custom_backend() {
+            self.poll_platform();
+            #[cfg(all(
+                feature = "playstation",
+                any(target_os = "linux", target_os = "macos", target_os = "windows")
+            ))]
+            self.poll_playstation();
+        }
+        #[cfg(feature = "mouse")]
+        self.update_mouse_stick();
+        let polled_ms = time::now_ms();
+        if let Some(replayed) = self.next_recording_frame() {
+            // Process the replayed state while keeping the backend state intact:
+            let backend_state = std::mem::replace(&mut self.gamepads, replayed);
+            self.raw_timestamps = [event::RawTimestamps::default(); MAX_GAMEPADS];
+            self.process();
+            self.gamepads = backend_state;
+        } else {
+            self.process();
         }
-        #[cfg(not(any(target_family = "wasm", target_os = "android")))]
+        let processed_ms = time::now_ms();
+        self.poll_stats = PollStats {
+            total_ms: processed_ms - started_ms,
+            backend_ms: polled_ms - started_ms,
+            processing_ms: processed_ms - polled_ms,
+            events: self.events().count(),
+            backend_events: std::mem::take(&mut self.backend_events),
+        };
+        self.record_latency(processed_ms);
+    }
+
+    /// Update the raw state from the platform backend.
+    // Without a backend there is nothing to update:
+    #[cfg_attr(
+        backend = "none",
+        allow(clippy::missing_const_for_fn, clippy::needless_pass_by_ref_mut)
+    )]
+    fn poll_platform(&mut self) {
+        #[cfg(feature = "mock")]
+        self.mock
+            .take_state(&mut self.gamepads, &mut self.raw_timestamps);
+        #[cfg(backend = "android_winit")]
         {
+            self.poll_winit();
+        }
+        #[cfg(backend = "gilrs")]
+        if let Some(background_poller) = &self.background_poller {
+            background_poller.take_state(&mut self.gamepads, &mut self.raw_timestamps);
+            // For Gamepads::gilrs_id() to keep working while the state is in the thread:
+            background_poller.with_gilrs(|state| self.gilrs.copy_gamepad_ids(state));
+        } else {
             self.poll_gilrs();
         }
-        #[cfg(target_family = "wasm")]
+        #[cfg(any(
+            backend = "emscripten",
+            backend = "evdev",
+            backend = "game_controller",
+            backend = "game_input",
+            backend = "paddleboat",
+            backend = "raw_input",
+            backend = "sdl3",
+            backend = "wasi_host",
+            backend = "web_bindgen",
+            backend = "web_direct",
+            backend = "wgi"
+        ))]
         {
+            let previously_pressed_bits = self
+                .gamepads
+                .map(|gamepad| (gamepad.pressed_bits, gamepad.high_pressed_bits));
             for gamepad in self.gamepads.iter_mut() {
-                gamepad.last_pressed_bits = gamepad.pressed_bits;
+                gamepad.just_pressed_bits = 0;
+                gamepad.high_just_pressed_bits = 0;
             }
-            #[cfg(not(feature = "wasm-bindgen"))]
+            #[cfg(backend = "wasi_host")]
             {
                 let pointer = self.gamepads.as_ptr();
-                unsafe { backend_web_direct::getGamepads(pointer) }
+                unsafe { backend_wasi::get_gamepads(pointer, js::PROTOCOL_VERSION) }
             }
-            #[cfg(feature = "wasm-bindgen")]
+            #[cfg(backend = "web_direct")]
+            {
+                backend_web_direct::get_gamepads(&mut self.gamepads);
+                self.poll_remapped_web_direct();
+            }
+            #[cfg(backend = "web_bindgen")]
             {
                 backend_web_bindgen::poll(self);
             }
+            #[cfg(backend = "emscripten")]
+            {
+                backend_emscripten::poll(self);
+            }
+            #[cfg(backend = "evdev")]
+            {
+                backend_evdev::poll(self);
+            }
+            #[cfg(backend = "game_controller")]
+            {
+                backend_game_controller::poll(self);
+            }
+            #[cfg(backend = "game_input")]
+            {
+                backend_game_input::poll(self);
+            }
+            #[cfg(backend = "paddleboat")]
+            {
+                backend_paddleboat::poll(self);
+            }
+            #[cfg(backend = "raw_input")]
+            {
+                backend_raw_input::poll(self);
+            }
+            #[cfg(backend = "sdl3")]
+            {
+                backend_sdl3::poll(self);
+            }
+            #[cfg(backend = "wgi")]
+            {
+                backend_wgi::poll(self);
+            }
+            for (gamepad, (previously_pressed_bits, previously_high_pressed_bits)) in
+                self.gamepads.iter_mut().zip(previously_pressed_bits)
+            {
+                // The backend may have reported presses in between polls, in addition to these:
+                gamepad.just_pressed_bits |= gamepad.pressed_bits & !previously_pressed_bits;
+                gamepad.high_just_pressed_bits |=
+                    gamepad.high_pressed_bits & !previously_high_pressed_bits;
+            }
         }
     }
-}
 
-/// A button on a gamepad.
-///
-/// Check for the current state of button presses on a gamepad using one of:
-///
-/// - [Gamepad::all_currently_pressed()]
-/// - [Gamepad::all_just_pressed()]
-/// - [Gamepad::is_currently_pressed()]
-/// - [Gamepad::is_just_pressed()]
-///
-/// Different platforms call the buttons different things, see the below pictures for an overview, as
-/// well as the individual button documentations for a comparison.
-///
-/// # Playstation
-/// ![Playstation gamepad layout](https://www.gran-turismo.com/images/c/i17AZsIsc9rpTb.jpg)
-///
-/// # Switch
-/// ![Switch gamepad layout](https://oyster.ignimgs.com/mediawiki/apis.ign.com/nintendo-nx/b/bb/Joycon.jpg?width=960)
-///
-/// # Xbox
-/// ![Xbox gamepad layout](https://upload.wikimedia.org/wikipedia/commons/thumb/2/2c/360_controller.svg/2880px-360_controller.svg.png)
-///
-/// # W3C Gamepad API standard gamepad layout:
-/// ![Visual representation of a Standard Gamepad layout](https://w3c.github.io/gamepad/standard_gamepad.svg)
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub enum Button {
-    /// Lowermost button in right cluster
-    ///
-    /// - Playstation: `X`/`Cross` button
-    /// - Switch: `B` button
-    /// - Xbox: `A` button
-    /// - Gamepad API: `buttons[0]` / `Bottom button in right cluster`
-    ActionDown,
-    /// Rightmost button in right cluster
-    ///
-    /// - Gamepad API: `buttons[1]` / `Right button in right cluster`
-    /// - Playstation: `O`/`Circle` button
-    /// - Switch: `A`
-    /// - Xbox: `B`
-    ActionRight,
-    /// Leftmost button in right cluster
-    ///
-    /// - Gamepad API: `buttons[2]` / `Left button in right cluster`
-    /// - Playstation: `□`/`Square`
-    /// - Switch: `Y`
-    /// - Xbox: `X`
-    ActionLeft,
-    /// Topmost button in right cluster
-    ///
-    /// - Gamepad API: `buttons[3]` / `Top button in right cluster`
-    /// - Playstation: `△`/`Triangle`
-    /// - Switch: `X`
-    /// - Xbox: `Y`
-    ActionUp,
-    /// Top left front button
-    ///
-    /// - Gamepad API: `buttons[4]` / `Top left front button`
-    /// - Playstation: `L1`
-    /// - Switch: `L`
-    /// - Xbox: `LB` (`Left Bumper`)
-    FrontLeftUpper,
-    /// Top right front button
-    ///
-    /// - Gamepad API: `buttons[5]` / `Top right front button`
-    /// - Playstation: `R1`
-    /// - Switch: `R`
-    /// - Xbox: `RB` (`Right Bumper`)
-    FrontRightUpper,
-    /// Bottom left front button
+    /// Block until a button is pressed on any gamepad, polling repeatedly while waiting.
     ///
-    /// - Gamepad API: `buttons[6]` / `Bottom left front button`
-    /// - Playstation: `L2`
-    /// - Switch: `ZL`
-    /// - Xbox: `LT` (`Left Trigger`)
-    FrontLeftLower,
-    /// Bottom right front button
+    /// Returns the gamepad and button pressed, or `None` if nothing was pressed within the timeout.
+    /// This is useful for e.g. "Press any button to start" screens and command line tools.
     ///
-    /// - Gamepad API: `buttons[7]` / `Bottom right front button`
-    /// - Playstation: `R2`
-    /// - Switch: `ZR`
-    /// - Xbox: `RT` (`Right Trigger`)
-    FrontRightLower,
-    /// Left button in center cluster - select/back
-    ///
-    /// - Gamepad API: `buttons[8]` / `Left button in center cluster`
-    /// - Playstation: `SELECT`
-    /// - Switch: `Capture`
-    /// - Xbox: `RT` (`Right Trigger`)
-    LeftCenterCluster,
-    /// Right button in center cluster - start/forward.
-    ///
-    /// - Gamepad API: `buttons[9]` / `Right button in center cluster`
-    /// - Playstation: `Start`
-    /// - Switch: `Home`
-    /// - Xbox: `Start`
-    RightCenterCluster,
-    /// Left stick pressed button.
-    LeftStick,
-    /// Right stick pressed button.
-    RightStick,
-    /// D-pad up button.
-    DPadUp,
-    /// D-pad down button.
-    DPadDown,
-    /// D-pad left button.
-    DPadLeft,
-    /// D-pad right button.
-    DPadRight,
-    /// Mode button.
-    ///
-    /// - Gamepad API: `buttons[16]` / `Center button in center cluster`
-    Mode,
-}
-
-impl Button {
-    /// An iterator over all button types.
-    pub fn all() -> impl Iterator<Item = Self> {
-        [
-            Self::ActionDown,
-            Self::ActionRight,
-            Self::ActionLeft,
-            Self::ActionUp,
-            Self::FrontLeftUpper,
-            Self::FrontRightUpper,
-            Self::FrontLeftLower,
-            Self::FrontRightLower,
-            Self::LeftCenterCluster,
-            Self::RightCenterCluster,
-            Self::LeftStick,
-            Self::RightStick,
-            Self::DPadUp,
-            Self::DPadDown,
-            Self::DPadLeft,
-            Self::DPadRight,
-            Self::Mode,
-        ]
-        .into_iter()
+    /// Not available on wasm, where blocking is not possible.
+    #[cfg(not(target_family = "wasm"))]
+    pub fn wait_for_input(&mut self, timeout: std::time::Duration) -> Option<(GamepadId, Button)> {
+        const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(4);
+        let started_at = std::time::Instant::now();
+        loop {
+            self.poll();
+            for gamepad in self.all() {
+                if let Some(button) = gamepad.all_just_pressed().next() {
+                    return Some((gamepad.id(), button));
+                }
+            }
+            let elapsed = started_at.elapsed();
+            if elapsed >= timeout {
+                return None;
+            }
+            std::thread::sleep(POLL_INTERVAL.min(timeout - elapsed));
+        }
     }
 }
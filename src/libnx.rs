@@ -0,0 +1,285 @@
+//! Reading the controllers of the Nintendo Switch using the HID services of libnx, for homebrew
+//! built with devkitPro.
+//!
+//! The homebrew target has no `std`, so there is no [Gamepads](crate::Gamepads) instance there,
+//! and [Npads] instead keeps a [Gamepad] for each player slot, fed from libnx on each poll.
+
+use crate::{Button, Gamepad, GamepadId, GamepadMapping};
+use gamepads_core::MAX_GAMEPADS;
+
+/// `HidNpadIdType`, of which `No1` to `No8` are the player slots.
+type NpadIdType = u32;
+const NPAD_ID_HANDHELD: NpadIdType = 0x20;
+
+/// `HidNpadStyleTag` bits of the styles read.
+const STYLE_FULL_KEY: u32 = 1 << 0;
+const STYLE_HANDHELD: u32 = 1 << 1;
+const STYLE_JOY_DUAL: u32 = 1 << 2;
+const STYLE_JOY_LEFT: u32 = 1 << 3;
+const STYLE_JOY_RIGHT: u32 = 1 << 4;
+
+/// The `HidNpadAttribute` bit telling that a controller is connected.
+const ATTRIBUTE_IS_CONNECTED: u32 = 1 << 0;
+
+/// `HidNpadButton` bits.
+const NPAD_A: u64 = 1 << 0;
+const NPAD_B: u64 = 1 << 1;
+const NPAD_X: u64 = 1 << 2;
+const NPAD_Y: u64 = 1 << 3;
+const NPAD_STICK_L: u64 = 1 << 4;
+const NPAD_STICK_R: u64 = 1 << 5;
+const NPAD_L: u64 = 1 << 6;
+const NPAD_R: u64 = 1 << 7;
+const NPAD_ZL: u64 = 1 << 8;
+const NPAD_ZR: u64 = 1 << 9;
+const NPAD_PLUS: u64 = 1 << 10;
+const NPAD_MINUS: u64 = 1 << 11;
+const NPAD_LEFT: u64 = 1 << 12;
+const NPAD_UP: u64 = 1 << 13;
+const NPAD_RIGHT: u64 = 1 << 14;
+const NPAD_DOWN: u64 = 1 << 15;
+const NPAD_LEFT_SL: u64 = 1 << 24;
+const NPAD_LEFT_SR: u64 = 1 << 25;
+const NPAD_RIGHT_SL: u64 = 1 << 26;
+const NPAD_RIGHT_SR: u64 = 1 << 27;
+
+/// The buttons of controllers held upright, where the layout follows the position of the
+/// buttons and not their labels, as for the Switch in the documentation of [Button].
+const BUTTONS: [(u64, Button); 16] = [
+    (NPAD_B, Button::ActionDown),
+    (NPAD_A, Button::ActionRight),
+    (NPAD_Y, Button::ActionLeft),
+    (NPAD_X, Button::ActionUp),
+    (NPAD_L, Button::FrontLeftUpper),
+    (NPAD_R, Button::FrontRightUpper),
+    (NPAD_ZL, Button::FrontLeftLower),
+    (NPAD_ZR, Button::FrontRightLower),
+    (NPAD_MINUS, Button::LeftCenterCluster),
+    (NPAD_PLUS, Button::RightCenterCluster),
+    (NPAD_STICK_L, Button::LeftStick),
+    (NPAD_STICK_R, Button::RightStick),
+    (NPAD_UP, Button::DPadUp),
+    (NPAD_DOWN, Button::DPadDown),
+    (NPAD_LEFT, Button::DPadLeft),
+    (NPAD_RIGHT, Button::DPadRight),
+];
+
+/// The buttons of a single left Joy-Con held sideways, with the rail on top.
+const JOY_LEFT_BUTTONS: [(u64, Button); 8] = [
+    (NPAD_LEFT, Button::ActionDown),
+    (NPAD_DOWN, Button::ActionRight),
+    (NPAD_UP, Button::ActionLeft),
+    (NPAD_RIGHT, Button::ActionUp),
+    (NPAD_LEFT_SL, Button::FrontLeftUpper),
+    (NPAD_LEFT_SR, Button::FrontRightUpper),
+    (NPAD_MINUS, Button::RightCenterCluster),
+    (NPAD_STICK_L, Button::LeftStick),
+];
+
+/// The buttons of a single right Joy-Con held sideways, with the rail on top.
+const JOY_RIGHT_BUTTONS: [(u64, Button); 8] = [
+    (NPAD_A, Button::ActionDown),
+    (NPAD_X, Button::ActionRight),
+    (NPAD_B, Button::ActionLeft),
+    (NPAD_Y, Button::ActionUp),
+    (NPAD_RIGHT_SL, Button::FrontLeftUpper),
+    (NPAD_RIGHT_SR, Button::FrontRightUpper),
+    (NPAD_PLUS, Button::RightCenterCluster),
+    (NPAD_STICK_R, Button::LeftStick),
+];
+
+/// The maximum value of thumbstick axes, `JOYSTICK_MAX`.
+const STICK_MAX: f32 = 32767.;
+
+/// `HidAnalogStickState`, positive upwards.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct AnalogStickState {
+    x: i32,
+    y: i32,
+}
+
+/// `HidNpadCommonState`, which the states of all styles read are.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct NpadCommonState {
+    sampling_number: u64,
+    buttons: u64,
+    analog_stick_l: AnalogStickState,
+    analog_stick_r: AnalogStickState,
+    attributes: u32,
+    reserved: u32,
+}
+
+type GetStates = unsafe extern "C" fn(NpadIdType, *mut NpadCommonState, usize) -> usize;
+
+#[link(name = "nx")]
+extern "C" {
+    fn hidInitializeNpad();
+    fn hidSetSupportedNpadStyleSet(style_set: u32) -> u32;
+    fn hidSetSupportedNpadIdType(ids: *const NpadIdType, count: usize) -> u32;
+    fn hidGetNpadStyleSet(id: NpadIdType) -> u32;
+    fn hidGetNpadStatesFullKey(id: NpadIdType, states: *mut NpadCommonState, count: usize)
+        -> usize;
+    fn hidGetNpadStatesHandheld(
+        id: NpadIdType,
+        states: *mut NpadCommonState,
+        count: usize,
+    ) -> usize;
+    fn hidGetNpadStatesJoyDual(id: NpadIdType, states: *mut NpadCommonState, count: usize)
+        -> usize;
+    fn hidGetNpadStatesJoyLeft(id: NpadIdType, states: *mut NpadCommonState, count: usize)
+        -> usize;
+    fn hidGetNpadStatesJoyRight(
+        id: NpadIdType,
+        states: *mut NpadCommonState,
+        count: usize,
+    ) -> usize;
+}
+
+/// The latest state of a controller together with its style, or `None` if no supported
+/// controller is connected.
+fn read_state(id: NpadIdType) -> Option<(NpadCommonState, u32)> {
+    let style_set = unsafe { hidGetNpadStyleSet(id) };
+    let (get_states, style): (GetStates, u32) = if style_set & STYLE_FULL_KEY != 0 {
+        (hidGetNpadStatesFullKey, STYLE_FULL_KEY)
+    } else if style_set & STYLE_HANDHELD != 0 {
+        (hidGetNpadStatesHandheld, STYLE_HANDHELD)
+    } else if style_set & STYLE_JOY_DUAL != 0 {
+        (hidGetNpadStatesJoyDual, STYLE_JOY_DUAL)
+    } else if style_set & STYLE_JOY_LEFT != 0 {
+        (hidGetNpadStatesJoyLeft, STYLE_JOY_LEFT)
+    } else if style_set & STYLE_JOY_RIGHT != 0 {
+        (hidGetNpadStatesJoyRight, STYLE_JOY_RIGHT)
+    } else {
+        return None;
+    };
+    let mut state = NpadCommonState::default();
+    let count = unsafe { get_states(id, &mut state, 1) };
+    (count > 0 && state.attributes & ATTRIBUTE_IS_CONNECTED != 0).then_some((state, style))
+}
+
+/// The controllers of the Nintendo Switch, read using libnx and enabled by the `libnx` feature
+/// on the `aarch64-nintendo-switch-freestanding` target.
+///
+/// Each player slot, as assigned by the system applet for choosing controllers, is reported as
+/// the gamepad with the same index, while the Joy-Cons attached to the console in handheld mode
+/// are reported as the first gamepad while no controller is in the first slot. Single Joy-Cons
+/// are held sideways, with their thumbstick as the left thumbstick, the SL and SR buttons as
+/// [Button::FrontLeftUpper] and [Button::FrontRightUpper], and their minus or plus button as
+/// [Button::RightCenterCluster]. The HOME and Capture buttons are reserved by the system, and
+/// not reported.
+///
+/// ```no_run
+/// use gamepads::{Button, Npads};
+///
+/// let mut npads = Npads::new();
+/// loop {
+///     npads.poll();
+///     for gamepad in npads.all() {
+///         if gamepad.is_just_pressed(Button::ActionDown) {
+///             // Jump.
+///         }
+///     }
+/// }
+/// ```
+pub struct Npads {
+    gamepads: [Gamepad; MAX_GAMEPADS],
+}
+
+impl Npads {
+    /// Initialize the HID services for reading controllers.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        // The player slots, followed by the Joy-Cons attached in handheld mode:
+        let ids: [NpadIdType; MAX_GAMEPADS + 1] = core::array::from_fn(|idx| {
+            if idx < MAX_GAMEPADS {
+                idx as NpadIdType
+            } else {
+                NPAD_ID_HANDHELD
+            }
+        });
+        unsafe {
+            hidInitializeNpad();
+            hidSetSupportedNpadStyleSet(
+                STYLE_FULL_KEY | STYLE_HANDHELD | STYLE_JOY_DUAL | STYLE_JOY_LEFT | STYLE_JOY_RIGHT,
+            );
+            hidSetSupportedNpadIdType(ids.as_ptr(), ids.len());
+        }
+        Self {
+            gamepads: core::array::from_fn(|idx| {
+                let mut gamepad = Gamepad::new(GamepadId(idx as u8));
+                gamepad.set_mapping(GamepadMapping::Standard);
+                gamepad
+            }),
+        }
+    }
+
+    /// Update the state of all controllers.
+    ///
+    /// Should be called on each frame before reading controller state.
+    pub fn poll(&mut self) {
+        for (idx, gamepad) in self.gamepads.iter_mut().enumerate() {
+            gamepad.begin_poll();
+            let id = idx as NpadIdType;
+            let read = read_state(id).or_else(|| {
+                if idx == 0 {
+                    read_state(NPAD_ID_HANDHELD)
+                } else {
+                    None
+                }
+            });
+            let Some((state, style)) = read else {
+                gamepad.set_connected(false);
+                continue;
+            };
+            gamepad.set_connected(true);
+            let buttons: &[(u64, Button)] = match style {
+                STYLE_JOY_LEFT => &JOY_LEFT_BUTTONS,
+                STYLE_JOY_RIGHT => &JOY_RIGHT_BUTTONS,
+                _ => &BUTTONS,
+            };
+            for &(bit, button) in buttons {
+                let pressed = state.buttons & bit != 0;
+                if pressed != gamepad.is_currently_pressed(button) {
+                    if pressed {
+                        gamepad.press(button);
+                    } else {
+                        gamepad.release(button);
+                    }
+                }
+            }
+
+            let stick =
+                |stick: AnalogStickState| (stick.x as f32 / STICK_MAX, stick.y as f32 / STICK_MAX);
+            let (left, right) = match style {
+                // Rotated to be held sideways, with the rail on top:
+                STYLE_JOY_LEFT => {
+                    let (x, y) = stick(state.analog_stick_l);
+                    ((-y, x), (0., 0.))
+                }
+                STYLE_JOY_RIGHT => {
+                    let (x, y) = stick(state.analog_stick_r);
+                    ((y, -x), (0., 0.))
+                }
+                _ => (stick(state.analog_stick_l), stick(state.analog_stick_r)),
+            };
+            gamepad.set_left_stick(left);
+            gamepad.set_right_stick(right);
+        }
+    }
+
+    /// An iterator over all connected controllers.
+    pub fn all(&self) -> impl Iterator<Item = &Gamepad> {
+        self.gamepads
+            .iter()
+            .filter(|gamepad| gamepad.is_connected())
+    }
+
+    /// Get a connected controller by its id.
+    pub fn get(&self, gamepad_id: GamepadId) -> Option<&Gamepad> {
+        self.gamepads
+            .get(usize::from(gamepad_id.value()))
+            .filter(|gamepad| gamepad.is_connected())
+    }
+}
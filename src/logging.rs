@@ -0,0 +1,51 @@
+//! Logging through the `log` crate if the `log` feature is enabled, and otherwise nothing.
+//!
+//! Messages are logged with targets starting with `gamepads::`, so they can be filtered
+//! separately. Use the `tracing-log` crate to consume them with `tracing`.
+
+// Not every backend logs at every level:
+#![allow(unused_macros, unused_imports)]
+
+#[cfg(feature = "log")]
+macro_rules! warning {
+    ($($arg:tt)+) => { log::warn!($($arg)+) };
+}
+
+#[cfg(feature = "log")]
+macro_rules! info {
+    ($($arg:tt)+) => { log::info!($($arg)+) };
+}
+
+#[cfg(feature = "log")]
+macro_rules! debug {
+    ($($arg:tt)+) => { log::debug!($($arg)+) };
+}
+
+#[cfg(feature = "log")]
+macro_rules! trace {
+    ($($arg:tt)+) => { log::trace!($($arg)+) };
+}
+
+// Without the log feature, only check the arguments so they are not reported as unused:
+
+#[cfg(not(feature = "log"))]
+macro_rules! warning {
+    ($($arg:tt)+) => { { let _ = format_args!($($arg)+); } };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! info {
+    ($($arg:tt)+) => { { let _ = format_args!($($arg)+); } };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! debug {
+    ($($arg:tt)+) => { { let _ = format_args!($($arg)+); } };
+}
+
+#[cfg(not(feature = "log"))]
+macro_rules! trace {
+    ($($arg:tt)+) => { { let _ = format_args!($($arg)+); } };
+}
+
+pub(crate) use {debug, info, trace, warning};
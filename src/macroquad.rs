@@ -0,0 +1,84 @@
+//! Helpers for [macroquad](https://macroquad.rs/) games, enabled by the `macroquad` feature,
+//! querying the keyboard of macroquad and all connected gamepads together.
+//!
+//! Gamepads are read from a [Gamepads] instance shared by the helpers, created when first used
+//! and polled by [next_frame()], which replaces `macroquad::window::next_frame()`:
+//!
+//! ```no_run
+//! use gamepads::macroquad as input;
+//! use gamepads::Button;
+//! use macroquad::input::KeyCode;
+//!
+//! #[macroquad::main("Game")]
+//! async fn main() {
+//!     loop {
+//!         if input::pressed(KeyCode::Space, Button::ActionDown) {
+//!             // Jump
+//!         }
+//!         let (x, y) = input::left_stick([KeyCode::W, KeyCode::A, KeyCode::S, KeyCode::D]);
+//!         // Move by (x, y)
+//!         input::next_frame().await;
+//!     }
+//! }
+//! ```
+use crate::{Button, Gamepad, Gamepads};
+use ::macroquad::input::{is_key_down, is_key_pressed, is_key_released, KeyCode};
+use std::cell::RefCell;
+
+thread_local! {
+    static GAMEPADS: RefCell<Option<Gamepads>> = const { RefCell::new(None) };
+}
+
+/// Access the shared gamepads, such as for rumble or reading a specific gamepad.
+pub fn with_gamepads<T>(f: impl FnOnce(&mut Gamepads) -> T) -> T {
+    GAMEPADS.with_borrow_mut(|gamepads| f(gamepads.get_or_insert_with(Gamepads::new)))
+}
+
+/// Whether any connected gamepad matches a condition.
+fn any_gamepad(condition: impl Fn(&Gamepad) -> bool) -> bool {
+    with_gamepads(|gamepads| gamepads.all().any(|gamepad| condition(&gamepad)))
+}
+
+/// Wait for the next frame as `macroquad::window::next_frame()`, then poll the shared gamepads
+/// for it.
+pub async fn next_frame() {
+    ::macroquad::window::next_frame().await;
+    with_gamepads(Gamepads::poll);
+}
+
+/// Whether a key, or a button on any connected gamepad, is currently held down.
+pub fn down(key: KeyCode, button: Button) -> bool {
+    is_key_down(key) || any_gamepad(|gamepad| gamepad.is_currently_pressed(button))
+}
+
+/// Whether a key, or a button on any connected gamepad, has just been pressed.
+pub fn pressed(key: KeyCode, button: Button) -> bool {
+    is_key_pressed(key) || any_gamepad(|gamepad| gamepad.is_just_pressed(button))
+}
+
+/// Whether a key, or a button on any connected gamepad, has just been released.
+pub fn released(key: KeyCode, button: Button) -> bool {
+    is_key_released(key) || any_gamepad(|gamepad| gamepad.is_just_released(button))
+}
+
+/// The `(x, y)` position of the left thumbstick of the gamepad pushed furthest, or of the keys
+/// moving it up, left, down and right if held, with negative values representing down or to
+/// the left.
+pub fn left_stick([up, left, down, right]: [KeyCode; 4]) -> (f32, f32) {
+    let key_axis = |negative, positive| {
+        f32::from(u8::from(is_key_down(positive))) - f32::from(u8::from(is_key_down(negative)))
+    };
+    let keys = (key_axis(left, right), key_axis(down, up));
+    if keys != (0., 0.) {
+        // Diagonals at the edge of the thumbstick, not beyond it:
+        let scale = keys.0.hypot(keys.1);
+        return (keys.0 / scale, keys.1 / scale);
+    }
+    with_gamepads(|gamepads| {
+        gamepads
+            .all()
+            .map(|gamepad| gamepad.left_stick())
+            .max_by(|a, b| a.0.hypot(a.1).total_cmp(&b.0.hypot(b.1)))
+            .unwrap_or((0., 0.))
+    })
+}
@@ -0,0 +1,169 @@
+use std::sync::{Arc, Mutex};
+
+use crate::event::RawTimestamps;
+use crate::{Button, Gamepad, GamepadId, Gamepads, MAX_GAMEPADS};
+
+/// Simulated state since the last poll, with presses accumulated until taken.
+struct MockState {
+    gamepads: [Gamepad; MAX_GAMEPADS],
+    raw_timestamps: [RawTimestamps; MAX_GAMEPADS],
+    /// Strong and weak magnitudes of the last rumble requested on each gamepad.
+    last_rumbles: [Option<(f32, f32)>; MAX_GAMEPADS],
+}
+
+/// A handle to simulated gamepads, replacing the platform backend when the `mock` feature is enabled.
+///
+/// Button and thumbstick state set through the handle is read by [Gamepads::poll()], so that
+/// code using [Gamepads] can be tested without gamepad hardware. Clones of a handle share state.
+///
+/// ```
+/// use gamepads::{Button, Gamepads, MockGamepads};
+///
+/// let mock = MockGamepads::new();
+/// let mut gamepads = Gamepads::from_mock(&mock);
+///
+/// let gamepad_id = mock.connect().unwrap();
+/// mock.press(gamepad_id, Button::ActionDown);
+/// mock.set_left_stick(gamepad_id, (0.5, -1.0));
+/// gamepads.poll();
+///
+/// let gamepad = gamepads.get(gamepad_id).unwrap();
+/// assert!(gamepad.is_just_pressed(Button::ActionDown));
+/// assert_eq!(gamepad.left_stick(), (0.5, -1.0));
+/// ```
+#[derive(Clone)]
+pub struct MockGamepads {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockGamepads {
+    /// Create a handle without any connected gamepads.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(MockState {
+                gamepads: std::array::from_fn(|idx| Gamepad::new(GamepadId(idx as u8))),
+                raw_timestamps: [RawTimestamps::default(); MAX_GAMEPADS],
+                last_rumbles: [None; MAX_GAMEPADS],
+            })),
+        }
+    }
+
+    fn update(&self, gamepad_id: GamepadId, f: impl FnOnce(&mut Gamepad, &mut RawTimestamps)) {
+        if let Ok(mut state) = self.state.lock() {
+            let state = &mut *state;
+            let idx = gamepad_id.0 as usize;
+            f(&mut state.gamepads[idx], &mut state.raw_timestamps[idx]);
+        }
+    }
+
+    /// Connect a gamepad, returning its id or `None` if the maximum number of gamepads are connected.
+    pub fn connect(&self) -> Option<GamepadId> {
+        self.state
+            .lock()
+            .ok()?
+            .gamepads
+            .iter_mut()
+            .find(|gamepad| !gamepad.connected)
+            .map(|gamepad| {
+                gamepad.connected = true;
+                gamepad.id
+            })
+    }
+
+    /// Disconnect a gamepad, releasing its buttons and centering its thumbsticks.
+    pub fn disconnect(&self, gamepad_id: GamepadId) {
+        self.update(gamepad_id, |gamepad, _| {
+            gamepad.connected = false;
+            gamepad.pressed_bits = 0;
+            gamepad.axes = [0.; 4];
+            gamepad.triggers = [0.; 2];
+            gamepad.extra_axes = [0.; crate::MAX_EXTRA_AXES];
+        });
+    }
+
+    /// Press a button, which is reported as just pressed in the next poll.
+    pub fn press(&self, gamepad_id: GamepadId, button: Button) {
+        self.update(gamepad_id, |gamepad, timestamps| {
+            gamepad.pressed_bits |= 1 << (button as u32);
+            gamepad.just_pressed_bits |= 1 << (button as u32);
+            timestamps.record_press(button as usize, crate::time::now_ms());
+        });
+    }
+
+    /// Release a button.
+    pub fn release(&self, gamepad_id: GamepadId, button: Button) {
+        self.update(gamepad_id, |gamepad, timestamps| {
+            gamepad.pressed_bits &= !(1 << (button as u32));
+            timestamps.record_release(button as usize, crate::time::now_ms());
+        });
+    }
+
+    /// Set the `(x, y)` position of the left thumbstick.
+    pub fn set_left_stick(&self, gamepad_id: GamepadId, (x, y): (f32, f32)) {
+        self.update(gamepad_id, |gamepad, timestamps| {
+            gamepad.axes[0] = x;
+            gamepad.axes[1] = y;
+            timestamps.record_axes(crate::time::now_ms());
+        });
+    }
+
+    /// Set the `(x, y)` position of the right thumbstick.
+    pub fn set_right_stick(&self, gamepad_id: GamepadId, (x, y): (f32, f32)) {
+        self.update(gamepad_id, |gamepad, timestamps| {
+            gamepad.axes[2] = x;
+            gamepad.axes[3] = y;
+            timestamps.record_axes(crate::time::now_ms());
+        });
+    }
+
+    /// Set how far the left and right triggers are pressed, in the range `[0.0, 1.0]`.
+    pub fn set_triggers(&self, gamepad_id: GamepadId, (left, right): (f32, f32)) {
+        self.update(gamepad_id, |gamepad, timestamps| {
+            gamepad.triggers[0] = left;
+            gamepad.triggers[1] = right;
+            timestamps.record_axes(crate::time::now_ms());
+        });
+    }
+
+    /// The strong and weak magnitudes of the last rumble requested on a gamepad, if any.
+    pub fn last_rumble(&self, gamepad_id: GamepadId) -> Option<(f32, f32)> {
+        self.state.lock().ok()?.last_rumbles[gamepad_id.0 as usize]
+    }
+
+    pub(crate) fn rumble(&self, gamepad_id: GamepadId, strong_magnitude: f32, weak_magnitude: f32) {
+        if let Ok(mut state) = self.state.lock() {
+            state.last_rumbles[gamepad_id.0 as usize] = Some((strong_magnitude, weak_magnitude));
+        }
+    }
+
+    /// Take the state set since the last call.
+    pub(crate) fn take_state(
+        &self,
+        gamepads: &mut [Gamepad; MAX_GAMEPADS],
+        raw_timestamps: &mut [RawTimestamps; MAX_GAMEPADS],
+    ) {
+        if let Ok(mut state) = self.state.lock() {
+            *gamepads = state.gamepads;
+            for gamepad in state.gamepads.iter_mut() {
+                gamepad.just_pressed_bits = 0;
+            }
+            *raw_timestamps = std::mem::take(&mut state.raw_timestamps);
+        }
+    }
+}
+
+impl Gamepads {
+    /// Construct a gamepads instance reading state from the given mock handle.
+    pub fn from_mock(mock: &MockGamepads) -> Self {
+        let mut gamepads = Self::new();
+        gamepads.mock = mock.clone();
+        gamepads.poll();
+        gamepads
+    }
+
+    /// A handle to the simulated gamepads read by this instance.
+    pub fn mock(&self) -> MockGamepads {
+        self.mock.clone()
+    }
+}
@@ -0,0 +1,248 @@
+use crate::{Button, Event, GamepadId};
+
+/// A mouse button, numbered as by the `button` property of browser mouse events.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MouseButton {
+    /// The left (primary) mouse button.
+    Left,
+    /// The middle mouse button, typically the wheel.
+    Middle,
+    /// The right (secondary) mouse button.
+    Right,
+}
+
+/// How mouse movement and buttons act as a gamepad, as enabled by
+/// [Gamepads::enable_mouse_stick()](crate::Gamepads::enable_mouse_stick).
+///
+/// By default, the left and right mouse buttons act as the right and left triggers,
+/// as is common for aiming and firing in shooters.
+///
+/// ```
+/// use gamepads::{Button, MouseButton, MouseStick};
+///
+/// let settings = MouseStick::new()
+///     .with_sensitivity(0.02)
+///     .with_max_deflection(0.8)
+///     .with_inverted_y(true)
+///     .map_button(MouseButton::Middle, Some(Button::RightStick));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MouseStick {
+    sensitivity: (f32, f32),
+    max_deflection: f32,
+    inverted_y: bool,
+    buttons: [Option<Button>; 3],
+}
+
+impl MouseStick {
+    /// The default settings, moving the thumbstick fully by 20 pixels of movement per poll.
+    pub const fn new() -> Self {
+        Self {
+            sensitivity: (0.05, 0.05),
+            max_deflection: 1.,
+            inverted_y: false,
+            buttons: [
+                Some(Button::FrontRightLower),
+                None,
+                Some(Button::FrontLeftLower),
+            ],
+        }
+    }
+
+    /// How far the thumbstick moves per pixel of mouse movement in between polls.
+    pub const fn with_sensitivity(mut self, sensitivity: f32) -> Self {
+        self.sensitivity = (sensitivity, sensitivity);
+        self
+    }
+
+    /// How far the thumbstick moves per pixel of horizontal and vertical mouse movement in
+    /// between polls, for aiming faster horizontally than vertically.
+    pub const fn with_sensitivity_xy(mut self, sensitivity_x: f32, sensitivity_y: f32) -> Self {
+        self.sensitivity = (sensitivity_x, sensitivity_y);
+        self
+    }
+
+    /// How far from the center the thumbstick moves at most, in the range `[0.0, 1.0]`, limiting
+    /// how fast fast mouse movement turns.
+    pub const fn with_max_deflection(mut self, max_deflection: f32) -> Self {
+        self.max_deflection = max_deflection.clamp(0., 1.);
+        self
+    }
+
+    /// Whether moving the mouse up moves the thumbstick down, as preferred for flying by some
+    /// players.
+    pub const fn with_inverted_y(mut self, inverted_y: bool) -> Self {
+        self.inverted_y = inverted_y;
+        self
+    }
+
+    /// Map a mouse button to a gamepad button, or to nothing if `None`.
+    pub const fn map_button(mut self, mouse_button: MouseButton, button: Option<Button>) -> Self {
+        self.buttons[mouse_button as usize] = button;
+        self
+    }
+}
+
+impl Default for MouseStick {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Mouse input received since the last poll, as written by the host javascript.
+#[cfg(backend = "web_direct")]
+#[derive(Default)]
+#[repr(C)]
+pub struct MouseSample {
+    /// The movement in pixels, with y positive downwards.
+    pub delta: [f32; 2],
+    /// A bit for each currently pressed [MouseButton].
+    pub pressed_bits: u32,
+    /// A bit for each [MouseButton] pressed since the last poll.
+    pub just_pressed_bits: u32,
+}
+
+/// Mouse input used as a gamepad, and the gamepad it is merged into.
+pub struct MouseState {
+    gamepad_id: GamepadId,
+    settings: MouseStick,
+    /// The movement since the last poll.
+    delta: (f32, f32),
+}
+
+impl crate::Gamepads {
+    /// Use mouse movement as the right thumbstick of a gamepad, and mouse buttons as its
+    /// buttons, merged into its state as by [Gamepads::inject()](crate::Gamepads::inject).
+    ///
+    /// On the web, mouse input is read while the pointer is locked, which the game requests using
+    /// `requestPointerLock()` on a click. Elsewhere, the game forwards mouse input using
+    /// [Gamepads::handle_mouse_motion()](crate::Gamepads::handle_mouse_motion) and
+    /// [Gamepads::handle_mouse_button()](crate::Gamepads::handle_mouse_button).
+    ///
+    /// Without a physical gamepad, inject [Event::Connected] to make the gamepad appear connected,
+    /// or enable the mouse stick on the same gamepad as a keyboard gamepad from the `keyboard`
+    /// feature, for playing with keyboard and mouse.
+    ///
+    /// ```
+    /// use gamepads::{Event, GamepadId, Gamepads, MouseStick};
+    ///
+    /// let mut gamepads = Gamepads::new();
+    /// let gamepad_id = GamepadId::new(0).unwrap();
+    /// gamepads.inject(Event::Connected { gamepad_id });
+    /// gamepads.enable_mouse_stick(gamepad_id, MouseStick::new());
+    ///
+    /// gamepads.handle_mouse_motion((10., -30.));
+    /// gamepads.poll();
+    /// let (x, y) = gamepads.get(gamepad_id).unwrap().right_stick();
+    /// assert!(x > 0. && y > 0.);
+    /// ```
+    pub fn enable_mouse_stick(&mut self, gamepad_id: GamepadId, settings: MouseStick) {
+        self.disable_mouse_stick();
+        // Discard mouse input from before being enabled:
+        #[cfg(any(backend = "web_bindgen", backend = "web_direct"))]
+        take_web_mouse_input();
+        self.mouse = Some(Box::new(MouseState {
+            gamepad_id,
+            settings,
+            delta: (0., 0.),
+        }));
+    }
+
+    /// Stop using the mouse as a gamepad, centering the thumbstick and releasing the buttons.
+    pub fn disable_mouse_stick(&mut self) {
+        let Some(mouse) = self.mouse.take() else {
+            return;
+        };
+        let gamepad_id = mouse.gamepad_id;
+        self.inject(Event::RightStickMoved {
+            gamepad_id,
+            position: (0., 0.),
+        });
+        for button in mouse.settings.buttons.into_iter().flatten() {
+            self.inject(Event::ButtonReleased { gamepad_id, button });
+        }
+    }
+
+    /// Handle relative mouse movement in pixels, with y positive downwards.
+    ///
+    /// Does nothing unless enabled by [Gamepads::enable_mouse_stick()](crate::Gamepads::enable_mouse_stick).
+    pub fn handle_mouse_motion(&mut self, (dx, dy): (f32, f32)) {
+        if let Some(mouse) = &mut self.mouse {
+            mouse.delta.0 += dx;
+            mouse.delta.1 += dy;
+        }
+    }
+
+    /// Handle a mouse button being pressed or released.
+    ///
+    /// Does nothing unless enabled by [Gamepads::enable_mouse_stick()](crate::Gamepads::enable_mouse_stick).
+    pub fn handle_mouse_button(&mut self, mouse_button: MouseButton, pressed: bool) {
+        let Some(mouse) = &self.mouse else {
+            return;
+        };
+        let gamepad_id = mouse.gamepad_id;
+        if let Some(button) = mouse.settings.buttons[mouse_button as usize] {
+            self.inject(if pressed {
+                Event::ButtonPressed { gamepad_id, button }
+            } else {
+                Event::ButtonReleased { gamepad_id, button }
+            });
+        }
+    }
+
+    /// Move the thumbstick by the mouse movement since the last poll.
+    pub(crate) fn update_mouse_stick(&mut self) {
+        if self.mouse.is_none() {
+            return;
+        }
+        #[cfg(any(backend = "web_bindgen", backend = "web_direct"))]
+        {
+            let (delta, pressed_bits, just_pressed_bits) = take_web_mouse_input();
+            self.handle_mouse_motion((delta[0], delta[1]));
+            for mouse_button in [MouseButton::Left, MouseButton::Middle, MouseButton::Right] {
+                let bit = 1 << (mouse_button as u32);
+                if just_pressed_bits & bit != 0 {
+                    self.handle_mouse_button(mouse_button, true);
+                }
+                if pressed_bits & bit == 0 {
+                    self.handle_mouse_button(mouse_button, false);
+                }
+            }
+        }
+        let Some(mouse) = &mut self.mouse else {
+            return;
+        };
+        let (dx, dy) = std::mem::take(&mut mouse.delta);
+        let MouseStick {
+            sensitivity,
+            max_deflection,
+            inverted_y,
+            ..
+        } = mouse.settings;
+        // Screen coordinates are positive downwards, while thumbsticks are positive upwards:
+        let dy = if inverted_y { dy } else { -dy };
+        let (x, y) = (dx * sensitivity.0, dy * sensitivity.1);
+        let scale = (x.hypot(y) / max_deflection).max(1.);
+        let gamepad_id = mouse.gamepad_id;
+        self.inject(Event::RightStickMoved {
+            gamepad_id,
+            position: (x / scale, y / scale),
+        });
+    }
+}
+
+/// Take the mouse movement, pressed bits and just pressed bits read by the web backend since
+/// the last call, while the pointer was locked.
+#[cfg(any(backend = "web_bindgen", backend = "web_direct"))]
+fn take_web_mouse_input() -> ([f32; 2], u32, u32) {
+    #[cfg(backend = "web_bindgen")]
+    {
+        crate::backend_web_bindgen::take_mouse()
+    }
+    #[cfg(backend = "web_direct")]
+    {
+        let mut sample = MouseSample::default();
+        unsafe { crate::backend_web_direct::gamepadsTakeMouse(&mut sample) };
+        (sample.delta, sample.pressed_bits, sample.just_pressed_bits)
+    }
+}
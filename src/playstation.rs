@@ -0,0 +1,723 @@
+//! Enhanced support for DualSense and DualShock 4 controllers on desktop, enabled by the
+//! `playstation` feature.
+//!
+//! The platform backend reads buttons and thumbsticks, while these controllers are also opened
+//! as HID devices using hidapi, to read their touchpad and motion sensors and to control their
+//! light bar, vibration and the adaptive triggers of the DualSense using HID output reports, as
+//! described by the `hid-playstation` driver of Linux.
+//!
+//! A HID device is associated with a gamepad of the platform backend whose name identifies the
+//! same model, in the order they are connected.
+
+use std::ffi::{c_char, c_int, c_uchar, c_ushort, c_void, CStr, CString};
+
+use crate::{GamepadId, Motion};
+
+const VENDOR_SONY: c_ushort = 0x054c;
+const PRODUCT_DUALSENSE: c_ushort = 0x0ce6;
+const PRODUCT_DUALSENSE_EDGE: c_ushort = 0x0df2;
+const PRODUCT_DUALSHOCK4: c_ushort = 0x05c4;
+const PRODUCT_DUALSHOCK4_2: c_ushort = 0x09cc;
+const PRODUCT_DUALSHOCK4_DONGLE: c_ushort = 0x0ba0;
+
+/// The size of the largest input and output reports, which are those over Bluetooth.
+const REPORT_SIZE: usize = 78;
+/// The minimum size of full input reports, as opposed to the reduced reports sent over
+/// Bluetooth until the calibration is read.
+const INPUT_REPORT_MIN_SIZE: usize = 64;
+
+/// The feature report with the sensor calibration, reading which enables full input reports
+/// over Bluetooth.
+const FEATURE_REPORT_CALIBRATION_BT: u8 = 0x05;
+const FEATURE_REPORT_CALIBRATION_BT_SIZE: usize = 41;
+
+/// The byte prepended to Bluetooth output reports for their CRC32.
+const OUTPUT_CRC32_SEED: u8 = 0xa2;
+
+/// Input reports of the DualSense over USB and Bluetooth.
+const DS_INPUT_REPORT_USB: u8 = 0x01;
+const DS_INPUT_REPORT_BT: u8 = 0x31;
+
+/// Offsets in the common state of DualSense input reports.
+const DS_INPUT_BUTTONS2: usize = 9;
+const DS_INPUT_GYRO: usize = 15;
+const DS_INPUT_ACCEL: usize = 21;
+const DS_INPUT_TOUCH_POINTS: usize = 32;
+
+/// Output reports of the DualSense over USB and Bluetooth, where Bluetooth reports have a
+/// sequence number and a tag before the common part and end with a CRC32.
+const DS_OUTPUT_REPORT_USB: u8 = 0x02;
+const DS_OUTPUT_REPORT_BT: u8 = 0x31;
+const DS_OUTPUT_REPORT_USB_SIZE: usize = 48;
+const DS_OUTPUT_TAG: u8 = 0x10;
+
+/// The `valid_flag*` bits of the common part of DualSense output reports.
+const DS_FLAG0_COMPATIBLE_VIBRATION: u8 = 1 << 0;
+const DS_FLAG0_HAPTICS_SELECT: u8 = 1 << 1;
+const DS_FLAG0_RIGHT_TRIGGER_EFFECT: u8 = 1 << 2;
+const DS_FLAG0_LEFT_TRIGGER_EFFECT: u8 = 1 << 3;
+const DS_FLAG1_LIGHTBAR_CONTROL_ENABLE: u8 = 1 << 2;
+const DS_FLAG2_COMPATIBLE_VIBRATION2: u8 = 1 << 2;
+
+/// Offsets in the common part of DualSense output reports.
+const DS_OUTPUT_MOTOR_RIGHT: usize = 2;
+const DS_OUTPUT_MOTOR_LEFT: usize = 3;
+const DS_OUTPUT_RIGHT_TRIGGER: usize = 10;
+const DS_OUTPUT_LEFT_TRIGGER: usize = 21;
+const DS_OUTPUT_VALID_FLAG2: usize = 38;
+const DS_OUTPUT_LIGHTBAR: usize = 44;
+
+/// Input reports of the DualShock 4 over USB and Bluetooth.
+const DS4_INPUT_REPORT_USB: u8 = 0x01;
+const DS4_INPUT_REPORT_BT: u8 = 0x11;
+
+/// Offsets in the common state of DualShock 4 input reports, where the touch points are those
+/// of the first touch report.
+const DS4_INPUT_BUTTONS2: usize = 6;
+const DS4_INPUT_GYRO: usize = 12;
+const DS4_INPUT_ACCEL: usize = 18;
+const DS4_INPUT_TOUCH_POINTS: usize = 34;
+
+/// Output reports of the DualShock 4 over USB and Bluetooth, where Bluetooth reports have a
+/// hardware control byte and an audio control byte before the common part and end with a CRC32.
+const DS4_OUTPUT_REPORT_USB: u8 = 0x05;
+const DS4_OUTPUT_REPORT_BT: u8 = 0x11;
+const DS4_OUTPUT_REPORT_USB_SIZE: usize = 32;
+const DS4_OUTPUT_HWCTL_HID: u8 = 1 << 7;
+const DS4_OUTPUT_HWCTL_CRC32: u8 = 1 << 6;
+
+/// The `valid_flag0` bits of the common part of DualShock 4 output reports.
+const DS4_FLAG0_MOTOR: u8 = 1 << 0;
+const DS4_FLAG0_LED: u8 = 1 << 1;
+
+/// Offsets in the common part of DualShock 4 output reports.
+const DS4_OUTPUT_MOTOR_RIGHT: usize = 3;
+const DS4_OUTPUT_MOTOR_LEFT: usize = 4;
+const DS4_OUTPUT_LIGHTBAR: usize = 5;
+
+/// Bits of the third button byte of both models.
+const BUTTONS2_TOUCHPAD: u8 = 1 << 1;
+const BUTTONS2_MIC: u8 = 1 << 2;
+
+/// Sensor units, without applying the calibration of the individual controller.
+const GYRO_UNITS_PER_DEG_S: f32 = 16.;
+const ACCEL_UNITS_PER_G: f32 = 8192.;
+const STANDARD_GRAVITY: f32 = 9.806_65;
+
+/// How often to look for new devices while a gamepad of a supported model has no associated
+/// HID device.
+const ENUMERATE_INTERVAL_MS: f64 = 1000.;
+
+#[repr(C)]
+struct HidDevice {
+    _private: [u8; 0],
+}
+
+/// `struct hid_device_info`, of which only the leading fields are declared.
+#[repr(C)]
+struct HidDeviceInfo {
+    path: *mut c_char,
+    vendor_id: c_ushort,
+    product_id: c_ushort,
+    serial_number: *mut c_void,
+    release_number: c_ushort,
+    manufacturer_string: *mut c_void,
+    product_string: *mut c_void,
+    usage_page: c_ushort,
+    usage: c_ushort,
+    interface_number: c_int,
+    next: *mut Self,
+}
+
+#[cfg_attr(target_os = "linux", link(name = "hidapi-hidraw"))]
+#[cfg_attr(not(target_os = "linux"), link(name = "hidapi"))]
+extern "C" {
+    fn hid_init() -> c_int;
+    fn hid_enumerate(vendor_id: c_ushort, product_id: c_ushort) -> *mut HidDeviceInfo;
+    fn hid_free_enumeration(devs: *mut HidDeviceInfo);
+    fn hid_open_path(path: *const c_char) -> *mut HidDevice;
+    fn hid_close(dev: *mut HidDevice);
+    fn hid_set_nonblocking(dev: *mut HidDevice, nonblock: c_int) -> c_int;
+    fn hid_read(dev: *mut HidDevice, data: *mut c_uchar, length: usize) -> c_int;
+    fn hid_write(dev: *mut HidDevice, data: *const c_uchar, length: usize) -> c_int;
+    fn hid_get_feature_report(dev: *mut HidDevice, data: *mut c_uchar, length: usize) -> c_int;
+}
+
+/// The CRC32 of Bluetooth output reports.
+fn crc32(bytes: impl IntoIterator<Item = u8>) -> u32 {
+    !bytes.into_iter().fold(!0, |crc, byte| {
+        (0..8).fold(crc ^ u32::from(byte), |crc, _| {
+            (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+/// The supported controller models.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Model {
+    DualSense,
+    DualShock4,
+}
+
+impl Model {
+    const fn from_product_id(product_id: c_ushort) -> Option<Self> {
+        match product_id {
+            PRODUCT_DUALSENSE | PRODUCT_DUALSENSE_EDGE => Some(Self::DualSense),
+            PRODUCT_DUALSHOCK4 | PRODUCT_DUALSHOCK4_2 | PRODUCT_DUALSHOCK4_DONGLE => {
+                Some(Self::DualShock4)
+            }
+            _ => None,
+        }
+    }
+
+    /// The model identified by a gamepad name as reported by the platform backend, such as
+    /// "PS4 Controller" from the SDL mappings used by gilrs or "Sony Interactive Entertainment
+    /// Wireless Controller" from the kernel, which is the DualShock 4 unless named a DualSense.
+    fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        if name.contains("dualsense") || name.contains("ps5") {
+            Some(Self::DualSense)
+        } else if name.contains("dualshock")
+            || name.contains("ps4")
+            || name.contains("wireless controller")
+        {
+            Some(Self::DualShock4)
+        } else {
+            None
+        }
+    }
+
+    /// The buttons of the third button byte reported as extra buttons, as by the sdl3 backend.
+    const fn extra_buttons(self) -> &'static [(u8, u8)] {
+        match self {
+            Self::DualSense => &[(BUTTONS2_MIC, 17), (BUTTONS2_TOUCHPAD, 18)],
+            Self::DualShock4 => &[(BUTTONS2_TOUCHPAD, 18)],
+        }
+    }
+
+    /// The resolution of the touchpad.
+    const fn touchpad_size(self) -> (f32, f32) {
+        match self {
+            Self::DualSense => (1920., 1080.),
+            Self::DualShock4 => (1920., 942.),
+        }
+    }
+}
+
+/// An effect of the adaptive triggers of a DualSense, set using
+/// [Gamepads::set_trigger_effects()](crate::Gamepads::set_trigger_effects).
+///
+/// Positions are how far the trigger is pressed and strengths are relative to the maximum, all
+/// in the range `[0.0, 1.0]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TriggerEffect {
+    /// No resistance.
+    #[default]
+    Off,
+    /// Resistance from a position until fully pressed, such as for a bow being drawn.
+    Feedback {
+        /// Where the resistance starts.
+        start: f32,
+        /// How strong the resistance is.
+        strength: f32,
+    },
+    /// Resistance between two positions, which gives way after the end like a trigger of a gun.
+    Weapon {
+        /// Where the resistance starts.
+        start: f32,
+        /// Where the resistance gives way.
+        end: f32,
+        /// How strong the resistance is.
+        strength: f32,
+    },
+    /// Vibration from a position until fully pressed, such as for an automatic weapon.
+    Vibration {
+        /// Where the vibration starts.
+        start: f32,
+        /// How strong the vibration is.
+        amplitude: f32,
+        /// The frequency of the vibration in Hz.
+        frequency: u8,
+    },
+}
+
+impl TriggerEffect {
+    /// The mode and parameters of the effect in an output report.
+    fn write(self, out: &mut [u8]) {
+        let byte = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+        let (mode, parameters) = match self {
+            Self::Off => (0x05, [0; 3]),
+            Self::Feedback { start, strength } => (0x01, [byte(start), byte(strength), 0]),
+            Self::Weapon {
+                start,
+                end,
+                strength,
+            } => (0x02, [byte(start), byte(end), byte(strength)]),
+            Self::Vibration {
+                start,
+                amplitude,
+                frequency,
+            } => (0x06, [frequency, byte(amplitude), byte(start)]),
+        };
+        out.fill(0);
+        out[0] = mode;
+        out[1..4].copy_from_slice(&parameters);
+    }
+}
+
+/// A rumble effect, started and stopped on polls as output reports have no durations.
+#[derive(Clone, Copy)]
+struct Rumble {
+    start_ms: f64,
+    stop_ms: f64,
+    motors: (u8, u8),
+    started: bool,
+}
+
+/// What is controlled using output reports, sent when changed.
+#[derive(Clone, Copy, Default, PartialEq)]
+struct Output {
+    /// The strong (left) and weak (right) motors.
+    motors: (u8, u8),
+    trigger_effects: (TriggerEffect, TriggerEffect),
+    lightbar: Option<(u8, u8, u8)>,
+}
+
+/// An opened controller, closed when dropped.
+struct Device {
+    device: *mut HidDevice,
+    path: CString,
+    model: Model,
+    bluetooth: bool,
+    gamepad_id: Option<GamepadId>,
+    output_sequence: u8,
+    sent: Option<Output>,
+    output: Output,
+    rumble: Option<Rumble>,
+    /// The pressed bits of the extra buttons.
+    extra_pressed_bits: u32,
+    touch_points: [Option<(f32, f32)>; 2],
+    motion: Option<Motion>,
+}
+
+impl Device {
+    fn open(path: &CStr, model: Model, bluetooth: bool) -> Option<Self> {
+        let device = unsafe { hid_open_path(path.as_ptr()) };
+        if device.is_null() {
+            crate::logging::warning!("Failed to open HID device at {path:?}");
+            return None;
+        }
+        unsafe {
+            hid_set_nonblocking(device, 1);
+            if bluetooth {
+                let mut calibration = [0; FEATURE_REPORT_CALIBRATION_BT_SIZE];
+                calibration[0] = FEATURE_REPORT_CALIBRATION_BT;
+                hid_get_feature_report(device, calibration.as_mut_ptr(), calibration.len());
+            }
+        }
+        Some(Self {
+            device,
+            path: path.to_owned(),
+            model,
+            bluetooth,
+            gamepad_id: None,
+            output_sequence: 0,
+            sent: None,
+            output: Output::default(),
+            rumble: None,
+            extra_pressed_bits: 0,
+            touch_points: [None; 2],
+            motion: None,
+        })
+    }
+
+    /// Read all pending input reports, returning whether the device is still connected.
+    fn read(&mut self) -> bool {
+        let mut report = [0; REPORT_SIZE];
+        loop {
+            let length = unsafe { hid_read(self.device, report.as_mut_ptr(), report.len()) };
+            let Ok(length) = usize::try_from(length) else {
+                return false;
+            };
+            if length == 0 {
+                return true;
+            } else if length < INPUT_REPORT_MIN_SIZE {
+                continue;
+            }
+            let state = match (self.model, report[0]) {
+                (Model::DualSense, DS_INPUT_REPORT_USB)
+                | (Model::DualShock4, DS4_INPUT_REPORT_USB) => &report[1..],
+                (Model::DualSense, DS_INPUT_REPORT_BT) => &report[2..],
+                (Model::DualShock4, DS4_INPUT_REPORT_BT) => &report[3..],
+                _ => continue,
+            };
+            self.parse(state);
+        }
+    }
+
+    /// Parse the common state of an input report.
+    fn parse(&mut self, state: &[u8]) {
+        let (buttons2, gyro, accel, touch_points) = match self.model {
+            Model::DualSense => (
+                DS_INPUT_BUTTONS2,
+                DS_INPUT_GYRO,
+                DS_INPUT_ACCEL,
+                DS_INPUT_TOUCH_POINTS,
+            ),
+            Model::DualShock4 => (
+                DS4_INPUT_BUTTONS2,
+                DS4_INPUT_GYRO,
+                DS4_INPUT_ACCEL,
+                DS4_INPUT_TOUCH_POINTS,
+            ),
+        };
+        let i16_at =
+            |offset: usize| f32::from(i16::from_le_bytes([state[offset], state[offset + 1]]));
+        self.extra_pressed_bits = self
+            .model
+            .extra_buttons()
+            .iter()
+            .filter(|&&(mask, _)| state[buttons2] & mask != 0)
+            .fold(0, |bits, &(_, index)| bits | (1 << index));
+        self.motion = Some(Motion {
+            acceleration: [0, 2, 4]
+                .map(|axis| i16_at(accel + axis) / ACCEL_UNITS_PER_G * STANDARD_GRAVITY),
+            rotation_rate: [0, 2, 4]
+                .map(|axis| (i16_at(gyro + axis) / GYRO_UNITS_PER_DEG_S).to_radians()),
+        });
+        let (width, height) = self.model.touchpad_size();
+        for (idx, touch_point) in self.touch_points.iter_mut().enumerate() {
+            let point = &state[touch_points + idx * 4..][..4];
+            // The highest bit of the contact byte is set while not touched:
+            *touch_point = (point[0] & 0x80 == 0).then(|| {
+                let x = u16::from(point[1]) | (u16::from(point[2] & 0x0f) << 8);
+                let y = u16::from(point[2] >> 4) | (u16::from(point[3]) << 4);
+                (f32::from(x) / (width - 1.), f32::from(y) / (height - 1.))
+            });
+        }
+    }
+
+    /// Start and stop rumble effects, and send an output report if anything changed.
+    fn write(&mut self, now_ms: f64) {
+        if let Some(rumble) = &mut self.rumble {
+            if now_ms >= rumble.stop_ms {
+                self.output.motors = (0, 0);
+                self.rumble = None;
+            } else if !rumble.started && now_ms >= rumble.start_ms {
+                self.output.motors = rumble.motors;
+                rumble.started = true;
+            }
+        }
+        if self.sent == Some(self.output) {
+            return;
+        }
+
+        let mut report = [0; REPORT_SIZE];
+        let length = match self.model {
+            Model::DualSense => self.write_dualsense(&mut report),
+            Model::DualShock4 => self.write_dualshock4(&mut report),
+        };
+        if self.bluetooth {
+            let crc_offset = REPORT_SIZE - 4;
+            let crc = crc32(
+                std::iter::once(OUTPUT_CRC32_SEED).chain(report[..crc_offset].iter().copied()),
+            );
+            report[crc_offset..].copy_from_slice(&crc.to_le_bytes());
+        }
+
+        if unsafe { hid_write(self.device, report.as_ptr(), length) } < 0 {
+            crate::logging::warning!("Failed to write HID output report");
+        } else {
+            self.sent = Some(self.output);
+        }
+    }
+
+    /// Fill in an output report of the DualSense, returning its length.
+    fn write_dualsense(&mut self, report: &mut [u8; REPORT_SIZE]) -> usize {
+        let (common, length) = if self.bluetooth {
+            report[0] = DS_OUTPUT_REPORT_BT;
+            report[1] = self.output_sequence << 4;
+            report[2] = DS_OUTPUT_TAG;
+            self.output_sequence = (self.output_sequence + 1) % 16;
+            (&mut report[3..], REPORT_SIZE)
+        } else {
+            report[0] = DS_OUTPUT_REPORT_USB;
+            (&mut report[1..], DS_OUTPUT_REPORT_USB_SIZE)
+        };
+        // Rumble using the improved emulation of rumble motors by newer firmware:
+        common[0] = DS_FLAG0_COMPATIBLE_VIBRATION
+            | DS_FLAG0_HAPTICS_SELECT
+            | DS_FLAG0_RIGHT_TRIGGER_EFFECT
+            | DS_FLAG0_LEFT_TRIGGER_EFFECT;
+        common[DS_OUTPUT_VALID_FLAG2] = DS_FLAG2_COMPATIBLE_VIBRATION2;
+        (common[DS_OUTPUT_MOTOR_LEFT], common[DS_OUTPUT_MOTOR_RIGHT]) = self.output.motors;
+        self.output
+            .trigger_effects
+            .0
+            .write(&mut common[DS_OUTPUT_LEFT_TRIGGER..][..11]);
+        self.output
+            .trigger_effects
+            .1
+            .write(&mut common[DS_OUTPUT_RIGHT_TRIGGER..][..11]);
+        if let Some(color) = self.output.lightbar {
+            common[1] = DS_FLAG1_LIGHTBAR_CONTROL_ENABLE;
+            common[DS_OUTPUT_LIGHTBAR..][..3].copy_from_slice(&<[u8; 3]>::from(color));
+        }
+        length
+    }
+
+    /// Fill in an output report of the DualShock 4, returning its length.
+    fn write_dualshock4(&self, report: &mut [u8; REPORT_SIZE]) -> usize {
+        let (common, length) = if self.bluetooth {
+            report[0] = DS4_OUTPUT_REPORT_BT;
+            report[1] = DS4_OUTPUT_HWCTL_HID | DS4_OUTPUT_HWCTL_CRC32;
+            (&mut report[3..], REPORT_SIZE)
+        } else {
+            report[0] = DS4_OUTPUT_REPORT_USB;
+            (&mut report[1..], DS4_OUTPUT_REPORT_USB_SIZE)
+        };
+        common[0] = DS4_FLAG0_MOTOR;
+        (
+            common[DS4_OUTPUT_MOTOR_LEFT],
+            common[DS4_OUTPUT_MOTOR_RIGHT],
+        ) = self.output.motors;
+        if let Some(color) = self.output.lightbar {
+            common[0] |= DS4_FLAG0_LED;
+            common[DS4_OUTPUT_LIGHTBAR..][..3].copy_from_slice(&<[u8; 3]>::from(color));
+        }
+        length
+    }
+}
+
+impl Drop for Device {
+    fn drop(&mut self) {
+        unsafe { hid_close(self.device) };
+    }
+}
+
+/// The opened controllers, layered on top of the platform backend.
+pub struct Layer {
+    initialized: bool,
+    devices: Vec<Device>,
+    enumerated_ms: Option<f64>,
+}
+
+impl Layer {
+    pub fn new() -> Self {
+        let initialized = unsafe { hid_init() } == 0;
+        if !initialized {
+            crate::logging::warning!("Failed to initialize hidapi");
+        }
+        Self {
+            initialized,
+            devices: Vec::new(),
+            enumerated_ms: None,
+        }
+    }
+
+    /// Open controllers not already opened.
+    fn enumerate(&mut self) {
+        let devices = unsafe { hid_enumerate(VENDOR_SONY, 0) };
+        let mut info = devices;
+        while let Some(device_info) = unsafe { info.as_ref() } {
+            info = device_info.next;
+            let Some(model) = Model::from_product_id(device_info.product_id) else {
+                continue;
+            };
+            if device_info.path.is_null() {
+                continue;
+            }
+            let path = unsafe { CStr::from_ptr(device_info.path) };
+            if self
+                .devices
+                .iter()
+                .any(|device| device.path.as_c_str() == path)
+            {
+                continue;
+            }
+            // Only the USB interface has an interface number:
+            let bluetooth = device_info.interface_number < 0;
+            if let Some(device) = Device::open(path, model, bluetooth) {
+                self.devices.push(device);
+            }
+        }
+        unsafe { hid_free_enumeration(devices) };
+    }
+
+    fn device(&self, gamepad_id: GamepadId) -> Option<&Device> {
+        self.devices
+            .iter()
+            .find(|device| device.gamepad_id == Some(gamepad_id))
+    }
+
+    fn device_mut(&mut self, gamepad_id: GamepadId) -> Option<&mut Device> {
+        self.devices
+            .iter_mut()
+            .find(|device| device.gamepad_id == Some(gamepad_id))
+    }
+}
+
+impl crate::Gamepads {
+    /// Read the HID devices of controllers, associated with gamepads of the platform backend
+    /// identified as the same model by their name.
+    pub(crate) fn poll_playstation(&mut self) {
+        if !self.playstation.initialized {
+            return;
+        }
+        let models: Vec<(GamepadId, Model)> = self
+            .gamepads
+            .iter()
+            .filter(|gamepad| gamepad.connected)
+            .filter_map(|gamepad| {
+                let model = Model::from_name(&self.name(gamepad.id)?)?;
+                Some((gamepad.id, model))
+            })
+            .collect();
+
+        let now_ms = crate::time::now_ms();
+        let layer = &mut self.playstation;
+        layer.devices.retain_mut(Device::read);
+        for device in layer.devices.iter_mut() {
+            if device
+                .gamepad_id
+                .is_some_and(|gamepad_id| !models.contains(&(gamepad_id, device.model)))
+            {
+                device.gamepad_id = None;
+            }
+        }
+        let mut unassociated: Vec<(GamepadId, Model)> = models
+            .into_iter()
+            .filter(|&(gamepad_id, _)| layer.device(gamepad_id).is_none())
+            .collect();
+        if unassociated.len()
+            > layer
+                .devices
+                .iter()
+                .filter(|device| device.gamepad_id.is_none())
+                .count()
+            && layer
+                .enumerated_ms
+                .is_none_or(|enumerated_ms| now_ms - enumerated_ms >= ENUMERATE_INTERVAL_MS)
+        {
+            layer.enumerated_ms = Some(now_ms);
+            layer.enumerate();
+        }
+        for device in layer
+            .devices
+            .iter_mut()
+            .filter(|device| device.gamepad_id.is_none())
+        {
+            if let Some(idx) = unassociated
+                .iter()
+                .position(|&(_, model)| model == device.model)
+            {
+                device.gamepad_id = Some(unassociated.remove(idx).0);
+            }
+        }
+
+        for device in layer.devices.iter_mut() {
+            device.write(now_ms);
+            let Some(gamepad_id) = device.gamepad_id else {
+                continue;
+            };
+            let gamepad = &mut self.gamepads[usize::from(gamepad_id.0)];
+            let raw_timestamps = &mut self.raw_timestamps[usize::from(gamepad_id.0)];
+            for &(_, index) in device.model.extra_buttons() {
+                let bit = 1 << index;
+                let pressed = device.extra_pressed_bits & bit != 0;
+                if pressed == (gamepad.pressed_bits & bit != 0) {
+                    continue;
+                }
+                if pressed {
+                    gamepad.pressed_bits |= bit;
+                    gamepad.just_pressed_bits |= bit;
+                    raw_timestamps.record_press(usize::from(index), now_ms);
+                } else {
+                    gamepad.pressed_bits &= !bit;
+                    raw_timestamps.record_release(usize::from(index), now_ms);
+                }
+            }
+        }
+    }
+
+    /// Rumble a controller using its HID device, returning whether it has one.
+    pub(crate) fn rumble_playstation(
+        &mut self,
+        gamepad_id: GamepadId,
+        duration_ms: u32,
+        start_delay_ms: u32,
+        strong_magnitude: f32,
+        weak_magnitude: f32,
+    ) -> bool {
+        let Some(device) = self.playstation.device_mut(gamepad_id) else {
+            return false;
+        };
+        let motor = |magnitude: f32| (magnitude.clamp(0., 1.) * 255.).round() as u8;
+        let start_ms = crate::time::now_ms() + f64::from(start_delay_ms);
+        device.rumble = Some(Rumble {
+            start_ms,
+            stop_ms: start_ms + f64::from(duration_ms),
+            motors: (motor(strong_magnitude), motor(weak_magnitude)),
+            started: false,
+        });
+        device.write(crate::time::now_ms());
+        true
+    }
+
+    /// The motion sensor readings of a controller with a HID device.
+    pub(crate) fn motion_playstation(&self, gamepad_id: GamepadId) -> Option<Motion> {
+        self.playstation.device(gamepad_id)?.motion
+    }
+
+    /// Set the light bar color of a controller, returning whether it has a HID device.
+    pub(crate) fn set_led_playstation(
+        &mut self,
+        gamepad_id: GamepadId,
+        color: (u8, u8, u8),
+    ) -> bool {
+        let Some(device) = self.playstation.device_mut(gamepad_id) else {
+            return false;
+        };
+        device.output.lightbar = Some(color);
+        device.write(crate::time::now_ms());
+        true
+    }
+
+    /// Whether a gamepad has been opened as a HID device by the `playstation` feature.
+    pub(crate) fn is_playstation(&self, gamepad_id: GamepadId) -> bool {
+        self.playstation.device(gamepad_id).is_some()
+    }
+
+    /// Set the effects of the adaptive triggers of a DualSense controller, enabled by the
+    /// `playstation` feature.
+    ///
+    /// Effects stay active until replaced, so set [TriggerEffect::Off] to stop them. Gamepads
+    /// which are not DualSense controllers, or whose HID device could not be opened, are ignored.
+    pub fn set_trigger_effects(
+        &mut self,
+        gamepad_id: GamepadId,
+        left: TriggerEffect,
+        right: TriggerEffect,
+    ) {
+        if let Some(device) = self
+            .playstation
+            .device_mut(gamepad_id)
+            .filter(|device| device.model == Model::DualSense)
+        {
+            device.output.trigger_effects = (left, right);
+            device.write(crate::time::now_ms());
+        }
+    }
+
+    /// The positions of up to two fingers on the touchpad of a DualSense or DualShock 4
+    /// controller, enabled by the `playstation` feature.
+    ///
+    /// Positions are `(x, y)` in the range `[0.0, 1.0]`, from the top left corner. Clicking the
+    /// touchpad is reported as the extra button 18, see
+    /// [Gamepad::is_extra_button_pressed()](crate::Gamepad::is_extra_button_pressed).
+    pub fn touchpad(&self, gamepad_id: GamepadId) -> [Option<(f32, f32)>; 2] {
+        self.playstation
+            .device(gamepad_id)
+            .map_or([None; 2], |device| device.touch_points)
+    }
+}
@@ -0,0 +1,244 @@
+use std::sync::{Arc, Mutex};
+
+use crate::event::RawTimestamps;
+use crate::{BackendState, Capabilities, Gamepad, GamepadBackend, GamepadId, MAX_GAMEPADS};
+
+/// A rumble effect requested from a [PortBackend] using [Gamepads::rumble()](crate::Gamepads::rumble).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RumbleRequest {
+    /// The gamepad to rumble.
+    pub gamepad_id: GamepadId,
+    /// Duration of the rumble in milliseconds.
+    pub duration_ms: u32,
+    /// Delay of the rumble in milliseconds.
+    pub start_delay_ms: u32,
+    /// The vibration magnitude for the low frequency rumble in the range `[0.0, 1.0]`.
+    pub strong_magnitude: f32,
+    /// The vibration magnitude for the high frequency rumble in the range `[0.0, 1.0]`.
+    pub weak_magnitude: f32,
+}
+
+/// State pushed by the platform since the last poll.
+struct Pushed {
+    /// The latest state of each gamepad, as pushed by the platform.
+    latest: [Gamepad; MAX_GAMEPADS],
+    /// Whether each gamepad has been pushed since the last poll.
+    changed: [bool; MAX_GAMEPADS],
+    /// Bits pressed since the last poll, including those already released again.
+    just_pressed_bits: [u32; MAX_GAMEPADS],
+    high_just_pressed_bits: [u64; MAX_GAMEPADS],
+    raw_timestamps: [RawTimestamps; MAX_GAMEPADS],
+    names: [Option<String>; MAX_GAMEPADS],
+    capabilities: [Capabilities; MAX_GAMEPADS],
+}
+
+type RumbleCallback = Box<dyn FnMut(RumbleRequest) + Send>;
+type LedCallback = Box<dyn FnMut(GamepadId, (u8, u8, u8)) + Send>;
+
+/// A backend for porting to platforms not supported by this crate, where the platform code
+/// pushes gamepad state when it changes instead of being polled.
+///
+/// This suits closed platforms such as consoles, whose input APIs deliver state through their
+/// own callbacks or threads, so that ports can be maintained out of tree. The platform code
+/// pushes the state of each gamepad as a [Gamepad] value using a [PortHandle], from any thread,
+/// and receives rumble requests through [PortBackend::on_rumble()]. Pushed state is applied on
+/// the next [Gamepads::poll()](crate::Gamepads::poll), where buttons pressed and released again
+/// in between are reported as just pressed.
+///
+/// For a backend that reads the platform when polled, implement [GamepadBackend] instead.
+///
+/// ```
+/// use gamepads::{Button, Gamepad, GamepadId, Gamepads, PortBackend};
+///
+/// let backend = PortBackend::new().on_rumble(|request| {
+///     // Forward to the rumble API of the platform.
+///     println!("Rumble {:?}", request.gamepad_id);
+/// });
+/// let port = backend.handle();
+/// let mut gamepads = Gamepads::new();
+/// gamepads.set_backend(backend);
+///
+/// // From the input callback or thread of the platform:
+/// let mut gamepad = Gamepad::new(GamepadId::new(0).unwrap());
+/// gamepad.set_connected(true);
+/// gamepad.press(Button::ActionDown);
+/// gamepad.set_left_stick((0.5, 0.));
+/// port.push(&gamepad);
+///
+/// gamepads.poll();
+/// let gamepad = gamepads.get(GamepadId::new(0).unwrap()).unwrap();
+/// assert!(gamepad.is_just_pressed(Button::ActionDown));
+/// assert_eq!(gamepad.left_stick_x(), 0.5);
+/// ```
+pub struct PortBackend {
+    pushed: Arc<Mutex<Pushed>>,
+    on_rumble: Option<RumbleCallback>,
+    on_led: Option<LedCallback>,
+}
+
+/// A handle for pushing gamepad state into a [PortBackend], which may be cloned and used from
+/// any thread.
+#[derive(Clone)]
+pub struct PortHandle {
+    pushed: Arc<Mutex<Pushed>>,
+}
+
+impl PortBackend {
+    /// Create a backend without gamepads, until pushed using a [PortHandle].
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            pushed: Arc::new(Mutex::new(Pushed {
+                latest: std::array::from_fn(|idx| Gamepad::new(GamepadId(idx as u8))),
+                changed: [false; MAX_GAMEPADS],
+                just_pressed_bits: [0; MAX_GAMEPADS],
+                high_just_pressed_bits: [0; MAX_GAMEPADS],
+                raw_timestamps: [RawTimestamps::default(); MAX_GAMEPADS],
+                names: Default::default(),
+                capabilities: [Capabilities::default(); MAX_GAMEPADS],
+            })),
+            on_rumble: None,
+            on_led: None,
+        }
+    }
+
+    /// A handle for pushing gamepad state into this backend.
+    pub fn handle(&self) -> PortHandle {
+        PortHandle {
+            pushed: Arc::clone(&self.pushed),
+        }
+    }
+
+    /// Receive rumble requests made using [Gamepads::rumble()](crate::Gamepads::rumble).
+    pub fn on_rumble(mut self, callback: impl FnMut(RumbleRequest) + Send + 'static) -> Self {
+        self.on_rumble = Some(Box::new(callback));
+        self
+    }
+
+    /// Receive light bar colors set using [Gamepads::set_led()](crate::Gamepads::set_led).
+    pub fn on_led(
+        mut self,
+        callback: impl FnMut(GamepadId, (u8, u8, u8)) + Send + 'static,
+    ) -> Self {
+        self.on_led = Some(Box::new(callback));
+        self
+    }
+}
+
+impl PortHandle {
+    /// Push the current state of a gamepad, identified by its [Gamepad::id()], replacing the
+    /// state pushed before. Push a disconnected gamepad, such as [Gamepad::new()], when it is
+    /// disconnected.
+    pub fn push(&self, gamepad: &Gamepad) {
+        let Ok(mut pushed) = self.pushed.lock() else {
+            return;
+        };
+        let idx = usize::from(gamepad.id.0);
+        let now_ms = crate::time::now_ms();
+        let previous = pushed.latest[idx];
+        let (pressed_bits, high_pressed_bits) = if gamepad.connected {
+            (gamepad.pressed_bits, gamepad.high_pressed_bits)
+        } else {
+            (0, 0)
+        };
+        let changed_bits = previous.pressed_bits ^ pressed_bits;
+        for bit_idx in (0..32).filter(|bit_idx| changed_bits & (1 << bit_idx) != 0) {
+            if pressed_bits & (1 << bit_idx) != 0 {
+                pushed.raw_timestamps[idx].record_press(bit_idx, now_ms);
+            } else {
+                pushed.raw_timestamps[idx].record_release(bit_idx, now_ms);
+            }
+        }
+        if previous.axes != gamepad.axes || previous.triggers != gamepad.triggers {
+            pushed.raw_timestamps[idx].record_axes(now_ms);
+        }
+        pushed.just_pressed_bits[idx] |= changed_bits & pressed_bits;
+        pushed.high_just_pressed_bits[idx] |= high_pressed_bits & !previous.high_pressed_bits;
+        pushed.latest[idx] = *gamepad;
+        if !gamepad.connected {
+            pushed.latest[idx].set_connected(false);
+        }
+        pushed.changed[idx] = true;
+    }
+
+    /// Set the name of a gamepad, as reported by [Gamepads::name()](crate::Gamepads::name).
+    pub fn set_name(&self, gamepad_id: GamepadId, name: Option<String>) {
+        if let Ok(mut pushed) = self.pushed.lock() {
+            pushed.names[usize::from(gamepad_id.0)] = name;
+        }
+    }
+
+    /// Set what a gamepad supports, as reported by
+    /// [Gamepads::capabilities()](crate::Gamepads::capabilities).
+    pub fn set_capabilities(&self, gamepad_id: GamepadId, capabilities: Capabilities) {
+        if let Ok(mut pushed) = self.pushed.lock() {
+            pushed.capabilities[usize::from(gamepad_id.0)] = capabilities;
+        }
+    }
+}
+
+impl GamepadBackend for PortBackend {
+    fn poll(&mut self, state: &mut BackendState) {
+        let Ok(mut pushed) = self.pushed.lock() else {
+            return;
+        };
+        for idx in 0..MAX_GAMEPADS {
+            if !std::mem::take(&mut pushed.changed[idx]) {
+                continue;
+            }
+            let latest = pushed.latest[idx];
+            let gamepad = &mut state.gamepads[idx];
+            gamepad.connected = latest.connected;
+            gamepad.mapping = latest.mapping;
+            gamepad.pressed_bits = latest.pressed_bits;
+            gamepad.just_pressed_bits |= std::mem::take(&mut pushed.just_pressed_bits[idx]);
+            gamepad.axes = latest.axes;
+            gamepad.triggers = latest.triggers;
+            gamepad.extra_axes = latest.extra_axes;
+            gamepad.high_pressed_bits = latest.high_pressed_bits;
+            gamepad.high_just_pressed_bits |=
+                std::mem::take(&mut pushed.high_just_pressed_bits[idx]);
+            let raw_timestamps = std::mem::take(&mut pushed.raw_timestamps[idx]);
+            state.raw_timestamps[idx].merge(&raw_timestamps);
+        }
+    }
+
+    fn rumble(
+        &mut self,
+        gamepad_id: GamepadId,
+        duration_ms: u32,
+        start_delay_ms: u32,
+        strong_magnitude: f32,
+        weak_magnitude: f32,
+    ) {
+        if let Some(on_rumble) = &mut self.on_rumble {
+            on_rumble(RumbleRequest {
+                gamepad_id,
+                duration_ms,
+                start_delay_ms,
+                strong_magnitude,
+                weak_magnitude,
+            });
+        }
+    }
+
+    fn capabilities(&self, gamepad_id: GamepadId) -> Capabilities {
+        self.pushed.lock().map_or_else(
+            |_| Capabilities::default(),
+            |pushed| pushed.capabilities[usize::from(gamepad_id.0)],
+        )
+    }
+
+    fn name(&self, gamepad_id: GamepadId) -> Option<String> {
+        self.pushed
+            .lock()
+            .ok()
+            .and_then(|pushed| pushed.names[usize::from(gamepad_id.0)].clone())
+    }
+
+    fn set_led(&mut self, gamepad_id: GamepadId, color: (u8, u8, u8)) {
+        if let Some(on_led) = &mut self.on_led {
+            on_led(gamepad_id, color);
+        }
+    }
+}
@@ -0,0 +1,440 @@
+use crate::{Button, GamepadId, Remap};
+
+/// Per-gamepad configuration and state used when processing raw backend state
+/// into the state exposed by [crate::Gamepads::get()] and [crate::Gamepads::all()].
+#[derive(Clone, Copy, Default)]
+pub struct Processing {
+    remap: Remap,
+    /// Whether the shift button of the remap was held at the last poll.
+    shift_held: bool,
+    /// Whether another button was pressed or a thumbstick moved while holding the shift button,
+    /// so that releasing it is not a tap.
+    shift_used: bool,
+    /// Bits of buttons in toggle mode.
+    toggle_bits: u32,
+    /// Bits of buttons in toggle mode which are currently toggled on.
+    toggled_on_bits: u32,
+    /// Minimum duration a button needs to be held before it registers as pressed.
+    min_press_duration_ms: u32,
+    /// Bits of buttons which have been held for at least the minimum press duration.
+    debounced_bits: u32,
+    /// When each button was last pressed, indexed by bit.
+    pressed_at_ms: [f64; 32],
+    /// Bits of buttons with turbo enabled.
+    turbo_bits: u32,
+    /// Interval between synthesized presses for buttons with turbo enabled, indexed by bit.
+    turbo_interval_ms: [f64; 32],
+    /// When the next press should be synthesized for buttons with turbo enabled, indexed by bit.
+    turbo_next_press_ms: [f64; 32],
+    /// Whether menu repeat is enabled.
+    menu_repeat: bool,
+    /// Delay after a direction is pressed until it starts repeating in menu repeat mode.
+    menu_repeat_delay_ms: f64,
+    /// Interval between repeats in menu repeat mode.
+    menu_repeat_interval_ms: f64,
+    /// Bits of D-pad buttons pressed by moving the left thumbstick in menu repeat mode.
+    menu_repeat_stick_bits: u32,
+    /// When the next repeat should happen for D-pad buttons, indexed by bit.
+    menu_repeat_next_ms: [f64; 32],
+    /// For how long presses are buffered.
+    buffer_ms: u32,
+    /// Bits of buttons which have been pressed at least once.
+    ever_pressed_bits: u32,
+    /// Bits of buttons with a buffered press, which has not yet been consumed or expired.
+    buffered_bits: u32,
+    /// Bits of buttons reported as just pressed only due to an earlier, buffered press.
+    carried_bits: u32,
+    /// When each button was last registered as just pressed, indexed by bit.
+    last_just_pressed_ms: [f64; 32],
+    /// When state was last processed.
+    processed_at_ms: f64,
+}
+
+const DPAD_BITS: u32 = (1 << Button::DPadUp as u32)
+    | (1 << Button::DPadDown as u32)
+    | (1 << Button::DPadLeft as u32)
+    | (1 << Button::DPadRight as u32);
+
+/// How far the left thumbstick needs to be moved to act as a D-pad direction in menu repeat mode.
+const MENU_REPEAT_STICK_THRESHOLD: f32 = 0.5;
+
+/// How far a thumbstick needs to be moved while holding the shift button of a remap for its
+/// release to not count as a tap.
+const SHIFT_STICK_THRESHOLD: f32 = 0.5;
+
+impl Processing {
+    /// Apply the remap to the raw pressed and just pressed bits, returning them with whether
+    /// the shift button is held.
+    ///
+    /// Buttons changing their mapping as the shift button is pressed or released are reported
+    /// as just pressed under their new mapping, and a tap of the shift button alone as its own
+    /// mapping being pressed and released in between polls.
+    fn remap_bits(&mut self, raw: &crate::Gamepad) -> (u32, u32, bool) {
+        let shift_bit = self.remap.shift_bit();
+        let shifted = raw.pressed_bits & shift_bit != 0;
+        let other_pressed_bits = raw.pressed_bits & !shift_bit;
+        let pressed_bits = self.remap.apply_to_bits(other_pressed_bits, shifted);
+        let mut just_pressed_bits = self
+            .remap
+            .apply_to_bits(raw.just_pressed_bits & !shift_bit, shifted);
+        if shift_bit == 0 {
+            return (pressed_bits, just_pressed_bits, false);
+        }
+
+        if shifted != self.shift_held {
+            just_pressed_bits |= pressed_bits
+                & !self
+                    .remap
+                    .apply_to_bits(other_pressed_bits, self.shift_held);
+        }
+        let shift_pressed = shifted || raw.just_pressed_bits & shift_bit != 0;
+        if shift_pressed {
+            if !self.shift_held {
+                self.shift_used = false;
+            }
+            let stick_moved = raw
+                .axes
+                .iter()
+                .any(|axis| axis.abs() > SHIFT_STICK_THRESHOLD);
+            self.shift_used |=
+                (other_pressed_bits | raw.just_pressed_bits) & !shift_bit != 0 || stick_moved;
+        }
+        if !shifted && shift_pressed | self.shift_held && !self.shift_used {
+            just_pressed_bits |= self.remap.apply_to_bits(shift_bit, false);
+        }
+        self.shift_held = shifted;
+        (pressed_bits, just_pressed_bits, shifted)
+    }
+
+    /// Filter out button presses not held for the minimum press duration.
+    fn debounce(&mut self, now_ms: f64, pressed_bits: &mut u32, just_pressed_bits: &mut u32) {
+        if self.min_press_duration_ms == 0 {
+            return;
+        }
+
+        for bit_idx in 0..32 {
+            if *just_pressed_bits & (1 << bit_idx) != 0 {
+                self.pressed_at_ms[bit_idx] = now_ms;
+            }
+        }
+
+        let mut held_long_enough_bits = 0;
+        for bit_idx in 0..32 {
+            if *pressed_bits & (1 << bit_idx) != 0
+                && now_ms - self.pressed_at_ms[bit_idx] >= f64::from(self.min_press_duration_ms)
+            {
+                held_long_enough_bits |= 1 << bit_idx;
+            }
+        }
+
+        *just_pressed_bits = held_long_enough_bits & !self.debounced_bits;
+        *pressed_bits = held_long_enough_bits;
+        self.debounced_bits = held_long_enough_bits;
+    }
+
+    /// Synthesize repeated presses for held buttons with turbo enabled.
+    fn turbo(&mut self, now_ms: f64, pressed_bits: u32, just_pressed_bits: &mut u32) {
+        let held_turbo_bits = pressed_bits & self.turbo_bits;
+        if held_turbo_bits == 0 {
+            return;
+        }
+
+        for bit_idx in 0..32 {
+            let bit = 1 << bit_idx;
+            if held_turbo_bits & bit == 0 {
+                continue;
+            }
+            let interval_ms = self.turbo_interval_ms[bit_idx];
+            if *just_pressed_bits & bit != 0 {
+                self.turbo_next_press_ms[bit_idx] = now_ms + interval_ms;
+            } else if now_ms >= self.turbo_next_press_ms[bit_idx] {
+                *just_pressed_bits |= bit;
+                // Avoid a burst of presses if not polled for a while:
+                self.turbo_next_press_ms[bit_idx] =
+                    (self.turbo_next_press_ms[bit_idx] + interval_ms).max(now_ms);
+            }
+        }
+    }
+
+    /// Let the left thumbstick act as the D-pad and repeat held D-pad directions, if in menu repeat mode.
+    fn menu_repeat(
+        &mut self,
+        now_ms: f64,
+        left_stick: (f32, f32),
+        pressed_bits: &mut u32,
+        just_pressed_bits: &mut u32,
+    ) {
+        if !self.menu_repeat {
+            return;
+        }
+
+        let mut stick_bits = 0;
+        for (value, negative_button, positive_button) in [
+            (left_stick.0, Button::DPadLeft, Button::DPadRight),
+            (left_stick.1, Button::DPadDown, Button::DPadUp),
+        ] {
+            if value <= -MENU_REPEAT_STICK_THRESHOLD {
+                stick_bits |= 1 << (negative_button as u32);
+            } else if value >= MENU_REPEAT_STICK_THRESHOLD {
+                stick_bits |= 1 << (positive_button as u32);
+            }
+        }
+        *just_pressed_bits |= stick_bits & !self.menu_repeat_stick_bits & !*pressed_bits;
+        *pressed_bits |= stick_bits;
+        self.menu_repeat_stick_bits = stick_bits;
+
+        let held_dpad_bits = *pressed_bits & DPAD_BITS;
+        for bit_idx in 0..32 {
+            let bit = 1 << bit_idx;
+            if held_dpad_bits & bit == 0 {
+                continue;
+            }
+            if *just_pressed_bits & bit != 0 {
+                self.menu_repeat_next_ms[bit_idx] = now_ms + self.menu_repeat_delay_ms;
+            } else if now_ms >= self.menu_repeat_next_ms[bit_idx] {
+                *just_pressed_bits |= bit;
+                self.menu_repeat_next_ms[bit_idx] =
+                    (self.menu_repeat_next_ms[bit_idx] + self.menu_repeat_interval_ms).max(now_ms);
+            }
+        }
+    }
+
+    /// Record presses and keep buffered presses as just pressed, if input buffering is enabled.
+    fn buffer(&mut self, now_ms: f64, just_pressed_bits: &mut u32) {
+        self.processed_at_ms = now_ms;
+        for bit_idx in 0..32 {
+            if *just_pressed_bits & (1 << bit_idx) != 0 {
+                self.last_just_pressed_ms[bit_idx] = now_ms;
+            }
+        }
+        self.ever_pressed_bits |= *just_pressed_bits;
+
+        if self.buffer_ms == 0 {
+            self.carried_bits = 0;
+            return;
+        }
+        self.buffered_bits |= *just_pressed_bits;
+        for bit_idx in 0..32 {
+            if now_ms - self.last_just_pressed_ms[bit_idx] > f64::from(self.buffer_ms) {
+                self.buffered_bits &= !(1 << bit_idx);
+            }
+        }
+        self.carried_bits = self.buffered_bits & !*just_pressed_bits;
+        *just_pressed_bits |= self.buffered_bits;
+    }
+
+    pub const fn remap(&self) -> &Remap {
+        &self.remap
+    }
+
+    /// Bits of buttons reported as just pressed only due to an earlier, buffered press.
+    pub const fn carried_bits(&self) -> u32 {
+        self.carried_bits
+    }
+
+    fn was_pressed_within(&self, bit_idx: usize, duration_ms: u32) -> bool {
+        self.ever_pressed_bits & (1 << bit_idx) != 0
+            && self.processed_at_ms - self.last_just_pressed_ms[bit_idx] <= f64::from(duration_ms)
+    }
+
+    /// Apply toggle mode to buttons configured for it.
+    const fn toggle(
+        &mut self,
+        previously_pressed_bits: u32,
+        pressed_bits: &mut u32,
+        just_pressed_bits: &mut u32,
+    ) {
+        // Note that toggled_on_bits is always a subset of toggle_bits.
+        self.toggled_on_bits ^= *just_pressed_bits & self.toggle_bits;
+        *pressed_bits = (*pressed_bits & !self.toggle_bits) | self.toggled_on_bits;
+        *just_pressed_bits = (*just_pressed_bits & !self.toggle_bits)
+            | (self.toggled_on_bits & !previously_pressed_bits);
+    }
+}
+
+impl crate::Gamepads {
+    /// Set the remapping of buttons and thumbsticks to apply to a gamepad.
+    ///
+    /// See [Remap] for the available remappings and presets, such as one-handed layouts. This
+    /// replaces the profile applied to dance pads and arcade sticks by default, see
+    /// [Gamepads::set_device_profiles()].
+    pub const fn set_remap(&mut self, gamepad_id: GamepadId, remap: Remap) {
+        self.processing[gamepad_id.0 as usize].remap = remap;
+    }
+
+    /// Set whether a button should be in toggle mode.
+    ///
+    /// A button in toggle mode is held down logically after being pressed once, until it is
+    /// pressed again - the button does not need to be physically held down. This is useful as
+    /// an accessibility option for players who are unable to hold buttons, e.g. for sprinting
+    /// or aiming.
+    ///
+    /// Toggle mode is off for all buttons by default.
+    pub const fn set_toggle(&mut self, gamepad_id: GamepadId, button: Button, toggle: bool) {
+        let bit = 1 << (button as u32);
+        let processing = &mut self.processing[gamepad_id.0 as usize];
+        if toggle {
+            processing.toggle_bits |= bit;
+        } else {
+            processing.toggle_bits &= !bit;
+            processing.toggled_on_bits &= !bit;
+        }
+    }
+
+    /// Set the minimum duration buttons on a gamepad need to be held before they register as pressed.
+    ///
+    /// Presses shorter than this are ignored, which filters out accidental taps caused by e.g. tremors.
+    /// Note that this delays all button presses on the gamepad by the given duration.
+    ///
+    /// The default is `0`, meaning that all button presses register immediately.
+    pub const fn set_min_press_duration(&mut self, gamepad_id: GamepadId, duration_ms: u32) {
+        self.processing[gamepad_id.0 as usize].min_press_duration_ms = duration_ms;
+    }
+
+    /// Set the turbo rate of a button, making it repeatedly register as just pressed while held.
+    ///
+    /// While the button is held down, [Gamepad::is_just_pressed()](crate::Gamepad::is_just_pressed)
+    /// reports it as just pressed `rate_hz` times per second, as if it was repeatedly pressed. This is
+    /// often known as auto-fire.
+    ///
+    /// A rate of `0.0` (the default) disables turbo for the button.
+    pub fn set_turbo(&mut self, gamepad_id: GamepadId, button: Button, rate_hz: f32) {
+        let bit_idx = button as usize;
+        let processing = &mut self.processing[gamepad_id.0 as usize];
+        if rate_hz > 0. {
+            processing.turbo_bits |= 1 << bit_idx;
+            processing.turbo_interval_ms[bit_idx] = 1000. / f64::from(rate_hz);
+        } else {
+            processing.turbo_bits &= !(1 << bit_idx);
+        }
+    }
+
+    /// Enable menu repeat mode, suitable for navigating menus.
+    ///
+    /// In this mode, holding a D-pad direction makes it register as just pressed again after
+    /// `initial_delay_ms`, and then repeatedly every `interval_ms` for as long as it is held.
+    /// Moving the left thumbstick in a direction also acts as pressing the corresponding
+    /// D-pad button.
+    ///
+    /// Menu repeat mode is disabled by default, see [Gamepads::disable_menu_repeat()].
+    pub fn enable_menu_repeat(
+        &mut self,
+        gamepad_id: GamepadId,
+        initial_delay_ms: u32,
+        interval_ms: u32,
+    ) {
+        let processing = &mut self.processing[gamepad_id.0 as usize];
+        processing.menu_repeat = true;
+        processing.menu_repeat_delay_ms = f64::from(initial_delay_ms);
+        processing.menu_repeat_interval_ms = f64::from(interval_ms);
+    }
+
+    /// Disable menu repeat mode enabled by [Gamepads::enable_menu_repeat()].
+    pub const fn disable_menu_repeat(&mut self, gamepad_id: GamepadId) {
+        let processing = &mut self.processing[gamepad_id.0 as usize];
+        processing.menu_repeat = false;
+        processing.menu_repeat_stick_bits = 0;
+    }
+
+    /// Set for how long button presses are buffered.
+    ///
+    /// A buffered press keeps being reported by [Gamepad::is_just_pressed()](crate::Gamepad::is_just_pressed)
+    /// on each poll until either `buffer_ms` has passed since the press, or it has been consumed using
+    /// [Gamepads::consume_press()]. This allows for forgiving controls, where e.g. a jump button pressed
+    /// slightly before landing still causes a jump.
+    ///
+    /// The default is `0`, meaning that presses are not buffered.
+    pub const fn set_input_buffer(&mut self, gamepad_id: GamepadId, buffer_ms: u32) {
+        let processing = &mut self.processing[gamepad_id.0 as usize];
+        processing.buffer_ms = buffer_ms;
+        if buffer_ms == 0 {
+            processing.buffered_bits = 0;
+            processing.carried_bits = 0;
+        }
+    }
+
+    /// Consume a buffered button press, so that it is no longer reported as just pressed.
+    ///
+    /// See [Gamepads::set_input_buffer()].
+    pub const fn consume_press(&mut self, gamepad_id: GamepadId, button: Button) {
+        let bit = 1 << (button as u32);
+        let gamepad_idx = gamepad_id.0 as usize;
+        self.processing[gamepad_idx].buffered_bits &= !bit;
+        self.processed[gamepad_idx].just_pressed_bits &= !bit;
+    }
+
+    /// Check if a button was pressed within the given duration before the last [Gamepads::poll()].
+    ///
+    /// This works regardless of whether input buffering is enabled using [Gamepads::set_input_buffer()].
+    pub fn was_pressed_within(
+        &self,
+        gamepad_id: GamepadId,
+        button: Button,
+        duration_ms: u32,
+    ) -> bool {
+        self.processing[gamepad_id.0 as usize].was_pressed_within(button as usize, duration_ms)
+    }
+
+    /// When [Gamepads::poll()] was last called, in milliseconds since an unspecified point in time.
+    ///
+    /// This uses the same monotonic clock as the timestamps of [Gamepads::events()].
+    pub const fn last_poll_ms(&self) -> f64 {
+        self.last_poll_ms
+    }
+
+    /// Process the raw state written by the backend into the state exposed to the user.
+    pub(crate) fn process(&mut self) {
+        let now_ms = crate::time::now_ms();
+        let previous = self.processed;
+        self.apply_device_profiles();
+        let mut raw_gamepads = self.combine_joycons();
+        self.merge_units(&mut raw_gamepads);
+        for (((raw, processed), processing), injected) in raw_gamepads
+            .iter()
+            .zip(self.processed.iter_mut())
+            .zip(self.processing.iter_mut())
+            .zip(self.injected.iter_mut())
+        {
+            let raw = &injected.take_merged(raw);
+            let previously_pressed_bits = processed.pressed_bits;
+            let previous_high_pressed_bits = processed.high_pressed_bits;
+            let (mut pressed_bits, mut just_pressed_bits, shifted) = processing.remap_bits(raw);
+
+            let axes = processing
+                .remap
+                .apply_to_axes(raw.axes, pressed_bits, shifted);
+
+            processing.debounce(now_ms, &mut pressed_bits, &mut just_pressed_bits);
+            processing.menu_repeat(
+                now_ms,
+                (axes[0], axes[1]),
+                &mut pressed_bits,
+                &mut just_pressed_bits,
+            );
+            processing.toggle(
+                previously_pressed_bits,
+                &mut pressed_bits,
+                &mut just_pressed_bits,
+            );
+            processing.turbo(now_ms, pressed_bits, &mut just_pressed_bits);
+
+            *processed = *raw;
+            processed.pressed_bits = pressed_bits;
+            processed.axes = axes;
+            // Released since the last poll, or pressed and released in between polls:
+            processed.just_released_bits =
+                (previously_pressed_bits | just_pressed_bits) & !pressed_bits;
+            processing.buffer(now_ms, &mut just_pressed_bits);
+            processed.just_pressed_bits = just_pressed_bits;
+            // Buttons from index 32 are not remapped or processed:
+            processed.high_just_released_bits =
+                (previous_high_pressed_bits | raw.high_just_pressed_bits) & !raw.high_pressed_bits;
+        }
+
+        self.tag_changes(&previous);
+        self.recognize_combos(now_ms);
+        self.last_poll_ms = now_ms;
+        self.dispatch_events(&previous, now_ms);
+        self.publish_to_readers();
+    }
+}
@@ -0,0 +1,74 @@
+use std::sync::{Arc, RwLock};
+
+use crate::{Gamepad, GamepadId, MAX_GAMEPADS};
+
+/// A cheap handle for reading gamepad state from other threads.
+///
+/// Obtained using [Gamepads::reader()](crate::Gamepads::reader). The state is that of the
+/// last [Gamepads::poll()](crate::Gamepads::poll), so a single thread can own the [Gamepads]
+/// instance and poll it while any number of threads read the state.
+///
+/// ```
+/// use gamepads::Gamepads;
+///
+/// let mut gamepads = Gamepads::new();
+/// let reader = gamepads.reader();
+/// std::thread::spawn(move || {
+///     for gamepad in reader.all() {
+///         println!("Left thumbstick: {:?}", gamepad.left_stick());
+///     }
+/// });
+/// gamepads.poll();
+/// ```
+#[derive(Clone)]
+pub struct GamepadsReader {
+    state: Arc<RwLock<[Gamepad; MAX_GAMEPADS]>>,
+}
+
+impl GamepadsReader {
+    fn snapshot(&self) -> Option<[Gamepad; MAX_GAMEPADS]> {
+        self.state.read().ok().map(|state| *state)
+    }
+
+    /// Get a gamepad by id, returning `None` if it is no longer connected.
+    ///
+    /// See [Gamepads::get()](crate::Gamepads::get).
+    pub fn get(&self, gamepad_id: GamepadId) -> Option<Gamepad> {
+        let pad = self.snapshot()?[gamepad_id.0 as usize];
+        pad.connected.then_some(pad)
+    }
+
+    /// Retrieve information about all connected gamepads.
+    ///
+    /// The state of all gamepads is read at once, so it is consistent across gamepads.
+    /// See [Gamepads::all()](crate::Gamepads::all).
+    pub fn all(&self) -> impl Iterator<Item = Gamepad> {
+        self.snapshot()
+            .into_iter()
+            .flatten()
+            .filter(|p| p.connected)
+    }
+}
+
+impl crate::Gamepads {
+    /// Create a handle for reading the state of gamepads from other threads.
+    ///
+    /// See [GamepadsReader] for more information.
+    pub fn reader(&mut self) -> GamepadsReader {
+        let state = self
+            .reader_state
+            .get_or_insert_with(|| Arc::new(RwLock::new(self.processed)));
+        GamepadsReader {
+            state: state.clone(),
+        }
+    }
+
+    /// Publish the processed state to readers, if any.
+    pub(crate) fn publish_to_readers(&self) {
+        if let Some(state) = &self.reader_state {
+            if let Ok(mut state) = state.write() {
+                *state = self.processed;
+            }
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use std::io::{Read, Write};
+
+use crate::{Gamepad, MAX_GAMEPADS};
+
+/// Magic bytes starting a recording, followed by the version of the format.
+const MAGIC: [u8; 5] = *b"GPREC";
+
+/// Version of the recording format, incremented when the frame layout changes.
+///
+/// Version 2 added the mapping, triggers, extra axes and buttons from index 32.
+const VERSION: u8 = 2;
+
+/// Number of bytes of the state of a connected gamepad in a frame: the mapping, pressed bits,
+/// thumbstick axes, just pressed bits, triggers, extra axes, and high pressed and just pressed bits.
+const GAMEPAD_LEN: usize = 1 + 4 + 4 * 4 + 4 + 2 * 4 + crate::MAX_EXTRA_AXES * 4 + 8 + 8;
+
+/// An active recording or replay.
+#[derive(Default)]
+pub enum Recorder {
+    #[default]
+    Inactive,
+    Recording {
+        writer: Box<dyn Write + Send>,
+        frame: u64,
+        error: Option<std::io::Error>,
+    },
+    Replaying {
+        reader: Box<dyn Read + Send>,
+        frame: u64,
+    },
+}
+
+fn write_frame(
+    writer: &mut dyn Write,
+    frame: u64,
+    gamepads: &[Gamepad; MAX_GAMEPADS],
+) -> std::io::Result<()> {
+    let connected_mask = gamepads
+        .iter()
+        .enumerate()
+        .filter(|(_, gamepad)| gamepad.connected)
+        .fold(0_u8, |mask, (idx, _)| mask | (1 << idx));
+    writer.write_all(&frame.to_le_bytes())?;
+    writer.write_all(&[connected_mask])?;
+    for gamepad in gamepads.iter().filter(|gamepad| gamepad.connected) {
+        let mut bytes = [0; GAMEPAD_LEN];
+        let mut out = &mut bytes[..];
+        out.write_all(&[gamepad.mapping])?;
+        out.write_all(&gamepad.pressed_bits.to_le_bytes())?;
+        for axis in gamepad.axes {
+            out.write_all(&axis.to_le_bytes())?;
+        }
+        out.write_all(&gamepad.just_pressed_bits.to_le_bytes())?;
+        for axis in gamepad.triggers.iter().chain(&gamepad.extra_axes) {
+            out.write_all(&axis.to_le_bytes())?;
+        }
+        out.write_all(&gamepad.high_pressed_bits.to_le_bytes())?;
+        out.write_all(&gamepad.high_just_pressed_bits.to_le_bytes())?;
+        writer.write_all(&bytes)?;
+    }
+    Ok(())
+}
+
+fn read_frame(reader: &mut dyn Read) -> std::io::Result<(u64, [Gamepad; MAX_GAMEPADS])> {
+    let mut frame_bytes = [0; 8];
+    reader.read_exact(&mut frame_bytes)?;
+    let mut connected_mask = [0; 1];
+    reader.read_exact(&mut connected_mask)?;
+
+    let mut gamepads: [Gamepad; MAX_GAMEPADS] =
+        std::array::from_fn(|idx| Gamepad::new(crate::GamepadId(idx as u8)));
+    for (idx, gamepad) in gamepads.iter_mut().enumerate() {
+        if connected_mask[0] & (1 << idx) == 0 {
+            continue;
+        }
+        let mut bytes = [0; GAMEPAD_LEN];
+        reader.read_exact(&mut bytes)?;
+        let mut bytes = &bytes[..];
+        let mut u32_bytes = [0; 4];
+        let mut u64_bytes = [0; 8];
+        let mut mapping = [0; 1];
+        bytes.read_exact(&mut mapping)?;
+        gamepad.connected = true;
+        gamepad.mapping = mapping[0];
+        bytes.read_exact(&mut u32_bytes)?;
+        gamepad.pressed_bits = u32::from_le_bytes(u32_bytes);
+        for axis in &mut gamepad.axes {
+            bytes.read_exact(&mut u32_bytes)?;
+            *axis = f32::from_le_bytes(u32_bytes);
+        }
+        bytes.read_exact(&mut u32_bytes)?;
+        gamepad.just_pressed_bits = u32::from_le_bytes(u32_bytes);
+        for axis in gamepad.triggers.iter_mut().chain(&mut gamepad.extra_axes) {
+            bytes.read_exact(&mut u32_bytes)?;
+            *axis = f32::from_le_bytes(u32_bytes);
+        }
+        bytes.read_exact(&mut u64_bytes)?;
+        gamepad.high_pressed_bits = u64::from_le_bytes(u64_bytes);
+        bytes.read_exact(&mut u64_bytes)?;
+        gamepad.high_just_pressed_bits = u64::from_le_bytes(u64_bytes);
+    }
+    Ok((u64::from_le_bytes(frame_bytes), gamepads))
+}
+
+impl crate::Gamepads {
+    /// Start recording the state of all gamepads on each [Gamepads::poll()] to a writer.
+    ///
+    /// Each poll is recorded as a frame, numbered from zero. The recording can be played back
+    /// using [Gamepads::start_replay()], which is useful for reproducing bug reports and for
+    /// automated gameplay tests. Any active recording or replay is stopped.
+    ///
+    /// ```no_run
+    /// use gamepads::Gamepads;
+    ///
+    /// let mut gamepads = Gamepads::new();
+    /// let file = std::fs::File::create("input.rec").unwrap();
+    /// gamepads.start_recording(std::io::BufWriter::new(file)).unwrap();
+    /// ```
+    pub fn start_recording(
+        &mut self,
+        mut writer: impl Write + Send + 'static,
+    ) -> std::io::Result<()> {
+        self.stop_recording()?;
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        self.recorder = Recorder::Recording {
+            writer: Box::new(writer),
+            frame: 0,
+            error: None,
+        };
+        Ok(())
+    }
+
+    /// Stop an active recording, flushing the writer.
+    ///
+    /// Returns the first error encountered while recording, if any.
+    pub fn stop_recording(&mut self) -> std::io::Result<()> {
+        if let Recorder::Recording { .. } = self.recorder {
+            if let Recorder::Recording {
+                mut writer, error, ..
+            } = std::mem::take(&mut self.recorder)
+            {
+                if let Some(error) = error {
+                    return Err(error);
+                }
+                writer.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Start replaying a recording made using [Gamepads::start_recording()].
+    ///
+    /// While replaying, [Gamepads::poll()] advances to the next recorded frame instead of
+    /// reading gamepad hardware. When the recording ends or turns out to be malformed,
+    /// polling of the hardware resumes. Any active recording or replay is stopped.
+    ///
+    /// Returns an error of kind [InvalidData](std::io::ErrorKind::InvalidData) if the reader
+    /// is not a recording, or was recorded in a format of another version of this crate.
+    pub fn start_replay(&mut self, mut reader: impl Read + Send + 'static) -> std::io::Result<()> {
+        self.stop_recording()?;
+        let mut magic = [0; MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "not a gamepads recording",
+            ));
+        }
+        let mut version = [0; 1];
+        reader.read_exact(&mut version)?;
+        if version[0] != VERSION {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "unsupported gamepads recording version {}, expected {VERSION}",
+                    version[0]
+                ),
+            ));
+        }
+        self.recorder = Recorder::Replaying {
+            reader: Box::new(reader),
+            frame: 0,
+        };
+        Ok(())
+    }
+
+    /// Stop an active replay, resuming polling of the hardware.
+    pub fn stop_replay(&mut self) {
+        if let Recorder::Replaying { .. } = self.recorder {
+            self.recorder = Recorder::Inactive;
+        }
+    }
+
+    /// Whether a recording is being replayed.
+    pub const fn is_replaying(&self) -> bool {
+        matches!(self.recorder, Recorder::Replaying { .. })
+    }
+
+    /// The number of the frame last recorded or replayed, if any while recording or replaying.
+    pub const fn recording_frame(&self) -> Option<u64> {
+        match self.recorder {
+            Recorder::Inactive => None,
+            Recorder::Recording { frame, .. } | Recorder::Replaying { frame, .. } => {
+                frame.checked_sub(1)
+            }
+        }
+    }
+
+    /// Record the raw state of this poll, or read the replayed state to use instead of it.
+    pub(crate) fn next_recording_frame(&mut self) -> Option<[Gamepad; MAX_GAMEPADS]> {
+        match &mut self.recorder {
+            Recorder::Inactive => None,
+            Recorder::Recording {
+                writer,
+                frame,
+                error,
+            } => {
+                if error.is_none() {
+                    if let Err(e) = write_frame(writer, *frame, &self.gamepads) {
+                        *error = Some(e);
+                    }
+                }
+                *frame += 1;
+                None
+            }
+            Recorder::Replaying { reader, frame } => {
+                if let Ok((recorded_frame, gamepads)) = read_frame(reader) {
+                    *frame = recorded_frame + 1;
+                    Some(gamepads)
+                } else {
+                    self.recorder = Recorder::Inactive;
+                    None
+                }
+            }
+        }
+    }
+}
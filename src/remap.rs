@@ -0,0 +1,254 @@
+use crate::Button;
+
+/// A remapping of the buttons and thumbsticks of a gamepad.
+///
+/// Apply to a gamepad using [Gamepads::set_remap()](crate::Gamepads::set_remap). Each physical
+/// button maps to itself unless remapped, and a physical button can be mapped to several
+/// logical buttons by calling [Remap::map_button()] multiple times.
+///
+/// ```
+/// use gamepads::{Button, Remap};
+///
+/// // Let the D-pad act as the right cluster action buttons, in addition to the D-pad:
+/// let remap = Remap::new()
+///     .map_button(Button::DPadDown, Button::ActionDown)
+///     .map_button(Button::DPadRight, Button::ActionRight);
+/// ```
+///
+/// A shift button adds a second layer of mappings, such as for reaching every button with one
+/// hand. While it is held, other buttons act as mapped by [Remap::map_shifted_button()]:
+///
+/// ```
+/// use gamepads::{Button, Remap};
+///
+/// // Holding the left shoulder button turns the D-pad into the action buttons:
+/// let remap = Remap::new()
+///     .shift_button(Button::FrontLeftUpper)
+///     .map_shifted_button(Button::DPadDown, Button::ActionDown)
+///     .map_shifted_button(Button::DPadRight, Button::ActionRight);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Remap {
+    /// Logical button bits for each physical button bit.
+    button_bits: [u32; 32],
+    sticks_swapped: bool,
+    dpad_as_left_stick: bool,
+    /// The bit of the shift button, or 0 without one.
+    shift_bit: u32,
+    /// Logical button bits for each physical button bit while the shift button is held, with 0
+    /// for those keeping their unshifted mapping.
+    shifted_button_bits: [u32; 32],
+    /// Whether the thumbsticks are swapped again while the shift button is held.
+    shifted_sticks_swapped: bool,
+}
+
+impl Remap {
+    /// A remapping which maps every button and thumbstick to itself.
+    pub const fn new() -> Self {
+        let mut button_bits = [0; 32];
+        let mut bit_idx = 0;
+        while bit_idx < 32 {
+            button_bits[bit_idx] = 1 << bit_idx;
+            bit_idx += 1;
+        }
+        Self {
+            button_bits,
+            sticks_swapped: false,
+            dpad_as_left_stick: false,
+            shift_bit: 0,
+            shifted_button_bits: [0; 32],
+            shifted_sticks_swapped: false,
+        }
+    }
+
+    /// Map a physical button to an additional logical button.
+    pub const fn map_button(mut self, from: Button, to: Button) -> Self {
+        self.button_bits[from as usize] |= 1 << (to as u32);
+        self
+    }
+
+    /// Map a physical button to only the given logical button, removing its other mappings.
+    pub const fn replace_button(mut self, from: Button, to: Button) -> Self {
+        self.button_bits[from as usize] = 1 << (to as u32);
+        self
+    }
+
+    /// Swap the left and right thumbsticks.
+    pub const fn swap_sticks(mut self) -> Self {
+        self.sticks_swapped = !self.sticks_swapped;
+        self
+    }
+
+    /// Use a physical button as shift button, which switches other buttons to their mappings
+    /// from [Remap::map_shifted_button()] while held.
+    ///
+    /// The shift button only acts as its own mapping when tapped alone: it is then reported as
+    /// pressed and released when let go, if no other button was pressed and no thumbstick was
+    /// moved while holding it.
+    pub const fn shift_button(mut self, button: Button) -> Self {
+        self.shift_bit = 1 << (button as u32);
+        self
+    }
+
+    /// Map a physical button to an additional logical button while the shift button is held,
+    /// instead of its unshifted mapping. See [Remap::shift_button()].
+    pub const fn map_shifted_button(mut self, from: Button, to: Button) -> Self {
+        self.shifted_button_bits[from as usize] |= 1 << (to as u32);
+        self
+    }
+
+    /// Swap the left and right thumbsticks while the shift button is held, compared to when
+    /// it is not. See [Remap::shift_button()].
+    pub const fn swap_sticks_shifted(mut self) -> Self {
+        self.shifted_sticks_swapped = !self.shifted_sticks_swapped;
+        self
+    }
+
+    /// Let the D-pad also move the left thumbstick to its edge while pressed, such as for the
+    /// lever of arcade sticks reported as the D-pad.
+    pub const fn dpad_as_left_stick(mut self) -> Self {
+        self.dpad_as_left_stick = true;
+        self
+    }
+
+    /// A preset for playing with only the left hand, mirroring [Remap::one_handed_right()].
+    ///
+    /// | Physical button | Acts as | Acts as while holding the left center button |
+    /// |-----------------|---------|----------------------------------------------|
+    /// | Left thumbstick | Left thumbstick | Right thumbstick |
+    /// | Left stick button | Left stick button | Right stick button |
+    /// | D-pad | Right cluster action buttons | D-pad |
+    /// | Left front buttons | Left front buttons | Right front buttons |
+    /// | Mode | Mode | Left center button (typically select) |
+    ///
+    /// Tapping the left center button alone acts as the right one (typically start).
+    pub const fn one_handed_left() -> Self {
+        Self::new()
+            .replace_button(Button::DPadDown, Button::ActionDown)
+            .replace_button(Button::DPadRight, Button::ActionRight)
+            .replace_button(Button::DPadLeft, Button::ActionLeft)
+            .replace_button(Button::DPadUp, Button::ActionUp)
+            .replace_button(Button::LeftCenterCluster, Button::RightCenterCluster)
+            .shift_button(Button::LeftCenterCluster)
+            .swap_sticks_shifted()
+            .map_shifted_button(Button::LeftStick, Button::RightStick)
+            .map_shifted_button(Button::DPadDown, Button::DPadDown)
+            .map_shifted_button(Button::DPadRight, Button::DPadRight)
+            .map_shifted_button(Button::DPadLeft, Button::DPadLeft)
+            .map_shifted_button(Button::DPadUp, Button::DPadUp)
+            .map_shifted_button(Button::FrontLeftUpper, Button::FrontRightUpper)
+            .map_shifted_button(Button::FrontLeftLower, Button::FrontRightLower)
+            .map_shifted_button(Button::Mode, Button::LeftCenterCluster)
+    }
+
+    /// A preset for playing with only the right hand, mirroring [Remap::one_handed_left()].
+    ///
+    /// | Physical button | Acts as | Acts as while holding the right center button |
+    /// |-----------------|---------|-----------------------------------------------|
+    /// | Right thumbstick | Left thumbstick | Right thumbstick |
+    /// | Right stick button | Left stick button | Right stick button |
+    /// | Right cluster action buttons | Right cluster action buttons | D-pad |
+    /// | Right front buttons | Right front buttons | Left front buttons |
+    /// | Mode | Mode | Left center button (typically select) |
+    ///
+    /// Tapping the right center button alone acts as itself (typically start).
+    pub const fn one_handed_right() -> Self {
+        Self::new()
+            .swap_sticks()
+            .replace_button(Button::RightStick, Button::LeftStick)
+            .shift_button(Button::RightCenterCluster)
+            .swap_sticks_shifted()
+            .map_shifted_button(Button::RightStick, Button::RightStick)
+            .map_shifted_button(Button::ActionDown, Button::DPadDown)
+            .map_shifted_button(Button::ActionRight, Button::DPadRight)
+            .map_shifted_button(Button::ActionLeft, Button::DPadLeft)
+            .map_shifted_button(Button::ActionUp, Button::DPadUp)
+            .map_shifted_button(Button::FrontRightUpper, Button::FrontLeftUpper)
+            .map_shifted_button(Button::FrontRightLower, Button::FrontLeftLower)
+            .map_shifted_button(Button::Mode, Button::LeftCenterCluster)
+    }
+
+    /// A preset for dance pads, applied to them by default, see
+    /// [DeviceKind::DancePad](crate::DeviceKind::DancePad).
+    ///
+    /// The four arrows, which dance pads commonly report as the right cluster action buttons,
+    /// act as the D-pad instead, while the other buttons keep their function.
+    pub const fn dance_pad() -> Self {
+        Self::new()
+            .replace_button(Button::ActionLeft, Button::DPadLeft)
+            .replace_button(Button::ActionDown, Button::DPadDown)
+            .replace_button(Button::ActionUp, Button::DPadUp)
+            .replace_button(Button::ActionRight, Button::DPadRight)
+    }
+
+    /// A preset for arcade sticks, applied to them by default, see
+    /// [DeviceKind::ArcadeStick](crate::DeviceKind::ArcadeStick).
+    ///
+    /// The lever, which arcade sticks commonly report as the D-pad, also moves the left
+    /// thumbstick, for games steering with it, while the buttons keep their function.
+    pub const fn arcade_stick() -> Self {
+        Self::new().dpad_as_left_stick()
+    }
+
+    /// The logical button bits a physical button bit maps to, shifted or not.
+    pub(crate) const fn targets(&self, bit_idx: usize) -> u32 {
+        self.button_bits[bit_idx] | self.shifted_button_bits[bit_idx]
+    }
+
+    /// The bit of the shift button, or 0 without one.
+    pub(crate) const fn shift_bit(&self) -> u32 {
+        self.shift_bit
+    }
+
+    /// The logical button bits of physical button bits, using the shifted mappings if shifted.
+    pub(crate) const fn apply_to_bits(&self, bits: u32, shifted: bool) -> u32 {
+        let mut result = 0;
+        let mut bit_idx = 0;
+        while bit_idx < 32 {
+            if bits & (1 << bit_idx) != 0 {
+                result |= if shifted && self.shifted_button_bits[bit_idx] != 0 {
+                    self.shifted_button_bits[bit_idx]
+                } else {
+                    self.button_bits[bit_idx]
+                };
+            }
+            bit_idx += 1;
+        }
+        result
+    }
+
+    /// The axes with the sticks swapped, and the left stick moved by the D-pad of the logical
+    /// pressed bits if enabled.
+    pub(crate) fn apply_to_axes(
+        &self,
+        axes: [f32; 4],
+        pressed_bits: u32,
+        shifted: bool,
+    ) -> [f32; 4] {
+        let sticks_swapped = self.sticks_swapped ^ (shifted && self.shifted_sticks_swapped);
+        let mut axes = if sticks_swapped {
+            [axes[2], axes[3], axes[0], axes[1]]
+        } else {
+            axes
+        };
+        if self.dpad_as_left_stick {
+            let pressed =
+                |button: Button| f32::from(u8::from(pressed_bits & (1 << button as u32) != 0));
+            let x = pressed(Button::DPadRight) - pressed(Button::DPadLeft);
+            let y = pressed(Button::DPadUp) - pressed(Button::DPadDown);
+            if x != 0. || y != 0. {
+                // Diagonals at the edge of the thumbstick, not beyond it:
+                let length = x.hypot(y);
+                axes[0] = x / length;
+                axes[1] = y / length;
+            }
+        }
+        axes
+    }
+}
+
+impl Default for Remap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
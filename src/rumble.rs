@@ -0,0 +1,155 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll, Waker};
+
+/// How far a rumble effect has come, as reported by [RumbleHandle::status()].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RumbleStatus {
+    /// The effect is waiting for its start delay or still playing.
+    Playing,
+    /// The effect played to the end.
+    Complete,
+    /// The effect was stopped early, e.g. by another effect played on the same gamepad.
+    Preempted,
+    /// The effect could not be played, e.g. as the gamepad is not connected or cannot rumble.
+    Failed,
+    /// The backend does not report when effects finish, which is only done on the web.
+    Unknown,
+}
+
+struct Shared {
+    status: RumbleStatus,
+    waker: Option<Waker>,
+    /// The id of the effect in the host javascript, until it has finished.
+    #[cfg(backend = "web_direct")]
+    effect_id: Option<u32>,
+}
+
+#[cfg(backend = "web_direct")]
+impl Drop for Shared {
+    fn drop(&mut self) {
+        if let Some(effect_id) = self.effect_id {
+            unsafe { crate::backend_web_direct::gamepadsForgetEffect(effect_id) }
+        }
+    }
+}
+
+/// A handle to a rumble effect, returned by [Gamepads::rumble()](crate::Gamepads::rumble).
+///
+/// Use it to chain effects once the previous one has finished, either by checking
+/// [RumbleHandle::status()] on each tick or by awaiting the handle:
+///
+/// ```
+/// use gamepads::{Button, GamepadId, Gamepads, RumbleHandle};
+///
+/// /// Called on each tick to follow a strong rumble by a weak one.
+/// fn tick(gamepads: &mut Gamepads, gamepad_id: GamepadId, strong: &mut Option<RumbleHandle>) {
+///     gamepads.poll();
+///     if strong.as_ref().is_some_and(RumbleHandle::is_finished) {
+///         *strong = None;
+///         gamepads.rumble(gamepad_id, 200, 0, 0.2, 0.2);
+///     } else if gamepads
+///         .get(gamepad_id)
+///         .is_some_and(|gamepad| gamepad.is_just_pressed(Button::ActionDown))
+///     {
+///         *strong = Some(gamepads.rumble(gamepad_id, 500, 0, 1.0, 0.5));
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct RumbleHandle(Arc<Mutex<Shared>>);
+
+impl RumbleHandle {
+    pub(crate) fn new(status: RumbleStatus) -> Self {
+        Self(Arc::new(Mutex::new(Shared {
+            status,
+            waker: None,
+            #[cfg(backend = "web_direct")]
+            effect_id: None,
+        })))
+    }
+
+    /// A handle for an effect played by the host javascript, where `0` means it was not played.
+    #[cfg(backend = "web_direct")]
+    pub(crate) fn web_direct(effect_id: u32) -> Self {
+        if effect_id == 0 {
+            return Self::new(RumbleStatus::Failed);
+        }
+        let handle = Self::new(RumbleStatus::Playing);
+        handle.lock().effect_id = Some(effect_id);
+        handle
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Shared> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Record that the effect has finished, waking a task awaiting it.
+    #[cfg(backend = "web_bindgen")]
+    pub(crate) fn finish(&self, status: RumbleStatus) {
+        let mut shared = self.lock();
+        shared.status = status;
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// How far the effect has come.
+    pub fn status(&self) -> RumbleStatus {
+        #[cfg(backend = "web_direct")]
+        self.update_web_direct();
+        self.lock().status
+    }
+
+    /// Update the status from the host javascript, while the effect is playing.
+    #[cfg(backend = "web_direct")]
+    fn update_web_direct(&self) {
+        let mut shared = self.lock();
+        let Some(effect_id) = shared.effect_id else {
+            return;
+        };
+        shared.status = match unsafe { crate::backend_web_direct::gamepadsEffectStatus(effect_id) }
+        {
+            0 => RumbleStatus::Playing,
+            1 => RumbleStatus::Complete,
+            2 => RumbleStatus::Preempted,
+            _ => RumbleStatus::Failed,
+        };
+        if shared.status != RumbleStatus::Playing {
+            // The host javascript forgets finished effects once reported:
+            shared.effect_id = None;
+        }
+    }
+
+    /// Whether the effect has finished playing, or its status is unknown.
+    pub fn is_finished(&self) -> bool {
+        self.status() != RumbleStatus::Playing
+    }
+}
+
+impl std::fmt::Debug for RumbleHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RumbleHandle").field(&self.status()).finish()
+    }
+}
+
+/// Resolves to the final status once the effect has finished.
+impl Future for RumbleHandle {
+    type Output = RumbleStatus;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let status = self.status();
+        if status != RumbleStatus::Playing {
+            return Poll::Ready(status);
+        }
+        if cfg!(backend = "web_direct") {
+            // Nothing notifies of finished effects, so yield to the executor to be polled again:
+            cx.waker().wake_by_ref();
+        } else {
+            self.lock().waker = Some(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
@@ -0,0 +1,29 @@
+/// Performance statistics of a call to [Gamepads::poll()](crate::Gamepads::poll).
+///
+/// Obtained using [Gamepads::last_poll_stats()](crate::Gamepads::last_poll_stats).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct PollStats {
+    /// Total time spent in the poll, in milliseconds.
+    pub total_ms: f64,
+    /// Time spent reading state from the backend, in milliseconds.
+    pub backend_ms: f64,
+    /// Time spent processing the state, including invoking event callbacks, in milliseconds.
+    pub processing_ms: f64,
+    /// The number of events detected, as returned by [Gamepads::events()](crate::Gamepads::events).
+    pub events: usize,
+    /// The number of raw events read from the backend since the previous poll.
+    ///
+    /// Only counted by backends receiving events (gilrs on desktop, and Android), and zero
+    /// for backends reading the current state directly.
+    pub backend_events: u32,
+}
+
+impl crate::Gamepads {
+    /// Performance statistics of the last [Gamepads::poll()].
+    ///
+    /// Useful for verifying that input polling stays within the frame budget.
+    pub const fn last_poll_stats(&self) -> PollStats {
+        self.poll_stats
+    }
+}
@@ -0,0 +1,434 @@
+use crate::{BackendState, Button, Capabilities, GamepadBackend, GamepadId, MAX_GAMEPADS};
+use std::ffi::{c_char, c_int, c_void, CStr};
+
+type InputHandle = u64;
+type InputActionSetHandle = u64;
+type InputDigitalActionHandle = u64;
+type InputAnalogActionHandle = u64;
+
+/// The maximum number of controllers reported by `GetConnectedControllers`,
+/// `STEAM_INPUT_MAX_COUNT`.
+const STEAM_INPUT_MAX_COUNT: usize = 16;
+
+/// The action set of the action manifest, see `steam/game_actions_X.vdf`.
+const ACTION_SET: &CStr = c"gamepad";
+
+/// The digital actions of the action set, one for each button.
+const DIGITAL_ACTIONS: [(&CStr, Button); 17] = [
+    (c"action_down", Button::ActionDown),
+    (c"action_right", Button::ActionRight),
+    (c"action_left", Button::ActionLeft),
+    (c"action_up", Button::ActionUp),
+    (c"front_left_upper", Button::FrontLeftUpper),
+    (c"front_right_upper", Button::FrontRightUpper),
+    (c"front_left_lower", Button::FrontLeftLower),
+    (c"front_right_lower", Button::FrontRightLower),
+    (c"left_center_cluster", Button::LeftCenterCluster),
+    (c"right_center_cluster", Button::RightCenterCluster),
+    (c"left_stick", Button::LeftStick),
+    (c"right_stick", Button::RightStick),
+    (c"dpad_up", Button::DPadUp),
+    (c"dpad_down", Button::DPadDown),
+    (c"dpad_left", Button::DPadLeft),
+    (c"dpad_right", Button::DPadRight),
+    (c"mode", Button::Mode),
+];
+
+/// The digital actions of the back buttons and the trackpad clicks of the Steam Deck, and the
+/// extra button index they are reported as, with the back buttons as the back paddles of the
+/// sdl3 backend.
+const EXTRA_DIGITAL_ACTIONS: [(&CStr, u8); 6] = [
+    (c"back_right_upper", 19),
+    (c"back_left_upper", 20),
+    (c"back_right_lower", 21),
+    (c"back_left_lower", 22),
+    (c"left_trackpad_click", 23),
+    (c"right_trackpad_click", 24),
+];
+
+/// The analog actions of the action set, for the thumbsticks, the triggers and the trackpads
+/// of the Steam Deck.
+const ANALOG_ACTIONS: [&CStr; 6] = [
+    c"left_thumbstick",
+    c"right_thumbstick",
+    c"left_trigger",
+    c"right_trigger",
+    c"left_trackpad",
+    c"right_trackpad",
+];
+
+/// `InputDigitalActionData_t`.
+#[repr(C)]
+struct DigitalActionData {
+    state: bool,
+    active: bool,
+}
+
+/// `InputAnalogActionData_t`.
+#[repr(C)]
+struct AnalogActionData {
+    mode: c_int,
+    x: f32,
+    y: f32,
+    active: bool,
+}
+
+// The flat API of the Steamworks SDK, with `input` being the `ISteamInput` interface:
+#[cfg_attr(
+    all(target_os = "windows", target_pointer_width = "64"),
+    link(name = "steam_api64")
+)]
+#[cfg_attr(
+    not(all(target_os = "windows", target_pointer_width = "64")),
+    link(name = "steam_api")
+)]
+extern "C" {
+    fn SteamAPI_IsSteamRunning() -> bool;
+    fn SteamAPI_SteamInput_v006() -> *mut c_void;
+    fn SteamAPI_ISteamInput_Init(input: *mut c_void, explicitly_call_run_frame: bool) -> bool;
+    fn SteamAPI_ISteamInput_Shutdown(input: *mut c_void) -> bool;
+    fn SteamAPI_ISteamInput_RunFrame(input: *mut c_void, reserved_value: bool);
+    fn SteamAPI_ISteamInput_GetConnectedControllers(
+        input: *mut c_void,
+        handles_out: *mut InputHandle,
+    ) -> c_int;
+    fn SteamAPI_ISteamInput_GetActionSetHandle(
+        input: *mut c_void,
+        action_set_name: *const c_char,
+    ) -> InputActionSetHandle;
+    fn SteamAPI_ISteamInput_ActivateActionSet(
+        input: *mut c_void,
+        input_handle: InputHandle,
+        action_set_handle: InputActionSetHandle,
+    );
+    fn SteamAPI_ISteamInput_GetDigitalActionHandle(
+        input: *mut c_void,
+        action_name: *const c_char,
+    ) -> InputDigitalActionHandle;
+    fn SteamAPI_ISteamInput_GetDigitalActionData(
+        input: *mut c_void,
+        input_handle: InputHandle,
+        digital_action_handle: InputDigitalActionHandle,
+    ) -> DigitalActionData;
+    fn SteamAPI_ISteamInput_GetAnalogActionHandle(
+        input: *mut c_void,
+        action_name: *const c_char,
+    ) -> InputAnalogActionHandle;
+    fn SteamAPI_ISteamInput_GetAnalogActionData(
+        input: *mut c_void,
+        input_handle: InputHandle,
+        analog_action_handle: InputAnalogActionHandle,
+    ) -> AnalogActionData;
+    fn SteamAPI_ISteamInput_TriggerVibration(
+        input: *mut c_void,
+        input_handle: InputHandle,
+        left_speed: u16,
+        right_speed: u16,
+    );
+    fn SteamAPI_ISteamInput_GetInputTypeForHandle(
+        input: *mut c_void,
+        input_handle: InputHandle,
+    ) -> c_int;
+}
+
+/// The name of a controller from its `ESteamInputType`.
+const fn input_type_name(input_type: c_int) -> Option<&'static str> {
+    Some(match input_type {
+        2 => "Steam Controller",
+        3 => "Xbox 360 Controller",
+        4 => "Xbox One Controller",
+        5 => "Generic Gamepad",
+        6 => "PS4 Controller",
+        7 => "Apple MFi Controller",
+        8 => "Android Controller",
+        9 => "Nintendo Switch Joy-Con Pair",
+        10 => "Nintendo Switch Joy-Con",
+        11 => "Nintendo Switch Pro Controller",
+        12 => "Steam Mobile Touch Controller",
+        13 => "PS3 Controller",
+        14 => "PS5 Controller",
+        15 => "Steam Deck",
+        _ => return None,
+    })
+}
+
+/// The handles of the action set and its actions, which are 0 until Steam has loaded the
+/// action manifest.
+#[derive(Default)]
+struct ActionHandles {
+    action_set: InputActionSetHandle,
+    digital: [InputDigitalActionHandle; DIGITAL_ACTIONS.len()],
+    extra_digital: [InputDigitalActionHandle; EXTRA_DIGITAL_ACTIONS.len()],
+    analog: [InputAnalogActionHandle; ANALOG_ACTIONS.len()],
+}
+
+/// A rumble effect, started and stopped on polls as Steam Input has no durations.
+#[derive(Clone, Copy)]
+struct Rumble {
+    start_ms: f64,
+    stop_ms: f64,
+    speeds: (u16, u16),
+    started: bool,
+}
+
+/// A backend reading gamepads through the Steam Input API when running under Steam, enabled by
+/// the `steam-input` feature.
+///
+/// Games on Steam should read input through Steam Input, so that the bindings configured by
+/// players in Steam apply and the Steam Controller, the Steam Deck and other controllers supported
+/// by Steam work, instead of Steam Input being bypassed by reading the devices directly.
+/// Register it using [Gamepads::set_backend()](crate::Gamepads::set_backend) after initializing
+/// the Steamworks API, such as with the `steamworks` crate:
+///
+/// ```no_run
+/// use gamepads::{Gamepads, SteamInputBackend};
+///
+/// let mut gamepads = Gamepads::new();
+/// if let Some(steam_input) = SteamInputBackend::new() {
+///     gamepads.set_backend(steam_input);
+/// }
+/// ```
+///
+/// Steam Input reports actions instead of buttons, so the game needs to ship an action manifest
+/// with an action set named `gamepad` containing an action for each [Button] and for the
+/// thumbsticks and triggers, such as the
+/// [game_actions_X.vdf](https://github.com/fornwall/gamepads/blob/main/steam/game_actions_X.vdf)
+/// manifest of this crate, together with default bindings configured in the Steamworks partner
+/// site.
+///
+/// On the Steam Deck, the back buttons are reported as extra buttons using
+/// [Gamepad::is_extra_button_pressed()](crate::Gamepad::is_extra_button_pressed), as R4 (19),
+/// L4 (20), R5 (21) and L5 (22), and clicking the left and right trackpads as 23 and 24. The
+/// positions touched on the trackpads are reported as virtual sticks using
+/// [Gamepad::extra_axis()](crate::Gamepad::extra_axis), as 4 and 5 for the left trackpad and
+/// 6 and 7 for the right one.
+///
+/// This links to the `steam_api` library of the Steamworks SDK, which needs to be found by the
+/// linker, as it is when using the `steamworks` crate.
+pub struct SteamInputBackend {
+    input: *mut c_void,
+    handles: ActionHandles,
+    slots: [Option<InputHandle>; MAX_GAMEPADS],
+    rumbles: [Option<Rumble>; MAX_GAMEPADS],
+}
+
+// The Steam Input interface may be used from any thread:
+unsafe impl Send for SteamInputBackend {}
+
+impl SteamInputBackend {
+    /// Initialize Steam Input, returning `None` if not running under Steam or if the Steamworks
+    /// API has not been initialized.
+    pub fn new() -> Option<Self> {
+        let input = unsafe {
+            if !SteamAPI_IsSteamRunning() {
+                return None;
+            }
+            SteamAPI_SteamInput_v006()
+        };
+        if input.is_null() || !unsafe { SteamAPI_ISteamInput_Init(input, true) } {
+            crate::logging::warning!("Failed to initialize Steam Input");
+            return None;
+        }
+        Some(Self {
+            input,
+            handles: ActionHandles::default(),
+            slots: [None; MAX_GAMEPADS],
+            rumbles: [None; MAX_GAMEPADS],
+        })
+    }
+
+    fn update_handles(&mut self) {
+        let input = self.input;
+        unsafe {
+            self.handles.action_set =
+                SteamAPI_ISteamInput_GetActionSetHandle(input, ACTION_SET.as_ptr());
+            for (handle, (name, _)) in self.handles.digital.iter_mut().zip(DIGITAL_ACTIONS) {
+                *handle = SteamAPI_ISteamInput_GetDigitalActionHandle(input, name.as_ptr());
+            }
+            for (handle, (name, _)) in self
+                .handles
+                .extra_digital
+                .iter_mut()
+                .zip(EXTRA_DIGITAL_ACTIONS)
+            {
+                *handle = SteamAPI_ISteamInput_GetDigitalActionHandle(input, name.as_ptr());
+            }
+            for (handle, name) in self.handles.analog.iter_mut().zip(ANALOG_ACTIONS) {
+                *handle = SteamAPI_ISteamInput_GetAnalogActionHandle(input, name.as_ptr());
+            }
+        }
+    }
+
+    fn poll_rumble(&mut self) {
+        let now_ms = crate::time::now_ms();
+        for (slot, rumble_slot) in self.slots.iter().zip(self.rumbles.iter_mut()) {
+            let (Some(input_handle), Some(rumble)) = (slot, rumble_slot.as_mut()) else {
+                continue;
+            };
+            let speeds = if now_ms >= rumble.stop_ms {
+                *rumble_slot = None;
+                (0, 0)
+            } else if !rumble.started && now_ms >= rumble.start_ms {
+                rumble.started = true;
+                rumble.speeds
+            } else {
+                continue;
+            };
+            unsafe {
+                SteamAPI_ISteamInput_TriggerVibration(
+                    self.input,
+                    *input_handle,
+                    speeds.0,
+                    speeds.1,
+                );
+            }
+        }
+    }
+}
+
+impl Drop for SteamInputBackend {
+    fn drop(&mut self) {
+        unsafe { SteamAPI_ISteamInput_Shutdown(self.input) };
+    }
+}
+
+impl GamepadBackend for SteamInputBackend {
+    fn poll(&mut self, state: &mut BackendState) {
+        let mut connected = [0; STEAM_INPUT_MAX_COUNT];
+        let count = unsafe {
+            SteamAPI_ISteamInput_RunFrame(self.input, false);
+            SteamAPI_ISteamInput_GetConnectedControllers(self.input, connected.as_mut_ptr())
+        };
+        let connected = &connected[..usize::try_from(count).unwrap_or(0).min(connected.len())];
+        if self.handles.action_set == 0 {
+            self.update_handles();
+        }
+
+        for (idx, slot) in self.slots.iter_mut().enumerate() {
+            if slot.is_some_and(|input_handle| !connected.contains(&input_handle)) {
+                *slot = None;
+                self.rumbles[idx] = None;
+                state.disconnect(GamepadId(idx as u8));
+            }
+        }
+        for &input_handle in connected {
+            if self.slots.contains(&Some(input_handle)) {
+                continue;
+            }
+            let Some(gamepad_id) = state.connect() else {
+                crate::logging::warning!("Ignoring gamepad, as {MAX_GAMEPADS} are already in use");
+                break;
+            };
+            self.slots[usize::from(gamepad_id.0)] = Some(input_handle);
+        }
+
+        self.poll_rumble();
+        for (idx, slot) in self.slots.iter().enumerate() {
+            let Some(input_handle) = *slot else {
+                continue;
+            };
+            let gamepad_id = GamepadId(idx as u8);
+            let pressed_bits = state.gamepads[idx].pressed_bits;
+            unsafe {
+                SteamAPI_ISteamInput_ActivateActionSet(
+                    self.input,
+                    input_handle,
+                    self.handles.action_set,
+                );
+            }
+
+            let is_pressed = |handle| {
+                let data = unsafe {
+                    SteamAPI_ISteamInput_GetDigitalActionData(self.input, input_handle, handle)
+                };
+                data.active && data.state
+            };
+            for (&handle, (_, button)) in self.handles.digital.iter().zip(DIGITAL_ACTIONS) {
+                let pressed = is_pressed(handle);
+                if pressed != (pressed_bits & (1 << (button as u32)) != 0) {
+                    if pressed {
+                        state.press(gamepad_id, button);
+                    } else {
+                        state.release(gamepad_id, button);
+                    }
+                }
+            }
+            for (&handle, (_, extra_idx)) in
+                self.handles.extra_digital.iter().zip(EXTRA_DIGITAL_ACTIONS)
+            {
+                let pressed = is_pressed(handle);
+                if pressed != (pressed_bits & (1 << extra_idx) != 0) {
+                    if pressed {
+                        state.press_extra(gamepad_id, extra_idx);
+                    } else {
+                        state.release_extra(gamepad_id, extra_idx);
+                    }
+                }
+            }
+
+            let [left_stick, right_stick, left_trigger, right_trigger, left_trackpad, right_trackpad] =
+                self.handles.analog.map(|handle| {
+                    let data = unsafe {
+                        SteamAPI_ISteamInput_GetAnalogActionData(self.input, input_handle, handle)
+                    };
+                    if data.active {
+                        (data.x, data.y)
+                    } else {
+                        (0., 0.)
+                    }
+                });
+            state.set_left_stick(gamepad_id, left_stick);
+            state.set_right_stick(gamepad_id, right_stick);
+            state.set_triggers(gamepad_id, (left_trigger.0, right_trigger.0));
+            // The trackpads are reported as extra axes following the thumbsticks:
+            for (index, value) in (4..).zip([
+                left_trackpad.0,
+                left_trackpad.1,
+                right_trackpad.0,
+                right_trackpad.1,
+            ]) {
+                state.set_extra_axis(gamepad_id, index, value);
+            }
+        }
+    }
+
+    fn rumble(
+        &mut self,
+        gamepad_id: GamepadId,
+        duration_ms: u32,
+        start_delay_ms: u32,
+        strong_magnitude: f32,
+        weak_magnitude: f32,
+    ) {
+        let idx = usize::from(gamepad_id.0);
+        if self.slots[idx].is_none() {
+            return;
+        }
+        let speed = |magnitude: f32| (magnitude.clamp(0., 1.) * f32::from(u16::MAX)) as u16;
+        let start_ms = crate::time::now_ms() + f64::from(start_delay_ms);
+        self.rumbles[idx] = Some(Rumble {
+            start_ms,
+            stop_ms: start_ms + f64::from(duration_ms),
+            speeds: (speed(strong_magnitude), speed(weak_magnitude)),
+            started: false,
+        });
+        self.poll_rumble();
+    }
+
+    fn capabilities(&self, gamepad_id: GamepadId) -> Capabilities {
+        Capabilities {
+            rumble: self.slots[usize::from(gamepad_id.0)].is_some(),
+            trigger_rumble: false,
+            motion: false,
+            led: false,
+            force_feedback: false,
+        }
+    }
+
+    fn name(&self, gamepad_id: GamepadId) -> Option<String> {
+        let input_handle = self.slots[usize::from(gamepad_id.0)]?;
+        let input_type =
+            unsafe { SteamAPI_ISteamInput_GetInputTypeForHandle(self.input, input_handle) };
+        input_type_name(input_type).map(String::from)
+    }
+}
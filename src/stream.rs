@@ -0,0 +1,204 @@
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll, Waker};
+
+use crate::{Event, Gamepads};
+
+/// How often the stream polls for new events while waiting.
+const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(4);
+
+/// The tasks waiting on streams for the poll interval to elapse, woken together by a single
+/// timer shared by all streams.
+static WAITING: Mutex<Vec<Waker>> = Mutex::new(Vec::new());
+
+/// Signalled when a task starts waiting, for the timer thread to stop sleeping while none are.
+#[cfg(not(target_family = "wasm"))]
+static WAITING_CHANGED: std::sync::Condvar = std::sync::Condvar::new();
+
+/// Wake the task waiting on a stream after the poll interval.
+fn wake_after_interval(waker: &Waker) {
+    let Ok(mut waiting) = WAITING.lock() else {
+        return;
+    };
+    let timer_idle = waiting.is_empty();
+    if !waiting.iter().any(|w| w.will_wake(waker)) {
+        waiting.push(waker.clone());
+    }
+    drop(waiting);
+    if timer_idle {
+        start_timer();
+    }
+}
+
+/// Wake all tasks waiting on streams, when the poll interval has elapsed.
+fn wake_waiting() {
+    let waiting = WAITING
+        .lock()
+        .map(|mut waiting| std::mem::take(&mut *waiting))
+        .unwrap_or_default();
+    waiting.into_iter().for_each(Waker::wake);
+}
+
+/// Wake the waiting tasks after the poll interval, from a thread started by the first stream
+/// waiting and sleeping while no task is waiting.
+#[cfg(not(target_family = "wasm"))]
+fn start_timer() {
+    static TIMER_THREAD: std::sync::Once = std::sync::Once::new();
+    TIMER_THREAD.call_once(|| {
+        std::thread::spawn(|| loop {
+            let Ok(waiting) = WAITING.lock() else {
+                return;
+            };
+            if WAITING_CHANGED
+                .wait_while(waiting, |waiting| waiting.is_empty())
+                .is_err()
+            {
+                return;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+            wake_waiting();
+        });
+    });
+    WAITING_CHANGED.notify_one();
+}
+
+#[cfg(all(
+    target_family = "wasm",
+    not(any(target_os = "emscripten", target_os = "wasi")),
+    feature = "backend-web-bindgen"
+))]
+#[wasm_bindgen::prelude::wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(callback: &wasm_bindgen::JsValue, delay_ms: u32);
+}
+
+/// Wake the waiting tasks after the poll interval, yielding to the event loop of the browser
+/// until then.
+#[cfg(all(
+    target_family = "wasm",
+    not(any(target_os = "emscripten", target_os = "wasi")),
+    feature = "backend-web-bindgen"
+))]
+fn start_timer() {
+    set_timeout(
+        &wasm_bindgen::closure::Closure::once_into_js(wake_waiting),
+        POLL_INTERVAL.as_millis() as u32,
+    );
+}
+
+/// Wake the waiting tasks after the poll interval, yielding to the event loop of the browser
+/// until the host javascript calls back into [gamepads_wake_streams()].
+#[cfg(all(
+    target_family = "wasm",
+    not(any(target_os = "emscripten", target_os = "wasi")),
+    not(feature = "backend-web-bindgen")
+))]
+fn start_timer() {
+    unsafe { crate::backend_web_direct::gamepadsWakeAfter(POLL_INTERVAL.as_millis() as u32) };
+}
+
+/// Called by the host javascript when the delay of `gamepadsWakeAfter` has elapsed.
+#[cfg(all(
+    target_family = "wasm",
+    not(any(target_os = "emscripten", target_os = "wasi")),
+    not(feature = "backend-web-bindgen")
+))]
+#[no_mangle]
+pub extern "C" fn gamepads_wake_streams() {
+    wake_waiting();
+}
+
+/// Wake the waiting tasks after the poll interval, yielding to the event loop of the browser
+/// until then.
+#[cfg(target_os = "emscripten")]
+fn start_timer() {
+    extern "C" {
+        fn emscripten_async_call(
+            func: extern "C" fn(*mut std::ffi::c_void),
+            arg: *mut std::ffi::c_void,
+            millis: std::ffi::c_int,
+        );
+    }
+    extern "C" fn on_timer(_arg: *mut std::ffi::c_void) {
+        wake_waiting();
+    }
+    unsafe {
+        emscripten_async_call(
+            on_timer,
+            std::ptr::null_mut(),
+            POLL_INTERVAL.as_millis() as std::ffi::c_int,
+        );
+    }
+}
+
+/// Wake the waiting tasks after sleeping for the poll interval, as there are no threads nor an
+/// event loop to wait on.
+#[cfg(target_os = "wasi")]
+fn start_timer() {
+    std::thread::sleep(POLL_INTERVAL);
+    wake_waiting();
+}
+
+/// A [futures_core::Stream] of gamepad [Event]s, created using [Gamepads::event_stream()].
+///
+/// The stream polls gamepads internally while waiting for events, so there is no need to call
+/// [Gamepads::poll()] when using it. Waiting streams are woken to poll again every few
+/// milliseconds by a timer shared by all streams, which on the web yields to the browser.
+pub struct EventStream {
+    gamepads: Gamepads,
+    pending: VecDeque<Event>,
+}
+
+impl EventStream {
+    /// Access the gamepads driven by this stream, e.g. to check state or rumble.
+    pub const fn gamepads(&mut self) -> &mut Gamepads {
+        &mut self.gamepads
+    }
+
+    /// Stop streaming and return the underlying gamepads.
+    pub fn into_inner(self) -> Gamepads {
+        self.gamepads
+    }
+}
+
+impl futures_core::Stream for EventStream {
+    type Item = Event;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        if this.pending.is_empty() {
+            this.gamepads.poll();
+            this.pending.extend(this.gamepads.events().map(|e| e.event));
+        }
+        this.pending.pop_front().map_or_else(
+            || {
+                wake_after_interval(cx.waker());
+                Poll::Pending
+            },
+            |event| Poll::Ready(Some(event)),
+        )
+    }
+}
+
+impl Gamepads {
+    /// Turn these gamepads into an asynchronous stream of events.
+    ///
+    /// Requires the `async` feature.
+    ///
+    /// ```ignore
+    /// use futures::StreamExt;
+    ///
+    /// let mut stream = gamepads::Gamepads::new().event_stream();
+    /// while let Some(event) = stream.next().await {
+    ///     println!("Event: {event:?}");
+    /// }
+    /// ```
+    pub const fn event_stream(self) -> EventStream {
+        EventStream {
+            gamepads: self,
+            pending: VecDeque::new(),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Support for [Tauri](https://tauri.app/) apps, enabled by the `tauri` feature, forwarding the
+//! gamepads of the webview to the Rust process.
+//!
+//! Browsers expose gamepads to the webview of an app, while the Rust process may not be able to
+//! read them itself, such as with the gamepads of the system webview on macOS. To use them from
+//! Rust, inject [SCRIPT] into the webview, which invokes a `gamepads_state` command whenever the
+//! gamepads change, and handle that command using [forward()] into a
+//! [PortBackend](crate::PortBackend) of the [Gamepads](crate::Gamepads) of the app:
+//!
+//! ```ignore
+//! use gamepads::{Gamepads, PortBackend, PortHandle};
+//!
+//! #[tauri::command]
+//! fn gamepads_state(gamepads: Vec<gamepads::tauri::WebGamepad>, port: tauri::State<PortHandle>) {
+//!     gamepads::tauri::forward(&port, &gamepads);
+//! }
+//!
+//! let backend = PortBackend::new();
+//! let port = backend.handle();
+//! let mut gamepads = Gamepads::new();
+//! gamepads.set_backend(backend);
+//!
+//! tauri::Builder::default()
+//!     .manage(port)
+//!     .invoke_handler(tauri::generate_handler![gamepads_state])
+//!     .setup(|app| {
+//!         tauri::WebviewWindowBuilder::new(app, "main", Default::default())
+//!             .initialization_script(gamepads::tauri::SCRIPT)
+//!             .build()?;
+//!         Ok(())
+//!     })
+//!     .run(tauri::generate_context!())
+//!     .unwrap();
+//! ```
+//!
+//! Gamepads are then polled as usual, such as from a thread of the game running along the app.
+//! Known gamepads without the standard mapping are remapped as by the web backends.
+
+use crate::{GamepadId, GamepadMapping, PortHandle, MAX_GAMEPADS};
+
+/// A script to inject into the webview, invoking the `gamepads_state` command with the connected
+/// gamepads as a `gamepads` array of [WebGamepad] each time they change.
+pub const SCRIPT: &str = include_str!("../js/tauri-gamepads-0.1.js");
+
+/// The state of a gamepad in the webview, as sent by [SCRIPT].
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct WebGamepad {
+    /// The `index` of the gamepad in the Gamepad API.
+    pub index: u32,
+    /// The `id` of the gamepad in the Gamepad API, such as
+    /// `"054c-0ce6-DualSense Wireless Controller"`.
+    pub id: String,
+    /// The `mapping` of the gamepad in the Gamepad API, such as `"standard"`.
+    pub mapping: String,
+    /// Whether each button is pressed, and its value in the range `[0.0, 1.0]`.
+    pub buttons: Vec<(bool, f32)>,
+    /// The axes of the gamepad, with y positive downwards as in the Gamepad API.
+    pub axes: Vec<f32>,
+}
+
+impl WebGamepad {
+    /// The state of the gamepad as a [Gamepad](crate::Gamepad), or `None` if its index is beyond
+    /// the gamepads supported.
+    fn to_gamepad(&self) -> Option<crate::Gamepad> {
+        let gamepad_id = u8::try_from(self.index).ok().and_then(GamepadId::new)?;
+        let mut gamepad = crate::Gamepad::new(gamepad_id);
+        let mut raw = crate::web_mapping::RawGamepad::default();
+        for (button_idx, &(pressed, _)) in self.buttons.iter().enumerate() {
+            if pressed {
+                raw.press(button_idx);
+            }
+        }
+        for (axis, value) in raw.axes.iter_mut().zip(&self.axes) {
+            *axis = *value;
+        }
+
+        let mapping = crate::web_mapping::mapping_from_str(&self.mapping);
+        gamepad.set_connected(true);
+        gamepad.set_mapping(mapping);
+        if mapping == GamepadMapping::Standard {
+            // The analog values of the left and right triggers in the standard layout:
+            let value = |button_idx: usize| self.buttons.get(button_idx).map_or(0., |b| b.1);
+            gamepad.triggers = [value(6), value(7)];
+        } else if let Some((pressed_bits, axes)) =
+            crate::web_mapping::remap(mapping, &self.id, &raw)
+        {
+            gamepad.pressed_bits = pressed_bits;
+            gamepad.axes = axes;
+            return Some(gamepad);
+        }
+        crate::web_mapping::write_unmapped(&raw, &mut gamepad);
+        Some(gamepad)
+    }
+}
+
+/// Push the gamepads sent by [SCRIPT] into a [PortBackend](crate::PortBackend), as the handler
+/// of the `gamepads_state` command. Gamepads not among them are pushed as disconnected.
+///
+/// ```
+/// use gamepads::tauri::WebGamepad;
+/// use gamepads::{Button, Gamepads, PortBackend};
+///
+/// let backend = PortBackend::new();
+/// let port = backend.handle();
+/// let mut gamepads = Gamepads::new();
+/// gamepads.set_backend(backend);
+///
+/// // As sent by the webview when a button is pressed:
+/// let mut buttons = vec![(false, 0.); 17];
+/// buttons[0] = (true, 1.);
+/// gamepads::tauri::forward(
+///     &port,
+///     &[WebGamepad {
+///         index: 0,
+///         id: "Xbox 360 Controller (XInput STANDARD GAMEPAD)".to_string(),
+///         mapping: "standard".to_string(),
+///         buttons,
+///         axes: vec![0.; 4],
+///     }],
+/// );
+///
+/// gamepads.poll();
+/// let gamepad = gamepads.all().next().unwrap();
+/// assert!(gamepad.is_just_pressed(Button::ActionDown));
+/// ```
+pub fn forward(port: &PortHandle, gamepads: &[WebGamepad]) {
+    let mut connected = [false; MAX_GAMEPADS];
+    for web_gamepad in gamepads {
+        if let Some(gamepad) = web_gamepad.to_gamepad() {
+            connected[usize::from(gamepad.id().value())] = true;
+            port.set_name(gamepad.id(), Some(web_gamepad.id.clone()));
+            port.push(&gamepad);
+        }
+    }
+    for idx in (0..MAX_GAMEPADS).filter(|&idx| !connected[idx]) {
+        if let Some(gamepad_id) = u8::try_from(idx).ok().and_then(GamepadId::new) {
+            port.push(&crate::Gamepad::new(gamepad_id));
+        }
+    }
+}
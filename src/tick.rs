@@ -0,0 +1,45 @@
+use crate::{Gamepad, MAX_GAMEPADS};
+
+impl crate::Gamepads {
+    /// The number of the last [Gamepads::poll()](crate::Gamepads::poll), increasing by one on each poll.
+    ///
+    /// Compare with [Gamepad::last_change_tick()] to detect stale or already handled input:
+    ///
+    /// ```
+    /// use gamepads::Gamepads;
+    ///
+    /// let mut gamepads = Gamepads::new();
+    /// let mut handled_tick = 0;
+    /// gamepads.poll();
+    /// for gamepad in gamepads.iter() {
+    ///     if gamepad.last_change_tick() > handled_tick {
+    ///         println!("New input on {:?}", gamepad.id());
+    ///     }
+    /// }
+    /// handled_tick = gamepads.tick();
+    /// ```
+    pub const fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Tag processed gamepads which changed from the `previous` state with the current tick.
+    pub(crate) fn tag_changes(&mut self, previous: &[Gamepad; MAX_GAMEPADS]) {
+        for (processed, previous) in self.processed.iter_mut().zip(previous) {
+            let changed = processed.connected != previous.connected
+                || processed.pressed_bits != previous.pressed_bits
+                || processed.high_pressed_bits != previous.high_pressed_bits
+                || processed.axes != previous.axes
+                || processed.triggers != previous.triggers
+                || processed.extra_axes != previous.extra_axes
+                || processed.just_pressed_bits != 0
+                || processed.just_released_bits != 0
+                || processed.high_just_pressed_bits != 0
+                || processed.high_just_released_bits != 0;
+            processed.last_change_tick = if changed {
+                self.tick
+            } else {
+                previous.last_change_tick
+            };
+        }
+    }
+}
@@ -0,0 +1,42 @@
+/// Milliseconds elapsed since an unspecified, fixed point in time.
+///
+/// This is monotonic, and used for timing related processing such as debouncing.
+#[cfg(any(
+    not(target_family = "wasm"),
+    target_os = "emscripten",
+    target_os = "wasi"
+))]
+pub fn now_ms() -> f64 {
+    static START: std::sync::OnceLock<std::time::Instant> = std::sync::OnceLock::new();
+    START
+        .get_or_init(std::time::Instant::now)
+        .elapsed()
+        .as_secs_f64()
+        * 1000.
+}
+
+/// Milliseconds elapsed since an unspecified, fixed point in time.
+///
+/// This is monotonic, and used for timing related processing such as debouncing.
+#[cfg(all(
+    target_family = "wasm",
+    not(any(target_os = "emscripten", target_os = "wasi")),
+    feature = "backend-web-bindgen"
+))]
+pub fn now_ms() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map_or(0., |performance| performance.now())
+}
+
+/// Milliseconds elapsed since an unspecified, fixed point in time.
+///
+/// This is monotonic, and used for timing related processing such as debouncing.
+#[cfg(all(
+    target_family = "wasm",
+    not(any(target_os = "emscripten", target_os = "wasi")),
+    not(feature = "backend-web-bindgen")
+))]
+pub fn now_ms() -> f64 {
+    unsafe { crate::backend_web_direct::gamepadsNow() }
+}
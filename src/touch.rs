@@ -0,0 +1,272 @@
+use crate::{Button, Event, GamepadId, MAX_GAMEPADS};
+
+/// What a touch control acts as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TouchControlKind {
+    /// The left thumbstick, moved by dragging from its center.
+    LeftStick,
+    /// The right thumbstick, moved by dragging from its center.
+    RightStick,
+    /// A button, pressed while touched.
+    Button(Button),
+}
+
+/// A circular control of an on-screen touch gamepad, as returned by
+/// [Gamepads::touch_controls()](crate::Gamepads::touch_controls) for drawing.
+///
+/// Positions are in the coordinates the touches are given in, typically pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct TouchControl {
+    /// What the control acts as.
+    pub kind: TouchControlKind,
+    /// The center of the control.
+    pub center: (f32, f32),
+    /// The radius of the control, within which touches start using it.
+    pub radius: f32,
+    /// Whether the control is currently touched.
+    pub touched: bool,
+    /// The `(x, y)` position of a thumbstick, with each component in the range `[-1.0, 1.0]`
+    /// and negative values representing down or to the left. Always `(0., 0.)` for buttons.
+    pub stick_position: (f32, f32),
+    /// The id of the touch using the control.
+    touch_id: Option<u64>,
+}
+
+/// The controls of an on-screen touch gamepad, for playing without a physical gamepad
+/// on phones and tablets.
+///
+/// Enable it using [Gamepads::enable_touch_gamepad()](crate::Gamepads::enable_touch_gamepad).
+///
+/// ```
+/// use gamepads::{Button, TouchLayout};
+///
+/// // A thumbstick to the lower left, and two buttons to the lower right of an 800x600 screen:
+/// let layout = TouchLayout::new()
+///     .with_left_stick((120., 480.), 80.)
+///     .with_button(Button::ActionDown, (680., 500.), 40.)
+///     .with_button(Button::ActionRight, (750., 430.), 40.);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TouchLayout {
+    controls: Vec<TouchControl>,
+}
+
+impl TouchLayout {
+    /// A layout without any controls.
+    pub const fn new() -> Self {
+        Self {
+            controls: Vec::new(),
+        }
+    }
+
+    /// A layout with a left thumbstick to the lower left, the four action buttons to the lower
+    /// right and start in the upper right, sized after a screen of the given size.
+    pub fn standard(screen_width: f32, screen_height: f32) -> Self {
+        let unit = screen_width.min(screen_height) / 10.;
+        let margin = 2. * unit;
+        let (right, bottom) = (screen_width - margin, screen_height - margin);
+        Self::new()
+            .with_left_stick((margin, bottom), 1.5 * unit)
+            .with_button(Button::ActionDown, (right, bottom + unit), 0.6 * unit)
+            .with_button(Button::ActionRight, (right + unit, bottom), 0.6 * unit)
+            .with_button(Button::ActionLeft, (right - unit, bottom), 0.6 * unit)
+            .with_button(Button::ActionUp, (right, bottom - unit), 0.6 * unit)
+            .with_button(Button::RightCenterCluster, (right + unit, unit), 0.5 * unit)
+    }
+
+    /// Add a left thumbstick at the given center and radius.
+    pub fn with_left_stick(self, center: (f32, f32), radius: f32) -> Self {
+        self.with_control(TouchControlKind::LeftStick, center, radius)
+    }
+
+    /// Add a right thumbstick at the given center and radius.
+    pub fn with_right_stick(self, center: (f32, f32), radius: f32) -> Self {
+        self.with_control(TouchControlKind::RightStick, center, radius)
+    }
+
+    /// Add a button at the given center and radius.
+    pub fn with_button(self, button: Button, center: (f32, f32), radius: f32) -> Self {
+        self.with_control(TouchControlKind::Button(button), center, radius)
+    }
+
+    fn with_control(mut self, kind: TouchControlKind, center: (f32, f32), radius: f32) -> Self {
+        self.controls.push(TouchControl {
+            kind,
+            center,
+            radius,
+            touched: false,
+            stick_position: (0., 0.),
+            touch_id: None,
+        });
+        self
+    }
+}
+
+/// The phase of a touch given to [Gamepads::handle_touch()](crate::Gamepads::handle_touch).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TouchPhase {
+    /// A finger touched the screen.
+    Started,
+    /// A finger moved while touching the screen.
+    Moved,
+    /// A finger was lifted from the screen.
+    Ended,
+    /// The touch was cancelled by the system, which is handled as if ended.
+    Cancelled,
+}
+
+/// An enabled on-screen touch gamepad, and the gamepad slot it feeds.
+pub struct TouchGamepad {
+    gamepad_id: GamepadId,
+    layout: TouchLayout,
+}
+
+impl TouchControl {
+    /// The event to inject for the current state of the control.
+    const fn event(&self, gamepad_id: GamepadId) -> Event {
+        match self.kind {
+            TouchControlKind::LeftStick => Event::LeftStickMoved {
+                gamepad_id,
+                position: self.stick_position,
+            },
+            TouchControlKind::RightStick => Event::RightStickMoved {
+                gamepad_id,
+                position: self.stick_position,
+            },
+            TouchControlKind::Button(button) if self.touched => {
+                Event::ButtonPressed { gamepad_id, button }
+            }
+            TouchControlKind::Button(button) => Event::ButtonReleased { gamepad_id, button },
+        }
+    }
+
+    /// Move a thumbstick towards a touch, as far as the edge of the control.
+    fn move_stick(&mut self, (x, y): (f32, f32)) {
+        let dx = (x - self.center.0) / self.radius;
+        // Screen coordinates are positive downwards, while thumbsticks are positive upwards:
+        let dy = (self.center.1 - y) / self.radius;
+        let scale = dx.hypot(dy).max(1.);
+        self.stick_position = (dx / scale, dy / scale);
+    }
+}
+
+impl crate::Gamepads {
+    /// Enable an on-screen touch gamepad with the given layout, returning the id of the gamepad
+    /// slot it appears as, or `None` if all slots are in use.
+    ///
+    /// The touch gamepad is not drawn by this crate. Instead, the game forwards touches using
+    /// [Gamepads::handle_touch()](crate::Gamepads::handle_touch) and draws the controls returned
+    /// by [Gamepads::touch_controls()](crate::Gamepads::touch_controls), for example as circles
+    /// using its engine. Input is merged into the slot as by [Gamepads::inject()](crate::Gamepads::inject),
+    /// so it drives the same processing as physical gamepads.
+    ///
+    /// ```
+    /// use gamepads::{Gamepads, TouchLayout, TouchPhase};
+    ///
+    /// let mut gamepads = Gamepads::new();
+    /// let touch_gamepad_id = gamepads.enable_touch_gamepad(TouchLayout::standard(800., 600.));
+    ///
+    /// // Forward touches from the engine, such as from macroquad::input::touches():
+    /// gamepads.handle_touch(1, TouchPhase::Started, (120., 480.));
+    /// gamepads.handle_touch(1, TouchPhase::Moved, (300., 480.));
+    ///
+    /// gamepads.poll();
+    /// if let Some(gamepad) = touch_gamepad_id.and_then(|gamepad_id| gamepads.get(gamepad_id)) {
+    ///     assert_eq!(gamepad.left_stick(), (1., 0.));
+    /// }
+    /// for control in gamepads.touch_controls() {
+    ///     // Draw a circle at control.center with control.radius, highlighted if control.touched.
+    /// }
+    /// ```
+    pub fn enable_touch_gamepad(&mut self, layout: TouchLayout) -> Option<GamepadId> {
+        let gamepad_id = if let Some(previous) = self.touch.take() {
+            // Keep the slot, but release everything touched with the previous layout:
+            self.inject(Event::Disconnected {
+                gamepad_id: previous.gamepad_id,
+            });
+            previous.gamepad_id
+        } else {
+            // Use the last free slot, as backends connect physical gamepads to the first:
+            let gamepad_idx = (0..MAX_GAMEPADS)
+                .rev()
+                .find(|&idx| !self.gamepads[idx].connected && !self.processed[idx].connected)?;
+            GamepadId(gamepad_idx as u8)
+        };
+        self.inject(Event::Connected { gamepad_id });
+        self.touch = Some(Box::new(TouchGamepad { gamepad_id, layout }));
+        Some(gamepad_id)
+    }
+
+    /// Disable the on-screen touch gamepad, disconnecting its gamepad slot.
+    pub fn disable_touch_gamepad(&mut self) {
+        if let Some(touch) = self.touch.take() {
+            self.inject(Event::Disconnected {
+                gamepad_id: touch.gamepad_id,
+            });
+        }
+    }
+
+    /// Handle a touch, given by its id, phase and position, updating the on-screen touch gamepad.
+    ///
+    /// Does nothing unless enabled by [Gamepads::enable_touch_gamepad()](crate::Gamepads::enable_touch_gamepad).
+    pub fn handle_touch(&mut self, touch_id: u64, phase: TouchPhase, position: (f32, f32)) {
+        let Some(touch) = &mut self.touch else {
+            return;
+        };
+        let gamepad_id = touch.gamepad_id;
+        let mut events = Vec::new();
+        for control in &mut touch.layout.controls {
+            let is_stick = !matches!(control.kind, TouchControlKind::Button(_));
+            match phase {
+                TouchPhase::Started if control.touch_id.is_none() => {
+                    let (dx, dy) = (position.0 - control.center.0, position.1 - control.center.1);
+                    if dx.hypot(dy) > control.radius {
+                        continue;
+                    }
+                    control.touch_id = Some(touch_id);
+                    control.touched = true;
+                    if is_stick {
+                        control.move_stick(position);
+                    }
+                }
+                TouchPhase::Moved if is_stick && control.touch_id == Some(touch_id) => {
+                    control.move_stick(position);
+                }
+                TouchPhase::Ended | TouchPhase::Cancelled if control.touch_id == Some(touch_id) => {
+                    control.touch_id = None;
+                    control.touched = false;
+                    control.stick_position = (0., 0.);
+                }
+                _ => continue,
+            }
+            events.push(control.event(gamepad_id));
+        }
+        for event in events {
+            self.inject(event);
+        }
+    }
+
+    /// End all touches of the on-screen touch gamepad, such as when the app is sent to the
+    /// background, after which no touch events are received for fingers being lifted.
+    ///
+    /// Done automatically by the `backend-android-winit` feature when the app is suspended.
+    pub fn cancel_touches(&mut self) {
+        let touch_ids: Vec<u64> = self
+            .touch_controls()
+            .iter()
+            .filter_map(|control| control.touch_id)
+            .collect();
+        for touch_id in touch_ids {
+            self.handle_touch(touch_id, TouchPhase::Cancelled, (0., 0.));
+        }
+    }
+
+    /// The controls of the on-screen touch gamepad with their current state, for drawing them,
+    /// or nothing if not enabled.
+    pub fn touch_controls(&self) -> &[TouchControl] {
+        self.touch
+            .as_ref()
+            .map_or(&[], |touch| touch.layout.controls.as_slice())
+    }
+}
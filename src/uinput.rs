@@ -0,0 +1,212 @@
+use crate::evdev_mapping::{
+    ABS_RX, ABS_RY, ABS_RZ, ABS_X, ABS_Y, ABS_Z, BUTTONS, EV_ABS, EV_KEY, EV_SYN,
+};
+use crate::{Button, Gamepad};
+use std::fs::File;
+use std::io::Write;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::OpenOptionsExt;
+
+/// The `EV_SYN` event code ending a batch of changes.
+const SYN_REPORT: u16 = 0x00;
+
+/// The bus type of virtual devices.
+const BUS_VIRTUAL: u16 = 0x06;
+
+/// The maximum values of the thumbstick and trigger axes.
+const STICK_MAX: i32 = i16::MAX as i32;
+const TRIGGER_MAX: i32 = 1023;
+
+/// The axes of the gamepad specification, as the thumbsticks and the analog triggers.
+const AXES: [u16; 6] = [ABS_X, ABS_Y, ABS_RX, ABS_RY, ABS_Z, ABS_RZ];
+
+/// The `UI_*` ioctl requests of `linux/uinput.h`.
+const UINPUT: u32 = b'U' as u32;
+const UI_DEV_CREATE: libc::Ioctl = libc::_IO(UINPUT, 1);
+const UI_DEV_DESTROY: libc::Ioctl = libc::_IO(UINPUT, 2);
+const UI_DEV_SETUP: libc::Ioctl = libc::_IOW::<libc::uinput_setup>(UINPUT, 3);
+const UI_ABS_SETUP: libc::Ioctl = libc::_IOW::<libc::uinput_abs_setup>(UINPUT, 4);
+const UI_SET_EVBIT: libc::Ioctl = libc::_IOW::<libc::c_int>(UINPUT, 100);
+const UI_SET_KEYBIT: libc::Ioctl = libc::_IOW::<libc::c_int>(UINPUT, 101);
+
+/// Turn the result of an ioctl into an error if it failed.
+fn check(result: libc::c_int) -> std::io::Result<()> {
+    if result < 0 {
+        Err(std::io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+const fn event(type_: u16, code: u16, value: i32) -> libc::input_event {
+    libc::input_event {
+        time: libc::timeval {
+            tv_sec: 0,
+            tv_usec: 0,
+        },
+        type_,
+        code,
+        value,
+    }
+}
+
+/// A virtual gamepad created using uinput on Linux, enabled by the `uinput` feature.
+///
+/// The state of a [Gamepad] is mirrored into it with [VirtualGamepad::update()], such as to
+/// feed remapped or injected input to other applications, or input received over the network.
+/// Applications read it as a gamepad following the
+/// [Linux gamepad specification](https://docs.kernel.org/input/gamepad.html). The d-pad is reported as buttons, and the triggers both as analog axes and as buttons.
+/// Extra buttons and axes are not mirrored, and rumble effects from other applications are
+/// not supported.
+///
+/// Creating the device needs write access to `/dev/uinput`, usually given by membership in
+/// the `input` group or by a udev rule. The device is removed when dropped.
+///
+/// ```no_run
+/// use gamepads::{Gamepads, VirtualGamepad};
+///
+/// let mut gamepads = Gamepads::new();
+/// let mut virtual_gamepad = VirtualGamepad::new("Remapped gamepad", 0, 0).unwrap();
+/// loop {
+///     gamepads.poll();
+///     if let Some(gamepad) = gamepads.all().next() {
+///         virtual_gamepad.update(&gamepad).unwrap();
+///     }
+///     std::thread::sleep(std::time::Duration::from_millis(8));
+/// }
+/// ```
+pub struct VirtualGamepad {
+    file: File,
+    /// The buttons and axis values last written, so that only changes are written.
+    pressed_bits: u32,
+    values: [i32; AXES.len()],
+}
+
+impl VirtualGamepad {
+    /// Create a virtual gamepad with a name and the vendor and product id reported to
+    /// applications, which may use them to recognize it as a known controller.
+    pub fn new(name: &str, vendor_id: u16, product_id: u16) -> std::io::Result<Self> {
+        let file = File::options()
+            .write(true)
+            .custom_flags(libc::O_NONBLOCK)
+            .open("/dev/uinput")?;
+        let fd = file.as_raw_fd();
+
+        let mut setup = libc::uinput_setup {
+            id: libc::input_id {
+                bustype: BUS_VIRTUAL,
+                vendor: vendor_id,
+                product: product_id,
+                version: 1,
+            },
+            name: [0; libc::UINPUT_MAX_NAME_SIZE],
+            ff_effects_max: 0,
+        };
+        // The name is truncated to leave room for the terminating nul:
+        for (c_char, byte) in setup.name[..libc::UINPUT_MAX_NAME_SIZE - 1]
+            .iter_mut()
+            .zip(name.bytes().take_while(|&byte| byte != 0))
+        {
+            *c_char = byte as libc::c_char;
+        }
+
+        unsafe {
+            check(libc::ioctl(fd, UI_SET_EVBIT, libc::c_int::from(EV_KEY)))?;
+            for (code, _) in BUTTONS {
+                check(libc::ioctl(fd, UI_SET_KEYBIT, libc::c_int::from(code)))?;
+            }
+            check(libc::ioctl(fd, UI_SET_EVBIT, libc::c_int::from(EV_ABS)))?;
+            for code in AXES {
+                let trigger = code == ABS_Z || code == ABS_RZ;
+                let abs_setup = libc::uinput_abs_setup {
+                    code,
+                    absinfo: libc::input_absinfo {
+                        value: 0,
+                        minimum: if trigger { 0 } else { -STICK_MAX },
+                        maximum: if trigger { TRIGGER_MAX } else { STICK_MAX },
+                        fuzz: 0,
+                        flat: 0,
+                        resolution: 0,
+                    },
+                };
+                check(libc::ioctl(fd, UI_ABS_SETUP, &abs_setup))?;
+            }
+            check(libc::ioctl(fd, UI_DEV_SETUP, &setup))?;
+            check(libc::ioctl(fd, UI_DEV_CREATE))?;
+        }
+
+        Ok(Self {
+            file,
+            pressed_bits: 0,
+            values: [0; AXES.len()],
+        })
+    }
+
+    /// Mirror the state of a gamepad into the virtual gamepad, releasing all buttons and
+    /// centering all axes if it is disconnected.
+    ///
+    /// Triggers without analog values, as reported by [Gamepad::left_trigger()], are reported
+    /// as fully pressed while their button is pressed.
+    pub fn update(&mut self, gamepad: &Gamepad) -> std::io::Result<()> {
+        let gamepad = if gamepad.connected {
+            *gamepad
+        } else {
+            Gamepad::new(gamepad.id)
+        };
+        let mut events = Vec::new();
+
+        for (code, button) in BUTTONS {
+            let bit = 1 << (button as u32);
+            if (gamepad.pressed_bits ^ self.pressed_bits) & bit != 0 {
+                let pressed = gamepad.pressed_bits & bit != 0;
+                events.push(event(EV_KEY, code, i32::from(pressed)));
+            }
+        }
+        self.pressed_bits = gamepad.pressed_bits;
+
+        let stick = |value: f32| (value.clamp(-1., 1.) * STICK_MAX as f32).round() as i32;
+        let trigger = |value: f32, button: Button| {
+            let value = if value == 0. && gamepad.is_currently_pressed(button) {
+                1.
+            } else {
+                value
+            };
+            (value.clamp(0., 1.) * TRIGGER_MAX as f32).round() as i32
+        };
+        // Thumbsticks are positive upwards, while axes are positive downwards:
+        let values = [
+            stick(gamepad.axes[0]),
+            stick(-gamepad.axes[1]),
+            stick(gamepad.axes[2]),
+            stick(-gamepad.axes[3]),
+            trigger(gamepad.triggers[0], Button::FrontLeftLower),
+            trigger(gamepad.triggers[1], Button::FrontRightLower),
+        ];
+        for ((code, value), previous) in AXES.into_iter().zip(values).zip(&mut self.values) {
+            if value != *previous {
+                events.push(event(EV_ABS, code, value));
+                *previous = value;
+            }
+        }
+
+        if events.is_empty() {
+            return Ok(());
+        }
+        events.push(event(EV_SYN, SYN_REPORT, 0));
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                events.as_ptr().cast::<u8>(),
+                std::mem::size_of_val(events.as_slice()),
+            )
+        };
+        self.file.write_all(bytes)
+    }
+}
+
+impl Drop for VirtualGamepad {
+    fn drop(&mut self) {
+        unsafe {
+            libc::ioctl(self.file.as_raw_fd(), UI_DEV_DESTROY);
+        }
+    }
+}
@@ -0,0 +1,366 @@
+//! Remapping of gamepads without the "standard" mapping of the Gamepad API.
+//!
+//! Browsers only map buttons and axes of known gamepads to the standard layout, while others
+//! expose them in a device specific order. Known devices are looked up here by the vendor and
+//! product id found in the `id` string of the gamepad. WebXR controllers use the `xr-standard`
+//! mapping, which is remapped the same way. HID devices read by the raw input backend on Windows
+//! and joysticks read by the evdev backend on Linux are given an `id` like Chromium uses, with
+//! their buttons and axes in the same order, so they are remapped the same way too.
+
+use crate::{Button, GamepadMapping};
+
+/// The number of raw axes read, being the two thumbsticks followed by the extra axes, enough for
+/// the hat switch commonly exposed as axis 9.
+pub const MAX_RAW_AXES: usize = 4 + crate::MAX_EXTRA_AXES;
+
+/// The value of a hat switch axis when centered, the step after the 8 directions.
+#[cfg(any(backend = "evdev", backend = "raw_input"))]
+pub const HAT_CENTERED: f32 = 9. / 7.;
+
+/// Buttons and axes of a gamepad, in the device specific order.
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct RawGamepad {
+    pub pressed_bits: u32,
+    pub axes: [f32; MAX_RAW_AXES],
+    /// The [GamepadMapping] as a `u8`.
+    pub mapping: u8,
+    /// Bits of the buttons from index 32, up to [MAX_BUTTONS](crate::MAX_BUTTONS).
+    pub high_pressed_bits: u64,
+}
+
+impl RawGamepad {
+    /// Set a raw button as pressed by its index, ignoring indices from
+    /// [MAX_BUTTONS](crate::MAX_BUTTONS).
+    pub const fn press(&mut self, button_idx: usize) {
+        if button_idx < 32 {
+            self.pressed_bits |= 1 << button_idx;
+        } else if button_idx < crate::MAX_BUTTONS {
+            self.high_pressed_bits |= 1 << (button_idx - 32);
+        }
+    }
+}
+
+/// The [GamepadMapping] of a `mapping` string of the Gamepad API.
+#[cfg(any(backend = "emscripten", backend = "web_bindgen", feature = "tauri"))]
+pub fn mapping_from_str(mapping: &str) -> GamepadMapping {
+    match mapping {
+        "standard" => GamepadMapping::Standard,
+        "" => GamepadMapping::Unmapped,
+        "xr-standard" => GamepadMapping::XrStandard,
+        _ => GamepadMapping::Unknown,
+    }
+}
+
+/// How the directional pad of a device is exposed.
+enum DPad {
+    /// As a hat switch on an axis, with the 8 directions clockwise from up in the range
+    /// `[-1.0, 1.0]`, and values above that when centered.
+    Hat(usize),
+    /// As the x and y axes at the given indices.
+    Axes(usize, usize),
+    /// Not at all.
+    None,
+}
+
+/// The layout of a device, with axis indices as ordered by HID usage like Chromium does.
+struct Mapping {
+    vendor: u16,
+    product: u16,
+    /// The button of each raw button index, if any.
+    buttons: &'static [Option<Button>],
+    /// The raw x and y axis indices of the left and right thumbsticks, if any.
+    sticks: [Option<(usize, usize)>; 2],
+    /// The raw axis indices of the left and right triggers, if exposed as axes.
+    triggers: Option<(usize, usize)>,
+    dpad: DPad,
+    /// The raw button index and the index beyond [Button::Mode] of extra buttons, matching
+    /// the indices of devices with the standard mapping.
+    extra_buttons: &'static [(usize, u8)],
+}
+
+/// Buttons of DirectInput gamepads with face buttons from the top going counterclockwise.
+const DIRECT_INPUT_BUTTONS_TOP_FIRST: &[Option<Button>] = &[
+    Some(Button::ActionUp),
+    Some(Button::ActionRight),
+    Some(Button::ActionDown),
+    Some(Button::ActionLeft),
+    Some(Button::FrontLeftUpper),
+    Some(Button::FrontRightUpper),
+    Some(Button::FrontLeftLower),
+    Some(Button::FrontRightLower),
+    Some(Button::LeftCenterCluster),
+    Some(Button::RightCenterCluster),
+    Some(Button::LeftStick),
+    Some(Button::RightStick),
+];
+
+/// Buttons of HID gamepads with an Xbox like layout, with the triggers exposed as axes.
+const HID_BUTTONS_XBOX_ORDER: &[Option<Button>] = &[
+    Some(Button::ActionDown),
+    Some(Button::ActionRight),
+    Some(Button::ActionLeft),
+    Some(Button::ActionUp),
+    Some(Button::FrontLeftUpper),
+    Some(Button::FrontRightUpper),
+    Some(Button::LeftCenterCluster),
+    Some(Button::RightCenterCluster),
+    Some(Button::Mode),
+    Some(Button::LeftStick),
+    Some(Button::RightStick),
+];
+
+/// The mapping of 8BitDo gamepads in D-input mode, whose buttons follow the HID report with
+/// gaps for the unused C and Z buttons, and whose face buttons are labeled as on Nintendo
+/// gamepads, with A on the right.
+const fn eightbitdo_d_input(product: u16) -> Mapping {
+    Mapping {
+        vendor: 0x2dc8,
+        product,
+        buttons: &[
+            Some(Button::ActionRight),
+            Some(Button::ActionDown),
+            None,
+            Some(Button::ActionUp),
+            Some(Button::ActionLeft),
+            None,
+            Some(Button::FrontLeftUpper),
+            Some(Button::FrontRightUpper),
+            Some(Button::FrontLeftLower),
+            Some(Button::FrontRightLower),
+            Some(Button::LeftCenterCluster),
+            Some(Button::RightCenterCluster),
+            Some(Button::Mode),
+            Some(Button::LeftStick),
+            Some(Button::RightStick),
+        ],
+        sticks: [Some((0, 1)), Some((2, 5))],
+        triggers: None,
+        dpad: DPad::Hat(9),
+        extra_buttons: &[],
+    }
+}
+
+/// The `xr-standard` mapping, with the thumbstick as the left thumbstick and the touchpad
+/// as the right thumbstick.
+const XR_STANDARD: Mapping = Mapping {
+    vendor: 0,
+    product: 0,
+    buttons: &[
+        Some(Button::FrontRightLower),
+        Some(Button::FrontRightUpper),
+        Some(Button::RightStick),
+        Some(Button::LeftStick),
+        Some(Button::ActionDown),
+        Some(Button::ActionRight),
+    ],
+    sticks: [Some((2, 3)), Some((0, 1))],
+    triggers: None,
+    dpad: DPad::None,
+    extra_buttons: &[],
+};
+
+const MAPPINGS: &[Mapping] = &[
+    // DragonRise generic USB gamepads, sold under many brands:
+    Mapping {
+        vendor: 0x0079,
+        product: 0x0006,
+        buttons: DIRECT_INPUT_BUTTONS_TOP_FIRST,
+        sticks: [Some((0, 1)), Some((5, 2))],
+        triggers: None,
+        dpad: DPad::Hat(9),
+        extra_buttons: &[],
+    },
+    // Logitech Dual Action, and F310/F510 in DirectInput mode:
+    Mapping {
+        vendor: 0x046d,
+        product: 0xc216,
+        buttons: &[
+            Some(Button::ActionLeft),
+            Some(Button::ActionDown),
+            Some(Button::ActionRight),
+            Some(Button::ActionUp),
+            Some(Button::FrontLeftUpper),
+            Some(Button::FrontRightUpper),
+            Some(Button::FrontLeftLower),
+            Some(Button::FrontRightLower),
+            Some(Button::LeftCenterCluster),
+            Some(Button::RightCenterCluster),
+            Some(Button::LeftStick),
+            Some(Button::RightStick),
+        ],
+        sticks: [Some((0, 1)), Some((2, 5))],
+        triggers: None,
+        dpad: DPad::Hat(9),
+        extra_buttons: &[],
+    },
+    // iBuffalo Classic USB Gamepad, a SNES style gamepad:
+    Mapping {
+        vendor: 0x0583,
+        product: 0x2060,
+        buttons: &[
+            Some(Button::ActionRight),
+            Some(Button::ActionDown),
+            Some(Button::ActionUp),
+            Some(Button::ActionLeft),
+            Some(Button::FrontLeftUpper),
+            Some(Button::FrontRightUpper),
+            Some(Button::LeftCenterCluster),
+            Some(Button::RightCenterCluster),
+        ],
+        sticks: [None, None],
+        triggers: None,
+        dpad: DPad::Axes(0, 1),
+        extra_buttons: &[],
+    },
+    // Google Stadia controller, with the Assistant and Capture buttons as 17 and 18 like
+    // Chromium and Firefox report them with the standard mapping:
+    Mapping {
+        vendor: 0x18d1,
+        product: 0x9400,
+        buttons: HID_BUTTONS_XBOX_ORDER,
+        sticks: [Some((0, 1)), Some((2, 5))],
+        triggers: Some((3, 4)),
+        dpad: DPad::Hat(9),
+        extra_buttons: &[(11, 17), (12, 18)],
+    },
+    // Amazon Luna controller, with the microphone button as 17:
+    Mapping {
+        vendor: 0x1949,
+        product: 0x0419,
+        buttons: HID_BUTTONS_XBOX_ORDER,
+        sticks: [Some((0, 1)), Some((2, 5))],
+        triggers: Some((3, 4)),
+        dpad: DPad::Hat(9),
+        extra_buttons: &[(11, 17)],
+    },
+    // 8BitDo gamepads in D-input mode, while their X-input and Switch modes are read as the
+    // gamepads they emulate. Their keyboard mode is not a gamepad, so it is not read:
+    eightbitdo_d_input(0x3100), // Wireless Adapter
+    eightbitdo_d_input(0x3820), // NES30 Pro
+    eightbitdo_d_input(0x6001), // SN30 Pro
+    eightbitdo_d_input(0x6002), // SN30 Pro+
+    eightbitdo_d_input(0x6101), // SN30 Pro, newer firmware
+    eightbitdo_d_input(0x9000), // FC30 Pro
+];
+
+/// Parse the vendor and product id from the `id` string of a gamepad.
+///
+/// Chromium uses ids like `"USB Gamepad (Vendor: 0079 Product: 0006)"`,
+/// while Firefox uses ids like `"79-6-USB Gamepad"`.
+fn vendor_and_product(id: &str) -> Option<(u16, u16)> {
+    if let Some((_, ids)) = id.split_once("Vendor: ") {
+        let (vendor, ids) = ids.split_once(" Product: ")?;
+        let product = ids.get(..4)?;
+        return Some((
+            u16::from_str_radix(vendor, 16).ok()?,
+            u16::from_str_radix(product, 16).ok()?,
+        ));
+    }
+    let mut parts = id.splitn(3, '-');
+    let vendor = u16::from_str_radix(parts.next()?, 16).ok()?;
+    let product = u16::from_str_radix(parts.next()?, 16).ok()?;
+    Some((vendor, product))
+}
+
+impl Mapping {
+    fn find(id: &str) -> Option<&'static Self> {
+        let (vendor, product) = vendor_and_product(id)?;
+        MAPPINGS
+            .iter()
+            .find(|mapping| mapping.vendor == vendor && mapping.product == product)
+    }
+}
+
+/// The axes of a gamepad in the raw order, with y positive upwards as for thumbsticks.
+pub fn raw_axes(raw: &RawGamepad) -> [f32; 4] {
+    std::array::from_fn(|axes_idx| raw.axes[axes_idx] * if axes_idx % 2 == 1 { -1. } else { 1. })
+}
+
+/// Write the raw state of a device which is not remapped to a gamepad, with its buttons and axes in
+/// the device specific order, of which those beyond the standard layout are extra buttons and axes.
+pub fn write_unmapped(raw: &RawGamepad, gamepad: &mut crate::Gamepad) {
+    gamepad.pressed_bits = raw.pressed_bits;
+    gamepad.high_pressed_bits = raw.high_pressed_bits;
+    gamepad.axes = raw_axes(raw);
+    gamepad.extra_axes.copy_from_slice(&raw.axes[4..]);
+}
+
+/// Remap the raw state of a gamepad without the standard mapping to the pressed bits and axes
+/// of the standard layout, or `None` if the device is not known.
+pub fn remap(mapping: GamepadMapping, id: &str, raw: &RawGamepad) -> Option<(u32, [f32; 4])> {
+    let mapping = if mapping == GamepadMapping::XrStandard {
+        &XR_STANDARD
+    } else {
+        Mapping::find(id)?
+    };
+    let raw_axis = |idx: usize| raw.axes.get(idx).copied().unwrap_or_default();
+
+    let mut pressed_bits = 0;
+    for (raw_idx, button) in mapping.buttons.iter().enumerate() {
+        if let Some(button) = button {
+            if raw.pressed_bits & (1 << raw_idx) != 0 {
+                pressed_bits |= 1 << (*button as u32);
+            }
+        }
+    }
+    for &(raw_idx, extra_idx) in mapping.extra_buttons {
+        if raw.pressed_bits & (1 << raw_idx) != 0 {
+            pressed_bits |= 1 << extra_idx;
+        }
+    }
+    if let Some((left, right)) = mapping.triggers {
+        // Raw trigger axes range from -1.0 when released to 1.0 when fully pressed:
+        for (idx, button) in [
+            (left, Button::FrontLeftLower),
+            (right, Button::FrontRightLower),
+        ] {
+            if raw_axis(idx) > 0. {
+                pressed_bits |= 1 << (button as u32);
+            }
+        }
+    }
+    let (dpad_x, dpad_y) = match mapping.dpad {
+        DPad::Hat(idx) => {
+            let value = raw_axis(idx);
+            if value > 1.1 {
+                (0., 0.)
+            } else {
+                // Directions clockwise from up, in steps of 2/7:
+                let step = ((value + 1.) * 3.5).round() as usize % 8;
+                [
+                    (0., -1.),
+                    (1., -1.),
+                    (1., 0.),
+                    (1., 1.),
+                    (0., 1.),
+                    (-1., 1.),
+                    (-1., 0.),
+                    (-1., -1.),
+                ][step]
+            }
+        }
+        DPad::Axes(x, y) => (raw_axis(x), raw_axis(y)),
+        DPad::None => (0., 0.),
+    };
+    for (pressed, button) in [
+        (dpad_y < -0.5, Button::DPadUp),
+        (dpad_y > 0.5, Button::DPadDown),
+        (dpad_x < -0.5, Button::DPadLeft),
+        (dpad_x > 0.5, Button::DPadRight),
+    ] {
+        if pressed {
+            pressed_bits |= 1 << (button as u32);
+        }
+    }
+
+    let mut axes = [0.; 4];
+    for (stick_idx, stick) in mapping.sticks.iter().enumerate() {
+        if let Some((x, y)) = *stick {
+            axes[stick_idx * 2] = raw_axis(x);
+            // Raw axes are positive downwards, while thumbsticks are positive upwards:
+            axes[stick_idx * 2 + 1] = -raw_axis(y);
+        }
+    }
+    Some((pressed_bits, axes))
+}
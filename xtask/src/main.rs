@@ -0,0 +1,69 @@
+//! Development tasks for the gamepads crate, run using `cargo xtask <task>`.
+
+use std::path::Path;
+use std::process::ExitCode;
+
+/// The TypeScript definitions generated from the Rust types.
+const TYPESCRIPT_PATH: &str = "js/gamepads-module-0.2.d.ts";
+
+/// The javascript source, which hardcodes the layout of gamepad state.
+const JS_SOURCE_PATH: &str = "js/gamepads-src-0.2.js";
+
+fn main() -> ExitCode {
+    let result = match std::env::args().nth(1).as_deref() {
+        Some("generate-ts") => generate_ts(),
+        Some("check-backend") => check_backend(),
+        _ => Err("Usage: cargo xtask generate-ts|check-backend".to_string()),
+    };
+    if let Err(message) = result {
+        eprintln!("{message}");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
+
+/// Write the TypeScript definitions, after checking that the javascript source uses the
+/// same layout as the Rust types.
+fn generate_ts() -> Result<(), String> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let source = std::fs::read_to_string(root.join(JS_SOURCE_PATH))
+        .map_err(|error| format!("Failed to read {JS_SOURCE_PATH}: {error}"))?;
+    for (name, value) in [
+        ("PROTOCOL_VERSION", gamepads::js::PROTOCOL_VERSION as usize),
+        ("MAX_GAMEPADS", gamepads::js::MAX_GAMEPADS),
+        ("BYTES_PER_GAMEPAD", gamepads::js::BYTES_PER_GAMEPAD),
+        ("MAPPING_OFFSET", gamepads::js::MAPPING_OFFSET),
+        (
+            "JUST_PRESSED_OFFSET",
+            gamepads::js::JUST_PRESSED_BITS_OFFSET,
+        ),
+        ("TRIGGERS_OFFSET", gamepads::js::TRIGGERS_OFFSET),
+        ("EXTRA_AXES_OFFSET", gamepads::js::EXTRA_AXES_OFFSET),
+        ("MAX_EXTRA_AXES", gamepads::js::MAX_EXTRA_AXES),
+        (
+            "HIGH_PRESSED_OFFSET",
+            gamepads::js::HIGH_PRESSED_BITS_OFFSET,
+        ),
+    ] {
+        if !source.contains(&format!("const {name} = {value};")) {
+            return Err(format!(
+                "{JS_SOURCE_PATH} should define `const {name} = {value};`"
+            ));
+        }
+    }
+    std::fs::write(
+        root.join(TYPESCRIPT_PATH),
+        gamepads::js::typescript_definitions(),
+    )
+    .map_err(|error| format!("Failed to write {TYPESCRIPT_PATH}: {error}"))
+}
+
+/// Initialize the backend selected by the enabled features and poll it once, failing if it
+/// cannot be initialized, such as to check the declarations of a platform API in CI.
+fn check_backend() -> Result<(), String> {
+    let mut gamepads = gamepads::Gamepads::try_new()
+        .map_err(|error| format!("Failed to initialize the backend: {error}"))?;
+    gamepads.poll();
+    println!("Connected gamepads: {}", gamepads.all().count());
+    Ok(())
+}